@@ -0,0 +1,102 @@
+//! Derive macro for `binary_heap`, re-exported by it with the `derive` feature
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Index, Member};
+
+/// Derive `PartialEq` and `PartialOrd` from a single field, the priority key
+///
+/// Exactly one field of the structure must be annotated with `#[heap_key]`; two values are
+/// compared by comparing their keys only, the other fields being ignored. With
+/// `#[heap_key(reverse)]`, the order of the keys is reversed, so that a `BinaryHeap` pops the
+/// element with the smallest key first.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, HeapOrd};
+///
+/// #[derive(HeapOrd)]
+/// struct Job {
+///     #[heap_key]
+///     priority: u32,
+///     name: String,
+/// }
+///
+/// let mut jobs = BinaryHeap::new();
+/// jobs.insert(Job { priority: 1, name: "backup".to_string() });
+/// jobs.insert(Job { priority: 5, name: "deploy".to_string() });
+///
+/// assert_eq!("deploy", jobs.pop().unwrap().name);
+/// ```
+#[proc_macro_derive(HeapOrd, attributes(heap_key))]
+pub fn derive_heap_ord(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(syn::Error::new(input.span(), "`HeapOrd` can only be derived for structures")),
+    };
+
+    // find the key field and its direction
+    let mut key = None;
+    let fields_iter: Box<dyn Iterator<Item = _>> = match fields {
+        Fields::Named(named) => Box::new(named.named.iter()),
+        Fields::Unnamed(unnamed) => Box::new(unnamed.unnamed.iter()),
+        Fields::Unit => Box::new(core::iter::empty()),
+    };
+    for (index, field) in fields_iter.enumerate() {
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("heap_key")) {
+            if key.is_some() {
+                return Err(syn::Error::new(attr.span(), "only one field can be marked `#[heap_key]`"));
+            }
+            let mut reverse = false;
+            if !matches!(attr.meta, syn::Meta::Path(_)) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("reverse") {
+                        reverse = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `reverse`"))
+                    }
+                })?;
+            }
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(index)),
+            };
+            key = Some((member, field.ty.clone(), reverse));
+        }
+    }
+    let (member, ty, reverse) = key.ok_or_else(|| {
+        syn::Error::new(input.ident.span(), "`HeapOrd` needs a field marked `#[heap_key]`")
+    })?;
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.push(syn::parse_quote!(#ty: ::core::cmp::PartialOrd));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (first, second) = if reverse {
+        (quote!(other), quote!(self))
+    } else {
+        (quote!(self), quote!(other))
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                self.#member == other.#member
+            }
+        }
+
+        impl #impl_generics ::core::cmp::PartialOrd for #name #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::cmp::PartialOrd::partial_cmp(&#first.#member, &#second.#member)
+            }
+        }
+    })
+}
@@ -1,23 +1,16 @@
-//! This example shows how to use a binary heap to sort (key, value) pairs according to their keys. 
+//! This example shows how to use a binary heap to sort tasks according to their priority levels. 
 
 use binary_heap::BinaryHeap;
 
-// A (key, value) structure
-#[derive(PartialEq, Clone)]
-struct KeyValuePair<K: PartialOrd + Clone, V: PartialEq + Clone> {
-    key: K, 
-    value: V,
+// A task with a priority level
+struct Task {
+    priority: u32, 
+    description: &'static str,
 }
 
-impl<K: PartialOrd + Clone, V: PartialEq + Clone> KeyValuePair<K, V> {
-    pub fn new(key: K, value: V) -> Self {
-        KeyValuePair { key, value }
-    }
-}
-
-impl<K: PartialOrd + Clone, V: PartialEq + Clone> PartialOrd for KeyValuePair<K, V> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.key.partial_cmp(&other.key)
+impl Task {
+    pub fn new(priority: u32, description: &'static str) -> Self {
+        Task { priority, description }
     }
 }
 
@@ -26,19 +19,23 @@ fn main() {
 
     // List of tasks with level of priority (higher ↔ more urgent)
     let tasks = vec![
-        KeyValuePair::new(4, "Clean the car"),
-        KeyValuePair::new(2, "Read ‘The Lord of the Rings’"),
-        KeyValuePair::new(8, "Proofread the new draft for the paper"),
-        KeyValuePair::new(6, "Update documentation in the Git repository"),
-        KeyValuePair::new(2, "Read ‘The Silmarillion’"),
-        KeyValuePair::new(7, "Update algorithm 2.3"),
+        Task::new(4, "Clean the car"),
+        Task::new(2, "Read ‘The Lord of the Rings’"),
+        Task::new(8, "Proofread the new draft for the paper"),
+        Task::new(6, "Update documentation in the Git repository"),
+        Task::new(2, "Read ‘The Silmarillion’"),
+        Task::new(7, "Update algorithm 2.3"),
     ];
     
-    // Sort them using a binary heap
-    let tasks_sorted = BinaryHeap::sort(&tasks);
+    // Sort them using a binary heap ordered by priority level
+    let mut heap = BinaryHeap::new_by_key(|task: &Task| task.priority);
+    for task in tasks {
+        heap.insert(task);
+    }
+    let tasks_sorted = heap.to_vec();
 
     // Print the result
-    for KeyValuePair { key: k, value: v } in tasks_sorted.iter() {
+    for Task { priority: k, description: v } in tasks_sorted.iter() {
         println!("Task: {}, Priority level: {}", v, k);
     }
 }
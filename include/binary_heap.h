@@ -0,0 +1,59 @@
+#ifndef BINARY_HEAP_H
+#define BINARY_HEAP_H
+
+/* Generated with cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque max binary heap of payloads ordered by `uint64_t` keys
+typedef struct BinaryHeapHandle BinaryHeapHandle;
+
+// Create a new empty heap
+//
+// The heap must be destroyed with `heap_free`.
+struct BinaryHeapHandle *heap_new(void);
+
+// Destroy a heap (the payloads it still contains are not freed); do nothing if `heap` is null
+//
+// # Safety
+//
+// `heap` must be null or a pointer returned by `heap_new` which has not been freed yet.
+void heap_free(struct BinaryHeapHandle *heap);
+
+// Insert `payload` with key `key`; return `false` if `heap` is null
+//
+// # Safety
+//
+// `heap` must be null or a valid pointer returned by `heap_new`.
+bool heap_insert(struct BinaryHeapHandle *heap, uint64_t key, void *payload);
+
+// Remove the element with the largest key, writing its key to `*key` and its payload to
+// `*payload` (if these pointers are not null); return `false` if the heap is empty or null
+//
+// # Safety
+//
+// `heap` must be null or a valid pointer returned by `heap_new`; `key` and `payload` must be
+// null or valid for writes.
+bool heap_pop(struct BinaryHeapHandle *heap, uint64_t *key, void **payload);
+
+// Write the key and payload of the element with the largest key to `*key` and `*payload` (if
+// these pointers are not null) without removing it; return `false` if the heap is empty or null
+//
+// # Safety
+//
+// `heap` must be null or a valid pointer returned by `heap_new`; `key` and `payload` must be
+// null or valid for writes.
+bool heap_peek(const struct BinaryHeapHandle *heap, uint64_t *key, void **payload);
+
+// Return the number of elements in the heap (0 if `heap` is null)
+//
+// # Safety
+//
+// `heap` must be null or a valid pointer returned by `heap_new`.
+size_t heap_size(const struct BinaryHeapHandle *heap);
+
+#endif  /* BINARY_HEAP_H */
@@ -0,0 +1,346 @@
+//! Heap whose elements can be accessed through handles
+//!
+//! The heap array holds the elements with the indices of their slots in a slab (a vector of
+//! slots, with a list of free slots). Each slot records the position of its element in the heap
+//! array, updated at each swap, so that the element of a handle can be found in $\Theta(1)$ time.
+
+use crate::{BinaryHeap, Compare, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Stable reference to an element of an `AddressableHeap`, a `TombstoneHeap`, a `PairingHeap`,
+/// a `FibonacciHeap`, or a `RankPairingHeap`, or to an item of a `DelayQueue`
+///
+/// A handle stays valid until its element is popped or removed; after that, functions taking
+/// it return `None` (or `false`), even if the slot of the element has been reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    pub(crate) index: usize,        // index of the slot
+    pub(crate) generation: u32,     // generation of the slot when the element was inserted
+}
+
+/// Translation of the handles of a heap melded into another one
+///
+/// The elements of the melded heap are moved to the slab of the other one; `map` returns the
+/// new handle of each of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HandleMap {
+    pub(crate) offset: usize,   // index of the first slot of the melded heap in the new slab
+}
+
+impl HandleMap {
+
+    /// Translate a handle to an element of the melded heap
+    #[inline]
+    pub fn map(&self, handle: Handle) -> Handle {
+        Handle { index: handle.index + self.offset, generation: handle.generation }
+    }
+}
+
+// slot of the slab, with the position of its element in the heap array (or `None` if it is
+// free); the generation is incremented each time the slot is freed
+#[derive(Clone, Debug)]
+struct Slot {
+    generation: u32,
+    position: Option<usize>,
+}
+
+// order of the (element, slot index) pairs: the elements are compared with `C`
+#[derive(Clone, Copy, Debug, Default)]
+struct ElementComparator<C>(C);
+
+impl<T, C: Compare<T>> Compare<(T, usize)> for ElementComparator<C> {
+    #[inline]
+    fn compare(&self, a: &(T, usize), b: &(T, usize)) -> Option<Ordering> {
+        self.0.compare(&a.0, &b.0)
+    }
+}
+
+// update the positions of the elements at positions `i` and `j` of `data`, after they were
+// swapped
+fn track<T>(slots: &mut [Slot]) -> impl FnMut(&[(T, usize)], usize, usize) + '_ {
+    |data, i, j| {
+        slots[data[i].1].position = Some(i);
+        slots[data[j].1].position = Some(j);
+    }
+}
+
+/// A heap whose elements can be changed or removed through the handles returned by `insert`
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::AddressableHeap;
+///
+/// let mut jobs = AddressableHeap::new();
+/// let backup = jobs.insert(3);
+/// let report = jobs.insert(5);
+/// let cleanup = jobs.insert(1);
+///
+/// // reprioritize and cancel in-flight jobs
+/// jobs.increase_key(cleanup, 8);
+/// jobs.remove(report);
+///
+/// assert_eq!(Some(&3), jobs.get(backup));
+/// assert_eq!(Some(8), jobs.pop());
+/// assert_eq!(Some(3), jobs.pop());
+/// assert_eq!(None, jobs.get(backup));
+/// ```
+#[derive(Clone, Debug)]
+pub struct AddressableHeap<T, C = MaxComparator> {
+    heap: BinaryHeap<(T, usize), ElementComparator<C>>,     // elements with their slot indices
+    slots: Vec<Slot>,                                       // slab of positions
+    free: Vec<usize>,                                       // indices of the free slots
+}
+
+impl<T: core::cmp::PartialOrd> AddressableHeap<T> {
+
+    /// Create a new empty `AddressableHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        AddressableHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> AddressableHeap<T, C> {
+
+    /// Create a new empty `AddressableHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        AddressableHeap {
+            heap: BinaryHeap::with_comparator(ElementComparator(comparator)),
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert an element in the heap, returning a handle to it
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap
+    /// (amortized, as the slab may grow).
+    pub fn insert(&mut self, x: T) -> Handle {
+        let position = Some(self.size());
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].position = position;
+                index
+            },
+            None => {
+                self.slots.push(Slot { generation: 0, position });
+                self.slots.len() - 1
+            },
+        };
+        self.heap.push_with((x, index), track(&mut self.slots));
+        Handle { index, generation: self.slots[index].generation }
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let (x, index) = self.heap.pop_with(track(&mut self.slots))?;
+        self.free_slot(index);
+        Some(x)
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first().map(|(x, _)| x)
+    }
+
+    /// Return a reference to the element of `handle` (or `None` if it is no longer in the heap)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|pos| &self.heap.data[pos].0)
+    }
+
+    /// Whether the element of `handle` is still in the heap
+    #[inline]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    /// Replace the element of `handle` by a smaller one `x`, returning the old element (or
+    /// `None`, leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact larger, the heap is repaired as well.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn decrease_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Replace the element of `handle` by a larger one `x`, returning the old element (or `None`,
+    /// leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact smaller, the heap is repaired as well.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn increase_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Get the handle of the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    pub fn peek_handle(&self) -> Option<Handle> {
+        let index = self.heap.data.first()?.1;
+        Some(Handle { index, generation: self.slots[index].generation })
+    }
+
+    /// Apply `f` to the element of `handle` and repair the heap, returning the result of `f` (or
+    /// `None`, without calling `f`, if the element is no longer in the heap)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap,
+    /// plus the cost of `f`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::AddressableHeap;
+    ///
+    /// let mut jobs = AddressableHeap::new();
+    /// let backup = jobs.insert((3, "backup"));
+    /// jobs.insert((5, "report"));
+    /// jobs.update(backup, |job| job.0 = 9);
+    /// assert_eq!(Some(backup), jobs.peek_handle());
+    /// ```
+    pub fn update<R>(&mut self, handle: Handle, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let position = self.position(handle)?;
+        let result = f(&mut self.heap.data[position].0);
+        self.repair(position);
+        Some(result)
+    }
+
+    /// Remove the element of `handle` and return it (or `None` if it is no longer in the heap)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let position = self.position(handle)?;
+        Some(self.remove_at(position))
+    }
+
+    // position of the element of `handle` in the heap array, if it is still in the heap
+    fn position(&self, handle: Handle) -> Option<usize> {
+        self.slots.get(handle.index)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.position)
+    }
+
+    // replace the element of `handle` and repair the heap
+    fn replace(&mut self, handle: Handle, x: T) -> Option<T> {
+        let position = self.position(handle)?;
+        let old = core::mem::replace(&mut self.heap.data[position].0, x);
+        self.repair(position);
+        Some(old)
+    }
+
+    // remove the element at position `pos` of the heap array, freeing its slot
+    fn remove_at(&mut self, pos: usize) -> T {
+        let last = self.size() - 1;
+        self.heap.swap(pos, last);
+        track(&mut self.slots)(&self.heap.data, pos, last);
+        let (x, index) = self.heap.data.pop().unwrap();
+        self.free_slot(index);
+        if pos < last {
+            self.repair(pos);
+        }
+        x
+    }
+
+    // free the slot `index`, invalidating its handles
+    fn free_slot(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        slot.position = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+    }
+
+    // restore the heap property after the element at position `pos` has changed
+    fn repair(&mut self, pos: usize) {
+        if self.heap.sift_up_with(pos, track(&mut self.slots)) == pos {
+            let size = self.size();
+            self.heap.sift_down_with(pos, size, track(&mut self.slots));
+        }
+        self.heap.debug_validate();
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for AddressableHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::MinComparator;
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(7);
+        let mut heap = AddressableHeap::with_comparator(MinComparator);
+        let mut live: Vec<(Handle, u64)> = Vec::new();
+        let mut dead: Vec<Handle> = Vec::new();
+        for _ in 0..5_000 {
+            match next() % 4 {
+                0 | 1 => {
+                    let x = next() % 1000;
+                    live.push((heap.insert(x), x));
+                },
+                2 if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let x = next() % 1000;
+                    assert_eq!(Some(live[i].1), heap.decrease_key(live[i].0, x));
+                    live[i].1 = x;
+                },
+                _ if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let (handle, x) = live.swap_remove(i);
+                    assert_eq!(Some(x), heap.remove(handle));
+                    dead.push(handle);
+                },
+                _ => {},
+            }
+            assert_eq!(live.len(), heap.size());
+            assert_eq!(live.iter().map(|&(_, x)| x).min().as_ref(), heap.peek());
+        }
+        for &(handle, x) in live.iter() {
+            assert_eq!(Some(&x), heap.get(handle));
+        }
+        assert!(dead.iter().all(|&handle| !heap.contains(handle)));
+        let mut expected: Vec<u64> = live.iter().map(|&(_, x)| x).collect();
+        expected.sort_unstable();
+        let popped: Vec<u64> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(expected, popped);
+    }
+
+    #[test]
+    fn stale_handle() {
+        let mut heap = AddressableHeap::new();
+        let first = heap.insert('a');
+        assert_eq!(Some('a'), heap.pop());
+        let second = heap.insert('b');
+        assert_eq!(None, heap.get(first));
+        assert_eq!(None, heap.remove(first));
+        assert_eq!(None, heap.increase_key(first, 'z'));
+        assert_eq!(Some(&'b'), heap.get(second));
+    }
+}
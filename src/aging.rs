@@ -0,0 +1,150 @@
+//! Priority queue with aging, to prevent starvation
+//!
+//! Each value is stored with its base priority, its insertion time, and an effective priority,
+//! by which the heap is ordered. The effective priorities are recomputed from the waiting times
+//! by a boost function at each call to `age`, and the heap is then rebuilt in a single pass.
+
+use crate::{BinaryHeap, Compare};
+use core::cmp::Ordering;
+
+// value with its priorities and insertion time
+#[derive(Clone, Debug)]
+struct Entry<P, V> {
+    effective: P,   // priority used to order the heap
+    base: P,        // priority given at insertion
+    inserted: u64,  // insertion time
+    value: V,
+}
+
+// order of the entries: only the effective priorities are compared
+#[derive(Clone, Copy, Debug, Default)]
+struct EffectiveComparator;
+
+impl<P: core::cmp::PartialOrd, V> Compare<Entry<P, V>> for EffectiveComparator {
+    #[inline]
+    fn compare(&self, a: &Entry<P, V>, b: &Entry<P, V>) -> Option<Ordering> {
+        a.effective.partial_cmp(&b.effective)
+    }
+}
+
+/// A priority queue whose long-waiting values get higher priorities
+///
+/// Each value is inserted with a base priority and the current time (in any unit, as a `u64`).
+/// The function `age(now)` sets the effective priority of each value to `boost(&base, wait)`,
+/// where `wait` is the time elapsed since its insertion, and repairs the heap; `pop` returns a
+/// value with the highest effective priority. With a boost increasing with the waiting time,
+/// low-priority values are eventually popped, so the queue can be used as a fair scheduler.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::AgingQueue;
+///
+/// // one priority level gained every 10 time units
+/// let mut tasks = AgingQueue::new(|&priority: &u64, wait: u64| priority + wait / 10);
+/// tasks.insert(1, "low", 0);
+/// tasks.insert(5, "high", 50);
+///
+/// // without aging, the high-priority task would be first
+/// tasks.age(60);
+/// assert_eq!(Some((1, "low")), tasks.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct AgingQueue<P, V, F> {
+    heap: BinaryHeap<Entry<P, V>, EffectiveComparator>,
+    boost: F,   // effective priority as a function of the base priority and waiting time
+}
+
+impl<P: core::cmp::PartialOrd + Clone, V, F: Fn(&P, u64) -> P> AgingQueue<P, V, F> {
+
+    /// Create a new empty `AgingQueue` with the boost function `boost`
+    ///
+    /// `boost(&base, wait)` should be equal to `base` when `wait` is zero.
+    #[inline]
+    pub fn new(boost: F) -> Self {
+        AgingQueue { heap: BinaryHeap::with_comparator(EffectiveComparator), boost }
+    }
+
+    /// Get the number of values in the queue
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert `value` with base priority `priority` at time `now`
+    ///
+    /// Its effective priority is `priority` until the next call to `age`.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of values in the queue.
+    pub fn insert(&mut self, priority: P, value: V, now: u64) {
+        self.heap.insert(Entry { effective: priority.clone(), base: priority, inserted: now, value });
+    }
+
+    /// Remove and return a value with the highest effective priority, with its base priority (or
+    /// `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of values in the queue.
+    pub fn pop(&mut self) -> Option<(P, V)> {
+        self.heap.pop().map(|entry| (entry.base, entry.value))
+    }
+
+    /// Return references to a value with the highest effective priority and to its effective
+    /// priority (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<(&P, &V)> {
+        self.heap.data.first().map(|entry| (&entry.effective, &entry.value))
+    }
+
+    /// Recompute the effective priorities at time `now`, and repair the heap
+    ///
+    /// Values inserted after `now` keep their base priority.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ calls to the boost function and comparisons, where $n$
+    /// is the number of values in the queue.
+    pub fn age(&mut self, now: u64) {
+        let boost = &self.boost;
+        self.heap.adjust_all(|entry| {
+            entry.effective = boost(&entry.base, now.saturating_sub(entry.inserted));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_starvation() {
+        // a stream of high-priority tasks, and one low-priority task inserted first
+        let mut queue = AgingQueue::new(|&priority: &u64, wait: u64| priority + wait);
+        queue.insert(0, usize::MAX, 0);
+        let mut popped_at = None;
+        for now in 1..100 {
+            queue.insert(10, now as usize, now);
+            queue.age(now);
+            if let Some((_, usize::MAX)) = queue.pop() {
+                popped_at = Some(now);
+                break;
+            }
+        }
+        // the low-priority task has the same effective priority as the others at time 10
+        assert!(popped_at.is_some_and(|now| (10..=11).contains(&now)));
+    }
+
+    #[test]
+    fn without_aging() {
+        let mut queue = AgingQueue::new(|&priority: &i32, _| priority);
+        queue.insert(1, 'a', 0);
+        queue.insert(3, 'b', 5);
+        queue.insert(2, 'c', 9);
+        queue.age(100);
+        assert_eq!(Some((&3, &'b')), queue.peek());
+        assert_eq!(3, queue.size());
+        assert_eq!(Some((3, 'b')), queue.pop());
+        assert_eq!(Some((2, 'c')), queue.pop());
+    }
+}
@@ -0,0 +1,106 @@
+//! Generation of random heaps for property testing
+//!
+//! With the `proptest` or `quickcheck` feature, `BinaryHeap<T>` implements the `Arbitrary` trait
+//! of the corresponding crate. A random heap is built from a random vector of elements, and is
+//! shrunk by shrinking this vector and rebuilding the heap, so that shrunk values are always
+//! valid heaps.
+
+use crate::BinaryHeap;
+
+#[cfg(feature = "proptest")]
+mod proptest_impl {
+    use super::*;
+    use proptest::arbitrary::{any_with, Arbitrary};
+    use proptest::collection::{vec, SizeRange, VecStrategy};
+    use proptest::strategy::{Map, Strategy};
+
+    /// Strategy generating heaps whose elements are generated by `element`, with a number of
+    /// elements in `size`
+    ///
+    /// Only available with the `proptest` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::arbitrary::binary_heap;
+    /// use proptest::prelude::*;
+    ///
+    /// proptest! {
+    ///     fn root_is_maximum(heap in binary_heap(0..100u32, 1..20)) {
+    ///         let max = heap.get_max().unwrap();
+    ///         prop_assert!(heap.to_vec().iter().all(|&x| x <= max));
+    ///     }
+    /// }
+    /// # root_is_maximum();
+    /// ```
+    pub fn binary_heap<S>(element: S, size: impl Into<SizeRange>)
+        -> impl Strategy<Value = BinaryHeap<S::Value>>
+        where S: Strategy,
+              S::Value: core::cmp::PartialOrd
+    {
+        vec(element, size).prop_map(BinaryHeap::from)
+    }
+
+    impl<T: Arbitrary + core::cmp::PartialOrd> Arbitrary for BinaryHeap<T> {
+        type Parameters = (SizeRange, T::Parameters);
+        type Strategy = Map<VecStrategy<T::Strategy>, fn(Vec<T>) -> Self>;
+
+        fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+            vec(any_with::<T>(args), size).prop_map(BinaryHeap::from)
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub use proptest_impl::binary_heap;
+
+#[cfg(feature = "quickcheck")]
+impl<T: quickcheck::Arbitrary + core::cmp::PartialOrd> quickcheck::Arbitrary for BinaryHeap<T> {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        BinaryHeap::from(Vec::<T>::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.data.shrink().map(BinaryHeap::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // check that the heap property holds and that the elements are popped in order
+    fn check_heap<T: Ord + Clone>(heap: &BinaryHeap<T>) -> bool {
+        let valid = heap.is_valid_heap();
+        let popped = heap.clone().to_vec();
+        valid && popped.windows(2).all(|pair| pair[0] >= pair[1])
+    }
+
+    #[cfg(feature = "proptest")]
+    mod proptest_tests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn arbitrary_heaps_are_valid(heap in any::<BinaryHeap<i16>>()) {
+                prop_assert!(check_heap(&heap));
+            }
+
+            #[test]
+            fn strategy_sizes(heap in binary_heap(any::<u8>(), 3..10)) {
+                prop_assert!((3..10).contains(&heap.size()));
+                prop_assert!(check_heap(&heap));
+            }
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    #[test]
+    fn quickcheck_heaps_are_valid() {
+        fn property(heap: BinaryHeap<i16>) -> bool {
+            check_heap(&heap) && quickcheck::Arbitrary::shrink(&heap).all(|h| check_heap(&h))
+        }
+        quickcheck::quickcheck(property as fn(BinaryHeap<i16>) -> bool);
+    }
+}
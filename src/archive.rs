@@ -0,0 +1,148 @@
+//! `rkyv` support
+//!
+//! With the `rkyv` feature, `BinaryHeap<T>` implements `rkyv::Archive`, `rkyv::Serialize`, and
+//! `rkyv::Deserialize`. Its archived form, [`ArchivedBinaryHeap`], keeps the array
+//! representation of the heap, so that the largest elements can be read directly from the
+//! archive (for instance, from a memory-mapped file) without deserializing it.
+
+use crate::{ArchivedBinaryHeap, BinaryHeap};
+use alloc::vec::Vec;
+use rkyv::{Archive, Archived};
+
+// element of the archive discovered during a search for the largest elements, ordered by value
+struct Candidate<'a, A: core::cmp::PartialOrd> {
+    value: &'a A,
+    index: usize,
+}
+
+impl<A: core::cmp::PartialOrd> PartialEq for Candidate<'_, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<A: core::cmp::PartialOrd> PartialOrd for Candidate<'_, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.value.partial_cmp(other.value)
+    }
+}
+
+impl<T> ArchivedBinaryHeap<T>
+    where T: core::cmp::PartialOrd + Archive,
+          Archived<T>: core::cmp::PartialOrd
+{
+    /// Get the number of elements in the archived heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    ///
+    /// Only available with the `rkyv` feature.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Return a reference to the root (maximum) element of the archived heap, or `None` if it is
+    /// empty
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    ///
+    /// Only available with the `rkyv` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{ArchivedBinaryHeap, BinaryHeap};
+    ///
+    /// let heap = BinaryHeap::from_vec(&[1u32, 3, 2]);
+    /// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&heap).unwrap();
+    /// let archived = rkyv::access::<ArchivedBinaryHeap<u32>, rkyv::rancor::Error>(&bytes)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(Some(&3), archived.peek().map(|x| x.to_native()).as_ref());
+    /// ```
+    #[inline]
+    pub fn peek(&self) -> Option<&Archived<T>> {
+        self.data.first()
+    }
+
+    /// Return references to the `k` largest elements of the archived heap (or to all its
+    /// elements if it has less than `k`), in non-increasing order
+    ///
+    /// The archive is not modified: the elements are found by exploring the tree from the root,
+    /// keeping the candidates in an auxiliary heap.
+    ///
+    /// Worst-case complexity: $\Theta(k \log k)$.
+    ///
+    /// Only available with the `rkyv` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{ArchivedBinaryHeap, BinaryHeap};
+    ///
+    /// let heap = BinaryHeap::from_vec(&[5u32, 1, 4, 2, 3]);
+    /// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&heap).unwrap();
+    /// let archived = rkyv::access::<ArchivedBinaryHeap<u32>, rkyv::rancor::Error>(&bytes)
+    ///     .unwrap();
+    /// let top_3: Vec<u32> = archived.peek_top_k(3).iter().map(|x| x.to_native()).collect();
+    ///
+    /// assert_eq!(vec![5, 4, 3], top_3);
+    /// ```
+    pub fn peek_top_k(&self, k: usize) -> Vec<&Archived<T>> {
+        let size = self.size();
+        let mut result = Vec::<&Archived<T>>::with_capacity(k.min(size));
+        let mut candidates = BinaryHeap::<Candidate<Archived<T>>>::new();
+        if let Some(root) = self.data.first() {
+            candidates.insert(Candidate { value: root, index: 0 });
+        }
+
+        // the largest remaining candidate is the next element; its children become candidates
+        while result.len() < k {
+            let Some(Candidate { value, index }) = candidates.pop() else { break };
+            result.push(value);
+            for child in [(index << 1) + 1, (index << 1) + 2] {
+                if child < size {
+                    candidates.insert(Candidate { value: &self.data[child], index: child });
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rkyv::rancor::Error;
+
+    #[test]
+    fn round_trip() {
+        let heap = BinaryHeap::from_vec(&[0i64, 2, -2, 4, 6, 8, -1, 1]);
+        let bytes = rkyv::to_bytes::<Error>(&heap).unwrap();
+        let archived = rkyv::access::<ArchivedBinaryHeap<i64>, Error>(&bytes).unwrap();
+        assert_eq!(8, archived.size());
+        let heap_2 = rkyv::deserialize::<BinaryHeap<i64>, Error>(archived).unwrap();
+        assert_eq!(heap.data, heap_2.data);
+    }
+
+    #[test]
+    fn peek_top_k() {
+        let values = [0i32, 2, -2, 4, 6, 8, -1, 1, 3, 5, 7];
+        let heap = BinaryHeap::from_vec(&values);
+        let bytes = rkyv::to_bytes::<Error>(&heap).unwrap();
+        let archived = rkyv::access::<ArchivedBinaryHeap<i32>, Error>(&bytes).unwrap();
+        let top = |k| archived.peek_top_k(k).iter().map(|x| x.to_native()).collect::<Vec<_>>();
+        assert_eq!(Vec::<i32>::new(), top(0));
+        assert_eq!(vec![8, 7, 6, 5], top(4));
+        assert_eq!(BinaryHeap::sort(&values), top(100));
+    }
+
+    #[test]
+    fn empty() {
+        let heap = BinaryHeap::<u8>::new();
+        let bytes = rkyv::to_bytes::<Error>(&heap).unwrap();
+        let archived = rkyv::access::<ArchivedBinaryHeap<u8>, Error>(&bytes).unwrap();
+        assert_eq!(None, archived.peek());
+        assert!(archived.peek_top_k(3).is_empty());
+    }
+}
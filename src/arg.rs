@@ -0,0 +1,190 @@
+//! Heap of indices into a borrowed slice
+
+use crate::{BinaryHeap, Compare, MaxComparator};
+use alloc::vec::Vec;
+
+// order of the indices, given by that of the elements they point to
+#[derive(Clone, Copy, Debug)]
+struct IndexComparator<'a, T, C> {
+    values: &'a [T],
+    comparator: C,
+}
+
+impl<T, C: Compare<T>> Compare<usize> for IndexComparator<'_, T, C> {
+    #[inline]
+    fn compare(&self, a: &usize, b: &usize) -> Option<core::cmp::Ordering> {
+        self.comparator.compare(&self.values[*a], &self.values[*b])
+    }
+}
+
+/// A heap of indices into a slice, ordered by the elements they point to
+///
+/// Only the indices are moved during sifts, which makes it suitable for large elements; popping
+/// all the indices gives them in the order of the elements, as an incremental argsort. The slice
+/// is borrowed, so that the elements can not change while the heap exists. To order indices by a
+/// key computed from each index instead, use a `BinaryHeap<usize>` with a `KeyComparator`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::ArgHeap;
+///
+/// let records = ["delta", "alpha", "charlie", "bravo"];
+///
+/// // indices of the records in decreasing order, computed only as far as needed
+/// let mut heap = ArgHeap::from_slice(&records);
+/// assert_eq!(Some(0), heap.pop());
+/// assert_eq!(Some(2), heap.pop());
+/// assert_eq!(Some(&"bravo"), heap.peek_value());
+///
+/// // indices can be inserted again
+/// heap.insert(0);
+/// assert_eq!(vec![0, 3, 1], heap.to_vec());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ArgHeap<'a, T, C = MaxComparator> {
+    heap: BinaryHeap<usize, IndexComparator<'a, T, C>>,
+}
+
+impl<'a, T: core::cmp::PartialOrd> ArgHeap<'a, T> {
+
+    /// Create a new empty `ArgHeap` of indices into `values`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new(values: &'a [T]) -> Self {
+        ArgHeap::with_comparator(values, MaxComparator)
+    }
+
+    /// Create an `ArgHeap` holding all the indices of `values`, ordered by the natural order of
+    /// `T`
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the length of `values`.
+    #[inline]
+    pub fn from_slice(values: &'a [T]) -> Self {
+        ArgHeap::from_slice_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<'a, T, C: Compare<T>> ArgHeap<'a, T, C> {
+
+    /// Create a new empty `ArgHeap` of indices into `values`, ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(values: &'a [T], comparator: C) -> Self {
+        ArgHeap { heap: BinaryHeap::with_comparator(IndexComparator { values, comparator }) }
+    }
+
+    /// Create an `ArgHeap` holding all the indices of `values`, ordered by `comparator`
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the length of `values`.
+    pub fn from_slice_with_comparator(values: &'a [T], comparator: C) -> Self {
+        let heap = BinaryHeap::from_vec_with_comparator(
+            (0..values.len()).collect(),
+            IndexComparator { values, comparator },
+        );
+        ArgHeap { heap }
+    }
+
+    /// Return the slice the indices point into
+    #[inline]
+    pub fn values(&self) -> &'a [T] {
+        self.heap.comparator.values
+    }
+
+    /// Get the size of the heap (number of indices)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert an index in the heap
+    ///
+    /// An index can be inserted several times.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of indices in the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of the bounds of the slice.
+    pub fn insert(&mut self, index: usize) {
+        assert!(index < self.values().len(), "index out of bounds");
+        self.heap.insert(index);
+    }
+
+    /// Return the index of the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<usize> {
+        self.heap.data.first().copied()
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_value(&self) -> Option<&'a T> {
+        self.peek().map(|index| &self.values()[index])
+    }
+
+    /// Remove and return the index of the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of indices in the heap.
+    #[inline]
+    pub fn pop(&mut self) -> Option<usize> {
+        self.heap.pop()
+    }
+
+    /// Consume the heap and return a vector of its indices, in non-increasing order of their
+    /// elements
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of indices in the heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<usize> {
+        self.collect()
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for ArgHeap<'_, T, C> {
+    type Item = usize;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heap.size_hint()
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for ArgHeap<'_, T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for ArgHeap<'_, T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::MinComparator;
+
+    #[test]
+    fn argsort() {
+        let mut next = lcg(12345);
+        let values: Vec<u64> = (0..1000).map(|_| next() % 100).collect();
+        let indices = ArgHeap::from_slice_with_comparator(&values, MinComparator).to_vec();
+        let mut expected: Vec<usize> = (0..values.len()).collect();
+        expected.sort_by_key(|&i| values[i]);
+        assert_eq!(expected.iter().map(|&i| values[i]).collect::<Vec<u64>>(),
+                   indices.iter().map(|&i| values[i]).collect::<Vec<u64>>());
+        let mut sorted_indices = indices.clone();
+        sorted_indices.sort_unstable();
+        assert_eq!((0..values.len()).collect::<Vec<usize>>(), sorted_indices);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn insert_out_of_bounds() {
+        let mut heap = ArgHeap::new(&[1, 2, 3]);
+        heap.insert(3);
+    }
+}
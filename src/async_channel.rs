@@ -0,0 +1,336 @@
+//! Asynchronous multi-producer, multi-consumer channel delivering the largest pending element
+//! first
+//!
+//! The pending elements are kept in a `StableBinaryHeap` behind a mutex, so that equal elements
+//! are received in the order they were sent. Receivers waiting for an element register their
+//! wakers in a queue, and each sent element wakes the receiver which has waited the longest; a
+//! woken receiver dropped before taking an element passes the wake-up on. The channel does not
+//! depend on a particular runtime, and can be used with tokio or any other executor.
+//!
+//! Only available with the `std` feature.
+
+use crate::channel::{RecvError, SendError, TryRecvError};
+use crate::{Compare, MaxComparator, StableBinaryHeap};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+// state shared by the senders and receivers
+#[derive(Debug)]
+struct State<T, C> {
+    heap: StableBinaryHeap<T, C>,       // pending elements
+    waiting: VecDeque<(u64, Waker)>,    // wakers of the waiting receivers, the oldest first
+    next_waiter: u64,                   // identifier of the next waiting receiver
+    senders: usize,                     // number of live senders
+    receivers: usize,                   // number of live receivers
+}
+
+// lock the state, ignoring poisoning: a panic in a comparison leaves the heap valid, even if an
+// element may be lost
+#[inline]
+fn lock<T, C>(state: &Mutex<State<T, C>>) -> MutexGuard<'_, State<T, C>> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Sending half of an asynchronous priority channel, which can be cloned to send from several
+/// tasks
+#[derive(Debug)]
+pub struct PrioritySender<T, C = MaxComparator> {
+    state: Arc<Mutex<State<T, C>>>,
+}
+
+/// Receiving half of an asynchronous priority channel, which can be cloned to receive from
+/// several tasks
+#[derive(Debug)]
+pub struct PriorityReceiver<T, C = MaxComparator> {
+    state: Arc<Mutex<State<T, C>>>,
+}
+
+/// Create an asynchronous channel delivering the largest pending element first, by the natural
+/// order of `T`, and equal elements in the order they were sent
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::async_channel::priority_channel;
+///
+/// #[derive(Debug, PartialEq, PartialOrd)]
+/// enum Message { Bulk(u32), Control(&'static str) }
+///
+/// # async fn service() {
+/// let (sender, receiver) = priority_channel();
+/// sender.send(Message::Bulk(1)).unwrap();
+/// sender.send(Message::Control("shutdown")).unwrap();
+/// sender.send(Message::Bulk(2)).unwrap();
+///
+/// // control-plane messages come first, then bulk ones (by the derived order)
+/// assert_eq!(Ok(Message::Control("shutdown")), receiver.recv().await);
+/// assert_eq!(Ok(Message::Bulk(2)), receiver.recv().await);
+/// # }
+/// ```
+#[inline]
+pub fn priority_channel<T: core::cmp::PartialOrd>() -> (PrioritySender<T>, PriorityReceiver<T>) {
+    priority_channel_with_comparator(MaxComparator)
+}
+
+/// Create an asynchronous channel delivering the largest pending element first according to
+/// `comparator`, and equal elements in the order they were sent
+pub fn priority_channel_with_comparator<T, C: Compare<T>>(comparator: C) -> (PrioritySender<T, C>, PriorityReceiver<T, C>) {
+    let state = Arc::new(Mutex::new(State {
+        heap: StableBinaryHeap::with_comparator(comparator),
+        waiting: VecDeque::new(),
+        next_waiter: 0,
+        senders: 1,
+        receivers: 1,
+    }));
+    (PrioritySender { state: state.clone() }, PriorityReceiver { state })
+}
+
+impl<T, C: Compare<T>> PrioritySender<T, C> {
+
+    /// Send an element, or return it in an error if all the receivers have been dropped
+    ///
+    /// The channel is unbounded, so sending never waits.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention.
+    pub fn send(&self, x: T) -> Result<(), SendError<T>> {
+        let mut state = lock(&self.state);
+        if state.receivers == 0 {
+            return Err(SendError(x));
+        }
+        state.heap.insert(x);
+        let waiter = state.waiting.pop_front();
+        drop(state);
+        if let Some((_, waker)) = waiter {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Get the number of pending elements
+    ///
+    /// The result may be out of date as soon as it is returned.
+    #[inline]
+    pub fn size(&self) -> usize {
+        lock(&self.state).heap.size()
+    }
+}
+
+impl<T, C: Compare<T>> PriorityReceiver<T, C> {
+
+    /// Return a future resolving to the largest pending element once there is one, or to an
+    /// error if the channel is empty and all the senders have been dropped
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention, once an element is available.
+    #[inline]
+    pub fn recv(&self) -> Recv<'_, T, C> {
+        Recv { receiver: self, waiter: None }
+    }
+
+    /// Receive the largest pending element without waiting
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = lock(&self.state);
+        match state.heap.pop() {
+            Some(x) => Ok(x),
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Get the number of pending elements
+    ///
+    /// The result may be out of date as soon as it is returned.
+    #[inline]
+    pub fn size(&self) -> usize {
+        lock(&self.state).heap.size()
+    }
+}
+
+/// Future returned by `PriorityReceiver::recv`
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Recv<'a, T, C = MaxComparator> {
+    receiver: &'a PriorityReceiver<T, C>,
+    waiter: Option<u64>,    // identifier of the registered waker, if any
+}
+
+impl<T, C: Compare<T>> Future for Recv<'_, T, C> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = lock(&self.receiver.state);
+        if let Some(x) = state.heap.pop() {
+            if let Some(id) = self.waiter.take() {
+                state.waiting.retain(|(waiter, _)| *waiter != id);
+            }
+            return Poll::Ready(Ok(x));
+        }
+        if state.senders == 0 {
+            return Poll::Ready(Err(RecvError));
+        }
+
+        // register the waker, or update it if it is still queued, keeping its place otherwise
+        let registered = self.waiter.and_then(|id| state.waiting.iter_mut().find(|(waiter, _)| *waiter == id));
+        match registered {
+            Some((_, waker)) => waker.clone_from(cx.waker()),
+            None => {
+                let id = state.next_waiter;
+                state.next_waiter += 1;
+                state.waiting.push_back((id, cx.waker().clone()));
+                drop(state);
+                self.waiter = Some(id);
+            },
+        }
+        Poll::Pending
+    }
+}
+
+// a receiver woken up for an element it did not take passes the wake-up on
+impl<T, C> Drop for Recv<'_, T, C> {
+    fn drop(&mut self) {
+        let Some(id) = self.waiter else { return };
+        let mut state = lock(&self.receiver.state);
+        let queued = state.waiting.iter().position(|(waiter, _)| *waiter == id);
+        let next = match queued {
+            Some(position) => {
+                state.waiting.remove(position);
+                None
+            },
+            // woken up, possibly for an element: the next receiver checks
+            None => state.waiting.pop_front(),
+        };
+        drop(state);
+        if let Some((_, waker)) = next {
+            waker.wake();
+        }
+    }
+}
+
+impl<T, C> Clone for PrioritySender<T, C> {
+    fn clone(&self) -> Self {
+        lock(&self.state).senders += 1;
+        PrioritySender { state: self.state.clone() }
+    }
+}
+
+impl<T, C> Clone for PriorityReceiver<T, C> {
+    fn clone(&self) -> Self {
+        lock(&self.state).receivers += 1;
+        PriorityReceiver { state: self.state.clone() }
+    }
+}
+
+// the last sender wakes up the waiting receivers, so that they return errors
+impl<T, C> Drop for PrioritySender<T, C> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.state);
+        state.senders -= 1;
+        if state.senders == 0 {
+            let waiting = core::mem::take(&mut state.waiting);
+            drop(state);
+            for (_, waker) in waiting {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T, C> Drop for PriorityReceiver<T, C> {
+    fn drop(&mut self) {
+        lock(&self.state).receivers -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Wake;
+    use std::vec::Vec;
+
+    // waker unparking a thread, counting its wake-ups
+    struct ThreadWaker {
+        thread: std::thread::Thread,
+        wakes: AtomicUsize,
+    }
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.wakes.fetch_add(1, Ordering::SeqCst);
+            self.thread.unpark();
+        }
+    }
+
+    // run a future to completion on the current thread
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker { thread: std::thread::current(), wakes: AtomicUsize::new(0) }));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    #[test]
+    fn fifo_between_equal_priorities() {
+        let (sender, receiver) = priority_channel_with_comparator(crate::KeyComparator(|m: &(u8, u32)| m.0));
+        for (i, priority) in [1, 3, 1, 3, 2, 1].into_iter().enumerate() {
+            sender.send((priority, i as u32)).unwrap();
+        }
+        drop(sender);
+        let received: Vec<_> = core::iter::from_fn(|| block_on(receiver.recv()).ok()).collect();
+        assert_eq!(vec![(3, 1), (3, 3), (2, 4), (1, 0), (1, 2), (1, 5)], received);
+        assert_eq!(Err(TryRecvError::Disconnected), receiver.try_recv());
+    }
+
+    #[test]
+    fn waiting_receivers() {
+        let (sender, receiver) = priority_channel();
+        let received = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let receiver = receiver.clone();
+                let received = &received;
+                scope.spawn(move || {
+                    while let Ok(x) = block_on(receiver.recv()) {
+                        received.lock().unwrap().push(x);
+                    }
+                });
+            }
+            for i in 0..1000u32 {
+                sender.send(i).unwrap();
+            }
+            drop(sender);
+        });
+        let mut received = received.into_inner().unwrap();
+        received.sort_unstable();
+        assert_eq!((0..1000).collect::<Vec<_>>(), received);
+    }
+
+    #[test]
+    fn dropped_future_passes_wake_up_on() {
+        let (sender, receiver) = priority_channel::<u32>();
+        let (first, second) = (receiver.recv(), receiver.recv());
+        let (mut first, mut second) = (Box::pin(first), Box::pin(second));
+        let wakers: Vec<_> = (0..2).map(|_| Arc::new(ThreadWaker { thread: std::thread::current(), wakes: AtomicUsize::new(0) })).collect();
+        assert!(first.as_mut().poll(&mut Context::from_waker(&Waker::from(wakers[0].clone()))).is_pending());
+        assert!(second.as_mut().poll(&mut Context::from_waker(&Waker::from(wakers[1].clone()))).is_pending());
+
+        // the first future is woken up, but dropped, so the second one is woken up in turn
+        sender.send(7).unwrap();
+        assert_eq!((1, 0), (wakers[0].wakes.load(Ordering::SeqCst), wakers[1].wakes.load(Ordering::SeqCst)));
+        drop(first);
+        assert_eq!(1, wakers[1].wakes.load(Ordering::SeqCst));
+        assert_eq!(Poll::Ready(Ok(7)), second.as_mut().poll(&mut Context::from_waker(&Waker::from(wakers[1].clone()))));
+    }
+}
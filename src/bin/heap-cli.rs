@@ -0,0 +1,288 @@
+//! Sort lines or select the largest or smallest ones using a binary heap
+//!
+//! Lines are read from the standard input, or from files for `kmerge`; see `USAGE` for the
+//! commands and options. `topk` and `bottomk` only keep `N` lines in memory, and `kmerge` one
+//! line per file.
+
+use binary_heap::BinaryHeap;
+use binary_heap::merge::kmerge_lines_by_key;
+use std::cmp::{Ordering, Reverse};
+use std::io::{BufRead, Write};
+
+const USAGE: &str = "\
+usage: heap-cli [-n|--numeric] [-k|--keyed] [-r|--reverse]
+                (sort | topk N | bottomk N | kmerge FILE...)
+
+Read lines from the standard input (or from files for kmerge) and print them, ordered using a
+binary heap.
+
+commands:
+  sort            print all lines, from the largest to the smallest
+  topk N          print the N largest lines, from the largest
+  bottomk N       print the N smallest lines, from the smallest
+  kmerge FILE...  merge files sorted from the smallest to the largest line
+
+options:
+  -n, --numeric   compare keys as numbers instead of strings
+  -k, --keyed     each line is `key<TAB>value`; lines are compared by key only
+  -r, --reverse   for kmerge: the files are sorted from the largest to the smallest line";
+
+// what to print
+#[derive(Debug, PartialEq)]
+enum Command {
+    Sort,
+    TopK(usize),
+    BottomK(usize),
+    KMerge(Vec<String>),
+}
+
+// parsed command-line arguments
+#[derive(Debug, PartialEq)]
+struct Config {
+    command: Command,
+    numeric: bool,
+    keyed: bool,
+    reverse: bool,
+}
+
+// key of a line
+#[derive(PartialEq)]
+enum Key {
+    Number(f64),
+    Text(String),
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Key::Number(x), Key::Number(y)) => x.partial_cmp(y),
+            (Key::Text(x), Key::Text(y)) => x.partial_cmp(y),
+            _ => None,
+        }
+    }
+}
+
+// a line of the input with its key; lines are compared by key only
+struct Line {
+    key: Key,
+    text: String,
+}
+
+impl PartialEq for Line {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl PartialOrd for Line {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Config, String> {
+    let mut numeric = false;
+    let mut keyed = false;
+    let mut reverse = false;
+    let mut positional = Vec::<String>::new();
+    for arg in args {
+        match arg.as_str() {
+            "-n" | "--numeric" => numeric = true,
+            "-k" | "--keyed" => keyed = true,
+            "-r" | "--reverse" => reverse = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+    let count = |n: Option<&String>| -> Result<usize, String> {
+        let n = n.ok_or("missing number of lines")?;
+        n.parse().map_err(|_| format!("invalid number of lines: {}", n))
+    };
+    let command = match positional.first().map(String::as_str) {
+        Some("sort") if positional.len() == 1 => Command::Sort,
+        Some("topk") if positional.len() <= 2 => Command::TopK(count(positional.get(1))?),
+        Some("bottomk") if positional.len() <= 2 => Command::BottomK(count(positional.get(1))?),
+        Some("kmerge") => Command::KMerge(positional[1..].to_vec()),
+        Some(command @ ("sort" | "topk" | "bottomk")) => {
+            return Err(format!("too many arguments for {}", command))
+        },
+        Some(command) => return Err(format!("unknown command: {}", command)),
+        None => return Err(String::from("missing command")),
+    };
+    Ok(Config { command, numeric, keyed, reverse })
+}
+
+fn parse_key(text: &str, config: &Config) -> Result<Key, String> {
+    let key = if config.keyed {
+        match text.split_once('\t') {
+            Some((key, _)) => key,
+            None => return Err(format!("missing tab in line: {}", text)),
+        }
+    } else {
+        text
+    };
+    if config.numeric {
+        match key.trim().parse::<f64>() {
+            Ok(x) if !x.is_nan() => Ok(Key::Number(x)),
+            _ => Err(format!("invalid numeric key: {}", key)),
+        }
+    } else {
+        Ok(Key::Text(String::from(key)))
+    }
+}
+
+fn parse_line(text: String, config: &Config) -> Result<Line, String> {
+    Ok(Line { key: parse_key(&text, config)?, text })
+}
+
+// merge sorted inputs
+fn kmerge<R: BufRead, W: Write>(config: &Config, inputs: Vec<R>, output: W)
+    -> Result<(), String>
+{
+    let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let result = if config.reverse {
+        kmerge_lines_by_key(inputs, output, |line| {
+            parse_key(line, config).map(Reverse).map_err(invalid)
+        })
+    } else {
+        kmerge_lines_by_key(inputs, output, |line| parse_key(line, config).map_err(invalid))
+    };
+    result.map_err(|e| e.to_string())
+}
+
+// insert `x` in a heap holding at most `k` elements, dropping the largest one if needed
+fn insert_bounded<T: PartialOrd>(heap: &mut BinaryHeap<T>, x: T, k: usize) {
+    heap.insert(x);
+    if heap.size() > k {
+        heap.pop();
+    }
+}
+
+fn run<R: BufRead, W: Write>(config: &Config, input: R, mut output: W) -> Result<(), String> {
+    let lines = input.lines().map(|line| {
+        line.map_err(|e| e.to_string()).and_then(|text| parse_line(text, config))
+    });
+    let io_error = |e: std::io::Error| e.to_string();
+    match &config.command {
+        Command::Sort => {
+            let heap = BinaryHeap::from(lines.collect::<Result<Vec<Line>, String>>()?);
+            for line in heap {
+                writeln!(output, "{}", line.text).map_err(io_error)?;
+            }
+        },
+        &Command::TopK(k) => {
+            // the smallest of the `k` largest lines so far is at the root
+            let mut heap = BinaryHeap::<Reverse<Line>>::new();
+            for line in lines {
+                insert_bounded(&mut heap, Reverse(line?), k);
+            }
+            let mut top = heap.map(|Reverse(line)| line).collect::<Vec<Line>>();
+            top.reverse();
+            for line in top {
+                writeln!(output, "{}", line.text).map_err(io_error)?;
+            }
+        },
+        &Command::BottomK(k) => {
+            // the largest of the `k` smallest lines so far is at the root
+            let mut heap = BinaryHeap::<Line>::new();
+            for line in lines {
+                insert_bounded(&mut heap, line?, k);
+            }
+            let mut bottom = heap.collect::<Vec<Line>>();
+            bottom.reverse();
+            for line in bottom {
+                writeln!(output, "{}", line.text).map_err(io_error)?;
+            }
+        },
+        Command::KMerge(paths) => {
+            let inputs = paths.iter()
+                .map(|path| {
+                    std::fs::File::open(path)
+                        .map(std::io::BufReader::new)
+                        .map_err(|e| format!("{}: {}", path, e))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            kmerge(config, inputs, output)?;
+        },
+    }
+    Ok(())
+}
+
+fn main() {
+    let config = match parse_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("heap-cli: {}\n\n{}", message, USAGE);
+            std::process::exit(2);
+        },
+    };
+    let stdout = std::io::stdout();
+    if let Err(message) = run(&config, std::io::stdin().lock(), stdout.lock()) {
+        eprintln!("heap-cli: {}", message);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_with(args: &[&str], input: &str) -> Result<String, String> {
+        let config = parse_args(args.iter().map(|arg| String::from(*arg)))?;
+        let mut output = Vec::<u8>::new();
+        run(&config, input.as_bytes(), &mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Config { command: Command::TopK(3), numeric: true, keyed: false,
+                               reverse: false }),
+                   parse_args(["-n", "topk", "3"].iter().map(|arg| String::from(*arg))));
+        assert!(run_with(&["topk"], "").is_err());
+        assert!(run_with(&["topk", "x"], "").is_err());
+        assert!(run_with(&["sort", "3"], "").is_err());
+        assert!(run_with(&["shuffle"], "").is_err());
+        assert!(run_with(&["--verbose", "sort"], "").is_err());
+    }
+
+    #[test]
+    fn sort() {
+        assert_eq!("c\nb\na\n", run_with(&["sort"], "b\nc\na\n").unwrap());
+        assert_eq!("10\n9\n-1\n", run_with(&["-n", "sort"], "9\n-1\n10\n").unwrap());
+    }
+
+    #[test]
+    fn top_and_bottom() {
+        let input = "5\n1\n4\n1\n5\n9\n2\n6\n";
+        assert_eq!("9\n6\n5\n", run_with(&["-n", "topk", "3"], input).unwrap());
+        assert_eq!("1\n1\n2\n", run_with(&["-n", "bottomk", "3"], input).unwrap());
+        assert_eq!("", run_with(&["-n", "topk", "0"], input).unwrap());
+    }
+
+    #[test]
+    fn keyed() {
+        let input = "2\tread\n10\twrite\n7\tcook\n";
+        assert_eq!("10\twrite\n7\tcook\n", run_with(&["-k", "-n", "topk", "2"], input).unwrap());
+        // as strings, "7" > "2" > "10"
+        assert_eq!("7\tcook\n2\tread\n10\twrite\n", run_with(&["--keyed", "sort"], input).unwrap());
+        assert!(run_with(&["-k", "sort"], "no tab\n").is_err());
+        assert!(run_with(&["-n", "sort"], "NaN\n").is_err());
+    }
+
+    #[test]
+    fn kmerge_inputs() {
+        let merged = |args: &[&str], inputs: &[&str]| {
+            let config = parse_args(args.iter().map(|arg| String::from(*arg))).unwrap();
+            let mut output = Vec::<u8>::new();
+            kmerge(&config, inputs.iter().map(|input| input.as_bytes()).collect(), &mut output)
+                .map(|_| String::from_utf8(output).unwrap())
+        };
+        assert_eq!("2\n9\n10\n11\n",
+                   merged(&["-n", "kmerge"], &["2\n10\n", "9\n11\n"]).unwrap());
+        assert_eq!("11\n10\n9\n2\n",
+                   merged(&["-n", "-r", "kmerge"], &["10\n2\n", "11\n9\n"]).unwrap());
+        assert!(merged(&["-n", "kmerge"], &["x\n"]).is_err());
+        assert!(run_with(&["kmerge", "/nonexistent/file"], "").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Binomial heap
+//!
+//! A binomial heap is a list of heap-ordered trees with distinct ranks: a tree of rank $k$ has a
+//! root whose children are trees of ranks $0, 1, \dots, k - 1$, and $2^k$ nodes. The ranks of the
+//! trees of a heap of $n$ elements are the positions of the ones in the binary representation of
+//! $n$, and melding two heaps is like adding two binary numbers: two trees of the same rank are
+//! linked into a tree of the next rank, the smaller root becoming the last child of the other.
+//! (Vuillemin, *A data structure for manipulating priority queues*, 1978.)
+
+use crate::{Compare, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+// heap-ordered tree, whose children have ranks 0, 1, ..., rank - 1 in this order
+#[derive(Clone, Debug)]
+struct Tree<T> {
+    value: T,
+    children: Vec<Tree<T>>,
+}
+
+/// A mergeable heap with logarithmic worst-case operations
+///
+/// `insert`, `pop`, and `meld` take $O(\log n)$ time in the worst case. The structure is
+/// entirely different from that of `BinaryHeap`, which makes it a useful reference to test other
+/// heaps against.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinomialHeap;
+///
+/// let mut heap = BinomialHeap::from(vec![3, 1, 4]);
+/// let mut other = BinomialHeap::from(vec![1, 5, 9]);
+/// other.insert(2);
+/// heap.meld(other);
+///
+/// assert_eq!(7, heap.size());
+/// assert_eq!(vec![9, 5, 4, 3, 2, 1, 1], heap.to_vec());
+/// ```
+#[derive(Clone, Debug)]
+pub struct BinomialHeap<T, C = MaxComparator> {
+    trees: Vec<Option<Tree<T>>>,    // tree of each rank, if any
+    size: usize,                    // number of elements
+    comparator: C,                  // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> BinomialHeap<T> {
+
+    /// Create a new empty `BinomialHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        BinomialHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> BinomialHeap<T, C> {
+
+    /// Create a new empty `BinomialHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        BinomialHeap { trees: Vec::new(), size: 0, comparator }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $O(\log n)$, where $n$ is the number of elements in the heap
+    /// ($\Theta(1)$ amortized).
+    pub fn insert(&mut self, x: T) {
+        self.add_tree(Tree { value: x, children: Vec::new() }, 0);
+        self.size += 1;
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.max_rank().map(|rank| &self.trees[rank].as_ref().unwrap().value)
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let rank = self.max_rank()?;
+        let tree = self.trees[rank].take().unwrap();
+        while let Some(None) = self.trees.last() {
+            self.trees.pop();
+        }
+
+        // the children form a binomial heap, which is melded with the other trees
+        for (rank, child) in tree.children.into_iter().enumerate() {
+            self.add_tree(child, rank);
+        }
+        self.size -= 1;
+        Some(tree.value)
+    }
+
+    /// Move all the elements of `other` into this heap
+    ///
+    /// Worst-case complexity: $O(\log n + \log m)$, where $n$ and $m$ are the numbers of elements
+    /// in the two heaps.
+    pub fn meld(&mut self, other: BinomialHeap<T, C>) {
+        for (rank, tree) in other.trees.into_iter().enumerate() {
+            if let Some(tree) = tree {
+                self.add_tree(tree, rank);
+            }
+        }
+        self.size += other.size;
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the
+    /// heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // rank of the tree with the largest root
+    fn max_rank(&self) -> Option<usize> {
+        let mut max: Option<usize> = None;
+        for (rank, tree) in self.trees.iter().enumerate() {
+            if let Some(tree) = tree {
+                if max.is_none_or(|max| self.less(&self.trees[max].as_ref().unwrap().value, &tree.value)) {
+                    max = Some(rank);
+                }
+            }
+        }
+        max
+    }
+
+    // add a tree of rank `rank`, propagating the carry
+    fn add_tree(&mut self, mut tree: Tree<T>, mut rank: usize) {
+        loop {
+            if rank >= self.trees.len() {
+                self.trees.resize_with(rank + 1, || None);
+            }
+            match self.trees[rank].take() {
+                None => {
+                    self.trees[rank] = Some(tree);
+                    return;
+                },
+                Some(other) => {
+                    tree = self.link(tree, other);
+                    rank += 1;
+                },
+            }
+        }
+    }
+
+    // link two trees of the same rank into a tree of the next rank
+    fn link(&self, a: Tree<T>, b: Tree<T>) -> Tree<T> {
+        let (mut parent, child) = if self.less(&a.value, &b.value) { (b, a) } else { (a, b) };
+        parent.children.push(child);
+        parent
+    }
+
+    #[inline]
+    fn less(&self, a: &T, b: &T) -> bool {
+        self.comparator.compare(a, b) == Some(Ordering::Less)
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for BinomialHeap<T> {
+
+    /// Build a `BinomialHeap` from a vector
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        let mut heap = BinomialHeap::new();
+        for x in values {
+            heap.insert(x);
+        }
+        heap
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for BinomialHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for BinomialHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for BinomialHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for BinomialHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+
+    // check the shapes of the trees and the heap property
+    fn check<T, C: Compare<T>>(heap: &BinomialHeap<T, C>) {
+        fn check_tree<T, C: Compare<T>>(heap: &BinomialHeap<T, C>, tree: &Tree<T>, rank: usize) -> usize {
+            assert_eq!(rank, tree.children.len());
+            let mut size = 1;
+            for (rank, child) in tree.children.iter().enumerate() {
+                assert!(!heap.less(&tree.value, &child.value));
+                size += check_tree(heap, child, rank);
+            }
+            size
+        }
+        let mut size = 0;
+        for (rank, tree) in heap.trees.iter().enumerate() {
+            if let Some(tree) = tree {
+                size += check_tree(heap, tree, rank);
+            }
+        }
+        assert_eq!(heap.size(), size);
+        assert_eq!(size, size & ((1 << heap.trees.len()) - 1));
+    }
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let mut heap = BinomialHeap::new();
+        let mut reference = BinaryHeap::new();
+        for _ in 0..5000 {
+            match next() % 5 {
+                0 | 1 => assert_eq!(reference.pop(), heap.pop()),
+                2 => {
+                    let values: Vec<u64> = (0..(next() % 10)).map(|_| next() % 1000).collect();
+                    for &x in values.iter() {
+                        reference.insert(x);
+                    }
+                    heap.meld(BinomialHeap::from(values));
+                },
+                _ => {
+                    let x = next() % 1000;
+                    heap.insert(x);
+                    reference.insert(x);
+                },
+            }
+            check(&heap);
+            assert_eq!(reference.size(), heap.size());
+            assert_eq!(reference.get_max().as_ref(), heap.peek());
+        }
+        assert_eq!(reference.to_vec(), heap.to_vec());
+    }
+    #[test]
+    fn meld_larger_heap() {
+        let mut heap = BinomialHeap::new();
+        heap.meld(BinomialHeap::from(vec![1, 2, 3, 4]));
+        check(&heap);
+        assert_eq!(4, heap.size());
+
+        let mut heap = BinomialHeap::from(vec![5]);
+        heap.meld(BinomialHeap::from(vec![1, 2, 3, 4, 6, 7, 8, 9]));
+        check(&heap);
+        assert_eq!(vec![9, 8, 7, 6, 5, 4, 3, 2, 1], heap.to_vec());
+    }
+}
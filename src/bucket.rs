@@ -0,0 +1,156 @@
+//! Bucket queue for small ranges of integer priorities
+//!
+//! A bucket queue holds one FIFO queue per priority, and a cursor on the highest priority which
+//! may have a non-empty queue: no element is ever compared with another one.
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+/// A priority queue of values of type `V` with integer priorities in `0..N`
+///
+/// It provides the same functions as `PriorityQueue`: `pop` returns a pair with the highest
+/// priority, pairs with equal priorities being returned in the order in which they were inserted.
+/// `insert` takes $\Theta(1)$ time and `pop` $O(N)$ time, but only $\Theta(1)$ amortized time if
+/// the priorities of the popped pairs do not increase by more than a constant between pops. With
+/// few priorities, like the 256 levels of the default, it is faster than a heap.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BucketQueue;
+///
+/// let mut scheduler: BucketQueue<&str> = BucketQueue::new();
+/// scheduler.insert(10, "logging");
+/// scheduler.insert(200, "interrupt");
+/// scheduler.insert(10, "telemetry");
+///
+/// assert_eq!(Some((200, "interrupt")), scheduler.pop());
+/// assert_eq!(Some((10, "logging")), scheduler.pop());
+/// assert_eq!(Some((10, "telemetry")), scheduler.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct BucketQueue<V, const N: usize = 256> {
+    buckets: Vec<VecDeque<V>>,  // values of each priority, in insertion order
+    top: usize,                 // all the buckets above this one are empty
+    size: usize,                // number of pairs
+}
+
+impl<V, const N: usize> BucketQueue<V, N> {
+
+    /// Create a new empty `BucketQueue`
+    #[inline]
+    pub fn new() -> Self {
+        const { assert!(N >= 1, "a bucket queue needs at least one priority") };
+        BucketQueue { buckets: (0..N).map(|_| VecDeque::new()).collect(), top: 0, size: 0 }
+    }
+
+    /// Get the number of (priority, value) pairs in the queue
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert `value` with priority `priority`
+    ///
+    /// Worst-case complexity: $\Theta(1)$ (amortized, as the bucket may grow).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `priority` is not smaller than `N`.
+    pub fn insert(&mut self, priority: usize, value: V) {
+        assert!(priority < N, "priority out of range");
+        self.buckets[priority].push_back(value);
+        self.top = self.top.max(priority);
+        self.size += 1;
+    }
+
+    /// Remove and return a pair with the highest priority (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $O(N)$.
+    pub fn pop(&mut self) -> Option<(usize, V)> {
+        if self.size == 0 {
+            return None;
+        }
+        while self.buckets[self.top].is_empty() {
+            self.top -= 1;
+        }
+        self.size -= 1;
+        self.buckets[self.top].pop_front().map(|value| (self.top, value))
+    }
+
+    /// Return the highest priority (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $O(N)$.
+    #[inline]
+    pub fn peek_priority(&self) -> Option<usize> {
+        self.peek().map(|(priority, _)| priority)
+    }
+
+    /// Return a pair with the highest priority, with a reference to its value (or `None` if the
+    /// queue is empty)
+    ///
+    /// Worst-case complexity: $O(N)$.
+    pub fn peek(&self) -> Option<(usize, &V)> {
+        (0..=self.top).rev().find_map(|priority| {
+            self.buckets[priority].front().map(|value| (priority, value))
+        })
+    }
+}
+
+impl<V, const N: usize> core::default::Default for BucketQueue<V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pairs are returned from the highest to the lowest priority
+impl<V, const N: usize> Iterator for BucketQueue<V, N> {
+    type Item = (usize, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<V, const N: usize> ExactSizeIterator for BucketQueue<V, N> {}
+
+impl<V, const N: usize> core::iter::FusedIterator for BucketQueue<V, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::{KeyComparator, StableBinaryHeap};
+
+    #[test]
+    fn against_stable_heap() {
+        let mut next = lcg(12345);
+        let mut queue: BucketQueue<u32, 16> = BucketQueue::new();
+        let mut reference = StableBinaryHeap::with_comparator(KeyComparator(|x: &(usize, u32)| x.0));
+        for i in 0..5000 {
+            if next().is_multiple_of(3) {
+                assert_eq!(reference.pop(), queue.pop());
+            } else {
+                let priority = (next() % 16) as usize;
+                queue.insert(priority, i);
+                reference.insert((priority, i));
+            }
+            assert_eq!(reference.size(), queue.size());
+            assert_eq!(reference.peek().map(|&(priority, _)| priority), queue.peek_priority());
+        }
+        let expected: Vec<(usize, u32)> = core::iter::from_fn(|| reference.pop()).collect();
+        assert_eq!(expected, queue.collect::<Vec<(usize, u32)>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {
+        let mut queue: BucketQueue<()> = BucketQueue::new();
+        queue.insert(256, ());
+    }
+}
@@ -0,0 +1,280 @@
+//! Operations on all the elements of a heap, followed by a single rebuild
+
+use crate::{BinaryHeap, Compare, IncomparablePolicy};
+use alloc::vec::Vec;
+
+impl<T, C: Compare<T>, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Modify every element of the heap with `f`, then restore the heap property
+    ///
+    /// The elements are visited in the order of the heap array (not in sorted order). The heap is
+    /// rebuilt once, which is faster than popping, modifying, and inserting each element.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ calls to `f` and comparisons, where $n$ is the number
+    /// of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// // (urgency, task) pairs
+    /// let mut heap = BinaryHeap::from(vec![(3, 'a'), (5, 'b'), (1, 'c')]);
+    ///
+    /// // recompute the urgency of every task
+    /// heap.adjust_all(|(urgency, task)| if *task == 'c' { *urgency += 10 });
+    ///
+    /// assert_eq!(Some((11, 'c')), heap.pop());
+    /// ```
+    pub fn adjust_all<F: FnMut(&mut T)>(&mut self, f: F) {
+        self.data.iter_mut().for_each(f);
+        self.rebuild();
+    }
+
+    /// Modify every element of the heap with `f`, keeping only those for which it returns `true`,
+    /// then restore the heap property
+    ///
+    /// The elements are visited in the order of the heap array (not in sorted order), and the
+    /// heap is rebuilt once.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ calls to `f` and comparisons, where $n$ is the number
+    /// of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// // cache entries with decaying scores
+    /// let mut cache = BinaryHeap::from(vec![(8, "a"), (3, "b"), (6, "c")]);
+    ///
+    /// // halve the scores, evicting the entries whose score drops below 2
+    /// cache.retain_mut(|(score, _)| {
+    ///     *score /= 2;
+    ///     *score >= 2
+    /// });
+    ///
+    /// assert_eq!(vec![(4, "a"), (3, "c")], cache.to_vec());
+    /// ```
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, f: F) {
+        self.data.retain_mut(f);
+        self.rebuild();
+    }
+
+    /// Consume the heap and build a heap of the images of its elements by `f`, with the same
+    /// comparator
+    ///
+    /// The images are ordered by the comparator of this heap (which must also compare them, as
+    /// `MaxComparator` and `MinComparator` do), with the same policy for incomparable elements,
+    /// in a heap of the same arity. The elements are mapped in the order of the heap array, and
+    /// the new heap is built in a single pass (the memory of the array is reused when `T` and
+    /// `U` have the same size and alignment).
+    ///
+    /// This method is not called `map`, which is `Iterator::map` for a heap (yielding the mapped
+    /// elements in order).
+    ///
+    /// Worst-case complexity: $\Theta(n)$ calls to `f` and comparisons, where $n$ is the number
+    /// of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from(vec![-3, 1, 2]);
+    /// let squares = heap.transform(|x| x * x);
+    ///
+    /// assert_eq!(vec![9, 4, 1], squares.to_vec());
+    /// ```
+    pub fn transform<U, F: FnMut(T) -> U>(self, f: F) -> BinaryHeap<U, C, D>
+        where C: Compare<U>
+    {
+        let BinaryHeap { data, comparator, policy, .. } = self;
+        let mut heap = mapped(data, f, comparator, policy);
+        heap.rebuild();
+        heap
+    }
+
+    /// Consume the heap and build a heap of the images of its elements by `f`, ordered by
+    /// `comparator`
+    ///
+    /// As `transform`, for images which the comparator of this heap does not compare, or which
+    /// should be ordered differently.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ calls to `f` and comparisons, where $n$ is the number
+    /// of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, MinComparator};
+    ///
+    /// // (priority, name) pairs, mapped to the lengths of the names, shortest first
+    /// let heap = BinaryHeap::from(vec![(2, "alpha"), (5, "pi"), (1, "beta")]);
+    /// let lengths = heap.transform_with_comparator(|(_, name)| name.len(), MinComparator);
+    ///
+    /// assert_eq!(vec![2, 4, 5], lengths.to_vec());
+    /// ```
+    pub fn transform_with_comparator<U, C2: Compare<U>, F: FnMut(T) -> U>(self, f: F, comparator: C2)
+        -> BinaryHeap<U, C2, D>
+    {
+        let mut heap = mapped(self.data, f, comparator, self.policy);
+        heap.rebuild();
+        heap
+    }
+
+    /// Consume the heap and build a heap of the images of its elements by a monotone function
+    /// `f`, with the same comparator, reusing the layout of the heap array
+    ///
+    /// The caller asserts that `f` preserves the order: if `x` is not smaller than `y` for the
+    /// comparator of this heap, then neither is `f(x)` than `f(y)`. The images then satisfy the
+    /// heap property at the same positions, so no comparison is needed. If `f` is not monotone,
+    /// the new heap does not satisfy the heap property, and its elements will be popped in an
+    /// unspecified order (with the `debug-validate` feature, this panics in debug builds).
+    ///
+    /// Worst-case complexity: $\Theta(n)$ calls to `f`, where $n$ is the number of elements in the
+    /// heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from(vec![3, 1, 2]);
+    ///
+    /// // adding a constant offset preserves the order
+    /// let shifted = heap.transform_monotone(|x| x as f64 + 0.5);
+    ///
+    /// assert_eq!(vec![3.5, 2.5, 1.5], shifted.to_vec());
+    /// ```
+    pub fn transform_monotone<U, F: FnMut(T) -> U>(self, f: F) -> BinaryHeap<U, C, D>
+        where C: Compare<U>
+    {
+        let BinaryHeap { data, comparator, policy, .. } = self;
+        let heap = mapped(data, f, comparator, policy);
+        heap.debug_validate();
+        heap
+    }
+
+    /// Consume the heap and build a heap of the images of its elements by a monotone function
+    /// `f`, ordered by `comparator`, reusing the layout of the heap array
+    ///
+    /// As `transform_monotone`, where `f` maps the order of this heap to the order given by
+    /// `comparator`: if `x` is not smaller than `y` for the comparator of this heap, then `f(x)`
+    /// is not smaller than `f(y)` for `comparator`.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ calls to `f`, where $n$ is the number of elements in the
+    /// heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, MaxComparator, MinComparator};
+    ///
+    /// // a min-heap of costs, mapped by a decreasing function to a max-heap of gains
+    /// let costs = BinaryHeap::from_vec_with_comparator(vec![3, 1, 2], MinComparator);
+    /// let gains = costs.transform_monotone_with_comparator(|cost| 10 - cost, MaxComparator);
+    ///
+    /// assert_eq!(vec![9, 8, 7], gains.to_vec());
+    /// ```
+    pub fn transform_monotone_with_comparator<U, C2: Compare<U>, F: FnMut(T) -> U>(self, f: F, comparator: C2)
+        -> BinaryHeap<U, C2, D>
+    {
+        let heap = mapped(self.data, f, comparator, self.policy);
+        heap.debug_validate();
+        heap
+    }
+}
+
+// heap ordered by `comparator`, with the policy `policy`, of the images by `f` of the elements of
+// `data`, at the same positions
+fn mapped<T, U, C: Compare<U>, F: FnMut(T) -> U, const D: usize>(data: Vec<T>, f: F, comparator: C, policy: IncomparablePolicy)
+    -> BinaryHeap<U, C, D>
+{
+    let mut heap = BinaryHeap::dary_with_comparator(comparator);
+    heap.policy = policy;
+    heap.data = data.into_iter().map(f).collect();
+    heap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DaryHeap, MinComparator};
+
+    #[test]
+    fn adjust_all_1() {
+        let mut heap = BinaryHeap::from((0..100).collect::<Vec<i32>>());
+        heap.adjust_all(|x| *x = (*x - 42).abs());
+        assert_eq!(100, heap.size());
+        let mut expected: Vec<i32> = (0..100).map(|x: i32| (x - 42).abs()).collect();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(expected, heap.to_vec());
+    }
+
+    #[test]
+    fn retain_mut_1() {
+        let mut heap = BinaryHeap::from((0..100).collect::<Vec<u32>>());
+        heap.retain_mut(|x| {
+            *x = (*x * 37) % 101;
+            *x % 3 != 0
+        });
+        let mut expected: Vec<u32> = (0..100).map(|x| (x * 37) % 101).filter(|x| x % 3 != 0).collect();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(expected.len(), heap.size());
+        assert_eq!(expected, heap.to_vec());
+    }
+
+    #[test]
+    fn transform_1() {
+        let heap = BinaryHeap::from((0..50).collect::<Vec<i64>>());
+        let heap = heap.transform(|x| (x - 20) * (x - 20));
+        let mut expected: Vec<i64> = (0..50).map(|x| (x - 20) * (x - 20)).collect();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(expected, heap.to_vec());
+    }
+
+    #[test]
+    fn transform_dary() {
+        let mut heap = DaryHeap::<i64, 4>::new_dary();
+        for x in 0..50 {
+            heap.insert(x);
+        }
+        let heap: DaryHeap<i64, 4> = heap.transform(|x| (x - 20) * (x - 20));
+        let mut expected: Vec<i64> = (0..50).map(|x| (x - 20) * (x - 20)).collect();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(expected, heap.to_vec());
+    }
+
+    #[test]
+    fn transform_keeps_comparator() {
+        let heap = BinaryHeap::from_vec_with_comparator((0..50).collect::<Vec<i64>>(), MinComparator);
+        let heap: BinaryHeap<i64, MinComparator> = heap.transform(|x| (x - 20) * (x - 20));
+        let mut expected: Vec<i64> = (0..50).map(|x| (x - 20) * (x - 20)).collect();
+        expected.sort_unstable();
+        assert_eq!(expected, heap.to_vec());
+    }
+
+    #[test]
+    fn transform_with_comparator_1() {
+        let heap = BinaryHeap::from((0..50).collect::<Vec<i64>>());
+        let heap = heap.transform_with_comparator(|x| (x % 7, x), MinComparator);
+        let mut expected: Vec<(i64, i64)> = (0..50).map(|x| (x % 7, x)).collect();
+        expected.sort_unstable();
+        assert_eq!(expected, heap.to_vec());
+    }
+
+    #[test]
+    fn transform_monotone_1() {
+        // a min-heap mapped by an increasing function stays a min-heap
+        let heap = BinaryHeap::from_vec_with_comparator((0..50).collect::<Vec<i64>>(), MinComparator);
+        let heap = heap.transform_monotone(|x| 2 * x + 1);
+        assert!(heap.is_valid_heap());
+        assert_eq!((0..50).map(|x| 2 * x + 1).collect::<Vec<i64>>(), heap.to_vec());
+
+        // and mapped by a decreasing function, it gives a valid max-heap
+        let heap = BinaryHeap::from_vec_with_comparator((0..50).collect::<Vec<i64>>(), MinComparator);
+        let heap = heap.transform_monotone_with_comparator(|x| -2 * x, crate::MaxComparator);
+        assert_eq!((0..50).map(|x| -2 * x).collect::<Vec<i64>>(), heap.to_vec());
+    }
+}
@@ -0,0 +1,318 @@
+//! Calendar queue for discrete-event simulation
+//!
+//! A calendar queue sorts events like a desk calendar sorts appointments: time is divided into
+//! *days* of equal width, and a *year* of $m$ days wraps around a circular array of $m$ buckets,
+//! each holding its events in sorted order. Events are popped by walking through the days of the
+//! current year, taking from each bucket the events of the current day; if a whole year goes by
+//! without any, the earliest event is searched directly. The number of buckets doubles or halves
+//! with the number of events, and the width of the days is then re-estimated from the spacing
+//! of the earliest events, so that each day holds a few events and `insert` and `pop` take
+//! $O(1)$ expected time for the usual distributions of event times. (Brown, *Calendar queues: a
+//! fast O(1) priority queue implementation for the simulation event set problem*, 1988.)
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+// smallest number of buckets
+const MIN_BUCKETS: usize = 2;
+
+// number of earliest events sampled to estimate the width of the days
+const SAMPLE_SIZE: usize = 25;
+
+/// Types usable as times of a `CalendarQueue`
+pub trait CalendarTime: Copy + PartialOrd {
+
+    /// Convert the time to a floating-point number, to find its day
+    ///
+    /// The conversion must not decrease: if `a <= b`, then `a.to_f64() <= b.to_f64()`.
+    fn to_f64(self) -> f64;
+}
+
+// `CalendarTime` for each primitive numeric type
+macro_rules! impl_calendar_time {
+    ($($t:ty),*) => {
+        $(
+            impl CalendarTime for $t {
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    }
+}
+
+impl_calendar_time!(f32, f64, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A min-priority queue of events of type `V` with times of type `K`
+///
+/// `pop` returns an event with the earliest time, events with equal times being returned in the
+/// order in which they were inserted. `insert` and `pop` take $O(1)$ expected time when the times
+/// of the events are spread evenly enough, as is typical in discrete-event simulations where each
+/// popped event schedules new ones a little later (the *hold* operation); in the worst case, they
+/// take $O(n)$ time, where $n$ is the number of events.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::CalendarQueue;
+///
+/// let mut events = CalendarQueue::new();
+/// events.insert(2.5, "departure");
+/// events.insert(0.75, "arrival");
+/// events.insert(1.0, "service");
+///
+/// // hold: each popped event schedules a later one
+/// let (time, event) = events.pop().unwrap();
+/// assert_eq!((0.75, "arrival"), (time, event));
+/// events.insert(time + 3.0, "arrival");
+///
+/// assert_eq!(Some((1.0, "service")), events.pop());
+/// assert_eq!(Some((2.5, "departure")), events.pop());
+/// assert_eq!(Some((3.75, "arrival")), events.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct CalendarQueue<K, V> {
+    buckets: Vec<Vec<(K, V)>>,  // events of each bucket, from the latest to the earliest
+    width: f64,                 // width of a day
+    current: i64,               // index of the current day since time 0
+    size: usize,                // number of events
+}
+
+impl<K: CalendarTime, V> CalendarQueue<K, V> {
+
+    /// Create a new empty `CalendarQueue`
+    #[inline]
+    pub fn new() -> Self {
+        CalendarQueue {
+            buckets: (0..MIN_BUCKETS).map(|_| Vec::new()).collect(),
+            width: 1.0,
+            current: 0,
+            size: 0,
+        }
+    }
+
+    /// Get the number of events in the queue
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert `event` at time `time`
+    ///
+    /// Expected complexity: $O(1)$ (amortized, as the buckets are resized).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is a NaN.
+    pub fn insert(&mut self, time: K, event: V) {
+        assert!(!time.to_f64().is_nan(), "the time of an event must not be a NaN");
+        let day = self.day(time);
+        if self.size == 0 || day < self.current {
+            self.current = day;
+        }
+        self.push(time, event);
+        self.size += 1;
+        if self.size > 2 * self.buckets.len() {
+            self.resize(2 * self.buckets.len());
+        }
+    }
+
+    /// Remove and return an event with the earliest time, with its time (or `None` if the queue
+    /// is empty)
+    ///
+    /// Expected complexity: $O(1)$ (amortized, as the buckets are resized).
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let popped = self.take();
+        if self.buckets.len() > MIN_BUCKETS && self.size < self.buckets.len() / 2 {
+            self.resize(self.buckets.len() / 2);
+        }
+        popped
+    }
+
+    /// Return the earliest time (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(m)$, where $m$ is the number of buckets.
+    pub fn peek_time(&self) -> Option<K> {
+        self.earliest_bucket().map(|index| self.buckets[index].last().unwrap().0)
+    }
+
+    // index of the day of `time`, clamped to the range of `i64` (the conversion saturates, the
+    // infinite times thus falling on the first and last days)
+    fn day(&self, time: K) -> i64 {
+        let x = time.to_f64() / self.width;
+        let day = x as i64;
+        if day as f64 > x { day.saturating_sub(1) } else { day }
+    }
+
+    // index of the bucket of a day
+    #[inline]
+    fn bucket(&self, day: i64) -> usize {
+        day.rem_euclid(self.buckets.len() as i64) as usize
+    }
+
+    // add an event to its bucket, before the events with the same time
+    fn push(&mut self, time: K, event: V) {
+        let index = self.bucket(self.day(time));
+        let bucket = &mut self.buckets[index];
+        let position = bucket.partition_point(|&(t, _)| t > time);
+        bucket.insert(position, (time, event));
+    }
+
+    // bucket with the earliest event
+    fn earliest_bucket(&self) -> Option<usize> {
+        (0..self.buckets.len())
+            .filter(|&index| !self.buckets[index].is_empty())
+            .min_by(|&a, &b| {
+                let (a, b) = (self.buckets[a].last().unwrap().0, self.buckets[b].last().unwrap().0);
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            })
+    }
+
+    // remove and return the earliest event, without resizing
+    fn take(&mut self) -> Option<(K, V)> {
+        if self.size == 0 {
+            return None;
+        }
+
+        // walk through the days of a year
+        for _ in 0..self.buckets.len() {
+            let index = self.bucket(self.current);
+            if let Some(&(time, _)) = self.buckets[index].last() {
+                if self.day(time) <= self.current {
+                    self.size -= 1;
+                    return self.buckets[index].pop();
+                }
+            }
+            self.current += 1;
+        }
+
+        // no event in a whole year: jump to the day of the earliest one
+        let index = self.earliest_bucket().unwrap();
+        let (time, event) = self.buckets[index].pop().unwrap();
+        self.current = self.day(time);
+        self.size -= 1;
+        Some((time, event))
+    }
+
+    // change the number of buckets, estimating a new width of the days from the earliest events
+    fn resize(&mut self, count: usize) {
+        let size = self.size;
+        let sample: Vec<(K, V)> = (0..SAMPLE_SIZE).map_while(|_| self.take()).collect();
+
+        // three times the average spacing between the sampled times, ignoring outliers
+        let gaps: Vec<f64> = sample.windows(2)
+            .map(|pair| pair[1].0.to_f64() - pair[0].0.to_f64())
+            .collect();
+        if !gaps.is_empty() {
+            let average = gaps.iter().sum::<f64>() / gaps.len() as f64;
+            let close: Vec<f64> = gaps.into_iter().filter(|&gap| gap <= 2.0 * average).collect();
+            let width = 3.0 * close.iter().sum::<f64>() / close.len() as f64;
+            if width > 0.0 && width.is_finite() {
+                self.width = width;
+            }
+        }
+
+        // the events are pushed back from the earliest inserted to the latest, for each time
+        let events: Vec<(K, V)> = core::mem::take(&mut self.buckets).into_iter()
+            .flat_map(|bucket| bucket.into_iter().rev())
+            .collect();
+        self.buckets = (0..count).map(|_| Vec::new()).collect();
+        if let Some(&(time, _)) = sample.first() {
+            self.current = self.day(time);
+        }
+        for (time, event) in sample.into_iter().chain(events) {
+            self.push(time, event);
+        }
+        self.size = size;
+    }
+}
+
+impl<K: CalendarTime, V> core::default::Default for CalendarQueue<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Events are returned from the earliest to the latest
+impl<K: CalendarTime, V> Iterator for CalendarQueue<K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<K: CalendarTime, V> ExactSizeIterator for CalendarQueue<K, V> {}
+
+impl<K: CalendarTime, V> core::iter::FusedIterator for CalendarQueue<K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::{BinaryHeap, MinComparator};
+
+    #[test]
+    fn integer_times() {
+        let mut next = lcg(12345);
+
+        // (time, sequence number) pairs, popped in the same order as from the calendar queue
+        let mut queue = CalendarQueue::new();
+        let mut reference = BinaryHeap::with_comparator(MinComparator);
+        for i in 0..20_000u32 {
+            if next().is_multiple_of(3) {
+                assert_eq!(reference.pop(), queue.pop());
+            } else {
+                // mostly near-future events, with a few past and far-future ones
+                let base = queue.peek_time().unwrap_or(0i64);
+                let time = match next() % 20 {
+                    0 => base - (next() % 100) as i64,
+                    1 => base + (next() % 1_000_000) as i64,
+                    _ => base + (next() % 50) as i64,
+                };
+                queue.insert(time, i);
+                reference.insert((time, i));
+            }
+            assert_eq!(reference.size(), queue.size());
+            assert_eq!(reference.get_max().map(|(time, _)| time), queue.peek_time());
+        }
+        let expected: Vec<(i64, u32)> = reference.to_vec();
+        assert_eq!(expected, queue.collect::<Vec<(i64, u32)>>());
+    }
+
+    #[test]
+    fn hold() {
+        let mut random = lcg(7);
+        let mut next = || random() as f64 / (1u64 << 31) as f64;
+        let mut queue = CalendarQueue::new();
+        for _ in 0..10_000 {
+            queue.insert(next() * 100.0, ());
+        }
+        let mut last = 0.0;
+        for _ in 0..100_000 {
+            let (time, ()) = queue.pop().unwrap();
+            assert!(time >= last);
+            last = time;
+            queue.insert(time + next() * 100.0, ());
+        }
+        assert_eq!(10_000, queue.size());
+        assert!(queue.buckets.len() >= 2_500 && queue.buckets.len() <= 10_000);
+    }
+
+    #[test]
+    fn extreme_times() {
+        let times = [f64::NEG_INFINITY, -f64::MAX, -1e300, -1.0, 0.0, 1e300, f64::MAX, f64::INFINITY];
+        let mut queue = CalendarQueue::new();
+        for &time in times.iter().rev() {
+            queue.insert(time, ());
+        }
+        assert_eq!(times.to_vec(), queue.map(|(time, ())| time).collect::<Vec<f64>>());
+    }
+}
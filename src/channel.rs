@@ -0,0 +1,540 @@
+//! Multi-producer, multi-consumer channel delivering the largest pending element first
+//!
+//! The pending elements are kept in a `BinaryHeap` behind a mutex, and receivers waiting for an
+//! element sleep on a condition variable, which senders notify. A bounded channel has a capacity,
+//! and an `OverflowPolicy` deciding what `send` does when it is full; with the `Block` policy,
+//! senders waiting for space sleep on a second condition variable, which receivers notify. The
+//! channel is disconnected when all the senders or all the receivers have been dropped, as for
+//! `std::sync::mpsc`.
+//!
+//! Only available with the `std` feature.
+
+use crate::{BinaryHeap, Compare, MaxComparator};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+// state shared by the senders and receivers
+#[derive(Debug)]
+struct Shared<T, C> {
+    state: Mutex<State<T, C>>,
+    available: Condvar,     // notified when an element is sent, or the last sender is dropped
+    space: Condvar,         // notified when an element is received, or the last receiver is dropped
+}
+
+#[derive(Debug)]
+struct State<T, C> {
+    heap: BinaryHeap<T, C>, // pending elements
+    capacity: usize,        // maximum number of pending elements
+    policy: OverflowPolicy, // behaviour of `send` when the channel is full
+    senders: usize,         // number of live senders
+    receivers: usize,       // number of live receivers
+}
+
+impl<T, C: Compare<T>> State<T, C> {
+
+    // remove a smallest pending element (one of the leaves of the heap), unless `x` is smaller,
+    // and return the removed element or `x`
+    fn drop_lowest(&mut self, x: T) -> Option<T> {
+        let heap = &mut self.heap;
+        let first_leaf = heap.size() / 2;
+        let lowest = (first_leaf..heap.size()).reduce(|lowest, pos| {
+            if heap.compare_values(&heap.data[pos], &heap.data[lowest]) == Some(core::cmp::Ordering::Less) {
+                pos
+            } else {
+                lowest
+            }
+        })?;
+        if heap.compare_values(&heap.data[lowest], &x) != Some(core::cmp::Ordering::Less) {
+            return Some(x);
+        }
+
+        // replace the leaf, which may then need to move up
+        let removed = core::mem::replace(&mut heap.data[lowest], x);
+        heap.sift_up_with(lowest, |_, _, _| {});
+        Some(removed)
+    }
+}
+
+impl<T, C> Shared<T, C> {
+    // lock the state, ignoring poisoning: a panic in a comparison leaves the heap valid, even if
+    // an element may be lost
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, State<T, C>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Error returned by `async_channel::PrioritySender::send` when all the receivers have been
+/// dropped, with the element
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> core::fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SendError { .. }")
+    }
+}
+
+impl<T> core::fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "sending on a channel without receivers")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+/// Error returned by `Sender::send` and `Sender::try_send`, with the element
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is full (returned by `send` only with the `Reject` policy)
+    Full(T),
+    /// All the receivers have been dropped
+    Disconnected(T),
+}
+
+impl<T> core::fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Full(..)"),
+            TrySendError::Disconnected(_) => f.write_str("Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> core::fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "sending on a full channel"),
+            TrySendError::Disconnected(_) => write!(f, "sending on a channel without receivers"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+/// Error returned by `Receiver::recv` when the channel is empty and all the senders have been
+/// dropped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+impl core::fmt::Display for RecvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "receiving on an empty channel without senders")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Error returned by `Receiver::try_recv`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The channel is empty, but senders remain
+    Empty,
+    /// The channel is empty and all the senders have been dropped
+    Disconnected,
+}
+
+impl core::fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "receiving on an empty channel"),
+            TryRecvError::Disconnected => write!(f, "receiving on an empty channel without senders"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+/// Error returned by `Receiver::recv_timeout`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// No element was sent before the timeout
+    Timeout,
+    /// The channel is empty and all the senders have been dropped
+    Disconnected,
+}
+
+impl core::fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "timed out waiting on a channel"),
+            RecvTimeoutError::Disconnected => write!(f, "receiving on an empty channel without senders"),
+        }
+    }
+}
+
+impl std::error::Error for RecvTimeoutError {}
+
+/// Behaviour of `Sender::send` when a bounded channel is full
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Wait until an element is received
+    #[default]
+    Block,
+    /// Drop the smallest element among the pending ones and the sent one
+    DropLowest,
+    /// Return the sent element in an error
+    Reject,
+}
+
+/// Sending half of a priority channel, which can be cloned to send from several threads
+#[derive(Debug)]
+pub struct Sender<T, C = MaxComparator> {
+    shared: Arc<Shared<T, C>>,
+}
+
+/// Receiving half of a priority channel, which can be cloned to receive from several threads
+#[derive(Debug)]
+pub struct Receiver<T, C = MaxComparator> {
+    shared: Arc<Shared<T, C>>,
+}
+
+/// Create a channel delivering the largest pending element first, by the natural order of `T`
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::channel::priority_channel;
+///
+/// let (sender, receiver) = priority_channel();
+/// let producer = std::thread::spawn(move || {
+///     for priority in [2, 9, 4] {
+///         sender.send(priority).unwrap();
+///     }
+/// });
+/// producer.join().unwrap();
+///
+/// // the pending elements come out largest first, then the channel is disconnected
+/// assert_eq!(Ok(9), receiver.recv());
+/// assert_eq!(Ok(4), receiver.recv());
+/// assert_eq!(Ok(2), receiver.recv());
+/// assert!(receiver.recv().is_err());
+/// ```
+#[inline]
+pub fn priority_channel<T: core::cmp::PartialOrd>() -> (Sender<T>, Receiver<T>) {
+    priority_channel_with_comparator(MaxComparator)
+}
+
+/// Create a channel delivering the largest pending element first, according to `comparator`
+pub fn priority_channel_with_comparator<T, C: Compare<T>>(comparator: C) -> (Sender<T, C>, Receiver<T, C>) {
+    channel(BinaryHeap::with_comparator(comparator), usize::MAX, OverflowPolicy::Block)
+}
+
+/// Create a channel holding at most `capacity` pending elements, delivering the largest one
+/// first by the natural order of `T`
+///
+/// When the channel is full, `send` follows `policy`, while `try_send` fails.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::channel::{bounded_priority_channel, OverflowPolicy, TrySendError};
+///
+/// // load shedding: keep the two most urgent events
+/// let (sender, receiver) = bounded_priority_channel(2, OverflowPolicy::DropLowest);
+/// for urgency in [5, 1, 8, 3] {
+///     sender.send(urgency).unwrap();
+/// }
+/// assert_eq!(Err(TrySendError::Full(9)), sender.try_send(9));
+/// assert_eq!(Ok(8), receiver.recv());
+/// assert_eq!(Ok(5), receiver.recv());
+/// ```
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+#[inline]
+pub fn bounded_priority_channel<T: core::cmp::PartialOrd>(capacity: usize, policy: OverflowPolicy)
+    -> (Sender<T>, Receiver<T>)
+{
+    bounded_priority_channel_with_comparator(capacity, policy, MaxComparator)
+}
+
+/// Create a channel holding at most `capacity` pending elements, delivering the largest one
+/// first according to `comparator`
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn bounded_priority_channel_with_comparator<T, C: Compare<T>>(capacity: usize, policy: OverflowPolicy, comparator: C)
+    -> (Sender<T, C>, Receiver<T, C>)
+{
+    assert!(capacity > 0, "a bounded channel needs a positive capacity");
+    channel(BinaryHeap::with_comparator(comparator), capacity, policy)
+}
+
+// create a channel with the given heap, capacity, and policy
+fn channel<T, C>(heap: BinaryHeap<T, C>, capacity: usize, policy: OverflowPolicy) -> (Sender<T, C>, Receiver<T, C>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State { heap, capacity, policy, senders: 1, receivers: 1 }),
+        available: Condvar::new(),
+        space: Condvar::new(),
+    });
+    (Sender { shared: shared.clone() }, Receiver { shared })
+}
+
+impl<T, C: Compare<T>> Sender<T, C> {
+
+    /// Send an element, or return it in a `TrySendError::Disconnected` error if all the receivers
+    /// have been dropped
+    ///
+    /// If the channel is full, its `OverflowPolicy` decides whether to wait until an element is
+    /// received, to drop the smallest element (possibly `x`), or to return `x` in a
+    /// `TrySendError::Full` error.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention, once there is space in the channel; $\Theta(n)$ when dropping the
+    /// smallest element.
+    pub fn send(&self, x: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.shared.lock();
+        loop {
+            if state.receivers == 0 {
+                return Err(TrySendError::Disconnected(x));
+            }
+            if state.heap.size() < state.capacity {
+                break;
+            }
+            match state.policy {
+                OverflowPolicy::Block => {
+                    state = self.shared.space.wait(state).unwrap_or_else(PoisonError::into_inner);
+                },
+                OverflowPolicy::DropLowest => {
+                    state.drop_lowest(x);
+                    drop(state);
+                    self.shared.available.notify_one();
+                    return Ok(());
+                },
+                OverflowPolicy::Reject => return Err(TrySendError::Full(x)),
+            }
+        }
+        state.heap.insert(x);
+        drop(state);
+        self.shared.available.notify_one();
+        Ok(())
+    }
+
+    /// Send an element without waiting, or return it in an error if the channel is full or all
+    /// the receivers have been dropped
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention.
+    pub fn try_send(&self, x: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.shared.lock();
+        if state.receivers == 0 {
+            return Err(TrySendError::Disconnected(x));
+        }
+        if state.heap.size() >= state.capacity {
+            return Err(TrySendError::Full(x));
+        }
+        state.heap.insert(x);
+        drop(state);
+        self.shared.available.notify_one();
+        Ok(())
+    }
+
+    /// Get the number of pending elements
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.shared.lock().heap.size()
+    }
+}
+
+impl<T, C: Compare<T>> Receiver<T, C> {
+
+    /// Remove and return the largest pending element, waiting for one if the channel is empty
+    ///
+    /// Returns an error if the channel is empty and all the senders have been dropped.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention and once an element is available.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(x) = state.heap.pop() {
+                drop(state);
+                self.shared.space.notify_one();
+                return Ok(x);
+            }
+            if state.senders == 0 {
+                return Err(RecvError);
+            }
+            state = self.shared.available.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Remove and return the largest pending element, without waiting
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.lock();
+        match state.heap.pop() {
+            Some(x) => {
+                drop(state);
+                self.shared.space.notify_one();
+                Ok(x)
+            },
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Remove and return the largest pending element, waiting at most `timeout` for one if the
+    /// channel is empty
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pending elements,
+    /// without contention and once an element is available.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(x) = state.heap.pop() {
+                drop(state);
+                self.shared.space.notify_one();
+                return Ok(x);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            state = self.shared.available
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Get the number of pending elements
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.shared.lock().heap.size()
+    }
+}
+
+impl<T, C> Clone for Sender<T, C> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Sender { shared: self.shared.clone() }
+    }
+}
+
+impl<T, C> Clone for Receiver<T, C> {
+    fn clone(&self) -> Self {
+        self.shared.lock().receivers += 1;
+        Receiver { shared: self.shared.clone() }
+    }
+}
+
+// the last sender wakes up the receivers waiting on an empty channel, so that they return errors
+impl<T, C> Drop for Sender<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.shared.available.notify_all();
+        }
+    }
+}
+
+// the last receiver wakes up the senders waiting on a full channel, so that they return errors
+impl<T, C> Drop for Receiver<T, C> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            drop(state);
+            self.shared.space.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn several_producers_and_consumers() {
+        let (sender, receiver) = priority_channel();
+        let received = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for worker in 0..4u64 {
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    for i in 0..1000 {
+                        sender.send(1000 * worker + i).unwrap();
+                    }
+                });
+            }
+            drop(sender);
+            for _ in 0..4 {
+                let receiver = receiver.clone();
+                let received = &received;
+                scope.spawn(move || {
+                    while let Ok(x) = receiver.recv() {
+                        received.lock().unwrap().push(x);
+                    }
+                });
+            }
+        });
+        let mut received = received.into_inner().unwrap();
+        received.sort_unstable();
+        assert_eq!((0..4000).collect::<Vec<u64>>(), received);
+    }
+
+    #[test]
+    fn disconnection() {
+        let (sender, receiver) = priority_channel();
+        assert_eq!(Err(TryRecvError::Empty), receiver.try_recv());
+        assert_eq!(Err(RecvTimeoutError::Timeout), receiver.recv_timeout(Duration::from_millis(10)));
+        sender.send(1).unwrap();
+        drop(sender);
+        assert_eq!(Ok(1), receiver.try_recv());
+        assert_eq!(Err(TryRecvError::Disconnected), receiver.try_recv());
+
+        let (sender, receiver) = priority_channel();
+        drop(receiver);
+        assert_eq!(Err(TrySendError::Disconnected(3)), sender.send(3));
+    }
+
+    #[test]
+    fn overflow_policies() {
+        let (sender, receiver) = bounded_priority_channel(3, OverflowPolicy::DropLowest);
+        for x in [4, 7, 1, 9, 0, 6] {
+            sender.send(x).unwrap();
+            assert!(receiver.size() <= 3);
+        }
+        assert_eq!(vec![9, 7, 6], (0..3).map(|_| receiver.recv().unwrap()).collect::<Vec<_>>());
+
+        let (sender, receiver) = bounded_priority_channel(2, OverflowPolicy::Reject);
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(Err(TrySendError::Full(3)), sender.send(3));
+        assert_eq!(Ok(2), receiver.recv());
+        sender.send(3).unwrap();
+
+        // a blocked sender resumes once an element is received
+        let (sender, receiver) = bounded_priority_channel(1, OverflowPolicy::Block);
+        sender.send(0).unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| sender.send(1).unwrap());
+            std::thread::sleep(Duration::from_millis(10));
+            assert_eq!(1, receiver.size());
+            assert_eq!(Ok(0), receiver.recv());
+        });
+        assert_eq!(Ok(1), receiver.recv());
+
+        // and fails once the receivers are dropped
+        sender.send(2).unwrap();
+        std::thread::scope(|scope| {
+            let blocked = scope.spawn(|| sender.send(3));
+            std::thread::sleep(Duration::from_millis(10));
+            drop(receiver);
+            assert_eq!(Err(TrySendError::Disconnected(3)), blocked.join().unwrap());
+        });
+    }
+}
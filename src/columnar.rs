@@ -0,0 +1,194 @@
+//! Export of the contents of a heap as Apache Arrow arrays
+//!
+//! The elements are written directly into Arrow array builders as they are popped from the heap,
+//! in non-increasing order, without building an intermediate vector.
+
+use crate::BinaryHeap;
+use arrow_array::builder::{ArrayBuilder, BooleanBuilder, PrimitiveBuilder, StringBuilder};
+use arrow_array::types::{Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+                         UInt16Type, UInt32Type, UInt64Type, UInt8Type};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::sync::Arc;
+
+/// A type whose values can be stored in an Arrow array
+///
+/// This trait is implemented for primitive numeric types, `bool`, and `String`.
+pub trait ArrowElement {
+
+    /// Builder for the arrays storing values of this type
+    type Builder: ArrayBuilder;
+
+    /// Arrow data type of the arrays storing values of this type
+    fn data_type() -> DataType;
+
+    /// Create a builder with space for `capacity` values
+    fn new_builder(capacity: usize) -> Self::Builder;
+
+    /// Append `self` to `builder`
+    fn append_to(self, builder: &mut Self::Builder);
+}
+
+// primitive types, stored in a `PrimitiveBuilder`
+macro_rules! impl_arrow_element_primitive {
+    ($($t:ty => ($arrow_type:ty, $data_type:expr)),*) => {
+        $(
+            impl ArrowElement for $t {
+                type Builder = PrimitiveBuilder<$arrow_type>;
+
+                fn data_type() -> DataType {
+                    $data_type
+                }
+
+                fn new_builder(capacity: usize) -> Self::Builder {
+                    PrimitiveBuilder::<$arrow_type>::with_capacity(capacity)
+                }
+
+                fn append_to(self, builder: &mut Self::Builder) {
+                    builder.append_value(self);
+                }
+            }
+        )*
+    };
+}
+
+impl_arrow_element_primitive!(
+    i8 => (Int8Type, DataType::Int8),
+    i16 => (Int16Type, DataType::Int16),
+    i32 => (Int32Type, DataType::Int32),
+    i64 => (Int64Type, DataType::Int64),
+    u8 => (UInt8Type, DataType::UInt8),
+    u16 => (UInt16Type, DataType::UInt16),
+    u32 => (UInt32Type, DataType::UInt32),
+    u64 => (UInt64Type, DataType::UInt64),
+    f32 => (Float32Type, DataType::Float32),
+    f64 => (Float64Type, DataType::Float64)
+);
+
+impl ArrowElement for bool {
+    type Builder = BooleanBuilder;
+
+    fn data_type() -> DataType {
+        DataType::Boolean
+    }
+
+    fn new_builder(capacity: usize) -> Self::Builder {
+        BooleanBuilder::with_capacity(capacity)
+    }
+
+    fn append_to(self, builder: &mut Self::Builder) {
+        builder.append_value(self);
+    }
+}
+
+impl ArrowElement for String {
+    type Builder = StringBuilder;
+
+    fn data_type() -> DataType {
+        DataType::Utf8
+    }
+
+    fn new_builder(capacity: usize) -> Self::Builder {
+        StringBuilder::with_capacity(capacity, 0)
+    }
+
+    fn append_to(self, builder: &mut Self::Builder) {
+        builder.append_value(self);
+    }
+}
+
+impl<T: core::cmp::PartialOrd + ArrowElement> BinaryHeap<T> {
+
+    /// Consume the heap and return an Arrow array of all its elements in non-increasing order
+    ///
+    /// Only available with the `arrow` feature.
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arrow_array::{Array, Int32Array};
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from([1, 3, 2]);
+    /// let array = heap.to_arrow_array();
+    ///
+    /// assert_eq!(&Int32Array::from(vec![3, 2, 1]), array.as_any().downcast_ref().unwrap());
+    /// ```
+    pub fn to_arrow_array(mut self) -> ArrayRef {
+        let mut builder = T::new_builder(self.size());
+        while let Some(x) = self.pop() {
+            x.append_to(&mut builder);
+        }
+        builder.finish()
+    }
+}
+
+impl<K, V> BinaryHeap<(K, V)>
+    where K: core::cmp::PartialOrd + ArrowElement,
+          V: core::cmp::PartialOrd + ArrowElement
+{
+    /// Consume a heap of `(key, payload)` pairs and return a record batch with a `key` and a
+    /// `payload` column, the rows being in non-increasing order
+    ///
+    /// Only available with the `arrow` feature.
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arrow_array::{Int64Array, StringArray};
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from([(1i64, String::from("a")), (3, String::from("c"))]);
+    /// let batch = heap.to_arrow().unwrap();
+    ///
+    /// assert_eq!(2, batch.num_rows());
+    /// assert_eq!(&Int64Array::from(vec![3, 1]),
+    ///            batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap());
+    /// assert_eq!(&StringArray::from(vec!["c", "a"]),
+    ///            batch.column(1).as_any().downcast_ref::<StringArray>().unwrap());
+    /// ```
+    pub fn to_arrow(mut self) -> Result<RecordBatch, ArrowError> {
+        let size = self.size();
+        let mut keys = K::new_builder(size);
+        let mut payloads = V::new_builder(size);
+        while let Some((key, payload)) = self.pop() {
+            key.append_to(&mut keys);
+            payload.append_to(&mut payloads);
+        }
+        let schema = Schema::new(vec![
+            Field::new("key", K::data_type(), false),
+            Field::new("payload", V::data_type(), false),
+        ]);
+        RecordBatch::try_new(Arc::new(schema), vec![keys.finish(), payloads.finish()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array, BooleanArray, Float64Array, UInt8Array};
+
+    #[test]
+    fn record_batch() {
+        let heap = BinaryHeap::from([(0.5, true), (2.5, false), (1.5, true), (2.5, true)]);
+        let batch = heap.to_arrow().unwrap();
+        assert_eq!("key", batch.schema().field(0).name());
+        assert_eq!(&DataType::Boolean, batch.schema().field(1).data_type());
+        let keys = batch.column(0).as_any().downcast_ref::<Float64Array>().unwrap();
+        let payloads = batch.column(1).as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(&Float64Array::from(vec![2.5, 2.5, 1.5, 0.5]), keys);
+        assert_eq!(&BooleanArray::from(vec![true, false, true, true]), payloads);
+    }
+
+    #[test]
+    fn empty() {
+        let heap = BinaryHeap::<(u8, u8)>::new();
+        assert_eq!(0, heap.to_arrow().unwrap().num_rows());
+        let array = BinaryHeap::<u8>::new().to_arrow_array();
+        assert_eq!(0, array.as_any().downcast_ref::<UInt8Array>().unwrap().len());
+    }
+}
@@ -0,0 +1,299 @@
+//! Concurrent heap with flat combining
+//!
+//! Each thread publishes its operation in a record, then tries to take the lock of the heap. The
+//! thread which gets it becomes the combiner: it applies all the published operations, writing
+//! their results in the records, while the other threads wait for their results instead of
+//! contending for the lock. The heap is thus only accessed by one thread at a time, which keeps
+//! its data in that thread's cache, and the lock is taken once per batch of operations. (Hendler,
+//! Incze, Shavit, and Tzafrir, *Flat combining and the synchronization-parallelism tradeoff*,
+//! 2010.)
+
+use crate::sharded::lock;
+use crate::{BinaryHeap, Compare, MaxComparator};
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::vec::Vec;
+
+// states of a record
+const EMPTY: u8 = 0;
+const PENDING: u8 = 1;
+const DONE: u8 = 2;
+
+// operation published in a record, replaced by its result
+#[derive(Debug)]
+enum Operation<T> {
+    None,
+    Insert(T),
+    Pop,
+    Popped(Option<T>),
+}
+
+// record of a thread, owned while its `in_use` flag is set
+#[derive(Debug)]
+struct Record<T> {
+    in_use: AtomicBool,
+    state: AtomicU8,
+    operation: Mutex<Operation<T>>,
+}
+
+/// A concurrent heap where one thread at a time applies the operations of all the others
+///
+/// Operations are published in records, which threads own for the duration of an operation;
+/// there should be at least as many records as threads (more records only make the combiner scan
+/// longer). Under contention, a single lock acquisition serves a whole batch of operations,
+/// which is much faster than each thread taking a global lock in turn. Pops are exact: each
+/// operation is applied to a single `BinaryHeap`.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::CombiningHeap;
+///
+/// let heap = CombiningHeap::new(8);
+/// std::thread::scope(|scope| {
+///     for worker in 0..8 {
+///         let heap = &heap;
+///         scope.spawn(move || {
+///             for job in 0..100 {
+///                 heap.insert(100 * worker + job);
+///             }
+///         });
+///     }
+/// });
+///
+/// assert_eq!(800, heap.size());
+/// assert_eq!(Some(799), heap.pop());
+/// ```
+#[derive(Debug)]
+pub struct CombiningHeap<T, C = MaxComparator> {
+    heap: Mutex<BinaryHeap<T, C>>,  // heap, whose lock is held by the combiner
+    records: Vec<Record<T>>,        // published operations
+    size: AtomicUsize,              // number of elements
+}
+
+impl<T: core::cmp::PartialOrd> CombiningHeap<T> {
+
+    /// Create a new empty `CombiningHeap` with `records` records, ordered by the natural order of
+    /// `T`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `records` is zero.
+    #[inline]
+    pub fn new(records: usize) -> Self {
+        CombiningHeap::with_comparator(records, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> CombiningHeap<T, C> {
+
+    /// Create a new empty `CombiningHeap` with `records` records, ordered by `comparator`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `records` is zero.
+    pub fn with_comparator(records: usize, comparator: C) -> Self {
+        assert!(records > 0, "a combining heap needs at least one record");
+        CombiningHeap {
+            heap: Mutex::new(BinaryHeap::with_comparator(comparator)),
+            records: (0..records).map(|_| Record {
+                in_use: AtomicBool::new(false),
+                state: AtomicU8::new(EMPTY),
+                operation: Mutex::new(Operation::None),
+            }).collect(),
+            size: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// With concurrent operations, the result may be out of date as soon as it is returned.
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap,
+    /// without contention.
+    pub fn insert(&self, x: T) {
+        self.apply(Operation::Insert(x));
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap,
+    /// without contention.
+    pub fn pop(&self) -> Option<T> {
+        match self.apply(Operation::Pop) {
+            Operation::Popped(root) => root,
+            _ => unreachable!(),
+        }
+    }
+
+    // publish an operation and wait until it is applied, combining the published operations if
+    // the lock of the heap is free, and return the result
+    fn apply(&self, operation: Operation<T>) -> Operation<T> {
+
+        // claim a free record, starting from one depending on the thread
+        let start = {
+            use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+            BuildHasherDefault::<DefaultHasher>::default().hash_one(std::thread::current().id()) as usize
+        };
+        let record = (0..)
+            .map(|i| &self.records[(start + i) % self.records.len()])
+            .find(|record| {
+                let claimed = record.in_use
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok();
+                if !claimed {
+                    core::hint::spin_loop();
+                }
+                claimed
+            })
+            .unwrap();
+
+        *lock(&record.operation) = operation;
+        record.state.store(PENDING, Ordering::Release);
+        let release = Release { heap: self, record };
+        while record.state.load(Ordering::Acquire) != DONE {
+            match self.heap.try_lock() {
+                Ok(mut heap) => self.combine(&mut heap),
+                Err(std::sync::TryLockError::Poisoned(error)) => self.combine(&mut error.into_inner()),
+                Err(std::sync::TryLockError::WouldBlock) => std::thread::yield_now(),
+            }
+        }
+        core::mem::forget(release);
+
+        let result = core::mem::replace(&mut *lock(&record.operation), Operation::None);
+        record.state.store(EMPTY, Ordering::Relaxed);
+        record.in_use.store(false, Ordering::Release);
+        result
+    }
+
+    // apply the pending operations of all the records
+    //
+    // If the comparator panics, the operation being applied is left in its record: a pop is
+    // still pending, to be applied by the next combiner, while an insertion is done (the heap
+    // keeping the element in both cases).
+    fn combine(&self, heap: &mut BinaryHeap<T, C>) {
+        for record in self.records.iter() {
+            if record.state.load(Ordering::Acquire) != PENDING {
+                continue;
+            }
+            let mut operation = lock(&record.operation);
+            match &*operation {
+                Operation::Insert(_) => {
+                    let Operation::Insert(x) = core::mem::replace(&mut *operation, Operation::None) else {
+                        unreachable!()
+                    };
+                    let done = Done(record);
+                    heap.insert(x);
+                    drop(done);
+                },
+                Operation::Pop => {
+                    *operation = Operation::Popped(heap.pop());
+                    record.state.store(DONE, Ordering::Release);
+                },
+                _ => record.state.store(DONE, Ordering::Release),
+            }
+            self.size.store(heap.size(), Ordering::Release);
+        }
+    }
+}
+
+// marks a record as done when dropped, including when the comparator panics
+struct Done<'a, T>(&'a Record<T>);
+
+impl<T> Drop for Done<'_, T> {
+    fn drop(&mut self) {
+        self.0.state.store(DONE, Ordering::Release);
+    }
+}
+
+// withdraws the operation of a thread and releases its record if the thread panics while
+// combining
+struct Release<'a, T, C> {
+    heap: &'a CombiningHeap<T, C>,
+    record: &'a Record<T>,
+}
+
+impl<T, C> Drop for Release<'_, T, C> {
+    fn drop(&mut self) {
+        // no other thread applies the operation while the lock of the heap is held
+        let heap = lock(&self.heap.heap);
+        self.heap.size.store(heap.size(), Ordering::Release);
+        *lock(&self.record.operation) = Operation::None;
+        self.record.state.store(EMPTY, Ordering::Relaxed);
+        self.record.in_use.store(false, Ordering::Release);
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for CombiningHeap<T, C> {
+
+    /// Create a new empty `CombiningHeap` with one record per available thread
+    fn default() -> Self {
+        let records = std::thread::available_parallelism().map_or(1, usize::from);
+        Self::with_comparator(records, C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_inserts_and_pops() {
+        // fewer records than threads, so that some threads wait for a record
+        let heap = CombiningHeap::new(4);
+        let popped = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for worker in 0..8u64 {
+                let heap = &heap;
+                let popped = &popped;
+                scope.spawn(move || {
+                    for i in 0..1000 {
+                        heap.insert(1000 * worker + i);
+                        if i % 3 == 0 {
+                            popped.lock().unwrap().push(heap.pop().unwrap());
+                        }
+                    }
+                });
+            }
+        });
+        let mut popped = popped.into_inner().unwrap();
+        assert_eq!(8000, popped.len() + heap.size());
+        let rest: Vec<u64> = core::iter::from_fn(|| heap.pop()).collect();
+        assert!(rest.windows(2).all(|pair| pair[0] >= pair[1]));
+        popped.extend(rest);
+        popped.sort_unstable();
+        assert_eq!((0..8000).collect::<Vec<u64>>(), popped);
+    }
+
+    #[test]
+    fn panicking_comparator() {
+        // the comparator panics when comparing 0
+        let heap = CombiningHeap::with_comparator(1, crate::FnComparator(|a: &u32, b: &u32| {
+            assert!(*a != 0 && *b != 0, "comparator failed");
+            a.cmp(b)
+        }));
+        heap.insert(2);
+        heap.insert(1);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| heap.insert(0)));
+        assert!(result.is_err());
+
+        // the element was inserted and the record released; the pop is put back when the
+        // comparator panics, then withdrawn
+        assert_eq!(3, heap.size());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| heap.pop()));
+        assert!(result.is_err());
+        assert_eq!(3, heap.size());
+        assert!(heap.records.iter().all(|record| !record.in_use.load(Ordering::Acquire)));
+        assert!(matches!(*lock(&heap.records[0].operation), Operation::None));
+    }
+}
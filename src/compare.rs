@@ -0,0 +1,136 @@
+//! Orderings of the elements of a heap
+//!
+//! A `BinaryHeap<T, C>` keeps at its root an element which is not smaller than any other,
+//! according to the comparator `C`. By default, `C` is `MaxComparator`, which uses the natural
+//! order of `T` (so the root is the maximum); `MinComparator` reverses it (so the root is the
+//! minimum).
+
+use core::cmp::Ordering;
+
+/// A way of comparing elements of type `T`
+///
+/// `compare(a, b)` returns `Some(Ordering::Less)` if `a` should be closer to the leaves of the
+/// heap than `b`, `Some(Ordering::Greater)` if it should be closer to the root, and `None` if the
+/// two elements are incomparable (in which case they are not reordered).
+pub trait Compare<T> {
+    /// Compare two elements
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering>;
+}
+
+/// Natural order of `T`, giving a max-heap
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MaxComparator;
+
+impl<T: core::cmp::PartialOrd> Compare<T> for MaxComparator {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        a.partial_cmp(b)
+    }
+}
+
+/// Reverse of the natural order of `T`, giving a min-heap
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MinComparator;
+
+impl<T: core::cmp::PartialOrd> Compare<T> for MinComparator {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        b.partial_cmp(a)
+    }
+}
+
+/// Order given by a closure `F: Fn(&T, &T) -> Ordering`
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, FnComparator};
+///
+/// // order points by their distance to the origin, the furthest at the root
+/// let mut heap = BinaryHeap::with_comparator(FnComparator(|a: &(i32, i32), b: &(i32, i32)| {
+///     (a.0 * a.0 + a.1 * a.1).cmp(&(b.0 * b.0 + b.1 * b.1))
+/// }));
+/// heap.insert((1, 1));
+/// heap.insert((0, 3));
+///
+/// assert_eq!(Some((0, 3)), heap.pop());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FnComparator<F>(pub F);
+
+impl<T, F: Fn(&T, &T) -> Ordering> Compare<T> for FnComparator<F> {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        Some((self.0)(a, b))
+    }
+}
+
+/// Natural order of a key extracted from each element by a closure `F: Fn(&T) -> K`
+///
+/// The key is computed again for each comparison, so the closure should be cheap (for instance,
+/// reading or copying a field).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyComparator<F>(pub F);
+
+impl<T, K: core::cmp::PartialOrd, F: Fn(&T) -> K> Compare<T> for KeyComparator<F> {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        (self.0)(a).partial_cmp(&(self.0)(b))
+    }
+}
+
+/// Total order of a type `T` implementing `Ord`, giving a max-heap
+///
+/// Unlike `MaxComparator`, this comparator never finds two elements incomparable, and uses `cmp`
+/// instead of `partial_cmp`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OrdComparator;
+
+impl<T: core::cmp::Ord> Compare<T> for OrdComparator {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        Some(a.cmp(b))
+    }
+}
+
+/// Total order of floating-point numbers given by `total_cmp`, giving a max-heap
+///
+/// All numbers are comparable: NaNs with a positive sign are larger than positive infinity, and
+/// NaNs with a negative sign smaller than negative infinity; `-0.0` is smaller than `0.0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TotalOrderComparator;
+
+// `Compare` implementation for each floating-point type
+macro_rules! impl_total_order {
+    ($($t:ty),*) => {
+        $(
+            impl Compare<$t> for TotalOrderComparator {
+                #[inline]
+                fn compare(&self, a: &$t, b: &$t) -> Option<Ordering> {
+                    Some(a.total_cmp(b))
+                }
+            }
+        )*
+    };
+}
+
+impl_total_order!(f32, f64);
+
+/// A boxed comparator compares elements like the comparator it contains
+///
+/// In particular, `Box<dyn Compare<T>>` can be used to choose the order of a heap at run time
+/// (see `DynHeap`).
+impl<T, C: Compare<T> + ?Sized> Compare<T> for alloc::boxed::Box<C> {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        (**self).compare(a, b)
+    }
+}
+
+/// A reference to a comparator compares elements like the comparator it points to
+impl<T, C: Compare<T> + ?Sized> Compare<T> for &C {
+    #[inline]
+    fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        (**self).compare(a, b)
+    }
+}
@@ -0,0 +1,282 @@
+//! Conversions between `BinaryHeap` and other types
+
+use crate::BinaryHeap;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+/// Build a `BinaryHeap` from a vector, reusing its memory
+///
+/// Worst-case complexity: $\Theta(n)$, where $n$ is the length of the vector.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+///
+/// let heap = BinaryHeap::from(vec![1, 3, 2]);
+///
+/// assert_eq!(vec![3, 2, 1], heap.to_vec());
+/// ```
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for BinaryHeap<T> {
+    fn from(values: Vec<T>) -> Self {
+        let mut heap = BinaryHeap::new();
+        heap.data = values;
+        heap.rebuild();
+        heap
+    }
+}
+
+/// Build a `BinaryHeap` from an array
+///
+/// Worst-case complexity: $\Theta(N)$.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+///
+/// let heap = BinaryHeap::from([1, 3, 2]);
+///
+/// assert_eq!(vec![3, 2, 1], heap.to_vec());
+/// ```
+impl<T: core::cmp::PartialOrd, const N: usize> From<[T; N]> for BinaryHeap<T> {
+    fn from(values: [T; N]) -> Self {
+        BinaryHeap::from(Vec::from(values))
+    }
+}
+
+/// Build a `BinaryHeap` from copies of the elements of a slice
+///
+/// Worst-case complexity: $\Theta(n)$, where $n$ is the length of the slice.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+///
+/// let values = [1, 3, 2];
+/// let heap = BinaryHeap::from(&values[..]);
+///
+/// assert_eq!(vec![3, 2, 1], heap.to_vec());
+/// ```
+impl<T: core::cmp::PartialOrd + Clone> From<&[T]> for BinaryHeap<T> {
+    fn from(values: &[T]) -> Self {
+        BinaryHeap::from(values.to_vec())
+    }
+}
+
+/// Build a `BinaryHeap` from a `BTreeSet`
+///
+/// The elements of the set come sorted; in decreasing order, they form a valid heap, so that no
+/// comparison is needed.
+///
+/// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the set.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+/// use std::collections::BTreeSet;
+///
+/// let set = BTreeSet::from([1, 3, 2]);
+/// let heap = BinaryHeap::from(set);
+///
+/// assert_eq!(vec![3, 2, 1], heap.to_vec());
+/// ```
+impl<T: core::cmp::Ord> From<BTreeSet<T>> for BinaryHeap<T> {
+    fn from(set: BTreeSet<T>) -> Self {
+        let mut heap = BinaryHeap::new();
+        heap.data = set.into_iter().rev().collect();
+        heap
+    }
+}
+
+/// Error returned when building a heap of floating-point numbers from a slice containing a NaN
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NanError {
+    /// Index of the first NaN in the slice
+    pub index: usize,
+}
+
+impl core::fmt::Display for NanError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "NaN at index {}", self.index)
+    }
+}
+
+impl core::error::Error for NanError {}
+
+/// Floating-point values to be checked for NaN when converted into a heap
+///
+/// `BinaryHeap<f64>` already implements `From<Vec<f64>>`, which does not check the values; the
+/// conversion from `NanChecked(values)` with `TryFrom` returns a `NanError` if they contain a NaN.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, NanChecked, NanError};
+///
+/// let heap = BinaryHeap::try_from(NanChecked(vec![1.0, 3.0, 2.0]));
+/// assert_eq!(vec![3.0, 2.0, 1.0], heap.unwrap().to_vec());
+///
+/// let heap = BinaryHeap::try_from(NanChecked(vec![1.0, f64::NAN]));
+/// assert_eq!(NanError { index: 1 }, heap.unwrap_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NanChecked<V>(pub V);
+
+// `TryFrom` and `try_from_floats` for each floating-point type
+macro_rules! impl_try_from_floats {
+    ($($t:ty),*) => {
+        $(
+            /// Build a heap from a vector of floating-point numbers, reusing its memory, or return
+            /// an error if it contains a NaN
+            ///
+            /// Worst-case complexity: $\Theta(n)$, where $n$ is the length of the vector.
+            impl TryFrom<NanChecked<Vec<$t>>> for BinaryHeap<$t> {
+                type Error = NanError;
+
+                fn try_from(values: NanChecked<Vec<$t>>) -> Result<Self, NanError> {
+                    match values.0.iter().position(|x| x.is_nan()) {
+                        Some(index) => Err(NanError { index }),
+                        None => Ok(BinaryHeap::from(values.0)),
+                    }
+                }
+            }
+
+            /// Build a heap from a slice of floating-point numbers, or return an error if it
+            /// contains a NaN
+            ///
+            /// Worst-case complexity: $\Theta(n)$, where $n$ is the length of the slice.
+            impl TryFrom<NanChecked<&[$t]>> for BinaryHeap<$t> {
+                type Error = NanError;
+
+                fn try_from(values: NanChecked<&[$t]>) -> Result<Self, NanError> {
+                    match values.0.iter().position(|x| x.is_nan()) {
+                        Some(index) => Err(NanError { index }),
+                        None => Ok(BinaryHeap::from(values.0)),
+                    }
+                }
+            }
+
+            impl BinaryHeap<$t> {
+
+                /// Build a heap from a slice of floating-point numbers, returning an error if the
+                /// slice contains a NaN (which can not be compared with other numbers and would
+                /// break the order of the heap)
+                ///
+                /// This is equivalent to `BinaryHeap::try_from(NanChecked(values))`.
+                ///
+                /// Worst-case complexity: $\Theta(n)$, where $n$ is the length of the slice.
+                ///
+                /// # Example
+                ///
+                /// ```
+                /// use binary_heap::{BinaryHeap, NanError};
+                ///
+                #[doc = concat!("let heap = BinaryHeap::<", stringify!($t), ">::try_from_floats(&[1.0, 3.0, 2.0]);")]
+                /// assert_eq!(vec![3.0, 2.0, 1.0], heap.unwrap().to_vec());
+                ///
+                #[doc = concat!("let heap = BinaryHeap::<", stringify!($t), ">::try_from_floats(&[1.0, ", stringify!($t), "::NAN]);")]
+                /// assert_eq!(NanError { index: 1 }, heap.unwrap_err());
+                /// ```
+                #[inline]
+                pub fn try_from_floats(values: &[$t]) -> Result<Self, NanError> {
+                    BinaryHeap::try_from(NanChecked(values))
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_floats!(f32, f64);
+
+/// Convert a `std::collections::BinaryHeap` into a `BinaryHeap`
+///
+/// Both types use the same array representation, so the array of `heap` is reused as is: the
+/// conversion does not compare nor move any element.
+///
+/// Worst-case complexity: $\Theta(1)$.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+///
+/// let std_heap = std::collections::BinaryHeap::from(vec![1, 3, 2]);
+/// let heap = BinaryHeap::from(std_heap);
+///
+/// assert_eq!(vec![3, 2, 1], heap.to_vec());
+/// ```
+impl<T: core::cmp::Ord> From<alloc::collections::BinaryHeap<T>> for BinaryHeap<T> {
+    fn from(heap: alloc::collections::BinaryHeap<T>) -> Self {
+        let mut result = BinaryHeap::new();
+        result.data = heap.into_vec();
+        result
+    }
+}
+
+/// Convert a `BinaryHeap` into a `std::collections::BinaryHeap`
+///
+/// Both types use the same array representation, so the array of `heap` is reused and its
+/// elements are not moved. The standard library does not allow building its heap from an array
+/// without checking it, which costs $\Theta(n)$ comparisons.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+///
+/// let heap = BinaryHeap::from_vec(&[1, 3, 2]);
+/// let std_heap = std::collections::BinaryHeap::from(heap);
+///
+/// assert_eq!(vec![3, 2, 1], std_heap.into_sorted_vec().into_iter().rev().collect::<Vec<_>>());
+/// ```
+impl<T: core::cmp::Ord> From<BinaryHeap<T>> for alloc::collections::BinaryHeap<T> {
+    fn from(heap: BinaryHeap<T>) -> Self {
+        alloc::collections::BinaryHeap::from(heap.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn std_layout_preserved() {
+        let values = [0, 2, -2, 4, 6, 8, -1, 1, 3, 5, 7, 3, 3];
+        let heap = BinaryHeap::from_vec(&values);
+        let data = heap.data.clone();
+        let std_heap = std::collections::BinaryHeap::from(heap);
+        assert_eq!(data, std_heap.clone().into_vec());
+        let heap_2 = BinaryHeap::from(std_heap);
+        assert_eq!(data, heap_2.data);
+    }
+
+    #[test]
+    fn from_vec_bulk() {
+        let values = vec![0, 2, -2, 4, 6, 8, -1, 1, 3, 5, 7, 3, 3];
+        let heap = BinaryHeap::from(values.clone());
+        assert_eq!(BinaryHeap::sort(&values), heap.to_vec());
+        assert!(BinaryHeap::<u8>::from(vec![]).data.is_empty());
+    }
+
+    #[test]
+    fn from_btree_set() {
+        let set: BTreeSet<isize> = [0, 2, -2, 4, 6, 8, -1, 1].into_iter().collect();
+        let heap = BinaryHeap::from(set);
+        assert_eq!(vec![8, 6, 4, 2, 1, 0, -1, -2], heap.data);
+    }
+
+    #[test]
+    fn from_std() {
+        let mut std_heap = std::collections::BinaryHeap::new();
+        for x in [5, 1, 4, 1, 5, 9, 2, 6] {
+            std_heap.push(x);
+        }
+        let data = std_heap.clone().into_vec();
+        let heap = BinaryHeap::from(std_heap);
+        assert_eq!(data, heap.data);
+        assert_eq!(vec![9, 6, 5, 5, 4, 2, 1, 1], heap.to_vec());
+    }
+}
@@ -0,0 +1,171 @@
+//! Heap storing distinct elements with their multiplicities
+//!
+//! The heap holds each distinct element once, and a hash map its number of occurrences: inserting
+//! an element already present only increments its count, and `pop` only removes an element from
+//! the heap when its count drops to zero. The memory used is proportional to the number of
+//! distinct elements.
+
+use crate::BinaryHeap;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A heap of elements with multiplicities
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::CountingHeap;
+///
+/// let mut heap = CountingHeap::new();
+/// for x in [3, 1, 3, 3, 2] {
+///     heap.insert(x);
+/// }
+///
+/// assert_eq!(5, heap.size());
+/// assert_eq!(3, heap.distinct());
+/// assert_eq!(Some((&3, 3)), heap.peek());
+/// assert_eq!(vec![3, 3, 3, 2, 1], heap.collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Debug)]
+pub struct CountingHeap<T> {
+    heap: BinaryHeap<T>,            // distinct elements
+    counts: HashMap<T, usize>,      // number of occurrences of each element
+    size: usize,                    // total number of occurrences
+}
+
+impl<T: core::cmp::PartialOrd + Hash + Eq + Clone> CountingHeap<T> {
+
+    /// Create a new empty `CountingHeap`
+    #[inline]
+    pub fn new() -> Self {
+        CountingHeap { heap: BinaryHeap::new(), counts: HashMap::new(), size: 0 }
+    }
+
+    /// Get the number of elements, counted with their multiplicities
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Get the number of distinct elements
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn distinct(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Get the number of occurrences of `x`
+    ///
+    /// Expected complexity: $\Theta(1)$.
+    #[inline]
+    pub fn occurrences(&self, x: &T) -> usize {
+        self.counts.get(x).copied().unwrap_or(0)
+    }
+
+    /// Insert one occurrence of `x`
+    ///
+    /// Expected complexity: $\Theta(1)$ if `x` is already present, $\Theta(\log d)$ otherwise,
+    /// where $d$ is the number of distinct elements.
+    #[inline]
+    pub fn insert(&mut self, x: T) {
+        self.insert_many(x, 1);
+    }
+
+    /// Insert `n` occurrences of `x`
+    ///
+    /// Expected complexity: $\Theta(1)$ if `x` is already present or `n` is zero, $\Theta(\log d)$
+    /// otherwise, where $d$ is the number of distinct elements.
+    pub fn insert_many(&mut self, x: T, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.size += n;
+        match self.counts.get_mut(&x) {
+            Some(count) => *count += n,
+            None => {
+                self.counts.insert(x.clone(), n);
+                self.heap.insert(x);
+            },
+        }
+    }
+
+    /// Remove one occurrence of the root element and return it (or `None` if the heap is empty)
+    ///
+    /// Expected complexity: $\Theta(1)$ if the root has other occurrences, $\Theta(\log d)$
+    /// otherwise, where $d$ is the number of distinct elements.
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.heap.data.first()?;
+        let count = self.counts.get_mut(root).unwrap();
+        self.size -= 1;
+        if *count > 1 {
+            *count -= 1;
+            Some(root.clone())
+        } else {
+            let root = self.heap.pop().unwrap();
+            self.counts.remove(&root);
+            Some(root)
+        }
+    }
+
+    /// Return a reference to the root element with its number of occurrences (or `None` if the
+    /// heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$ (expected).
+    pub fn peek(&self) -> Option<(&T, usize)> {
+        self.heap.data.first().map(|root| (root, self.counts[root]))
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Hash + Eq + Clone> core::default::Default for CountingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Hash + Eq + Clone> Iterator for CountingHeap<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    // the number of remaining elements is exactly the size of the heap
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Hash + Eq + Clone> ExactSizeIterator for CountingHeap<T> {}
+
+impl<T: core::cmp::PartialOrd + Hash + Eq + Clone> core::iter::FusedIterator for CountingHeap<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn many_occurrences() {
+        let mut heap = CountingHeap::new();
+        for i in 0..100_000u32 {
+            heap.insert(i % 7);
+        }
+        heap.insert_many(100, 3);
+        heap.insert_many(50, 0);
+        assert_eq!(100_003, heap.size());
+        assert_eq!(8, heap.distinct());
+        assert_eq!(0, heap.occurrences(&50));
+        assert_eq!(14_285, heap.occurrences(&6));
+        for _ in 0..3 {
+            assert_eq!(Some(100), heap.pop());
+        }
+        assert_eq!(Some((&6, 14_285)), heap.peek());
+        let rest: Vec<u32> = heap.collect();
+        assert_eq!(100_000, rest.len());
+        assert!(rest.windows(2).all(|w| w[0] >= w[1]));
+    }
+}
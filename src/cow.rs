@@ -0,0 +1,160 @@
+//! Heap with copy-on-write snapshots
+//!
+//! The heap is shared through an `Arc`: a snapshot is another pointer to it, and the first
+//! modification of the heap while a snapshot is alive copies it. Taking a snapshot, rolling back
+//! to it, and dropping it (committing the changes made since) are thus constant-time.
+
+use crate::{BinaryHeap, Compare};
+use alloc::sync::Arc;
+
+/// Saved state of a `CowHeap`, to roll back to
+#[derive(Clone, Debug)]
+pub struct CowSnapshot<T, C> {
+    heap: Arc<BinaryHeap<T, C>>,
+}
+
+/// A `BinaryHeap` with constant-time snapshots and rollbacks
+///
+/// `snapshot` saves the state of the heap without copying it; the elements are copied only when
+/// the heap is first modified while a snapshot is alive. `rollback` restores a saved state, and
+/// dropping a snapshot commits the changes made since, both in $\Theta(1)$ time. This suits
+/// speculative computations, which try a sequence of operations and then either keep or discard
+/// their effects.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, CowHeap};
+///
+/// let mut plan = CowHeap::new(BinaryHeap::from(vec![3, 8, 5]));
+///
+/// // try a sequence of operations, then roll back
+/// let snapshot = plan.snapshot();
+/// plan.pop();
+/// plan.insert(1);
+/// assert_eq!(Some(&5), plan.peek());
+/// plan.rollback(snapshot);
+/// assert_eq!(Some(&8), plan.peek());
+///
+/// // try another one, and commit it by dropping the snapshot
+/// let snapshot = plan.snapshot();
+/// plan.insert(10);
+/// drop(snapshot);
+/// assert_eq!(vec![10, 8, 5, 3], plan.into_inner().to_vec());
+/// ```
+#[derive(Clone, Debug)]
+pub struct CowHeap<T, C> {
+    heap: Arc<BinaryHeap<T, C>>,
+}
+
+impl<T: Clone, C: Compare<T> + Clone> CowHeap<T, C> {
+
+    /// Wrap `heap`
+    #[inline]
+    pub fn new(heap: BinaryHeap<T, C>) -> Self {
+        CowHeap { heap: Arc::new(heap) }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first()
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap,
+    /// plus $\Theta(n)$ to copy the heap if it is shared with a snapshot.
+    #[inline]
+    pub fn insert(&mut self, x: T) {
+        self.heap_mut().insert(x);
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap,
+    /// plus $\Theta(n)$ to copy the heap if it is shared with a snapshot.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap_mut().pop()
+    }
+
+    /// Return a reference to the heap
+    #[inline]
+    pub fn heap(&self) -> &BinaryHeap<T, C> {
+        &self.heap
+    }
+
+    /// Return a mutable reference to the heap, copying it first if it is shared with a snapshot
+    ///
+    /// Worst-case complexity: $\Theta(n)$ if the heap is shared, where $n$ is the number of
+    /// elements, and $\Theta(1)$ otherwise.
+    #[inline]
+    pub fn heap_mut(&mut self) -> &mut BinaryHeap<T, C> {
+        Arc::make_mut(&mut self.heap)
+    }
+
+    /// Save the current state of the heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn snapshot(&self) -> CowSnapshot<T, C> {
+        CowSnapshot { heap: self.heap.clone() }
+    }
+
+    /// Restore the state saved in `snapshot`
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn rollback(&mut self, snapshot: CowSnapshot<T, C>) {
+        self.heap = snapshot.heap;
+    }
+
+    /// Consume the wrapper and return the heap, copying it if it is shared with a snapshot
+    #[inline]
+    pub fn into_inner(self) -> BinaryHeap<T, C> {
+        Arc::unwrap_or_clone(self.heap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_snapshots() {
+        let mut heap = CowHeap::new(BinaryHeap::from((0..10).collect::<Vec<u32>>()));
+        let outer = heap.snapshot();
+        heap.pop();
+        let inner = heap.snapshot();
+        heap.insert(20);
+        heap.insert(30);
+        assert_eq!(11, heap.size());
+        heap.rollback(inner.clone());
+        assert_eq!(Some(&8), heap.peek());
+        heap.insert(15);
+        heap.rollback(inner);
+        assert_eq!(9, heap.size());
+        heap.rollback(outer);
+        assert_eq!((0..10).rev().collect::<Vec<u32>>(), heap.into_inner().to_vec());
+    }
+
+    #[test]
+    fn commit_without_copy() {
+        let mut heap = CowHeap::new(BinaryHeap::from(vec![1, 2, 3]));
+        let address = heap.heap() as *const BinaryHeap<i32, _>;
+        drop(heap.snapshot());
+        heap.insert(4);
+        assert_eq!(address, heap.heap() as *const BinaryHeap<i32, _>);
+    }
+}
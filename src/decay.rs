@@ -0,0 +1,177 @@
+//! Priority queue whose priorities decay exponentially with time
+//!
+//! The effective priority of a value inserted at time `t` with score `s` is
+//! `s * 2^(-(now - t) / half_life)`. With an exponential decay, the order of two values does not
+//! depend on `now`: the effective priority of the first one is larger than the second one's if
+//! and only if `ln(s_1) + λ t_1 > ln(s_2) + λ t_2`, with `λ = ln(2) / half_life`. This quantity is
+//! computed at comparison time from the stored score and timestamp, so the heap never needs to
+//! be rebuilt as time passes.
+
+use crate::{BinaryHeap, Compare};
+use core::cmp::Ordering;
+
+// value with its score and insertion time
+#[derive(Clone, Debug)]
+struct Entry<V> {
+    score: f64,
+    timestamp: f64,
+    value: V,
+}
+
+// order of the entries by effective priority, with the decay rate `λ`
+#[derive(Clone, Copy, Debug)]
+struct DecayComparator {
+    rate: f64,
+}
+
+impl DecayComparator {
+    // logarithm of the effective priority, up to a constant depending only on the current time
+    #[inline]
+    fn key<V>(&self, entry: &Entry<V>) -> f64 {
+        entry.score.ln() + self.rate * entry.timestamp
+    }
+}
+
+impl<V> Compare<Entry<V>> for DecayComparator {
+    #[inline]
+    fn compare(&self, a: &Entry<V>, b: &Entry<V>) -> Option<Ordering> {
+        self.key(a).partial_cmp(&self.key(b))
+    }
+}
+
+/// A priority queue whose priorities decay exponentially with time
+///
+/// Each value is inserted with a positive score and a timestamp (in any unit, as an `f64`); its
+/// effective priority at time `now` is the score halved every `half_life` time units. As the order
+/// of the values does not change with time, no periodic rebuild is needed: `pop` and `peek` take
+/// the current time only to compute the effective priority they return.
+///
+/// Only available with the `std` feature (for the floating-point functions).
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::DecayingHeap;
+///
+/// // trending posts, with a half-life of one hour (in seconds)
+/// let mut posts = DecayingHeap::new(3600.0);
+/// posts.insert(100.0, 0.0, "old but popular");
+/// posts.insert(60.0, 3600.0, "recent");
+///
+/// // at time 7200, the scores have decayed to 25 and 30
+/// let (score, post) = posts.pop(7200.0).unwrap();
+/// assert_eq!("recent", post);
+/// assert!((score - 30.0).abs() < 1e-9);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecayingHeap<V> {
+    heap: BinaryHeap<Entry<V>, DecayComparator>,
+    half_life: f64,
+}
+
+impl<V> DecayingHeap<V> {
+
+    /// Create a new empty `DecayingHeap` whose priorities are halved every `half_life` time
+    /// units
+    ///
+    /// # Panics
+    ///
+    /// Panics if `half_life` is not positive and finite.
+    pub fn new(half_life: f64) -> Self {
+        assert!(half_life > 0.0 && half_life.is_finite(), "the half-life must be positive and finite");
+        let rate = core::f64::consts::LN_2 / half_life;
+        DecayingHeap { heap: BinaryHeap::with_comparator(DecayComparator { rate }), half_life }
+    }
+
+    /// Get the number of values in the heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert `value` with score `score` at time `timestamp`
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of values in the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `score` is not positive and finite, or if `timestamp` is not finite.
+    pub fn insert(&mut self, score: f64, timestamp: f64, value: V) {
+        assert!(score > 0.0 && score.is_finite(), "the score must be positive and finite");
+        assert!(timestamp.is_finite(), "the timestamp must be finite");
+        self.heap.insert(Entry { score, timestamp, value });
+    }
+
+    /// Remove and return a value with the highest effective priority, with its effective priority
+    /// at time `now` (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of values in the heap.
+    pub fn pop(&mut self, now: f64) -> Option<(f64, V)> {
+        let entry = self.heap.pop()?;
+        Some((self.decayed(&entry, now), entry.value))
+    }
+
+    /// Return a reference to a value with the highest effective priority, with its effective
+    /// priority at time `now` (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    pub fn peek(&self, now: f64) -> Option<(f64, &V)> {
+        self.heap.data.first().map(|entry| (self.decayed(entry, now), &entry.value))
+    }
+
+    // effective priority of `entry` at time `now`
+    #[inline]
+    fn decayed(&self, entry: &Entry<V>, now: f64) -> f64 {
+        entry.score * (-(now - entry.timestamp) / self.half_life).exp2()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_1() {
+        let mut heap = DecayingHeap::new(10.0);
+        // effective priorities at time 20: 25, 40, 20, 80
+        heap.insert(100.0, 0.0, 'a');
+        heap.insert(80.0, 10.0, 'b');
+        heap.insert(20.0, 20.0, 'c');
+        heap.insert(40.0, 30.0, 'd');
+        let (score, value) = heap.peek(20.0).unwrap();
+        assert_eq!('d', *value);
+        assert!((score - 80.0).abs() < 1e-9);
+        let order: Vec<char> = std::iter::from_fn(|| heap.pop(20.0)).map(|(_, v)| v).collect();
+        assert_eq!(vec!['d', 'b', 'a', 'c'], order);
+        assert_eq!(0, heap.size());
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        use std::panic::catch_unwind;
+
+        // message of the panic of `f`
+        fn message(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+            let payload = catch_unwind(f).unwrap_err();
+            payload.downcast_ref::<&str>().unwrap().to_string()
+        }
+
+        for half_life in [0.0, -10.0, f64::INFINITY, f64::NAN] {
+            assert_eq!("the half-life must be positive and finite", message(move || {
+                DecayingHeap::<()>::new(half_life);
+            }));
+        }
+        for score in [0.0, -1.0, f64::INFINITY, f64::NAN] {
+            assert_eq!("the score must be positive and finite", message(move || {
+                DecayingHeap::new(10.0).insert(score, 0.0, ());
+            }));
+        }
+        for timestamp in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            assert_eq!("the timestamp must be finite", message(move || {
+                DecayingHeap::new(10.0).insert(1.0, timestamp, ());
+            }));
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Queue of items yielded once their deadlines have passed, driven by the `tokio` timer
+//!
+//! The items are kept in an `AddressableHeap` ordered by deadline, the earliest at the root, so
+//! that they can be reset or removed through the handles returned on insertion. A single `tokio`
+//! sleep is armed for the earliest deadline when the queue is polled.
+//!
+//! Only available with the `tokio` feature.
+
+use crate::{AddressableHeap, Compare, Handle};
+use core::cmp::Ordering;
+use core::future::{poll_fn, Future};
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use std::boxed::Box;
+use tokio::time::{Instant, Sleep};
+
+// item with its deadline, and a sequence number ordering items with equal deadlines
+#[derive(Debug)]
+struct Timer<T> {
+    deadline: Instant,
+    sequence: u64,
+    item: T,
+}
+
+// order of the timers: the earliest deadline is the largest, then the earliest insertion
+#[derive(Clone, Copy, Debug, Default)]
+struct EarliestFirst;
+
+impl<T> Compare<Timer<T>> for EarliestFirst {
+    #[inline]
+    fn compare(&self, a: &Timer<T>, b: &Timer<T>) -> Option<Ordering> {
+        Some((b.deadline, b.sequence).cmp(&(a.deadline, a.sequence)))
+    }
+}
+
+/// An item whose deadline has passed, returned by `DelayQueue::next` and
+/// `DelayQueue::poll_expired`
+#[derive(Debug)]
+pub struct Expired<T> {
+    item: T,
+    deadline: Instant,
+    key: Handle,
+}
+
+impl<T> Expired<T> {
+
+    /// Get a reference to the item
+    #[inline]
+    pub fn get_ref(&self) -> &T {
+        &self.item
+    }
+
+    /// Get a mutable reference to the item
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.item
+    }
+
+    /// Get the item
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+
+    /// Get the deadline of the item
+    #[inline]
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Get the key the item was inserted with, which is no longer valid
+    #[inline]
+    pub fn key(&self) -> Handle {
+        self.key
+    }
+}
+
+/// A queue yielding its items once their deadlines have passed, earliest deadline first
+///
+/// `insert_at` and `insert` return a key, with which the deadline of the item can be changed by
+/// `reset_at` or `reset`, or the item removed by `remove`. `next().await` waits until the earliest
+/// deadline has passed and returns its item, or returns `None` at once if the queue is empty.
+/// Items with equal deadlines are yielded in insertion order. The queue must be polled within a
+/// `tokio` runtime with the timer enabled.
+///
+/// Only available with the `tokio` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::DelayQueue;
+/// use std::time::Duration;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut timeouts = DelayQueue::new();
+/// let slow = timeouts.insert(Duration::from_millis(30), "slow request");
+/// timeouts.insert(Duration::from_millis(10), "fast request");
+///
+/// // the slow request answered in time
+/// assert_eq!(Some("slow request"), timeouts.remove(slow));
+/// assert_eq!("fast request", timeouts.next().await.unwrap().into_inner());
+/// assert!(timeouts.next().await.is_none());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DelayQueue<T> {
+    timers: AddressableHeap<Timer<T>, EarliestFirst>,
+    next_sequence: u64,             // sequence number of the next inserted item
+    sleep: Option<Pin<Box<Sleep>>>, // timer armed for the earliest deadline, once polled
+    waker: Option<Waker>,           // waker of the task waiting for the earliest deadline
+}
+
+impl<T> DelayQueue<T> {
+
+    /// Create a new empty `DelayQueue`
+    #[inline]
+    pub fn new() -> Self {
+        DelayQueue {
+            timers: AddressableHeap::with_comparator(EarliestFirst),
+            next_sequence: 0,
+            sleep: None,
+            waker: None,
+        }
+    }
+
+    /// Get the number of items in the queue
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.timers.size()
+    }
+
+    /// Insert an item yielded at `deadline`, and return its key
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of items in the queue.
+    pub fn insert_at(&mut self, deadline: Instant, item: T) -> Handle {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        let key = self.timers.insert(Timer { deadline, sequence, item });
+        self.wake_if_earliest(deadline);
+        key
+    }
+
+    /// Insert an item yielded once `timeout` has elapsed, and return its key
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of items in the queue.
+    #[inline]
+    pub fn insert(&mut self, timeout: Duration, item: T) -> Handle {
+        self.insert_at(Instant::now() + timeout, item)
+    }
+
+    /// Get the deadline of the item of `key` (or `None` if it is no longer in the queue)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn deadline(&self, key: Handle) -> Option<Instant> {
+        self.timers.get(key).map(|timer| timer.deadline)
+    }
+
+    /// Get a reference to the item of `key` (or `None` if it is no longer in the queue)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn get(&self, key: Handle) -> Option<&T> {
+        self.timers.get(key).map(|timer| &timer.item)
+    }
+
+    /// Get the earliest deadline of the queue (or `None` if it is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_deadline(&self) -> Option<Instant> {
+        self.timers.peek().map(|timer| timer.deadline)
+    }
+
+    /// Change the deadline of the item of `key`, returning `false` if it is no longer in the
+    /// queue
+    ///
+    /// The item is then yielded after the items already in the queue with the same deadline.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of items in the queue.
+    pub fn reset_at(&mut self, key: Handle, deadline: Instant) -> bool {
+        let sequence = self.next_sequence;
+        let reset = self.timers.update(key, |timer| {
+            timer.deadline = deadline;
+            timer.sequence = sequence;
+        });
+        if reset.is_none() {
+            return false;
+        }
+        self.next_sequence += 1;
+        self.wake_if_earliest(deadline);
+        true
+    }
+
+    /// Change the deadline of the item of `key` to `timeout` from now, returning `false` if it is
+    /// no longer in the queue
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of items in the queue.
+    #[inline]
+    pub fn reset(&mut self, key: Handle, timeout: Duration) -> bool {
+        self.reset_at(key, Instant::now() + timeout)
+    }
+
+    /// Remove the item of `key` and return it (or `None` if it is no longer in the queue)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of items in the queue.
+    #[inline]
+    pub fn remove(&mut self, key: Handle) -> Option<T> {
+        self.timers.remove(key).map(|timer| timer.item)
+    }
+
+    /// Remove all the items
+    pub fn clear(&mut self) {
+        while self.timers.pop().is_some() {}
+    }
+
+    /// Wait until the earliest deadline has passed, then remove its item and return it (or
+    /// return `None` at once if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of items in the queue,
+    /// once the deadline has passed.
+    pub async fn next(&mut self) -> Option<Expired<T>> {
+        poll_fn(|cx| self.poll_expired(cx)).await
+    }
+
+    /// Remove and return the item of the earliest deadline if it has passed, return `None` if
+    /// the queue is empty, or register the task to be woken up at the earliest deadline
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of items in the queue.
+    pub fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<Option<Expired<T>>> {
+        let Some(deadline) = self.peek_deadline() else {
+            self.waker = None;
+            return Poll::Ready(None);
+        };
+        if deadline > Instant::now() {
+            // arm the timer for the earliest deadline, which may have changed since the last poll
+            match &mut self.sleep {
+                Some(sleep) if sleep.deadline() == deadline => {},
+                Some(sleep) => sleep.as_mut().reset(deadline),
+                None => self.sleep = Some(Box::pin(tokio::time::sleep_until(deadline))),
+            }
+            if self.sleep.as_mut().unwrap().as_mut().poll(cx).is_pending() {
+                self.waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+
+        let key = self.timers.peek_handle().unwrap();
+        let timer = self.timers.pop().unwrap();
+        Poll::Ready(Some(Expired { item: timer.item, deadline: timer.deadline, key }))
+    }
+
+    // wake up the waiting task if `deadline` is now the earliest one, so that it arms its timer
+    // again
+    fn wake_if_earliest(&mut self, deadline: Instant) {
+        if self.peek_deadline() == Some(deadline) {
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> core::default::Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Unpin for DelayQueue<T> {}
+
+#[cfg(feature = "futures")]
+impl<T> futures_core::Stream for DelayQueue<T> {
+    type Item = Expired<T>;
+
+    /// Poll for the item of the earliest deadline; the stream ends whenever the queue is empty,
+    /// but can be polled again after an insertion
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Expired<T>>> {
+        self.get_mut().poll_expired(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[tokio::test(start_paused = true)]
+    async fn deadlines_in_order() {
+        let start = Instant::now();
+        let mut queue = DelayQueue::new();
+        let keys: Vec<Handle> = [50, 10, 30, 10, 40].into_iter().enumerate()
+            .map(|(i, ms)| queue.insert(Duration::from_millis(ms), i))
+            .collect();
+
+        // postpone item 1 past item 0, and cancel item 4
+        assert!(queue.reset(keys[1], Duration::from_millis(60)));
+        assert_eq!(Some(4), queue.remove(keys[4]));
+        assert!(!queue.reset(keys[4], Duration::from_millis(1)));
+
+        let mut yielded = Vec::new();
+        while let Some(expired) = queue.next().await {
+            assert!(expired.deadline() <= Instant::now());
+            yielded.push((expired.into_inner(), (Instant::now() - start).as_millis()));
+        }
+        assert_eq!(vec![(3, 10), (2, 30), (0, 50), (1, 60)], yielded);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn earlier_insertion_wakes_waiting_task() {
+        let start = Instant::now();
+        let queue = std::sync::Arc::new(tokio::sync::Mutex::new(DelayQueue::new()));
+        queue.lock().await.insert(Duration::from_secs(10), "late");
+
+        // the waiting task is woken up by the earlier item, inserted while it waits
+        let waiting = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let item = poll_fn(|cx| match queue.try_lock() {
+                    Ok(mut queue) => queue.poll_expired(cx),
+                    Err(_) => {
+                        cx.waker().wake_by_ref();
+                        Poll::Pending
+                    },
+                }).await;
+                (item.unwrap().into_inner(), Instant::now() - start)
+            })
+        };
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        queue.lock().await.insert(Duration::from_secs(1), "early");
+        assert_eq!(("early", Duration::from_secs(2)), waiting.await.unwrap());
+    }
+}
@@ -0,0 +1,22 @@
+//! `defmt` logging support, for embedded targets
+
+use crate::BinaryHeap;
+
+/// Compact `defmt` representation of a heap: its size, root, and depth (number of levels)
+///
+/// The other elements are not printed, to keep the log messages short.
+///
+/// Only available with the `defmt` feature.
+impl<T: core::cmp::PartialOrd + defmt::Format> defmt::Format for BinaryHeap<T> {
+    fn format(&self, f: defmt::Formatter) {
+        let size = self.size();
+        let depth = (usize::BITS - size.leading_zeros()) as usize;
+        defmt::write!(
+            f,
+            "BinaryHeap {{ size: {=usize}, root: {}, depth: {=usize} }}",
+            size,
+            self.data.first(),
+            depth,
+        );
+    }
+}
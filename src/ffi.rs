@@ -0,0 +1,179 @@
+//! C API
+//!
+//! A heap is manipulated through an opaque pointer to a [`BinaryHeapHandle`], created by
+//! [`heap_new`] and destroyed by [`heap_free`]. It stores `void*` payloads ordered by `uint64_t`
+//! keys; the payloads are never dereferenced nor freed by the heap, and remain owned by the
+//! caller.
+//!
+//! The C header `include/binary_heap.h` is generated with `cbindgen` from this module. To build
+//! a C library, compile the crate with the `ffi` feature as a static or dynamic library, for
+//! instance with `cargo rustc --release --features ffi --crate-type staticlib`.
+
+use crate::BinaryHeap;
+use core::ffi::c_void;
+
+// payload with its key; only the key is compared
+struct Entry {
+    key: u64,
+    payload: *mut c_void,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+/// Opaque max binary heap of payloads ordered by `uint64_t` keys
+pub struct BinaryHeapHandle {
+    heap: BinaryHeap<Entry>,
+}
+
+/// Create a new empty heap
+///
+/// The heap must be destroyed with `heap_free`.
+#[no_mangle]
+pub extern "C" fn heap_new() -> *mut BinaryHeapHandle {
+    Box::into_raw(Box::new(BinaryHeapHandle { heap: BinaryHeap::new() }))
+}
+
+/// Destroy a heap (the payloads it still contains are not freed); do nothing if `heap` is null
+///
+/// # Safety
+///
+/// `heap` must be null or a pointer returned by `heap_new` which has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn heap_free(heap: *mut BinaryHeapHandle) {
+    if !heap.is_null() {
+        drop(Box::from_raw(heap));
+    }
+}
+
+/// Insert `payload` with key `key`; return `false` if `heap` is null
+///
+/// # Safety
+///
+/// `heap` must be null or a valid pointer returned by `heap_new`.
+#[no_mangle]
+pub unsafe extern "C" fn heap_insert(heap: *mut BinaryHeapHandle, key: u64, payload: *mut c_void)
+    -> bool
+{
+    match heap.as_mut() {
+        Some(handle) => {
+            handle.heap.insert(Entry { key, payload });
+            true
+        },
+        None => false,
+    }
+}
+
+// write an entry to the output pointers which are not null
+unsafe fn write_entry(entry: &Entry, key: *mut u64, payload: *mut *mut c_void) {
+    if let Some(key) = key.as_mut() {
+        *key = entry.key;
+    }
+    if let Some(payload) = payload.as_mut() {
+        *payload = entry.payload;
+    }
+}
+
+/// Remove the element with the largest key, writing its key to `*key` and its payload to
+/// `*payload` (if these pointers are not null); return `false` if the heap is empty or null
+///
+/// # Safety
+///
+/// `heap` must be null or a valid pointer returned by `heap_new`; `key` and `payload` must be
+/// null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn heap_pop(heap: *mut BinaryHeapHandle,
+                                  key: *mut u64,
+                                  payload: *mut *mut c_void)
+    -> bool
+{
+    match heap.as_mut().and_then(|handle| handle.heap.pop()) {
+        Some(entry) => {
+            write_entry(&entry, key, payload);
+            true
+        },
+        None => false,
+    }
+}
+
+/// Write the key and payload of the element with the largest key to `*key` and `*payload` (if
+/// these pointers are not null) without removing it; return `false` if the heap is empty or null
+///
+/// # Safety
+///
+/// `heap` must be null or a valid pointer returned by `heap_new`; `key` and `payload` must be
+/// null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn heap_peek(heap: *const BinaryHeapHandle,
+                                   key: *mut u64,
+                                   payload: *mut *mut c_void)
+    -> bool
+{
+    match heap.as_ref().and_then(|handle| handle.heap.data.first()) {
+        Some(entry) => {
+            write_entry(entry, key, payload);
+            true
+        },
+        None => false,
+    }
+}
+
+/// Return the number of elements in the heap (0 if `heap` is null)
+///
+/// # Safety
+///
+/// `heap` must be null or a valid pointer returned by `heap_new`.
+#[no_mangle]
+pub unsafe extern "C" fn heap_size(heap: *const BinaryHeapHandle) -> usize {
+    heap.as_ref().map_or(0, |handle| handle.heap.size())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::null_mut;
+
+    #[test]
+    fn insert_peek_pop() {
+        let mut payloads = [10u8, 20, 30];
+        unsafe {
+            let heap = heap_new();
+            for (key, payload) in [2u64, 7, 5].into_iter().zip(payloads.iter_mut()) {
+                assert!(heap_insert(heap, key, payload as *mut u8 as *mut c_void));
+            }
+            assert_eq!(3, heap_size(heap));
+
+            let mut key = 0u64;
+            let mut payload: *mut c_void = null_mut();
+            assert!(heap_peek(heap, &mut key, &mut payload));
+            assert_eq!((7, 20), (key, *(payload as *mut u8)));
+
+            let mut popped = Vec::new();
+            while heap_pop(heap, &mut key, &mut payload) {
+                popped.push((key, *(payload as *mut u8)));
+            }
+            assert_eq!(vec![(7, 20), (5, 30), (2, 10)], popped);
+            assert!(!heap_peek(heap, null_mut(), null_mut()));
+            heap_free(heap);
+        }
+    }
+
+    #[test]
+    fn null_heap() {
+        unsafe {
+            assert!(!heap_insert(null_mut(), 0, null_mut()));
+            assert!(!heap_pop(null_mut(), null_mut(), null_mut()));
+            assert_eq!(0, heap_size(core::ptr::null()));
+            heap_free(null_mut());
+        }
+    }
+}
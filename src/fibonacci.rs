@@ -0,0 +1,519 @@
+//! Fibonacci heap
+//!
+//! A Fibonacci heap is a list of heap-ordered trees, each node keeping its children in a circular
+//! doubly-linked list, with a pointer to the root with the largest element. Insertions and melds
+//! only add trees to the list of roots; `pop` consolidates it by linking trees whose roots have
+//! the same degree (number of children). An element moved towards the root is cut from its
+//! parent if it became larger, and a parent losing a second child is cut as well (cascading
+//! cuts), which keeps the degrees logarithmic. (Fredman and Tarjan, *Fibonacci heaps and their
+//! uses in improved network optimization algorithms*, 1987.)
+//!
+//! The nodes are stored in a slab, like those of `AddressableHeap`.
+
+use crate::{Compare, Handle, HandleMap, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+// node of a tree; `left` and `right` link the node to its siblings (or to itself if it has none)
+#[derive(Clone, Debug)]
+struct Node<T> {
+    value: T,
+    parent: Option<usize>,
+    child: Option<usize>,   // any child
+    left: usize,
+    right: usize,
+    degree: usize,          // number of children
+    marked: bool,           // whether the node lost a child since it became a child itself
+}
+
+// slot of the slab; the generation is incremented each time the slot is freed
+#[derive(Clone, Debug)]
+struct Slot<T> {
+    generation: u32,
+    node: Option<Node<T>>,
+}
+
+/// A mergeable heap with amortized constant-time insertion, meld, and moves towards the root
+///
+/// `insert` and `meld` take $\Theta(1)$ time, moving an element towards the root (with
+/// `increase_key`, or `decrease_key` with `MinComparator`, as in Dijkstra's and Prim's
+/// algorithms) $\Theta(1)$ amortized time, and `pop` and `remove` $O(\log n)$ amortized time.
+/// Elements are accessed through the handles returned by `insert`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{FibonacciHeap, MinComparator};
+///
+/// let mut heap = FibonacciHeap::with_comparator(MinComparator);
+/// let a = heap.insert(10);
+/// let b = heap.insert(20);
+/// heap.insert(15);
+///
+/// heap.decrease_key(b, 5);
+/// assert_eq!(Some(10), heap.remove(a));
+///
+/// let mut other = FibonacciHeap::with_comparator(MinComparator);
+/// other.insert(12);
+/// heap.meld(other);
+///
+/// assert_eq!(Some(5), heap.pop());
+/// assert_eq!(Some(12), heap.pop());
+/// assert_eq!(Some(15), heap.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FibonacciHeap<T, C = MaxComparator> {
+    slots: Vec<Slot<T>>,    // slab of nodes
+    free: Vec<usize>,       // indices of the free slots
+    max: Option<usize>,     // root with the largest element
+    size: usize,            // number of elements
+    comparator: C,          // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> FibonacciHeap<T> {
+
+    /// Create a new empty `FibonacciHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        FibonacciHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> FibonacciHeap<T, C> {
+
+    /// Create a new empty `FibonacciHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        FibonacciHeap { slots: Vec::new(), free: Vec::new(), max: None, size: 0, comparator }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap, returning a handle to it
+    ///
+    /// Worst-case complexity: $\Theta(1)$ (amortized, as the slab may grow).
+    pub fn insert(&mut self, x: T) -> Handle {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot { generation: 0, node: None });
+                self.slots.len() - 1
+            },
+        };
+        self.slots[index].node = Some(Node {
+            value: x, parent: None, child: None, left: index, right: index, degree: 0, marked: false,
+        });
+        self.add_root(index);
+        self.size += 1;
+        Handle { index, generation: self.slots[index].generation }
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.max.map(|max| &self.node(max).value)
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let max = self.max?;
+        self.extract(max);
+        Some(self.free_slot(max))
+    }
+
+    /// Return a reference to the element of `handle` (or `None` if it is no longer in the heap)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.index(handle).map(|index| &self.node(index).value)
+    }
+
+    /// Whether the element of `handle` is still in the heap
+    #[inline]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.index(handle).is_some()
+    }
+
+    /// Replace the element of `handle` by a smaller one `x`, returning the old element (or
+    /// `None`, leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact larger, the heap is repaired as well.
+    ///
+    /// Amortized complexity: $\Theta(1)$ if `x` is not smaller than the old element (in
+    /// particular with `MinComparator`, if it is not larger for the natural order), and
+    /// $O(\log n)$ otherwise, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn decrease_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Replace the element of `handle` by a larger one `x`, returning the old element (or `None`,
+    /// leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact smaller, the heap is repaired as well.
+    ///
+    /// Amortized complexity: $\Theta(1)$ if `x` is not smaller than the old element, and
+    /// $O(\log n)$ otherwise, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn increase_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Remove the element of `handle` and return it (or `None` if it is no longer in the heap)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let index = self.index(handle)?;
+        self.extract(index);
+        Some(self.free_slot(index))
+    }
+
+    /// Move all the elements of `other` into this heap, returning the translation of the handles
+    /// of `other`
+    ///
+    /// The lists of roots are concatenated in $\Theta(1)$ time; the nodes of `other` are moved to
+    /// the slab of this heap without any comparison.
+    ///
+    /// Worst-case complexity: $\Theta(m)$ (amortized, as the slab may grow), where $m$ is the
+    /// number of slots of `other`.
+    pub fn meld(&mut self, other: FibonacciHeap<T, C>) -> HandleMap {
+        let offset = self.slots.len();
+        let shift = |index: Option<usize>| index.map(|index| index + offset);
+        self.slots.extend(other.slots.into_iter().map(|mut slot| {
+            if let Some(node) = slot.node.as_mut() {
+                node.parent = shift(node.parent);
+                node.child = shift(node.child);
+                node.left += offset;
+                node.right += offset;
+            }
+            slot
+        }));
+        self.free.extend(other.free.iter().map(|index| index + offset));
+        self.size += other.size;
+        match (self.max, shift(other.max)) {
+            (_, None) => {},
+            (None, other_max) => self.max = other_max,
+            (Some(max), Some(other_max)) => {
+                // concatenate the circular lists of roots
+                let max_right = self.node(max).right;
+                let other_left = self.node(other_max).left;
+                self.node_mut(max).right = other_max;
+                self.node_mut(other_max).left = max;
+                self.node_mut(other_left).right = max_right;
+                self.node_mut(max_right).left = other_left;
+                if self.less(max, other_max) {
+                    self.max = Some(other_max);
+                }
+            },
+        }
+        HandleMap { offset }
+    }
+
+    // slot index of `handle`, if its element is still in the heap
+    fn index(&self, handle: Handle) -> Option<usize> {
+        self.slots.get(handle.index)
+            .filter(|slot| slot.generation == handle.generation && slot.node.is_some())
+            .map(|_| handle.index)
+    }
+
+    // node in the slot `index`, which must be occupied
+    #[inline]
+    fn node(&self, index: usize) -> &Node<T> {
+        self.slots[index].node.as_ref().unwrap()
+    }
+
+    #[inline]
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        self.slots[index].node.as_mut().unwrap()
+    }
+
+    // whether the element of the node `i` is smaller than that of the node `j`
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        self.comparator.compare(&self.node(i).value, &self.node(j).value) == Some(Ordering::Less)
+    }
+
+    // free the slot `index` and return its element
+    fn free_slot(&mut self, index: usize) -> T {
+        let slot = &mut self.slots[index];
+        let node = slot.node.take().unwrap();
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        self.size -= 1;
+        node.value
+    }
+
+    // insert the node `b` (alone in its list) after the node `a` in the list of `a`
+    fn splice(&mut self, a: usize, b: usize) {
+        let a_right = self.node(a).right;
+        self.node_mut(b).left = a;
+        self.node_mut(b).right = a_right;
+        self.node_mut(a_right).left = b;
+        self.node_mut(a).right = b;
+    }
+
+    // remove the node `x` from its list of siblings, leaving it alone
+    fn unlink(&mut self, x: usize) {
+        let Node { left, right, .. } = *self.node(x);
+        self.node_mut(left).right = right;
+        self.node_mut(right).left = left;
+        self.node_mut(x).left = x;
+        self.node_mut(x).right = x;
+    }
+
+    // add the node `x` (alone in its list) to the list of roots
+    fn add_root(&mut self, x: usize) {
+        let node = self.node_mut(x);
+        node.parent = None;
+        node.marked = false;
+        match self.max {
+            None => self.max = Some(x),
+            Some(max) => {
+                self.splice(max, x);
+                if self.less(max, x) {
+                    self.max = Some(x);
+                }
+            },
+        }
+    }
+
+    // move the node `x` from the children of its parent to the list of roots, then cut its
+    // ancestors which have lost two children
+    fn cut(&mut self, mut x: usize) {
+        while let Some(parent) = self.node(x).parent {
+            let right = self.node(x).right;
+            let parent_node = self.node_mut(parent);
+            if parent_node.child == Some(x) {
+                parent_node.child = if right == x { None } else { Some(right) };
+            }
+            parent_node.degree -= 1;
+            self.unlink(x);
+            self.add_root(x);
+
+            // a parent losing its first child is marked, and cut when losing the second one
+            if self.node(parent).parent.is_none() {
+                break;
+            }
+            if !self.node(parent).marked {
+                self.node_mut(parent).marked = true;
+                break;
+            }
+            x = parent;
+        }
+    }
+
+    // remove the node `x` from the trees, moving its children to the list of roots
+    fn extract(&mut self, x: usize) {
+        self.cut(x);
+
+        // the children become roots
+        while let Some(child) = self.node(x).child {
+            let right = self.node(child).right;
+            self.node_mut(x).child = if right == child { None } else { Some(right) };
+            self.unlink(child);
+            let node = self.node_mut(child);
+            node.parent = None;
+            node.marked = false;
+            self.splice(x, child);
+        }
+        self.node_mut(x).degree = 0;
+
+        let right = self.node(x).right;
+        self.unlink(x);
+        if right == x {
+            self.max = None;
+        } else {
+            self.max = Some(right);
+            self.consolidate();
+        }
+    }
+
+    // link the roots with the same degree until all degrees are distinct, and find the maximum
+    fn consolidate(&mut self) {
+        let start = self.max.unwrap();
+        let mut roots = Vec::new();
+        let mut root = start;
+        loop {
+            roots.push(root);
+            root = self.node(root).right;
+            if root == start {
+                break;
+            }
+        }
+
+        let mut by_degree: Vec<Option<usize>> = Vec::new();
+        for root in roots {
+            let mut x = root;
+            let mut degree = self.node(x).degree;
+            loop {
+                if degree >= by_degree.len() {
+                    by_degree.resize(degree + 1, None);
+                }
+                let Some(mut y) = by_degree[degree].take() else { break };
+
+                // the root with the smaller element becomes a child of the other one
+                if self.less(x, y) {
+                    core::mem::swap(&mut x, &mut y);
+                }
+                self.unlink(y);
+                match self.node(x).child {
+                    None => self.node_mut(x).child = Some(y),
+                    Some(child) => self.splice(child, y),
+                }
+                let node = self.node_mut(y);
+                node.parent = Some(x);
+                node.marked = false;
+                self.node_mut(x).degree += 1;
+                degree += 1;
+            }
+            by_degree[degree] = Some(x);
+        }
+
+        self.max = None;
+        for root in by_degree.into_iter().flatten() {
+            if self.max.is_none_or(|max| self.less(max, root)) {
+                self.max = Some(root);
+            }
+        }
+    }
+
+    // replace the element of `handle` and repair the heap
+    fn replace(&mut self, handle: Handle, x: T) -> Option<T> {
+        let index = self.index(handle)?;
+        let smaller = self.comparator.compare(&x, &self.node(index).value) == Some(Ordering::Less);
+        if smaller {
+            // the children may now be larger: take the node out of the trees, then add it back
+            self.extract(index);
+            let old = core::mem::replace(&mut self.node_mut(index).value, x);
+            self.add_root(index);
+            Some(old)
+        } else {
+            let old = core::mem::replace(&mut self.node_mut(index).value, x);
+            match self.node(index).parent {
+                Some(parent) if self.less(parent, index) => self.cut(index),
+                _ => {},
+            }
+            if self.max.is_some_and(|max| self.less(max, index)) {
+                self.max = Some(index);
+            }
+            Some(old)
+        }
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for FibonacciHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::MinComparator;
+
+    // check the heap order, the degrees, and the links of the subtree of `x`, returning its size
+    fn check_tree<T, C: Compare<T>>(heap: &FibonacciHeap<T, C>, x: usize) -> usize {
+        let node = heap.node(x);
+        let mut size = 1;
+        let mut degree = 0;
+        if let Some(first) = node.child {
+            let mut child = first;
+            loop {
+                assert_eq!(Some(x), heap.node(child).parent);
+                assert_eq!(child, heap.node(heap.node(child).right).left);
+                assert!(!heap.less(x, child));
+                size += check_tree(heap, child);
+                degree += 1;
+                child = heap.node(child).right;
+                if child == first {
+                    break;
+                }
+            }
+        }
+        assert_eq!(degree, node.degree);
+        size
+    }
+
+    fn check<T, C: Compare<T>>(heap: &FibonacciHeap<T, C>) {
+        let mut size = 0;
+        if let Some(max) = heap.max {
+            let mut root = max;
+            loop {
+                assert_eq!(None, heap.node(root).parent);
+                assert!(!heap.less(max, root));
+                size += check_tree(heap, root);
+                root = heap.node(root).right;
+                if root == max {
+                    break;
+                }
+            }
+        }
+        assert_eq!(heap.size(), size);
+    }
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(7);
+        // distinct elements, so that the popped handle is known
+        let mut counter = 0;
+        let mut distinct = |x: u64| { counter += 1; x * 10_000 + counter };
+        let mut heap = FibonacciHeap::with_comparator(MinComparator);
+        let mut live: Vec<(Handle, u64)> = Vec::new();
+        for _ in 0..5_000 {
+            match next() % 6 {
+                0 | 1 => {
+                    let x = distinct(next() % 1000);
+                    live.push((heap.insert(x), x));
+                },
+                2 if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let x = distinct(next() % 1000);
+                    assert_eq!(Some(live[i].1), heap.decrease_key(live[i].0, x));
+                    live[i].1 = x;
+                },
+                3 if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let (handle, x) = live.swap_remove(i);
+                    assert_eq!(Some(x), heap.remove(handle));
+                    assert!(!heap.contains(handle));
+                },
+                4 => {
+                    let min = live.iter().enumerate().min_by_key(|&(_, &(_, x))| x).map(|(i, _)| i);
+                    let popped = heap.pop();
+                    assert_eq!(min.map(|i| live.swap_remove(i).1), popped);
+                },
+                _ => {
+                    let mut other = FibonacciHeap::with_comparator(MinComparator);
+                    let added: Vec<(Handle, u64)> = (0..(next() % 5))
+                        .map(|_| { let x = distinct(next() % 1000); (other.insert(x), x) })
+                        .collect();
+                    let map = heap.meld(other);
+                    live.extend(added.into_iter().map(|(handle, x)| (map.map(handle), x)));
+                },
+            }
+            check(&heap);
+            assert_eq!(live.len(), heap.size());
+            assert_eq!(live.iter().map(|&(_, x)| x).min().as_ref(), heap.peek());
+        }
+        for &(handle, x) in live.iter() {
+            assert_eq!(Some(&x), heap.get(handle));
+        }
+        let mut expected: Vec<u64> = live.iter().map(|&(_, x)| x).collect();
+        expected.sort_unstable();
+        let popped: Vec<u64> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(expected, popped);
+    }
+}
@@ -0,0 +1,268 @@
+//! Heap with a fixed capacity, stored inline
+//!
+//! The elements are kept in an array of `N` possibly uninitialized slots, of which the first
+//! `len` are initialized and form the heap; the heap never allocates, which makes it usable on
+//! targets without an allocator.
+
+use crate::{Compare, MaxComparator};
+use core::cmp::Ordering;
+use core::mem::MaybeUninit;
+
+/// A binary heap holding at most `N` elements, stored inline without any allocation
+///
+/// `insert` returns the element in an error when the heap is full. Apart from its capacity, the
+/// heap behaves as a `BinaryHeap` ordered by `C`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::FixedBinaryHeap;
+///
+/// let mut pending: FixedBinaryHeap<u8, 2> = FixedBinaryHeap::new();
+/// assert_eq!(Ok(()), pending.insert(3));
+/// assert_eq!(Ok(()), pending.insert(7));
+/// assert_eq!(Err(5), pending.insert(5));
+/// assert_eq!(Some(7), pending.pop());
+/// assert_eq!(Ok(()), pending.insert(5));
+/// assert_eq!(Some(&5), pending.peek());
+/// ```
+pub struct FixedBinaryHeap<T, const N: usize, C = MaxComparator> {
+    data: [MaybeUninit<T>; N],  // slots, the first `len` of which are initialized
+    len: usize,                 // number of elements
+    comparator: C,              // order of the elements
+}
+
+impl<T: PartialOrd, const N: usize> FixedBinaryHeap<T, N> {
+
+    /// Create a new empty `FixedBinaryHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub const fn new() -> Self {
+        FixedBinaryHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, const N: usize, C> FixedBinaryHeap<T, N, C> {
+
+    /// Create a new empty `FixedBinaryHeap` ordered by `comparator`
+    #[inline]
+    pub const fn with_comparator(comparator: C) -> Self {
+        FixedBinaryHeap { data: [const { MaybeUninit::uninit() }; N], len: 0, comparator }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub const fn size(&self) -> usize {
+        self.len
+    }
+
+    /// Get the capacity of the heap, `N`
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Check whether the heap is full
+    #[inline]
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Get the elements of the heap, in the order of the heap array
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` slots are initialized
+        unsafe { core::slice::from_raw_parts(self.data.as_ptr().cast::<T>(), self.len) }
+    }
+
+    // elements of the heap, in the order of the heap array
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` slots are initialized
+        unsafe { core::slice::from_raw_parts_mut(self.data.as_mut_ptr().cast::<T>(), self.len) }
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    /// Remove all the elements
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    pub fn clear(&mut self) {
+        let elements: *mut [T] = self.as_mut_slice();
+        // the length is reset first, so that a panic while dropping an element leaks the
+        // remaining ones instead of dropping them twice
+        self.len = 0;
+        // SAFETY: the elements are initialized, and no longer part of the heap
+        unsafe { core::ptr::drop_in_place(elements) };
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> FixedBinaryHeap<T, N, C> {
+
+    /// Insert an element in the heap, or return it in an error if the heap is full
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(x);
+        }
+        self.data[self.len].write(x);
+        self.len += 1;
+        self.sift_up(self.len - 1);
+        Ok(())
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let last = self.len - 1;
+        self.as_mut_slice().swap(0, last);
+        self.len = last;
+        // SAFETY: the slot was initialized, and is no longer part of the heap
+        let root = unsafe { self.data[last].assume_init_read() };
+        self.sift_down(0);
+        Some(root)
+    }
+
+    // whether the element at position `i` is smaller than the one at position `j`
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        let elements = self.as_slice();
+        self.comparator.compare(&elements[i], &elements[j]) == Some(Ordering::Less)
+    }
+
+    // ‘bubble up’ the element at position `pos`
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) >> 1;
+            if !self.less(parent, pos) {
+                break;
+            }
+            self.as_mut_slice().swap(parent, pos);
+            pos = parent;
+        }
+    }
+
+    // ‘bubble down’ the element at position `pos`
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let mut largest = pos;
+            for child in [(pos << 1) + 1, (pos << 1) + 2] {
+                if child < self.len && self.less(largest, child) {
+                    largest = child;
+                }
+            }
+            if largest == pos {
+                break;
+            }
+            self.as_mut_slice().swap(pos, largest);
+            pos = largest;
+        }
+    }
+}
+
+impl<T, const N: usize, C> Drop for FixedBinaryHeap<T, N, C> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone, const N: usize, C: Clone> Clone for FixedBinaryHeap<T, N, C> {
+    fn clone(&self) -> Self {
+        let mut heap = FixedBinaryHeap::with_comparator(self.comparator.clone());
+        for x in self.as_slice() {
+            // the order of the heap array is kept, so that no comparison is needed
+            heap.data[heap.len].write(x.clone());
+            heap.len += 1;
+        }
+        heap
+    }
+}
+
+impl<T: core::fmt::Debug, const N: usize, C> core::fmt::Debug for FixedBinaryHeap<T, N, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FixedBinaryHeap").field("data", &self.as_slice()).finish_non_exhaustive()
+    }
+}
+
+impl<T, const N: usize, C: Compare<T> + Default> core::default::Default for FixedBinaryHeap<T, N, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> Iterator for FixedBinaryHeap<T, N, C> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> ExactSizeIterator for FixedBinaryHeap<T, N, C> {}
+
+impl<T, const N: usize, C: Compare<T>> core::iter::FusedIterator for FixedBinaryHeap<T, N, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::{BinaryHeap, MinComparator};
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let mut fixed: FixedBinaryHeap<u64, 16, MinComparator> = FixedBinaryHeap::default();
+        let mut reference = BinaryHeap::with_comparator(MinComparator);
+        for _ in 0..10_000 {
+            if next().is_multiple_of(3) {
+                assert_eq!(reference.pop(), fixed.pop());
+            } else {
+                let x = next() % 100;
+                if reference.size() < 16 {
+                    reference.insert(x);
+                    assert_eq!(Ok(()), fixed.insert(x));
+                } else {
+                    assert_eq!(Err(x), fixed.insert(x));
+                }
+            }
+            assert_eq!(reference.size(), fixed.size());
+        }
+        let expected = reference.to_vec();
+        assert_eq!(expected, fixed.clone().collect::<Vec<_>>());
+        assert_eq!(expected, fixed.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn drops_elements() {
+        let counter = Rc::new(());
+        let mut heap: FixedBinaryHeap<(u8, Rc<()>), 4> = FixedBinaryHeap::new();
+        for i in 0..4 {
+            heap.insert((i, counter.clone())).unwrap();
+        }
+        assert!(heap.insert((9, counter.clone())).is_err());
+        drop(heap.pop());
+        assert_eq!(4, Rc::strong_count(&counter));
+        drop(heap);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+}
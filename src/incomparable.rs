@@ -0,0 +1,199 @@
+//! Handling of incomparable elements (such as NaN)
+
+use crate::{BinaryHeap, Compare};
+use core::cmp::Ordering;
+
+/// What a heap does with elements which can not be compared with others
+///
+/// An element is incomparable with another if the comparator returns `None` (for instance, with
+/// the natural order of `f64`, NaN is incomparable with every number, including itself).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IncomparablePolicy {
+    /// Incomparable elements are inserted and never reordered, which may break the order of the
+    /// heap (this is the behaviour of versions without policies)
+    #[default]
+    Ignore,
+    /// `insert` discards an element incomparable with itself or with one on its sift path
+    Reject,
+    /// `insert` panics if the element is incomparable with itself or with one on its sift path
+    Panic,
+    /// Elements incomparable with themselves (such as NaN) are smaller than all other elements,
+    /// and equal to each other
+    TreatAsSmallest,
+}
+
+/// Error returned by `try_insert` when the element is incomparable with itself or with one on its
+/// sift path
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IncomparableError<T> {
+    /// The element which could not be inserted
+    pub element: T,
+}
+
+impl<T> core::fmt::Display for IncomparableError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "element incomparable with an element of the heap")
+    }
+}
+
+impl<T: core::fmt::Debug> core::error::Error for IncomparableError<T> {}
+
+impl<T, C, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Get the policy for incomparable elements
+    #[inline]
+    pub fn incomparable_policy(&self) -> IncomparablePolicy {
+        self.policy
+    }
+
+    /// Set the policy for incomparable elements
+    ///
+    /// The policy applies to the following insertions; elements already in the heap are not
+    /// checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, IncomparablePolicy};
+    ///
+    /// let mut heap = BinaryHeap::<f64>::new();
+    /// heap.set_incomparable_policy(IncomparablePolicy::TreatAsSmallest);
+    /// heap.insert(1.0);
+    /// heap.insert(f64::NAN);
+    /// heap.insert(2.0);
+    ///
+    /// assert_eq!(Some(2.0), heap.pop());
+    /// assert_eq!(Some(1.0), heap.pop());
+    /// assert!(heap.pop().unwrap().is_nan());
+    /// ```
+    #[inline]
+    pub fn set_incomparable_policy(&mut self, policy: IncomparablePolicy) {
+        self.policy = policy;
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Insert an element in the heap, or return it in an error if it is incomparable with itself or
+    /// with one of the elements on its sift path (whatever the policy)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, IncomparableError};
+    ///
+    /// let mut heap = BinaryHeap::<f64>::new();
+    /// assert_eq!(Ok(()), heap.try_insert(1.0));
+    ///
+    /// let error = heap.try_insert(f64::NAN).unwrap_err();
+    /// assert!(error.element.is_nan());
+    /// assert_eq!(1, heap.size());
+    /// ```
+    pub fn try_insert(&mut self, x: T) -> Result<(), IncomparableError<T>> {
+        if self.is_incomparable(&x) {
+            return Err(IncomparableError { element: x });
+        }
+        self.push(x);
+        Ok(())
+    }
+
+    // whether `x` is incomparable with itself or with one of the elements on its sift path if it
+    // is inserted
+    //
+    // The element is compared with its future ancestors until one is not smaller; only this last
+    // comparison can give `None`. An element incomparable with itself is rejected even if the heap
+    // is empty, as it would otherwise become incomparable with all the later elements.
+    pub(crate) fn is_incomparable(&self, x: &T) -> bool {
+        if self.compare_values(x, x).is_none() {
+            return true;
+        }
+        let mut pos = self.size();
+        while pos > 0 {
+            let parent = Self::parent(pos);
+            match self.compare_values(&self.data[parent], x) {
+                Some(Ordering::Less) => pos = parent,
+                Some(_) => return false,
+                None => return true,
+            }
+        }
+        false
+    }
+
+    // compare two elements according to the comparator and the policy
+    #[inline]
+    pub(crate) fn compare_values(&self, a: &T, b: &T) -> Option<Ordering> {
+        match self.comparator.compare(a, b) {
+            None if self.policy == IncomparablePolicy::TreatAsSmallest => {
+                let a_comparable = self.comparator.compare(a, a).is_some();
+                let b_comparable = self.comparator.compare(b, b).is_some();
+                match (a_comparable, b_comparable) {
+                    (false, false) => Some(Ordering::Equal),
+                    (false, true) => Some(Ordering::Less),
+                    (true, false) => Some(Ordering::Greater),
+                    (true, true) => None,
+                }
+            },
+            ordering => ordering,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_insert_1() {
+        let mut heap = BinaryHeap::<f64>::new();
+        for x in [3.0, 1.0, 2.0] {
+            assert_eq!(Ok(()), heap.try_insert(x));
+        }
+        assert!(heap.try_insert(f64::NAN).is_err());
+        assert_eq!(vec![3.0, 2.0, 1.0], heap.to_vec());
+    }
+
+    #[test]
+    fn reject_1() {
+        let mut heap = BinaryHeap::<f64>::new();
+        heap.set_incomparable_policy(IncomparablePolicy::Reject);
+        heap.insert(1.0);
+        heap.insert(f64::NAN);
+        heap.insert(0.5);
+        assert_eq!(vec![1.0, 0.5], heap.to_vec());
+    }
+
+    #[test]
+    fn reject_2() {
+        let mut heap = BinaryHeap::<f64>::new();
+        heap.set_incomparable_policy(IncomparablePolicy::Reject);
+        heap.insert(f64::NAN);
+        assert_eq!(0, heap.size());
+        assert!(heap.try_insert(f64::NAN).is_err());
+        heap.insert(1.0);
+        assert_eq!(Ok(()), heap.try_insert(2.0));
+        assert_eq!(vec![2.0, 1.0], heap.to_vec());
+    }
+
+    #[test]
+    #[should_panic]
+    fn panic_1() {
+        let mut heap = BinaryHeap::<f64>::new();
+        heap.set_incomparable_policy(IncomparablePolicy::Panic);
+        heap.insert(1.0);
+        heap.insert(f64::NAN);
+    }
+
+    #[test]
+    fn treat_as_smallest_1() {
+        let mut heap = BinaryHeap::<f64>::new();
+        heap.set_incomparable_policy(IncomparablePolicy::TreatAsSmallest);
+        for x in [f64::NAN, 3.0, f64::NAN, -1.0, 7.0, f64::NAN, 0.0] {
+            heap.insert(x);
+        }
+        let sorted = heap.to_vec();
+        assert_eq!(vec![7.0, 3.0, 0.0, -1.0], sorted[..4]);
+        assert!(sorted[4..].iter().all(|x| x.is_nan()));
+    }
+}
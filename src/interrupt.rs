@@ -0,0 +1,174 @@
+//! Fixed-capacity heap shared between interrupt handlers and the main loop
+//!
+//! The heap is a `FixedBinaryHeap` behind a `critical_section::Mutex`, so that each operation
+//! runs inside a critical section (on single-core Cortex-M targets, with interrupts disabled)
+//! and cannot be preempted by an interrupt handler touching the same heap. The platform provides
+//! the critical section implementation, for instance `cortex-m` with its
+//! `critical-section-single-core` feature.
+//!
+//! Only available with the `critical-section` feature.
+
+use crate::{Compare, FixedBinaryHeap, MaxComparator};
+use core::cell::RefCell;
+use critical_section::Mutex;
+
+/// A heap of at most `N` elements, which can be used from interrupt handlers through a shared
+/// reference
+///
+/// `InterruptHeap::new` is a `const fn`, so that the heap can be placed in a `static` and shared
+/// between interrupt handlers, which typically insert, and the main loop, which typically pops.
+/// Each operation holds a critical section for its duration, which is $O(\log n)$.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::InterruptHeap;
+///
+/// static EVENTS: InterruptHeap<u8, 8> = InterruptHeap::new();
+///
+/// // in an interrupt handler
+/// fn on_interrupt(priority: u8) {
+///     // drop the event if the queue is full
+///     let _ = EVENTS.insert(priority);
+/// }
+///
+/// on_interrupt(2);
+/// on_interrupt(5);
+///
+/// // in the main loop
+/// assert_eq!(Some(5), EVENTS.pop());
+/// assert_eq!(Some(2), EVENTS.pop());
+/// assert_eq!(None, EVENTS.pop());
+/// ```
+pub struct InterruptHeap<T, const N: usize, C = MaxComparator> {
+    heap: Mutex<RefCell<FixedBinaryHeap<T, N, C>>>,
+}
+
+impl<T: PartialOrd, const N: usize> InterruptHeap<T, N> {
+
+    /// Create a new empty `InterruptHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub const fn new() -> Self {
+        InterruptHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, const N: usize, C> InterruptHeap<T, N, C> {
+
+    /// Create a new empty `InterruptHeap` ordered by `comparator`
+    #[inline]
+    pub const fn with_comparator(comparator: C) -> Self {
+        InterruptHeap { heap: Mutex::new(RefCell::new(FixedBinaryHeap::with_comparator(comparator))) }
+    }
+
+    /// Run `f` on the underlying heap inside a critical section
+    ///
+    /// This allows several operations to be performed atomically. `f` should be short, as
+    /// interrupts are delayed while it runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within `f`.
+    #[inline]
+    pub fn with<R>(&self, f: impl FnOnce(&mut FixedBinaryHeap<T, N, C>) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.heap.borrow_ref_mut(cs)))
+    }
+
+    /// Get the size of the heap (number of elements)
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.with(|heap| heap.size())
+    }
+
+    /// Get the capacity of the heap, `N`
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Remove all the elements
+    #[inline]
+    pub fn clear(&self) {
+        self.with(|heap| heap.clear())
+    }
+
+    /// Consume the `InterruptHeap` and return the underlying heap
+    #[inline]
+    pub fn into_inner(self) -> FixedBinaryHeap<T, N, C> {
+        self.heap.into_inner().into_inner()
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> InterruptHeap<T, N, C> {
+
+    /// Insert an element in the heap, or return it in an error if the heap is full
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn insert(&self, x: T) -> Result<(), T> {
+        self.with(|heap| heap.insert(x))
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn pop(&self) -> Option<T> {
+        self.with(|heap| heap.pop())
+    }
+}
+
+impl<T: Clone, const N: usize, C> InterruptHeap<T, N, C> {
+
+    /// Return a clone of the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$, plus the cost of the clone.
+    #[inline]
+    pub fn peek(&self) -> Option<T> {
+        self.with(|heap| heap.peek().cloned())
+    }
+}
+
+impl<T, const N: usize, C: Compare<T> + Default> Default for InterruptHeap<T, N, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T: core::fmt::Debug, const N: usize, C> core::fmt::Debug for InterruptHeap<T, N, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.with(|heap| f.debug_struct("InterruptHeap").field("data", &heap.as_slice()).finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MinComparator;
+    use std::sync::Arc;
+    use std::vec::Vec;
+
+    #[test]
+    fn shared_between_threads() {
+        // the `std` implementation of critical sections stands for disabled interrupts
+        let heap: Arc<InterruptHeap<u32, 64, MinComparator>> = Arc::new(InterruptHeap::default());
+        let producers: Vec<_> = (0..4)
+            .map(|t| {
+                let heap = heap.clone();
+                std::thread::spawn(move || {
+                    for i in 0..16 {
+                        heap.insert(i * 4 + t).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        assert_eq!(64, heap.size());
+        assert_eq!(Err(99), heap.insert(99));
+        assert_eq!(Some(0), heap.peek());
+        let popped: Vec<u32> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!((0..64).collect::<Vec<_>>(), popped);
+    }
+}
@@ -0,0 +1,361 @@
+//! Interval heap (double-ended priority queue)
+//!
+//! Each node of a complete binary tree holds an interval: two elements, the low one not larger
+//! than the high one (the last node may hold a single element). The interval of each node
+//! contains those of its children, so that the low elements form a min-heap and the high
+//! elements a max-heap. The nodes are stored in an array, the elements of node `k` at positions
+//! `2k` (low) and `2k + 1` (high). (van Leeuwen and Wood, *Interval heaps*, 1993.)
+
+use crate::{Compare, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A double-ended priority queue storing an interval of elements per node
+///
+/// Like `MinMaxHeap`, an `IntervalHeap` gives access to both its minimum and its maximum, with
+/// `pop_min` and `pop_max` in $\Theta(\log n)$ time, and can be built from a vector in
+/// $\Theta(n)$ time. Its tree has half as many levels, and its code is simpler. The order is
+/// given by the comparator `C`, the natural order of `T` by default.
+///
+/// `IntervalHeap` implements `Iterator` (popping the maximum) and `DoubleEndedIterator` (popping
+/// the minimum from the back).
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::IntervalHeap;
+///
+/// // trim both tails of a window of measurements
+/// let mut window = IntervalHeap::from(vec![12, 3, 45, 7, 9, 28, 5]);
+/// window.pop_min();
+/// window.pop_max();
+///
+/// assert_eq!(Some(&5), window.peek_min());
+/// assert_eq!(Some(&28), window.peek_max());
+/// ```
+#[derive(Clone, Debug)]
+pub struct IntervalHeap<T, C = MaxComparator> {
+    data: Vec<T>,
+    comparator: C,
+}
+
+impl<T: core::cmp::PartialOrd> IntervalHeap<T> {
+
+    /// Create a new empty `IntervalHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        IntervalHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> IntervalHeap<T, C> {
+
+    /// Create a new empty `IntervalHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        IntervalHeap { data: Vec::new(), comparator }
+    }
+
+    /// Build an `IntervalHeap` ordered by `comparator` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = IntervalHeap { data: values, comparator };
+        let nodes = heap.size().div_ceil(2);
+        for node in (0..nodes).rev() {
+            let low = node << 1;
+            if low + 1 < heap.size() && heap.less(low + 1, low) {
+                heap.data.swap(low, low + 1);
+            }
+            heap.sift_down_min(low);
+            if low + 1 < heap.size() {
+                heap.sift_down_max(low + 1);
+            }
+        }
+        heap
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        self.data.push(x);
+        let pos = self.size() - 1;
+        if pos & 1 == 1 {
+            // second element of its node: order the interval
+            if self.less(pos, pos - 1) {
+                self.data.swap(pos - 1, pos);
+                self.sift_up_min(pos - 1);
+            } else {
+                self.sift_up_max(pos);
+            }
+        } else if pos > 0 {
+            // single element of its node: compare it with the interval of the parent
+            let parent_low = (((pos >> 1) - 1) >> 1) << 1;
+            if self.less(pos, parent_low) {
+                self.sift_up_min(pos);
+            } else if self.less(parent_low + 1, pos) {
+                self.sift_up_max(pos);
+            }
+        }
+    }
+
+    /// Return a reference to the minimum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Return a reference to the maximum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_max(&self) -> Option<&T> {
+        self.data.get(1).or(self.data.first())
+    }
+
+    /// Remove and return the minimum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let x = self.data.swap_remove(0);
+        if !self.data.is_empty() {
+            self.sift_down_min(0);
+        }
+        Some(x)
+    }
+
+    /// Remove and return the maximum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop_max(&mut self) -> Option<T> {
+        if self.size() <= 1 {
+            return self.data.pop();
+        }
+        let x = self.data.swap_remove(1);
+        if self.size() > 1 {
+            self.sift_down_max(1);
+        }
+        Some(x)
+    }
+
+    /// Consume the heap and return a vector of all its elements, from the maximum to the minimum
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the
+    /// heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // whether the element at position `i` is smaller than the one at position `j`
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        self.comparator.compare(&self.data[i], &self.data[j]) == Some(Ordering::Less)
+    }
+
+    // move the low element at position `pos` up through the low elements of its ancestors
+    fn sift_up_min(&mut self, mut pos: usize) {
+        while pos > 1 {
+            let parent = (((pos >> 1) - 1) >> 1) << 1;
+            if self.less(pos, parent) {
+                self.data.swap(pos, parent);
+                pos = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    // move the high element (or single element of the last node) at position `pos` up through
+    // the high elements of its ancestors
+    fn sift_up_max(&mut self, mut pos: usize) {
+        while pos > 1 {
+            let parent = ((((pos >> 1) - 1) >> 1) << 1) + 1;
+            if self.less(parent, pos) {
+                self.data.swap(pos, parent);
+                pos = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    // move the low element at position `pos` down through the low elements of its descendants,
+    // keeping the element of each node on its path not larger than the high one
+    fn sift_down_min(&mut self, mut pos: usize) {
+        let size = self.size();
+        loop {
+            if pos + 1 < size && self.less(pos + 1, pos) {
+                self.data.swap(pos, pos + 1);
+            }
+
+            // smallest low element of the children
+            let first_child = (pos << 1) + 2;
+            if first_child >= size {
+                break;
+            }
+            let mut smallest = first_child;
+            if first_child + 2 < size && self.less(first_child + 2, first_child) {
+                smallest = first_child + 2;
+            }
+
+            if self.less(smallest, pos) {
+                self.data.swap(smallest, pos);
+                pos = smallest;
+            } else {
+                break;
+            }
+        }
+    }
+
+    // move the high element at position `pos` down through the high elements of its
+    // descendants, keeping the element of each node on its path not smaller than the low one
+    fn sift_down_max(&mut self, mut pos: usize) {
+        let size = self.size();
+        loop {
+            if self.less(pos, pos - 1) {
+                self.data.swap(pos - 1, pos);
+            }
+
+            // largest high element of the children (or single element of the last node)
+            let first_child = pos << 1;
+            if first_child >= size {
+                break;
+            }
+            let high = |low: usize| (low + 1).min(size - 1);
+            let mut largest = high(first_child);
+            if first_child + 2 < size && self.less(largest, high(first_child + 2)) {
+                largest = high(first_child + 2);
+            }
+
+            if self.less(pos, largest) {
+                self.data.swap(largest, pos);
+                if largest & 1 == 0 {
+                    // single element of the last node
+                    break;
+                }
+                pos = largest;
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for IntervalHeap<T> {
+
+    /// Build an `IntervalHeap` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        IntervalHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T: core::cmp::PartialOrd, const N: usize> From<[T; N]> for IntervalHeap<T> {
+
+    /// Build an `IntervalHeap` from an array
+    ///
+    /// Worst-case complexity: $\Theta(N)$.
+    fn from(values: [T; N]) -> Self {
+        IntervalHeap::from(Vec::from(values))
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for IntervalHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for IntervalHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop_max()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size(), Some(self.size()))
+    }
+}
+
+impl<T, C: Compare<T>> DoubleEndedIterator for IntervalHeap<T, C> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pop_min()
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for IntervalHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for IntervalHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use alloc::collections::VecDeque;
+
+    // check that each interval is ordered and contained in the interval of the parent node
+    fn check<T, C: Compare<T>>(heap: &IntervalHeap<T, C>) {
+        for pos in 0..heap.size() {
+            let node = pos >> 1;
+            if pos & 1 == 1 {
+                assert!(!heap.less(pos, pos - 1));
+            }
+            if node > 0 {
+                let parent_low = ((node - 1) >> 1) << 1;
+                assert!(!heap.less(pos, parent_low));
+                assert!(!heap.less(parent_low + 1, pos));
+            }
+        }
+    }
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(12345);
+        let mut heap = IntervalHeap::new();
+        let mut sorted = VecDeque::new();
+        for _ in 0..5000 {
+            match next() % 4 {
+                0 => assert_eq!(sorted.pop_front(), heap.pop_min()),
+                1 => assert_eq!(sorted.pop_back(), heap.pop_max()),
+                _ => {
+                    let x = next() % 1000;
+                    heap.insert(x);
+                    let pos = sorted.partition_point(|&y| y < x);
+                    sorted.insert(pos, x);
+                },
+            }
+            check(&heap);
+            assert_eq!(sorted.front(), heap.peek_min());
+            assert_eq!(sorted.back(), heap.peek_max());
+        }
+    }
+
+    #[test]
+    fn from_vec_1() {
+        for size in 0..100u32 {
+            let values: Vec<u32> = (0..size).map(|i| (i * 37) % 101).collect();
+            let heap = IntervalHeap::from(values.clone());
+            check(&heap);
+            let mut expected = values;
+            expected.sort_unstable();
+            assert_eq!(expected, heap.rev().collect::<Vec<u32>>());
+        }
+    }
+}
@@ -0,0 +1,461 @@
+//! Intrusive pairing heap
+//!
+//! The elements embed the links of the tree in a `HeapNode`, so that the heap never allocates:
+//! it only holds a reference to the root. The tree is that of `PairingHeap`: each node records
+//! its first child, its next sibling, and its previous sibling (or its parent, for a first
+//! child), which makes it possible to unlink a node given a reference to its element.
+//! (Fredman, Sedgewick, Sleator, and Tarjan, *The pairing heap: A new form of self-adjusting
+//! heap*, 1986.)
+
+use crate::{Compare, KeyComparator, MaxComparator};
+use core::cell::Cell;
+use core::cmp::Ordering;
+
+/// Links of an element in an `IntrusiveHeap`, to embed in the element
+///
+/// The links are references to other elements, stored in cells so that they can be updated
+/// through shared references.
+pub struct HeapNode<'a, T: ?Sized> {
+    child: Cell<Option<&'a T>>,     // first child
+    next: Cell<Option<&'a T>>,      // next sibling
+    prev: Cell<Option<&'a T>>,      // previous sibling, or parent for a first child
+    linked: Cell<bool>,             // whether the element is in a heap
+}
+
+impl<T: ?Sized> HeapNode<'_, T> {
+
+    /// Create the links of an element which is not in a heap
+    #[inline]
+    pub const fn new() -> Self {
+        HeapNode {
+            child: Cell::new(None),
+            next: Cell::new(None),
+            prev: Cell::new(None),
+            linked: Cell::new(false),
+        }
+    }
+
+    /// Whether the element is in a heap
+    #[inline]
+    pub fn is_linked(&self) -> bool {
+        self.linked.get()
+    }
+
+    // clear the links, marking the element as being in a heap or not
+    #[inline]
+    fn reset(&self, linked: bool) {
+        self.child.set(None);
+        self.next.set(None);
+        self.prev.set(None);
+        self.linked.set(linked);
+    }
+}
+
+impl<T: ?Sized> Default for HeapNode<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for HeapNode<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HeapNode").field("linked", &self.is_linked()).finish()
+    }
+}
+
+/// An element embedding a `HeapNode`
+pub trait HeapLinked<'a> {
+
+    /// Return the links of the element
+    fn node(&self) -> &HeapNode<'a, Self>;
+}
+
+/// A pairing heap whose links are embedded in its elements
+///
+/// The heap borrows its elements for the lifetime `'a`, which guarantees that they are neither
+/// moved nor dropped while they may be in the heap: the nodes are pinned by the borrow, without
+/// `Pin` or unsafe code. The elements can live in an array, an arena, or static memory; the heap
+/// itself only holds a reference to the root, and no operation allocates. The order may depend
+/// on fields in cells, in which case `update` must be called after changing them.
+///
+/// An element can be in at most one heap at a time: `insert` panics if it is already in one.
+/// Passing to `remove` or `update` an element in another heap is a logic error, which may corrupt
+/// both heaps (but not memory).
+///
+/// `insert` and `meld` take $\Theta(1)$ time, while `pop`, `remove`, and `update` take
+/// $O(\log n)$ amortized time.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{HeapLinked, HeapNode, IntrusiveHeap};
+/// use core::cell::Cell;
+///
+/// struct Timer<'a> {
+///     deadline: Cell<u64>,
+///     node: HeapNode<'a, Timer<'a>>,
+/// }
+///
+/// impl<'a> HeapLinked<'a> for Timer<'a> {
+///     fn node(&self) -> &HeapNode<'a, Self> {
+///         &self.node
+///     }
+/// }
+///
+/// let timers: Vec<Timer> = [30, 10, 20]
+///     .map(|deadline| Timer { deadline: Cell::new(deadline), node: HeapNode::new() })
+///     .into();
+///
+/// // earliest deadline first
+/// let mut heap = IntrusiveHeap::new_by_key(|timer: &Timer| core::cmp::Reverse(timer.deadline.get()));
+/// for timer in timers.iter() {
+///     heap.insert(timer);
+/// }
+///
+/// // cancel a timer, and postpone another one
+/// heap.remove(&timers[1]);
+/// timers[2].deadline.set(40);
+/// heap.update(&timers[2]);
+///
+/// assert_eq!(Some(30), heap.pop().map(|timer| timer.deadline.get()));
+/// assert_eq!(Some(40), heap.pop().map(|timer| timer.deadline.get()));
+/// assert!(heap.pop().is_none());
+/// ```
+pub struct IntrusiveHeap<'a, T: HeapLinked<'a>, C = MaxComparator> {
+    root: Option<&'a T>,    // root of the tree
+    size: usize,            // number of elements
+    comparator: C,          // order of the elements
+}
+
+impl<'a, T: HeapLinked<'a> + core::cmp::PartialOrd> IntrusiveHeap<'a, T> {
+
+    /// Create a new empty `IntrusiveHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        IntrusiveHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<'a, T: HeapLinked<'a>, K: core::cmp::PartialOrd, F: Fn(&T) -> K> IntrusiveHeap<'a, T, KeyComparator<F>> {
+
+    /// Create a new empty `IntrusiveHeap` whose root is the element with the largest key
+    #[inline]
+    pub fn new_by_key(key: F) -> Self {
+        IntrusiveHeap::with_comparator(KeyComparator(key))
+    }
+}
+
+impl<'a, T: HeapLinked<'a>, C: Compare<T>> IntrusiveHeap<'a, T, C> {
+
+    /// Create a new empty `IntrusiveHeap` ordered by `comparator`
+    #[inline]
+    pub const fn with_comparator(comparator: C) -> Self {
+        IntrusiveHeap { root: None, size: 0, comparator }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element is already in a heap.
+    pub fn insert(&mut self, x: &'a T) {
+        assert!(!x.node().is_linked(), "element already in a heap");
+        x.node().reset(true);
+        self.root = Some(match self.root {
+            None => x,
+            Some(root) => self.link(root, x),
+        });
+        self.size += 1;
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&'a T> {
+        self.root
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<&'a T> {
+        let root = self.root?;
+        self.root = self.merge_pairs(root.node().child.get());
+        root.node().reset(false);
+        self.size -= 1;
+        Some(root)
+    }
+
+    /// Remove an element from the heap, returning `false` if it was not in a heap
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn remove(&mut self, x: &'a T) -> bool {
+        if !x.node().is_linked() {
+            return false;
+        }
+        if self.root.is_some_and(|root| core::ptr::eq(root, x)) {
+            self.pop();
+            return true;
+        }
+        self.cut(x);
+        if let Some(tree) = self.merge_pairs(x.node().child.get()) {
+            self.root = Some(self.link(self.root.unwrap(), tree));
+        }
+        x.node().reset(false);
+        self.size -= 1;
+        true
+    }
+
+    /// Restore the heap property after the order of an element has changed, returning `false` if
+    /// it is not in a heap
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn update(&mut self, x: &'a T) -> bool {
+        if !self.remove(x) {
+            return false;
+        }
+        self.insert(x);
+        true
+    }
+
+    /// Move all the elements of `other` into this heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    pub fn meld(&mut self, mut other: IntrusiveHeap<'a, T, C>) {
+        if let Some(tree) = other.root.take() {
+            self.root = Some(match self.root {
+                None => tree,
+                Some(root) => self.link(root, tree),
+            });
+        }
+        self.size += core::mem::take(&mut other.size);
+    }
+
+    // link two roots, returning the new root
+    fn link(&self, a: &'a T, b: &'a T) -> &'a T {
+        let (parent, child) = if self.less(a, b) { (b, a) } else { (a, b) };
+        let first = parent.node().child.get();
+        child.node().next.set(first);
+        if let Some(first) = first {
+            first.node().prev.set(Some(child));
+        }
+        child.node().prev.set(Some(parent));
+        parent.node().child.set(Some(child));
+        parent.node().next.set(None);
+        parent.node().prev.set(None);
+        parent
+    }
+
+    // detach the sub-tree rooted at `x` from its parent and siblings
+    fn cut(&self, x: &'a T) {
+        let next = x.node().next.get();
+        if let Some(prev) = x.node().prev.get() {
+            if prev.node().child.get().is_some_and(|child| core::ptr::eq(child, x)) {
+                prev.node().child.set(next);
+            } else {
+                prev.node().next.set(next);
+            }
+        }
+        if let Some(next) = next {
+            next.node().prev.set(x.node().prev.get());
+        }
+        x.node().next.set(None);
+        x.node().prev.set(None);
+    }
+
+    // link the trees of a list in pairs from left to right, then the results from right to left
+    fn merge_pairs(&self, first: Option<&'a T>) -> Option<&'a T> {
+
+        // first pass: the linked pairs are stacked through their `next` links
+        let mut pairs: Option<&'a T> = None;
+        let mut current = first;
+        while let Some(a) = current {
+            let tree = match a.node().next.get() {
+                None => {
+                    current = None;
+                    a
+                },
+                Some(b) => {
+                    current = b.node().next.get();
+                    self.link(a, b)
+                },
+            };
+            tree.node().next.set(pairs);
+            pairs = Some(tree);
+        }
+
+        // second pass, from the last pair
+        let mut root: Option<&'a T> = None;
+        while let Some(tree) = pairs {
+            pairs = tree.node().next.get();
+            root = Some(match root {
+                None => tree,
+                Some(root) => self.link(root, tree),
+            });
+        }
+        if let Some(root) = root {
+            root.node().next.set(None);
+            root.node().prev.set(None);
+        }
+        root
+    }
+
+    #[inline]
+    fn less(&self, a: &T, b: &T) -> bool {
+        self.comparator.compare(a, b) == Some(Ordering::Less)
+    }
+}
+
+impl<'a, T: HeapLinked<'a>, C> IntrusiveHeap<'a, T, C> {
+
+    /// Remove all the elements from the heap
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    pub fn clear(&mut self) {
+
+        // visit the nodes in a list, replacing each one by its children
+        let mut current = self.root.take();
+        while let Some(x) = current {
+            if let Some(child) = x.node().child.get() {
+                let mut last = child;
+                while let Some(next) = last.node().next.get() {
+                    last = next;
+                }
+                last.node().next.set(x.node().next.get());
+                x.node().next.set(Some(child));
+            }
+            current = x.node().next.get();
+            x.node().reset(false);
+        }
+        self.size = 0;
+    }
+}
+
+impl<'a, T: HeapLinked<'a>, C: Compare<T> + Default> core::default::Default for IntrusiveHeap<'a, T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<'a, T: HeapLinked<'a>, C> core::fmt::Debug for IntrusiveHeap<'a, T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IntrusiveHeap").field("size", &self.size).finish()
+    }
+}
+
+// the elements left in the heap are unlinked, so that they can be inserted in another one
+impl<'a, T: HeapLinked<'a>, C> Drop for IntrusiveHeap<'a, T, C> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a, T: HeapLinked<'a>, C: Compare<T>> Iterator for IntrusiveHeap<'a, T, C> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<'a, T: HeapLinked<'a>, C: Compare<T>> ExactSizeIterator for IntrusiveHeap<'a, T, C> {}
+
+impl<'a, T: HeapLinked<'a>, C: Compare<T>> core::iter::FusedIterator for IntrusiveHeap<'a, T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+    use alloc::vec::Vec;
+
+    struct Item<'a> {
+        value: Cell<u64>,
+        node: HeapNode<'a, Item<'a>>,
+    }
+
+    impl<'a> HeapLinked<'a> for Item<'a> {
+        fn node(&self) -> &HeapNode<'a, Self> {
+            &self.node
+        }
+    }
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let items: Vec<Item> = (0..200)
+            .map(|_| Item { value: Cell::new(next() % 1000), node: HeapNode::new() })
+            .collect();
+        let mut heap = IntrusiveHeap::new_by_key(|item: &Item| item.value.get());
+        for _ in 0..5000 {
+            let item = &items[(next() as usize) % items.len()];
+            match next() % 4 {
+                0 => if item.node.is_linked() {
+                    item.value.set(next() % 1000);
+                    assert!(heap.update(item));
+                } else {
+                    heap.insert(item);
+                },
+                1 => assert_eq!(item.node.is_linked(), heap.remove(item)),
+                2 => {
+                    let root = heap.pop();
+                    assert!(root.is_none_or(|root| !root.node.is_linked()));
+                },
+                _ => if !item.node.is_linked() {
+                    heap.insert(item);
+                },
+            }
+            let linked: Vec<u64> = items.iter()
+                .filter(|item| item.node.is_linked())
+                .map(|item| item.value.get())
+                .collect();
+            assert_eq!(linked.len(), heap.size());
+            assert_eq!(linked.iter().max().copied(), heap.peek().map(|item| item.value.get()));
+        }
+        let reference = BinaryHeap::from(items.iter()
+            .filter(|item| item.node.is_linked())
+            .map(|item| item.value.get())
+            .collect::<Vec<u64>>());
+        let popped: Vec<u64> = heap.map(|item| item.value.get()).collect();
+        assert_eq!(reference.to_vec(), popped);
+        assert!(items.iter().all(|item| !item.node.is_linked()));
+    }
+
+    fn key(item: &Item) -> u64 {
+        item.value.get()
+    }
+
+    #[test]
+    fn drop_unlinks() {
+        let items: Vec<Item> = (0..50u64)
+            .map(|value| Item { value: Cell::new(value), node: HeapNode::new() })
+            .collect();
+        let mut heap = IntrusiveHeap::new_by_key(key);
+        let mut other = IntrusiveHeap::new_by_key(key);
+        for item in items.iter() {
+            heap.insert(item);
+        }
+        heap.pop();
+        heap.meld(IntrusiveHeap::new_by_key(key));
+        drop(heap);
+        for item in items.iter() {
+            other.insert(item);
+        }
+        assert_eq!(50, other.size());
+    }
+}
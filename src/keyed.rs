@@ -0,0 +1,506 @@
+//! Priority queue of keys, whose priorities can be changed
+//!
+//! The heap array holds (key, priority) pairs, and a hash map gives the position of each key in
+//! the array; it is updated at each swap, so that a key can be found in $\Theta(1)$ expected time
+//! and its priority changed or removed in $\Theta(\log n)$.
+
+use crate::{BinaryHeap, Compare, MaxComparator};
+use core::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// order of the (key, priority) pairs: the priorities are compared with `C`
+#[derive(Clone, Copy, Debug, Default)]
+struct PriorityComparator<C>(C);
+
+impl<K, P, C: Compare<P>> Compare<(K, P)> for PriorityComparator<C> {
+    #[inline]
+    fn compare(&self, a: &(K, P), b: &(K, P)) -> Option<Ordering> {
+        self.0.compare(&a.1, &b.1)
+    }
+}
+
+// update the positions of the pairs at positions `i` and `j` of `data`, after they were swapped
+fn track<K: Hash + Eq, P>(positions: &mut HashMap<K, usize>)
+    -> impl FnMut(&[(K, P)], usize, usize) + '_
+{
+    |data, i, j| {
+        *positions.get_mut(&data[i].0).unwrap() = i;
+        *positions.get_mut(&data[j].0).unwrap() = j;
+    }
+}
+
+/// A priority queue of distinct keys of type `K` with priorities of type `P`
+///
+/// Unlike `PriorityQueue`, each key is present at most once, and its priority can be changed, or
+/// the key removed, in $\Theta(\log n)$ time. The priorities are ordered by the comparator `C`:
+/// by default, `pop` returns a key with the highest priority; with `MinComparator` (for instance
+/// for Dijkstra's algorithm), a key with the lowest one.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{KeyedBinaryHeap, MinComparator};
+///
+/// // tentative distances in Dijkstra's algorithm
+/// let mut distances = KeyedBinaryHeap::with_comparator(MinComparator);
+/// distances.push("b", 7);
+/// distances.push("c", 9);
+/// distances.push("d", 14);
+///
+/// // a shorter path to `d` was found
+/// assert_eq!(Some(14), distances.change_priority(&"d", 11));
+/// distances.remove(&"c");
+///
+/// assert_eq!(Some(("b", 7)), distances.pop());
+/// assert_eq!(Some(("d", 11)), distances.pop());
+/// assert_eq!(None, distances.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct KeyedBinaryHeap<K, P, C = MaxComparator> {
+    heap: BinaryHeap<(K, P), PriorityComparator<C>>,    // heap of (key, priority) pairs
+    positions: HashMap<K, usize>,                       // position of each key in the heap array
+}
+
+impl<K: Hash + Eq + Clone, P: core::cmp::PartialOrd> KeyedBinaryHeap<K, P> {
+
+    /// Create a new empty `KeyedBinaryHeap`, popping the highest priority first
+    #[inline]
+    pub fn new() -> Self {
+        KeyedBinaryHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<K: Hash + Eq + Clone, P, C: Compare<P>> KeyedBinaryHeap<K, P, C> {
+
+    /// Create a new empty `KeyedBinaryHeap` with priorities ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        KeyedBinaryHeap {
+            heap: BinaryHeap::with_comparator(PriorityComparator(comparator)),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Get the number of keys in the heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Whether `key` is in the heap
+    ///
+    /// Expected complexity: $\Theta(1)$.
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    /// Return a reference to the priority of `key` (or `None` if it is not in the heap)
+    ///
+    /// Expected complexity: $\Theta(1)$.
+    #[inline]
+    pub fn priority(&self, key: &K) -> Option<&P> {
+        self.positions.get(key).map(|&pos| &self.heap.data[pos].1)
+    }
+
+    /// Return references to the key with the highest priority and to its priority (or `None` if
+    /// the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        self.heap.data.first().map(|(key, priority)| (key, priority))
+    }
+
+    /// Insert `key` with priority `priority`
+    ///
+    /// If `key` is already in the heap, its priority is replaced, and the old one is returned.
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn push(&mut self, key: K, priority: P) -> Option<P> {
+        if let Some(&pos) = self.positions.get(&key) {
+            let old = core::mem::replace(&mut self.heap.data[pos].1, priority);
+            self.repair(pos);
+            return Some(old);
+        }
+        self.positions.insert(key.clone(), self.size());
+        self.heap.push_with((key, priority), track(&mut self.positions));
+        None
+    }
+
+    /// Insert or reprioritize many keys at once
+    ///
+    /// Each `(key, priority)` pair is applied as by `push`. If the batch is large compared to the
+    /// heap (more than $n / \log_2 n$ pairs), the priorities are first all written, and the heap
+    /// is then repaired in a single pass, in $\Theta(n)$ time; otherwise, the pairs are pushed one
+    /// by one.
+    ///
+    /// Expected complexity: $\Theta(\min(n, k \log n))$, where $n$ is the number of keys in the
+    /// heap after the batch and $k$ the number of pairs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{KeyedBinaryHeap, MinComparator};
+    ///
+    /// let mut distances = KeyedBinaryHeap::with_comparator(MinComparator);
+    /// distances.push_batch((0..1000).map(|node| (node, u64::MAX)));
+    ///
+    /// // relax the edges of a frontier
+    /// distances.push_batch((0..1000).step_by(3).map(|node| (node, node as u64 + 5)));
+    ///
+    /// assert_eq!(Some((0, 5)), distances.pop());
+    /// assert_eq!(Some((3, 8)), distances.pop());
+    /// ```
+    pub fn push_batch<I: IntoIterator<Item = (K, P)>>(&mut self, updates: I) {
+        let updates: Vec<(K, P)> = updates.into_iter().collect();
+        let n = self.size() + updates.len();
+        let log_n = (usize::BITS - n.leading_zeros()) as usize;
+        if updates.len() * log_n <= n {
+            for (key, priority) in updates {
+                self.push(key, priority);
+            }
+            return;
+        }
+
+        // write all the priorities, then restore the heap property from the last parent node
+        for (key, priority) in updates {
+            match self.positions.get(&key) {
+                Some(&pos) => self.heap.data[pos].1 = priority,
+                None => {
+                    self.positions.insert(key.clone(), self.size());
+                    self.heap.data.push((key, priority));
+                },
+            }
+        }
+        let size = self.size();
+        for pos in (0..(size >> 1)).rev() {
+            self.heap.sift_down_with(pos, size, track(&mut self.positions));
+        }
+        self.heap.debug_validate();
+    }
+
+    /// Remove and return the key with the highest priority and its priority (or `None` if the
+    /// heap is empty)
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        let (key, priority) = self.heap.pop_with(track(&mut self.positions))?;
+        self.positions.remove(&key);
+        Some((key, priority))
+    }
+
+    /// Change the priority of `key`, returning the old one (or `None`, leaving the heap unchanged,
+    /// if `key` is not in the heap)
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn change_priority(&mut self, key: &K, priority: P) -> Option<P> {
+        let pos = *self.positions.get(key)?;
+        let old = core::mem::replace(&mut self.heap.data[pos].1, priority);
+        self.repair(pos);
+        Some(old)
+    }
+
+    /// Remove `key` from the heap, returning its priority (or `None` if it is not in the heap)
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn remove(&mut self, key: &K) -> Option<P> {
+        let pos = *self.positions.get(key)?;
+        Some(self.remove_at(pos).1)
+    }
+
+    // remove the pair at position `pos`, replacing it by the last one
+    fn remove_at(&mut self, pos: usize) -> (K, P) {
+        let last = self.size() - 1;
+        self.heap.swap(pos, last);
+        track(&mut self.positions)(&self.heap.data, pos, last);
+        let (key, priority) = self.heap.data.pop().unwrap();
+        self.positions.remove(&key);
+        if pos < last {
+            self.repair(pos);
+        }
+        (key, priority)
+    }
+
+    // restore the heap property after the priority at position `pos` has changed, returning the
+    // new position of the pair
+    fn repair(&mut self, pos: usize) -> usize {
+        let new_pos = match self.heap.sift_up_with(pos, track(&mut self.positions)) {
+            new_pos if new_pos == pos => {
+                let size = self.size();
+                self.heap.sift_down_with(pos, size, track(&mut self.positions))
+            },
+            new_pos => new_pos,
+        };
+        self.heap.debug_validate();
+        new_pos
+    }
+
+    /// Get the entry of `key`, to insert or update its priority with a single lookup
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::KeyedBinaryHeap;
+    ///
+    /// // count the occurrences of each word, the most frequent first
+    /// let mut counts = KeyedBinaryHeap::new();
+    /// for word in "to be or not to be to".split(' ') {
+    ///     counts.entry(word).and_modify(|count| *count += 1).or_insert(1);
+    /// }
+    ///
+    /// assert_eq!(Some(("to", 3)), counts.pop());
+    /// assert_eq!(Some(("be", 2)), counts.pop());
+    /// ```
+    pub fn entry(&mut self, key: K) -> KeyedEntry<'_, K, P, C> {
+        match self.positions.get(&key) {
+            Some(&pos) => KeyedEntry::Occupied(OccupiedEntry { heap: self, pos }),
+            None => KeyedEntry::Vacant(VacantEntry { heap: self, key }),
+        }
+    }
+}
+
+/// Entry of a key in a `KeyedBinaryHeap`, returned by `entry`
+///
+/// Only available with the `std` feature.
+pub enum KeyedEntry<'a, K, P, C> {
+    /// The key is in the heap
+    Occupied(OccupiedEntry<'a, K, P, C>),
+    /// The key is not in the heap
+    Vacant(VacantEntry<'a, K, P, C>),
+}
+
+/// Entry of a key present in a `KeyedBinaryHeap`
+pub struct OccupiedEntry<'a, K, P, C> {
+    heap: &'a mut KeyedBinaryHeap<K, P, C>,
+    pos: usize,     // position of the pair in the heap array
+}
+
+/// Entry of a key absent from a `KeyedBinaryHeap`
+pub struct VacantEntry<'a, K, P, C> {
+    heap: &'a mut KeyedBinaryHeap<K, P, C>,
+    key: K,
+}
+
+impl<'a, K: Hash + Eq + Clone, P, C: Compare<P>> KeyedEntry<'a, K, P, C> {
+
+    /// Get the key of the entry
+    pub fn key(&self) -> &K {
+        match self {
+            KeyedEntry::Occupied(entry) => entry.key(),
+            KeyedEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// If the key is in the heap, modify its priority with `f` and repair the heap
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn and_modify<F: FnOnce(&mut P)>(self, f: F) -> Self {
+        match self {
+            KeyedEntry::Occupied(mut entry) => {
+                entry.modify(f);
+                KeyedEntry::Occupied(entry)
+            },
+            vacant => vacant,
+        }
+    }
+
+    /// If the key is not in the heap, insert it with priority `priority`; return a reference to
+    /// the priority of the key
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn or_insert(self, priority: P) -> &'a P {
+        self.or_insert_with(|| priority)
+    }
+
+    /// If the key is not in the heap, insert it with priority `f()`; return a reference to the
+    /// priority of the key
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn or_insert_with<F: FnOnce() -> P>(self, f: F) -> &'a P {
+        match self {
+            KeyedEntry::Occupied(entry) => entry.into_priority(),
+            KeyedEntry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq + Clone, P, C: Compare<P>> OccupiedEntry<'a, K, P, C> {
+
+    /// Get the key of the entry
+    pub fn key(&self) -> &K {
+        &self.heap.heap.data[self.pos].0
+    }
+
+    /// Get the priority of the key
+    pub fn priority(&self) -> &P {
+        &self.heap.heap.data[self.pos].1
+    }
+
+    /// Convert the entry into a reference to the priority of the key
+    pub fn into_priority(self) -> &'a P {
+        &self.heap.heap.data[self.pos].1
+    }
+
+    /// Modify the priority of the key with `f` and repair the heap
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn modify<F: FnOnce(&mut P)>(&mut self, f: F) {
+        f(&mut self.heap.heap.data[self.pos].1);
+        self.pos = self.heap.repair(self.pos);
+    }
+
+    /// Replace the priority of the key, returning the old one
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn set_priority(&mut self, priority: P) -> P {
+        let old = core::mem::replace(&mut self.heap.heap.data[self.pos].1, priority);
+        self.pos = self.heap.repair(self.pos);
+        old
+    }
+
+    /// Remove the key from the heap, returning it with its priority
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn remove(self) -> (K, P) {
+        self.heap.remove_at(self.pos)
+    }
+}
+
+impl<'a, K: Hash + Eq + Clone, P, C: Compare<P>> VacantEntry<'a, K, P, C> {
+
+    /// Get the key of the entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Insert the key with priority `priority`, returning a reference to it
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of keys in the heap.
+    pub fn insert(self, priority: P) -> &'a P {
+        let heap = self.heap;
+        heap.positions.insert(self.key.clone(), heap.size());
+        let pos = heap.heap.push_with((self.key, priority), track(&mut heap.positions));
+        &heap.heap.data[pos].1
+    }
+}
+
+impl<K: Hash + Eq + Clone, P, C: Compare<P> + Default> core::default::Default
+    for KeyedBinaryHeap<K, P, C>
+{
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+/// Insert or reprioritize keys with `push_batch`
+impl<K: Hash + Eq + Clone, P, C: Compare<P>> Extend<(K, P)> for KeyedBinaryHeap<K, P, C> {
+    fn extend<I: IntoIterator<Item = (K, P)>>(&mut self, iter: I) {
+        self.push_batch(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+
+    // check the heap property and the position map
+    fn check<K: Hash + Eq + Clone, P, C: Compare<P>>(heap: &KeyedBinaryHeap<K, P, C>) {
+        assert_eq!(heap.size(), heap.positions.len());
+        for (pos, (key, _)) in heap.heap.data.iter().enumerate() {
+            assert_eq!(Some(&pos), heap.positions.get(key));
+        }
+        assert!(heap.heap.is_valid_heap());
+    }
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(2024);
+        let mut heap = KeyedBinaryHeap::<u64, u64>::new();
+        let mut reference = HashMap::<u64, u64>::new();
+        for _ in 0..5_000 {
+            let key = next() % 50;
+            match next() % 4 {
+                0 => {
+                    let priority = next() % 100;
+                    assert_eq!(reference.insert(key, priority), heap.push(key, priority));
+                },
+                1 => {
+                    let priority = next() % 100;
+                    let expected = reference.get_mut(&key).map(|p| core::mem::replace(p, priority));
+                    assert_eq!(expected, heap.change_priority(&key, priority));
+                },
+                2 => assert_eq!(reference.remove(&key), heap.remove(&key)),
+                _ => {
+                    let max = reference.values().max().copied();
+                    let popped = heap.pop();
+                    assert_eq!(max, popped.map(|(_, p)| p));
+                    if let Some((key, _)) = popped {
+                        reference.remove(&key);
+                    }
+                },
+            }
+            check(&heap);
+            assert_eq!(reference.len(), heap.size());
+        }
+    }
+
+    #[test]
+    fn push_existing_key() {
+        let mut heap = KeyedBinaryHeap::new();
+        assert_eq!(None, heap.push('a', 1));
+        assert_eq!(None, heap.push('b', 2));
+        assert_eq!(Some(1), heap.push('a', 3));
+        assert_eq!(2, heap.size());
+        assert!(heap.contains_key(&'b'));
+        assert_eq!(Some(&2), heap.priority(&'b'));
+        assert_eq!(Some((&'a', &3)), heap.peek());
+    }
+
+    #[test]
+    fn push_batch_1() {
+        // small and large batches, with new and existing keys
+        let mut heap = KeyedBinaryHeap::<u32, i64>::new();
+        heap.push_batch((0..100).map(|key| (key, key as i64)));
+        check(&heap);
+        heap.push_batch([(5, 1000), (200, -1)]);
+        check(&heap);
+        heap.extend((0..150).map(|key| (key, -(key as i64))));
+        check(&heap);
+        assert_eq!(151, heap.size());
+        assert_eq!(Some((0, 0)), heap.pop());
+        assert_eq!(Some(&-1), heap.priority(&200));
+        assert_eq!(Some(&-5), heap.priority(&5));
+    }
+
+    #[test]
+    fn entry_1() {
+        let mut heap = KeyedBinaryHeap::new();
+        for key in [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5] {
+            heap.entry(key).and_modify(|count| *count += 1).or_insert(1);
+            check(&heap);
+        }
+        assert_eq!(Some(&3), heap.priority(&5));
+        match heap.entry(9) {
+            KeyedEntry::Occupied(mut entry) => {
+                assert_eq!(1, entry.set_priority(10));
+                assert_eq!(&10, entry.priority());
+            },
+            KeyedEntry::Vacant(_) => panic!("9 is in the heap"),
+        }
+        check(&heap);
+        assert_eq!(&7, heap.entry(7).or_insert_with(|| 7));
+        if let KeyedEntry::Occupied(entry) = heap.entry(1) {
+            assert_eq!((1, 2), entry.remove());
+        }
+        check(&heap);
+        assert_eq!(Some((9, 10)), heap.pop());
+        assert_eq!(Some((7, 7)), heap.pop());
+    }
+}
@@ -0,0 +1,306 @@
+//! Leftist heap
+//!
+//! A leftist heap is a heap-ordered binary tree in which the rank of each node (the length of
+//! the path to its nearest empty subtree, always going right) is not smaller for the left child
+//! than for the right one. The rightmost path thus has at most $\log_2(n + 1)$ nodes, and two
+//! heaps are melded by merging their rightmost paths, swapping children where the ranks require
+//! it; insertions and removals of the root are melds. (Crane, *Linear lists and priority queues
+//! as balanced binary trees*, 1972.)
+
+use crate::{Compare, MaxComparator};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::cmp::Ordering;
+
+// node of the tree
+struct Node<T> {
+    value: T,
+    rank: usize,    // number of nodes on the rightmost path from this node
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+type Tree<T> = Option<Box<Node<T>>>;
+
+// rank of a tree (zero if it is empty)
+#[inline]
+fn rank<T>(tree: &Tree<T>) -> usize {
+    tree.as_ref().map_or(0, |node| node.rank)
+}
+
+/// A mergeable heap based on a leftist tree
+///
+/// `meld` takes $O(\log n + \log m)$ time, where $n$ and $m$ are the sizes of the heaps, and
+/// does not move the elements, which makes repeated melds of many small heaps cheap; `insert`
+/// and `pop` are melds, in $O(\log n)$ time.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::LeftistHeap;
+///
+/// // meld many small queues
+/// let mut heap = LeftistHeap::new();
+/// for i in 0..10 {
+///     heap.meld(LeftistHeap::from(vec![i, 10 * i, 100 * i]));
+/// }
+///
+/// assert_eq!(30, heap.size());
+/// assert_eq!(Some(900), heap.pop());
+/// assert_eq!(Some(800), heap.pop());
+/// ```
+pub struct LeftistHeap<T, C = MaxComparator> {
+    root: Tree<T>,
+    size: usize,    // number of elements
+    comparator: C,  // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> LeftistHeap<T> {
+
+    /// Create a new empty `LeftistHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        LeftistHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> LeftistHeap<T, C> {
+
+    /// Create a new empty `LeftistHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        LeftistHeap { root: None, size: 0, comparator }
+    }
+
+    /// Build a `LeftistHeap` ordered by `comparator` from a vector
+    ///
+    /// The elements are melded in pairs, then the resulting heaps in pairs, and so on.
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = LeftistHeap::with_comparator(comparator);
+        heap.size = values.len();
+        let mut trees: VecDeque<Tree<T>> = values.into_iter()
+            .map(|value| Some(Box::new(Node { value, rank: 1, left: None, right: None })))
+            .collect();
+        while trees.len() > 1 {
+            let a = trees.pop_front().unwrap();
+            let b = trees.pop_front().unwrap();
+            trees.push_back(heap.meld_trees(a, b));
+        }
+        heap.root = trees.pop_front().flatten();
+        heap
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        let node = Some(Box::new(Node { value: x, rank: 1, left: None, right: None }));
+        let root = self.root.take();
+        self.root = self.meld_trees(root, node);
+        self.size += 1;
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let node = *self.root.take()?;
+        self.root = self.meld_trees(node.left, node.right);
+        self.size -= 1;
+        Some(node.value)
+    }
+
+    /// Move all the elements of `other` into this heap (the two heaps are merged)
+    ///
+    /// Worst-case complexity: $O(\log n + \log m)$, where $n$ and $m$ are the numbers of elements
+    /// in the two heaps.
+    pub fn meld(&mut self, mut other: LeftistHeap<T, C>) {
+        let root = self.root.take();
+        self.root = self.meld_trees(root, other.root.take());
+        self.size += other.size;
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the
+    /// heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // meld two trees along their rightmost paths
+    //
+    // The recursion depth is at most the sum of the ranks, which is logarithmic.
+    fn meld_trees(&self, a: Tree<T>, b: Tree<T>) -> Tree<T> {
+        let (mut a, mut b) = match (a, b) {
+            (Some(a), Some(b)) => (a, b),
+            (a, None) => return a,
+            (None, b) => return b,
+        };
+        if self.comparator.compare(&a.value, &b.value) == Some(Ordering::Less) {
+            core::mem::swap(&mut a, &mut b);
+        }
+        let right = a.right.take();
+        a.right = self.meld_trees(right, Some(b));
+        if rank(&a.left) < rank(&a.right) {
+            core::mem::swap(&mut a.left, &mut a.right);
+        }
+        a.rank = rank(&a.right) + 1;
+        Some(a)
+    }
+}
+
+impl<T, C> LeftistHeap<T, C> {
+
+    // references to all the elements, in no particular order
+    //
+    // The tree is traversed with an explicit stack, as its leftmost path may be as long as the
+    // number of elements.
+    fn values(&self) -> Vec<&T> {
+        let mut values = Vec::with_capacity(self.size);
+        let mut stack: Vec<&Node<T>> = self.root.iter().map(|node| &**node).collect();
+        while let Some(node) = stack.pop() {
+            values.push(&node.value);
+            stack.extend(node.left.iter().chain(node.right.iter()).map(|node| &**node));
+        }
+        values
+    }
+}
+
+impl<T, C> Drop for LeftistHeap<T, C> {
+
+    // drop the nodes one by one, as the recursive drop of a long leftmost path could overflow
+    // the stack
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone> Clone for LeftistHeap<T, C> {
+
+    /// Clone the heap, building a new tree from its elements
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    fn clone(&self) -> Self {
+        let values = self.values().into_iter().cloned().collect();
+        LeftistHeap::from_vec_with_comparator(values, self.comparator.clone())
+    }
+}
+
+impl<T: core::fmt::Debug, C> core::fmt::Debug for LeftistHeap<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.values()).finish()
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for LeftistHeap<T> {
+
+    /// Build a `LeftistHeap` from a vector
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        LeftistHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for LeftistHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for LeftistHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for LeftistHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for LeftistHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+
+    // check the heap order and the ranks of the subtree of `node`, returning its size
+    fn check_tree<T, C: Compare<T>>(heap: &LeftistHeap<T, C>, tree: &Tree<T>) -> usize {
+        let Some(node) = tree else { return 0 };
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            assert_ne!(Some(Ordering::Less), heap.comparator.compare(&node.value, &child.value));
+        }
+        assert!(rank(&node.left) >= rank(&node.right));
+        assert_eq!(rank(&node.right) + 1, node.rank);
+        1 + check_tree(heap, &node.left) + check_tree(heap, &node.right)
+    }
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let mut heap = LeftistHeap::new();
+        let mut reference = BinaryHeap::new();
+        for _ in 0..2000 {
+            match next() % 5 {
+                0 | 1 => assert_eq!(reference.pop(), heap.pop()),
+                2 => {
+                    let values: Vec<u64> = (0..(next() % 10)).map(|_| next() % 1000).collect();
+                    for &x in values.iter() {
+                        reference.insert(x);
+                    }
+                    heap.meld(LeftistHeap::from(values));
+                },
+                _ => {
+                    let x = next() % 1000;
+                    heap.insert(x);
+                    reference.insert(x);
+                },
+            }
+            assert_eq!(heap.size(), check_tree(&heap, &heap.root));
+            assert_eq!(reference.get_max().as_ref(), heap.peek());
+        }
+        assert_eq!(reference.clone().to_vec(), heap.clone().to_vec());
+        assert_eq!(reference.to_vec(), heap.to_vec());
+    }
+
+    #[test]
+    fn long_left_path() {
+        // each new element becomes the root, the old tree being its left child
+        let mut heap = LeftistHeap::new();
+        for i in 0..1_000_000 {
+            heap.insert(i);
+        }
+        let copy = heap.clone();
+        assert_eq!(1_000_000, copy.size());
+        assert_eq!(Some(&999_999), copy.peek());
+    }
+}
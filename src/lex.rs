@@ -0,0 +1,87 @@
+//! Composite lexicographic keys with a direction per component
+//!
+//! Directions refer to the order in which elements are popped from a max-heap (such as
+//! `BinaryHeap<T>` or a heap created with `new_by_key`): components wrapped in `Desc` are popped
+//! from the largest to the smallest, and components wrapped in `Asc` from the smallest to the
+//! largest.
+
+use core::cmp::Ordering;
+
+/// Key component popped in descending order (the largest first)
+///
+/// This is the natural order of `T`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Desc<T>(pub T);
+
+/// Key component popped in ascending order (the smallest first)
+///
+/// This is the reverse of the natural order of `T`, like `std::cmp::Reverse`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Asc<T>(pub T);
+
+impl<T: PartialOrd> PartialOrd for Asc<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        other.0.partial_cmp(&self.0)
+    }
+}
+
+impl<T: Ord> Ord for Asc<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+/// Composite key comparing a tuple of `Asc` and `Desc` components lexicographically
+///
+/// The first component is compared first; the next one is only compared if the previous ones
+/// are equal.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{Asc, BinaryHeap, Desc, LexKey};
+///
+/// struct Job { priority: u8, deadline: u32, submit_time: u32 }
+///
+/// // highest priority first, then earliest deadline, then earliest submission
+/// let mut jobs = BinaryHeap::new_by_key(|job: &Job| {
+///     LexKey((Desc(job.priority), Asc(job.deadline), Asc(job.submit_time)))
+/// });
+/// jobs.insert(Job { priority: 1, deadline: 10, submit_time: 0 });
+/// jobs.insert(Job { priority: 2, deadline: 30, submit_time: 1 });
+/// jobs.insert(Job { priority: 2, deadline: 20, submit_time: 3 });
+/// jobs.insert(Job { priority: 2, deadline: 20, submit_time: 2 });
+///
+/// let order: Vec<u32> = jobs.map(|job| job.submit_time).collect();
+/// assert_eq!(vec![2, 3, 1, 0], order);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LexKey<K>(pub K);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BinaryHeap;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn directions_1() {
+        assert!(Asc(1) > Asc(2));
+        assert!(Desc(2) > Desc(1));
+        assert!(LexKey((Desc(2), Asc(5))) > LexKey((Desc(1), Asc(0))));
+        assert!(LexKey((Desc(1), Asc(0))) > LexKey((Desc(1), Asc(5))));
+        assert_eq!(None, Asc(f64::NAN).partial_cmp(&Asc(1.0)));
+    }
+
+    #[test]
+    fn heap_1() {
+        let mut heap = BinaryHeap::new();
+        for (a, b) in [(1, 'b'), (2, 'z'), (1, 'a'), (2, 'c')] {
+            heap.insert(LexKey((Desc(a), Asc(b))));
+        }
+        let order: Vec<(i32, char)> = heap.map(|LexKey((Desc(a), Asc(b)))| (a, b)).collect();
+        assert_eq!(vec![(2, 'c'), (2, 'z'), (1, 'a'), (1, 'b')], order);
+    }
+}
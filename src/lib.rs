@@ -1,14 +1,285 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use alloc::{format, vec::Vec};
+
+mod bulk;
+mod compare;
+pub use compare::{
+    Compare, FnComparator, KeyComparator, MaxComparator, MinComparator, OrdComparator,
+    TotalOrderComparator,
+};
+pub use core::cmp::Reverse;
+mod convert;
+pub use convert::{NanChecked, NanError};
+mod lex;
+pub use lex::{Asc, Desc, LexKey};
+mod incomparable;
+pub use incomparable::{IncomparableError, IncomparablePolicy};
+mod priority_queue;
+pub use priority_queue::PriorityQueue;
+mod stable;
+pub use stable::StableBinaryHeap;
+mod aging;
+pub use aging::AgingQueue;
+mod addressable;
+pub use addressable::{AddressableHeap, Handle, HandleMap};
+mod pairing;
+pub use pairing::PairingHeap;
+mod binomial;
+pub use binomial::BinomialHeap;
+mod skew_binomial;
+pub use skew_binomial::SkewBinomialHeap;
+mod persistent;
+pub use persistent::PersistentHeap;
+mod fibonacci;
+pub use fibonacci::FibonacciHeap;
+mod rank_pairing;
+pub use rank_pairing::RankPairingHeap;
+mod leftist;
+pub use leftist::LeftistHeap;
+mod skew;
+pub use skew::SkewHeap;
+mod randomized;
+pub use randomized::RandomizedHeap;
+mod weak;
+pub use weak::WeakHeap;
+mod post_order;
+pub use post_order::PostOrderHeap;
+mod smoothsort;
+pub use smoothsort::{smoothsort, smoothsort_by};
+pub mod soft;
+mod radix;
+pub use radix::{RadixHeap, RadixKey};
+mod bucket;
+pub use bucket::BucketQueue;
+mod calendar;
+pub use calendar::{CalendarQueue, CalendarTime};
+mod tombstone;
+pub use tombstone::TombstoneHeap;
+mod watch;
+pub use watch::WatchedHeap;
+mod view;
+pub use view::HeapView;
+mod soa;
+pub use soa::SoaHeap;
+mod arg;
+pub use arg::ArgHeap;
+mod intrusive;
+pub use intrusive::{HeapLinked, HeapNode, IntrusiveHeap};
+mod transaction;
+pub use transaction::Transaction;
+mod cow;
+pub use cow::{CowHeap, CowSnapshot};
+mod minmax;
+pub use minmax::MinMaxHeap;
+mod interval;
+pub use interval::IntervalHeap;
+mod fixed;
+pub use fixed::FixedBinaryHeap;
+pub mod spsc;
+#[cfg(feature = "viz")]
+mod viz;
+#[cfg(feature = "observer")]
+mod observer;
+#[cfg(feature = "observer")]
+pub use observer::HeapObserver;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "stats")]
+pub use stats::HeapStats;
+#[cfg(feature = "oplog")]
+mod oplog;
+#[cfg(feature = "oplog")]
+pub use oplog::{replay, Operation, RecordingHeap, ReplayError};
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "serde")]
+pub use serialization::DeserializePolicy;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod merge;
+#[cfg(feature = "std")]
+pub mod channel;
+#[cfg(feature = "std")]
+pub mod async_channel;
+#[cfg(feature = "std")]
+pub mod pool;
+#[cfg(feature = "std")]
+mod keyed;
+#[cfg(feature = "std")]
+pub use keyed::{KeyedBinaryHeap, KeyedEntry, OccupiedEntry, VacantEntry};
+#[cfg(feature = "std")]
+mod timer;
+#[cfg(feature = "std")]
+pub use timer::{PopExpired, Recurrence, TimerQueue};
+#[cfg(feature = "std")]
+mod counting;
+#[cfg(feature = "std")]
+pub use counting::CountingHeap;
+#[cfg(feature = "hash-index")]
+mod unique;
+#[cfg(feature = "hash-index")]
+pub use unique::UniqueBinaryHeap;
+#[cfg(feature = "std")]
+mod decay;
+#[cfg(feature = "std")]
+pub use decay::DecayingHeap;
+#[cfg(feature = "std")]
+mod sharded;
+#[cfg(feature = "std")]
+pub use sharded::ShardedHeap;
+#[cfg(feature = "std")]
+mod numa;
+#[cfg(feature = "std")]
+pub use numa::{NumaHandle, NumaShardedHeap};
+#[cfg(feature = "std")]
+mod multiqueue;
+#[cfg(feature = "std")]
+pub use multiqueue::MultiQueue;
+#[cfg(feature = "std")]
+mod combining;
+#[cfg(feature = "std")]
+pub use combining::CombiningHeap;
+#[cfg(feature = "std")]
+mod stealing;
+#[cfg(feature = "std")]
+pub use stealing::{StealingWorker, WorkStealingHeap};
+#[cfg(feature = "concurrent")]
+mod skiplist;
+#[cfg(feature = "concurrent")]
+pub use skiplist::SkipListQueue;
+#[cfg(feature = "futures")]
+mod stream;
+#[cfg(feature = "futures")]
+pub use stream::SortedStream;
+#[cfg(feature = "tokio")]
+mod delay;
+#[cfg(feature = "tokio")]
+pub use delay::{DelayQueue, Expired};
+#[cfg(feature = "critical-section")]
+mod interrupt;
+#[cfg(feature = "critical-section")]
+pub use interrupt::InterruptHeap;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "rayon")]
+pub use parallel::{par_kmerge, par_partial_sort, par_partial_sort_by};
+#[cfg(feature = "rkyv")]
+mod archive;
+#[cfg(feature = "arrow")]
+pub mod columnar;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub mod arbitrary;
+#[cfg(feature = "shadow")]
+mod shadow;
+#[cfg(feature = "shadow")]
+pub use shadow::ShadowHeap;
+#[cfg(feature = "zeroize")]
+mod zeroizing;
+#[cfg(feature = "zeroize")]
+pub use zeroizing::ZeroizingBinaryHeap;
+#[cfg(feature = "defmt")]
+mod embedded;
+#[cfg(feature = "derive")]
+pub use binary_heap_derive::HeapOrd;
+#[cfg(kani)]
+mod verification;
+
+// items used by the exported macros
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+}
+
+/// Create a `BinaryHeap` containing the arguments, in the same way as `vec!`
+///
+/// * `heap![]` creates an empty heap,
+/// * `heap![x, y, z]` creates a heap containing `x`, `y`, and `z`,
+/// * `heap![x; n]` creates a heap containing `n` clones of `x`.
+///
+/// The heap is built from a vector in $\Theta(n)$ time.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{heap, BinaryHeap};
+///
+/// let heap: BinaryHeap<isize> = heap![3, 1, 4, 1, 5];
+/// assert_eq!(vec![5, 4, 3, 1, 1], heap.to_vec());
+///
+/// let heap = heap![0; 3];
+/// assert_eq!(vec![0, 0, 0], heap.to_vec());
+/// ```
+#[macro_export]
+macro_rules! heap {
+    () => {
+        $crate::BinaryHeap::new()
+    };
+    ($elem:expr; $n:expr) => {
+        $crate::BinaryHeap::from($crate::__private::vec![$elem; $n])
+    };
+    ($($x:expr),+ $(,)?) => {
+        $crate::BinaryHeap::from($crate::__private::vec![$($x),+])
+    };
+}
+
 /// A binary max-heap structure
 ///
 /// We use an array representation for the heap, implemented as a `Vec`.
 ///
 /// The data type must implement the `PartialOrd` trait (needed to have a partial ordering between
 /// values).
-pub struct BinaryHeap<T: std::cmp::PartialOrd> {
+///
+/// The order is given by the comparator `C` (see the `Compare` trait). By default, it is the
+/// natural order of `T`, and the root is the maximum; `MinBinaryHeap<T>` uses the reverse order.
+///
+/// Two heaps compare equal if they contain the same elements with the same multiplicities,
+/// whatever the layout of their arrays; this requires `T: Ord`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct BinaryHeap<T, C = MaxComparator, const D: usize = 2> {
     data: Vec<T>,      // vector to store the data
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    comparator: C,     // order of the elements
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    policy: IncomparablePolicy, // handling of incomparable elements
+    #[cfg(feature = "viz")]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    last_sift_path: Vec<usize>, // positions visited during the last sift
+    #[cfg(feature = "observer")]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    observer: observer::ObserverSlot<T>, // functions called on structural operations
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    stats: stats::StatCounters, // numbers of operations
 }
 
-impl<T: std::cmp::PartialOrd> BinaryHeap<T> {
+/// A `BinaryHeap` whose root is the minimum element
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, MinBinaryHeap};
+///
+/// let mut heap: MinBinaryHeap<isize> = BinaryHeap::new_min();
+/// heap.insert(2);
+/// heap.insert(-1);
+/// heap.insert(3);
+///
+/// assert_eq!(vec![-1, 2, 3], heap.to_vec());
+/// ```
+pub type MinBinaryHeap<T> = BinaryHeap<T, MinComparator>;
+
+impl<T: core::cmp::PartialOrd> BinaryHeap<T> {
 
     /// Create a new empty `BinaryHeap`
     ///
@@ -21,8 +292,347 @@ impl<T: std::cmp::PartialOrd> BinaryHeap<T> {
     /// ```
     #[inline]
     pub fn new() -> Self {
-        BinaryHeap::<T> {
+        BinaryHeap::with_comparator(MaxComparator)
+    }
+
+    /// Create a new empty `MinBinaryHeap`, whose root is the minimum element
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new_min();
+    /// heap.insert(1);
+    /// heap.insert(0);
+    ///
+    /// assert_eq!(Some(0), heap.pop());
+    /// ```
+    #[inline]
+    pub fn new_min() -> MinBinaryHeap<T> {
+        BinaryHeap::with_comparator(MinComparator)
+    }
+}
+
+/// A `BinaryHeap` of totally ordered elements
+///
+/// With `T: Ord`, all elements are comparable, which guarantees that:
+/// * the root is a maximum element (no element is larger),
+/// * `pop` returns the elements in non-increasing order, so `to_vec` returns a sorted vector,
+/// * `search` finds every element of the heap.
+///
+/// With a type which only implements `PartialOrd`, incomparable elements (such as NaN) are not
+/// reordered and these properties may not hold. Comparisons use `cmp` instead of `partial_cmp`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, OrdHeap};
+///
+/// let mut heap: OrdHeap<u32> = BinaryHeap::new_ord();
+/// heap.insert(3);
+/// heap.insert(7);
+/// heap.insert(5);
+///
+/// assert_eq!(vec![7, 5, 3], heap.to_vec());
+/// ```
+pub type OrdHeap<T> = BinaryHeap<T, OrdComparator>;
+
+impl<T: core::cmp::Ord> OrdHeap<T> {
+
+    /// Create a new empty `OrdHeap`
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new_ord();
+    /// heap.insert("b");
+    /// heap.insert("a");
+    ///
+    /// assert_eq!(Some("b"), heap.pop());
+    /// ```
+    #[inline]
+    pub fn new_ord() -> Self {
+        BinaryHeap::with_comparator(OrdComparator)
+    }
+}
+
+/// A `BinaryHeap` of floating-point numbers ordered by `total_cmp`
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, TotalOrderHeap};
+///
+/// let mut heap: TotalOrderHeap<f64> = BinaryHeap::<f64>::new_total_order();
+/// heap.insert(0.5);
+/// heap.insert(f64::NAN);
+/// heap.insert(2.0);
+///
+/// assert!(heap.pop().unwrap().is_nan());
+/// assert_eq!(Some(2.0), heap.pop());
+/// ```
+pub type TotalOrderHeap<T> = BinaryHeap<T, TotalOrderComparator>;
+
+// `new_total_order` for each floating-point type
+macro_rules! impl_new_total_order {
+    ($($t:ty),*) => {
+        $(
+            impl BinaryHeap<$t> {
+
+                /// Create a new empty `TotalOrderHeap`, ordering the numbers with `total_cmp`
+                ///
+                /// All numbers are comparable, so no wrapper type is needed: NaNs with a positive
+                /// sign are larger than positive infinity, and NaNs with a negative sign smaller
+                /// than negative infinity.
+                ///
+                /// # Example
+                ///
+                /// ```
+                /// use binary_heap::BinaryHeap;
+                ///
+                #[doc = concat!("let mut heap = BinaryHeap::<", stringify!($t), ">::new_total_order();")]
+                /// heap.insert(-0.0);
+                /// heap.insert(0.0);
+                /// heap.insert(-1.0);
+                ///
+                /// assert_eq!(Some(0.0), heap.pop());
+                /// assert!(heap.pop().unwrap().is_sign_negative());
+                /// ```
+                #[inline]
+                pub fn new_total_order() -> TotalOrderHeap<$t> {
+                    BinaryHeap::with_comparator(TotalOrderComparator)
+                }
+            }
+        )*
+    };
+}
+
+impl_new_total_order!(f32, f64);
+
+/// A `BinaryHeap` ordered by a closure
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, BinaryHeapBy};
+///
+/// struct Task { name: &'static str, priority: u8 }
+///
+/// let mut heap: BinaryHeapBy<Task, _> = BinaryHeap::new_by(|a: &Task, b: &Task| {
+///     a.priority.cmp(&b.priority)
+/// });
+/// heap.insert(Task { name: "write", priority: 1 });
+/// heap.insert(Task { name: "read", priority: 2 });
+///
+/// assert_eq!("read", heap.pop().unwrap().name);
+/// ```
+pub type BinaryHeapBy<T, F> = BinaryHeap<T, FnComparator<F>>;
+
+impl<T, F: Fn(&T, &T) -> core::cmp::Ordering> BinaryHeapBy<T, F> {
+
+    /// Create a new empty `BinaryHeap` ordered by the closure `compare`
+    ///
+    /// The root is an element `x` such that `compare(x, y)` is not `Ordering::Less` for any other
+    /// element `y`. The element type does not need to implement `PartialOrd`.
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// // order strings by length
+    /// let mut heap = BinaryHeap::new_by(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+    /// heap.insert("heap");
+    /// heap.insert("binary");
+    ///
+    /// assert_eq!(Some("binary"), heap.pop());
+    /// ```
+    #[inline]
+    pub fn new_by(compare: F) -> Self {
+        BinaryHeap::with_comparator(FnComparator(compare))
+    }
+}
+
+/// A `BinaryHeap` ordered by a key extracted from each element
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, BinaryHeapByKey};
+///
+/// struct Task { name: &'static str, priority: u8 }
+///
+/// let mut heap: BinaryHeapByKey<Task, _> = BinaryHeap::new_by_key(|task: &Task| task.priority);
+/// heap.insert(Task { name: "write", priority: 1 });
+/// heap.insert(Task { name: "read", priority: 2 });
+///
+/// assert_eq!("read", heap.pop().unwrap().name);
+/// ```
+pub type BinaryHeapByKey<T, F> = BinaryHeap<T, KeyComparator<F>>;
+
+impl<T, K: core::cmp::PartialOrd, F: Fn(&T) -> K> BinaryHeapByKey<T, F> {
+
+    /// Create a new empty `BinaryHeap` ordered by the key `key(x)` of each element `x`
+    ///
+    /// The root is an element with the largest key. The keys are compared with `PartialOrd`; the
+    /// element type itself does not need to implement any comparison trait.
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// // order (name, age) pairs by age
+    /// let mut heap = BinaryHeap::new_by_key(|person: &(&str, u32)| person.1);
+    /// heap.insert(("Alice", 31));
+    /// heap.insert(("Bob", 42));
+    /// heap.insert(("Carol", 27));
+    ///
+    /// assert_eq!(Some(("Bob", 42)), heap.pop());
+    /// ```
+    #[inline]
+    pub fn new_by_key(key: F) -> Self {
+        BinaryHeap::with_comparator(KeyComparator(key))
+    }
+}
+
+/// A `BinaryHeap` whose order is chosen at run time
+///
+/// The comparator is a boxed trait object, so heaps with different orders have the same type and
+/// no code is generated for each comparator type.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, Compare, DynHeap, MaxComparator, MinComparator};
+///
+/// fn make_heap(smallest_first: bool) -> DynHeap<u32> {
+///     let comparator: Box<dyn Compare<u32>> = if smallest_first {
+///         Box::new(MinComparator)
+///     } else {
+///         Box::new(MaxComparator)
+///     };
+///     BinaryHeap::with_comparator(comparator)
+/// }
+///
+/// let mut heap = make_heap(true);
+/// heap.insert(2);
+/// heap.insert(1);
+///
+/// assert_eq!(Some(1), heap.pop());
+/// ```
+pub type DynHeap<T> = BinaryHeap<T, alloc::boxed::Box<dyn Compare<T>>>;
+
+impl<T> DynHeap<T> {
+
+    /// Create a new empty `DynHeap` ordered by the closure `compare`, boxed
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let by_length = |a: &String, b: &String| a.len().cmp(&b.len());
+    /// let mut heap = BinaryHeap::new_dyn(by_length);
+    /// heap.insert("heap".to_string());
+    /// heap.insert("binary".to_string());
+    ///
+    /// assert_eq!(Some("binary".to_string()), heap.pop());
+    /// ```
+    #[inline]
+    pub fn new_dyn<F>(compare: F) -> Self
+        where F: Fn(&T, &T) -> core::cmp::Ordering + 'static
+    {
+        BinaryHeap::with_comparator(alloc::boxed::Box::new(FnComparator(compare)))
+    }
+}
+
+/// A heap in which each node has `D` children
+///
+/// A larger arity gives a shallower tree, with $\log_D n$ levels: `insert` moves an element up
+/// through fewer levels, while `pop` compares up to $D$ children at each level. Arities of 4 or 8
+/// are often faster than 2 on large heaps, as the children of a node are contiguous in memory.
+/// The arity must be at least 2, and `DaryHeap<T, 2, C>` is `BinaryHeap<T, C>`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, DaryHeap};
+///
+/// let mut heap: DaryHeap<u32, 4> = BinaryHeap::new_dary();
+/// for x in [5, 1, 8, 3, 9, 2] {
+///     heap.insert(x);
+/// }
+///
+/// assert_eq!(vec![9, 8, 5, 3, 2, 1], heap.to_vec());
+/// ```
+pub type DaryHeap<T, const D: usize, C = MaxComparator> = BinaryHeap<T, C, D>;
+
+impl<T: core::cmp::PartialOrd, const D: usize> DaryHeap<T, D> {
+
+    /// Create a new empty `DaryHeap` of arity `D`, whose root is the maximum element
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, DaryHeap};
+    ///
+    /// let mut heap = DaryHeap::<u8, 8>::new_dary();
+    /// heap.insert(1);
+    /// heap.insert(2);
+    ///
+    /// assert_eq!(Some(2), heap.pop());
+    /// ```
+    #[inline]
+    pub fn new_dary() -> Self {
+        BinaryHeap::dary_with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C> BinaryHeap<T, C> {
+
+    /// Create a new empty `BinaryHeap` ordered by `comparator`
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, MinComparator};
+    ///
+    /// let heap = BinaryHeap::<isize, _>::with_comparator(MinComparator);
+    /// ```
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        BinaryHeap::dary_with_comparator(comparator)
+    }
+}
+
+impl<T, C, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Create a new empty heap of arity `D` ordered by `comparator`
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, DaryHeap, MinComparator};
+    ///
+    /// let heap: DaryHeap<isize, 4, _> = BinaryHeap::dary_with_comparator(MinComparator);
+    /// ```
+    #[inline]
+    pub fn dary_with_comparator(comparator: C) -> Self {
+        const { assert!(D >= 2, "the arity of a heap must be at least 2") };
+        BinaryHeap {
             data: Vec::<T>::new(),
+            comparator,
+            policy: IncomparablePolicy::Ignore,
+            #[cfg(feature = "viz")]
+            last_sift_path: Vec::new(),
+            #[cfg(feature = "observer")]
+            observer: observer::ObserverSlot::default(),
+            #[cfg(feature = "stats")]
+            stats: stats::StatCounters::default(),
         }
     }
 
@@ -45,8 +655,27 @@ impl<T: std::cmp::PartialOrd> BinaryHeap<T> {
         self.data.len()
     }
 
+    // position of the parent of the element at position `pos` (which must not be the root)
+    #[inline]
+    pub(crate) fn parent(pos: usize) -> usize {
+        (pos - 1) / D
+    }
+
+    // position of the first child of the element at position `pos` (the children are at the `D`
+    // consecutive positions starting from this one)
+    #[inline]
+    pub(crate) fn first_child(pos: usize) -> usize {
+        D * pos + 1
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> BinaryHeap<T, C, D> {
+
     /// Insert an element in the heap
     ///
+    /// If `x` is incomparable with itself or with one of the elements on its sift path, what
+    /// happens depends on the `IncomparablePolicy` of the heap.
+    ///
     /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
     ///
     /// # Example
@@ -61,28 +690,60 @@ impl<T: std::cmp::PartialOrd> BinaryHeap<T> {
     /// ```
     pub fn insert(&mut self, x: T) {
 
-        // push `x` in the data array
+        // apply the policy for incomparable elements
+        match self.policy {
+            IncomparablePolicy::Ignore | IncomparablePolicy::TreatAsSmallest => {},
+            IncomparablePolicy::Reject => if self.is_incomparable(&x) {
+                return;
+            },
+            IncomparablePolicy::Panic => if self.is_incomparable(&x) {
+                panic!("element incomparable with an element of the heap");
+            },
+        }
+
+        self.push(x);
+    }
+
+    // push `x` in the data array and move it to its position
+    #[inline]
+    fn push(&mut self, x: T) {
+        self.push_with(x, |_, _, _| {});
+    }
+
+    // push `x` in the data array and move it to its position, calling `on_swap` with the array
+    // after each swap, and return this position
+    fn push_with(&mut self, x: T, on_swap: impl FnMut(&[T], usize, usize)) -> usize {
         self.data.push(x);
+        #[cfg(feature = "observer")]
+        self.observer.on_insert(&self.data[self.size() - 1], self.size());
+        let pos = self.sift_up_with(self.size() - 1, on_swap);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(size = self.size(), depth = self.depth(), "insert");
+        self.debug_validate();
+        pos
+    }
 
-        // ‘bubble up’ the new element to its correct position
-        let mut current_pos: usize = self.data.len();
-        let mut parent_pos: usize = current_pos >> 1;
-        while current_pos > 1 // stop if the element is at the root of the heap
+    // ‘bubble up’ the element at position `pos` to its correct position, calling `on_swap` with
+    // the array after each swap, and return this position
+    fn sift_up_with(&mut self, pos: usize, mut on_swap: impl FnMut(&[T], usize, usize)) -> usize {
+        let mut current_pos: usize = pos;
+        self.start_sift(current_pos);
+        while current_pos > 0 // stop if the element is at the root of the heap
         {
-            
+            let parent_pos = Self::parent(current_pos);
+
             // if the new element is larger than that of the parent node, swap them
             // else, the element is already at the right position and we can stop
-            if self.data[parent_pos-1] < self.data[current_pos-1] {
-                self.data.swap(parent_pos-1, current_pos-1);
-                
-                // update the current position and parent position
+            if self.less(parent_pos, current_pos) {
+                self.swap(parent_pos, current_pos);
+                on_swap(&self.data, parent_pos, current_pos);
+                self.record_sift(parent_pos);
                 current_pos = parent_pos;
-                parent_pos >>= 1;
-
             } else {
                 break;
             }
         }
+        current_pos
     }
 
     /// remove and return the root element (or `None` if the heap is empty)
@@ -106,7 +767,13 @@ impl<T: std::cmp::PartialOrd> BinaryHeap<T> {
     /// assert_eq!(Some(0), max);
     /// assert_eq!(0, heap.size());
     /// ```
+    #[inline]
     pub fn pop(&mut self) -> Option<T> {
+        self.pop_with(|_, _, _| {})
+    }
+
+    // remove and return the root element, calling `on_swap` with the array after each swap
+    fn pop_with(&mut self, mut on_swap: impl FnMut(&[T], usize, usize)) -> Option<T> {
 
         // if the heap is empty, return `None`
         if self.data.is_empty() {
@@ -116,40 +783,164 @@ impl<T: std::cmp::PartialOrd> BinaryHeap<T> {
         let size = self.size();
 
         // exchange the root with the last element
-        self.data.swap(0, size-1);
+        self.swap(0, size-1);
+        on_swap(&self.data, 0, size-1);
 
-        // bubble down the root
-        let mut current_pos: usize = 0;
-        let mut pos_left_child = 1;
-        let mut pos_right_child = 2;
-        while pos_right_child + 1 < size // stop if the second children is the last element
-        {
-            let left_child_larger = self.data[pos_left_child] > self.data[current_pos];
-            let right_child_larger = self.data[pos_right_child] > self.data[current_pos];
-            if (left_child_larger || right_child_larger) // if the right children is larger
-                && self.data[pos_left_child] < self.data[pos_right_child]
-            {
-                self.data.swap(current_pos, pos_right_child);
-                current_pos = pos_right_child;
-            } else if left_child_larger {              // if the left children is larger
-                self.data.swap(current_pos, pos_left_child);
-                current_pos = pos_left_child;
-            } else {                                   // if no children is larger, stop
+        // bubble down the root, leaving the last element out of the heap
+        self.sift_down_with(0, size-1, on_swap);
+
+        // return the last element
+        let root = self.data.pop();
+        #[cfg(feature = "observer")]
+        if let Some(x) = &root {
+            self.observer.on_pop(x, self.size());
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(size = self.size(), depth = self.depth(), "pop");
+        self.debug_validate();
+        root
+    }
+
+    // ‘bubble down’ the element at position `pos`, considering only the first `end` elements
+    #[inline]
+    fn sift_down(&mut self, pos: usize, end: usize) {
+        self.sift_down_with(pos, end, |_, _, _| {});
+    }
+
+    // ‘bubble down’ the element at position `pos`, considering only the first `end` elements,
+    // calling `on_swap` with the array after each swap, and return its new position
+    fn sift_down_with(&mut self, pos: usize, end: usize, mut on_swap: impl FnMut(&[T], usize, usize))
+        -> usize
+    {
+        let mut current_pos: usize = pos;
+        self.start_sift(current_pos);
+        loop {
+            let first_child = Self::first_child(current_pos);
+            if first_child >= end { // stop if the element has no children in the heap
                 break;
             }
-            pos_left_child = (current_pos << 1) + 1;
-            pos_right_child = (current_pos << 1) + 2;
+
+            // find the largest child
+            let mut largest_child = first_child;
+            for child in (first_child + 1)..(first_child + D).min(end) {
+                if self.less(largest_child, child) {
+                    largest_child = child;
+                }
+            }
+
+            // if the largest child is larger than the element, swap them
+            // else, the element is already at the right position and we can stop
+            if self.less(current_pos, largest_child) {
+                self.swap(current_pos, largest_child);
+                on_swap(&self.data, current_pos, largest_child);
+                current_pos = largest_child;
+                self.record_sift(current_pos);
+            } else {
+                break;
+            }
+        }
+        current_pos
+    }
+
+    // whether the element at position `i` is smaller than the one at position `j`, according to
+    // the comparator and the policy for incomparable elements
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        #[cfg(feature = "stats")]
+        self.stats.comparison();
+        self.compare_values(&self.data[i], &self.data[j]) == Some(core::cmp::Ordering::Less)
+    }
+
+    // restore the heap property over the whole array
+    //
+    // Worst-case complexity: $\Theta(n)$ (each element is bubbled down, starting from the last
+    // parent node).
+    fn rebuild(&mut self) {
+        let size = self.size();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("rebuild", size, depth = self.depth()).entered();
+        if size > 1 {
+            for pos in (0..=Self::parent(size - 1)).rev() {
+                self.sift_down(pos, size);
+            }
+        }
+        self.debug_validate();
+    }
+
+    /// Check that no element of the heap array is larger than its parent, according to the
+    /// comparator and the policy for incomparable elements
+    ///
+    /// This holds after every operation, unless the heap was built with `transform_monotone` or
+    /// `transform_monotone_with_comparator` from a function which is not monotone.
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from(vec![2, 5, 1, 4]);
+    ///
+    /// assert!(heap.is_valid_heap());
+    /// ```
+    pub fn is_valid_heap(&self) -> bool {
+        (1..self.size()).all(|pos| {
+            self.compare_values(&self.data[Self::parent(pos)], &self.data[pos])
+                != Some(core::cmp::Ordering::Less)
+        })
+    }
+
+    // check the heap property after a mutating operation (only with the `debug-validate` feature,
+    // in debug builds)
+    #[inline]
+    fn debug_validate(&self) {
+        #[cfg(feature = "debug-validate")]
+        debug_assert!(self.is_valid_heap(), "the heap property does not hold");
+    }
+
+    // number of levels of the heap tree
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut full_size = 0; // number of elements in the first `depth` levels
+        while full_size < self.size() {
+            full_size = D * full_size + 1;
+            depth += 1;
         }
+        depth
+    }
 
-        // last swap if needed
-        if (pos_left_child + 1 < size)
-            && (self.data[pos_left_child] > self.data[current_pos])
+    // swap the elements at positions `i` and `j` (notifying the observer, if any)
+    #[inline]
+    fn swap(&mut self, i: usize, j: usize) {
+        self.data.swap(i, j);
+        #[cfg(feature = "observer")]
+        self.observer.on_swap(i, j);
+        #[cfg(feature = "stats")]
+        self.stats.swap();
+    }
+
+    // start recording a new sift path at position `pos` (only kept with the `viz` feature)
+    #[inline]
+    fn start_sift(&mut self, _pos: usize) {
+        #[cfg(feature = "stats")]
+        self.stats.start_sift();
+        #[cfg(feature = "viz")]
         {
-            self.data.swap(current_pos, pos_left_child);
+            self.last_sift_path.clear();
+            self.last_sift_path.push(_pos);
         }
+    }
 
-        // return the last element
-        self.data.pop()
+    // record that the sifted element moved to position `pos`
+    #[inline]
+    fn record_sift(&mut self, _pos: usize) {
+        #[cfg(feature = "stats")]
+        self.stats.sift_step();
+        #[cfg(feature = "viz")]
+        self.last_sift_path.push(_pos);
     }
 
     /// consume the heap and return a vector fo all its elements
@@ -185,7 +976,31 @@ impl<T: std::cmp::PartialOrd> BinaryHeap<T> {
     }
 }
 
-impl<T: std::cmp::PartialOrd + Clone> BinaryHeap<T> {
+impl<T, C: Compare<T>> BinaryHeap<T, C> {
+
+    /// Build a `BinaryHeap` ordered by `comparator` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    ///
+    /// # Example 
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, FnComparator};
+    ///
+    /// let compare = FnComparator(|a: &(u8, char), b: &(u8, char)| b.0.cmp(&a.0));
+    /// let heap = BinaryHeap::from_vec_with_comparator(vec![(2, 'b'), (1, 'a'), (3, 'c')], compare);
+    ///
+    /// assert_eq!(vec![(1, 'a'), (2, 'b'), (3, 'c')], heap.to_vec());
+    /// ```
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = BinaryHeap::with_comparator(comparator);
+        heap.data = values;
+        heap.rebuild();
+        heap
+    }
+}
+
+impl<T: Clone, C: Compare<T>, const D: usize> BinaryHeap<T, C, D> {
 
     /// return a copy of the maximum element if the heap is not empty
     ///
@@ -208,7 +1023,10 @@ impl<T: std::cmp::PartialOrd + Clone> BinaryHeap<T> {
             Some(self.data[0].clone())
         }
     }
-    
+}
+
+impl<T: core::cmp::PartialOrd + Clone> BinaryHeap<T> {
+
     /// Build a `BinaryHeap` from a slice
     /// 
     /// # Example 
@@ -244,12 +1062,12 @@ impl<T: std::cmp::PartialOrd + Clone> BinaryHeap<T> {
     /// assert_eq!(vec![3, 2, 1, 0, -1, -2, -3], sorted_values);
     /// ```
     pub fn sort(values: &[T]) -> Vec<T> {
-        let heap = BinaryHeap::from_vec(&values);
+        let heap = BinaryHeap::from_vec(values);
         heap.to_vec()
     }
 }
 
-impl<T: std::cmp::PartialOrd + std::cmp::PartialEq> BinaryHeap<T> {
+impl<T: core::cmp::PartialEq, C: Compare<T>, const D: usize> BinaryHeap<T, C, D> {
 
     /// Search an element `x` in the heap, returning `true` if it is present and `false` if it is
     /// not.
@@ -269,7 +1087,7 @@ impl<T: std::cmp::PartialOrd + std::cmp::PartialEq> BinaryHeap<T> {
     pub fn search(&self, x: &T) -> bool {
 
         // queue storing the indices of elements to process
-        let mut index_queue = std::collections::VecDeque::<usize>::new();
+        let mut index_queue = alloc::collections::VecDeque::<usize>::new();
 
         // add the root to the queue
         index_queue.push_front(0);
@@ -282,40 +1100,160 @@ impl<T: std::cmp::PartialOrd + std::cmp::PartialEq> BinaryHeap<T> {
             // If the index is not smaller than `size`, we hav ereached the end of the heap.
             // If `x` is larger than the elementwith the current index, we know `x` can't be in 
             // the sub-heap.
-            if (current_index < size) && !(*x > self.data[current_index]) {
+            if (current_index < size)
+                && self.compare_values(x, &self.data[current_index])
+                    != Some(core::cmp::Ordering::Greater)
+            {
 
                 // check if the current element is equal to `x`; if yes, return `true`
                 if *x == self.data[current_index] {
                     return true;
                 }
 
-                // push the indices of the two children to the queue
-                index_queue.push_front((current_index << 1) + 1);
-                index_queue.push_front((current_index << 1) + 2);
+                // push the indices of the children to the queue
+                let first_child = Self::first_child(current_index);
+                for child in first_child..(first_child + D) {
+                    index_queue.push_front(child);
+                }
             }
         }
 
         false
     }
+
+    /// Insert `x` in the heap if it does not contain an equal element, returning `true` if it was
+    /// inserted and `false` otherwise
+    ///
+    /// The heap is searched with `search`, skipping the sub-heaps whose roots are smaller than
+    /// `x`. With the `hash-index` feature, `UniqueBinaryHeap` checks membership in $\Theta(1)$
+    /// expected time instead.
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    ///
+    /// # Example
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::<isize>::new();
+    ///
+    /// assert!(heap.insert_unique(0));
+    /// assert!(!heap.insert_unique(0));
+    /// assert_eq!(1, heap.size());
+    /// ```
+    pub fn insert_unique(&mut self, x: T) -> bool {
+        if self.search(&x) {
+            false
+        } else {
+            self.insert(x);
+            true
+        }
+    }
+}
+
+impl<T: core::cmp::Ord, C, const D: usize> BinaryHeap<T, C, D> {
+
+    // references to the elements of the heap, sorted in increasing order
+    //
+    // Two heaps holding the same multiset of elements give the same sequence, whatever the
+    // layout of their arrays.
+    fn sorted_refs(&self) -> Vec<&T> {
+        let mut refs: Vec<&T> = self.data.iter().collect();
+        refs.sort_unstable();
+        refs
+    }
+}
+
+impl<T: core::cmp::Ord, C, const D: usize> PartialEq for BinaryHeap<T, C, D> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.size() == other.size()) && (self.sorted_refs() == other.sorted_refs())
+    }
 }
 
-impl<T: std::cmp::PartialOrd> std::default::Default for BinaryHeap<T> {
+impl<T: core::cmp::Ord, C, const D: usize> Eq for BinaryHeap<T, C, D> {}
+
+impl<T: core::cmp::Ord + core::hash::Hash, C, const D: usize> core::hash::Hash for BinaryHeap<T, C, D> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.sorted_refs().hash(state);
+    }
+}
+
+impl<T, C: Default, const D: usize> core::default::Default for BinaryHeap<T, C, D> {
     fn default() -> Self {
-        Self::new()
+        Self::dary_with_comparator(C::default())
+    }
+}
+
+impl<T: core::fmt::Display, C> BinaryHeap<T, C> {
+
+    // write the sub-tree rooted at `index`, each line starting with `prefix`
+    fn fmt_subtree(&self, f: &mut core::fmt::Formatter<'_>, index: usize, prefix: &str)
+        -> core::fmt::Result
+    {
+        let children: Vec<usize> = [(index << 1) + 1, (index << 1) + 2]
+            .into_iter()
+            .filter(|&child| child < self.size())
+            .collect();
+        for (i, &child) in children.iter().enumerate() {
+            let is_last = i + 1 == children.len();
+            let (branch, indent) = if is_last { ("└── ", "    ") } else { ("├── ", "│   ") };
+            writeln!(f, "{}{}{}", prefix, branch, self.data[child])?;
+            self.fmt_subtree(f, child, &format!("{}{}", prefix, indent))?;
+        }
+        Ok(())
     }
 }
 
-impl<T: std::cmp::PartialOrd> Iterator for BinaryHeap<T> {
+/// Draw the heap as a tree, one node per line, the left child of each node being written before
+/// the right one
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+///
+/// let heap = BinaryHeap::from_vec(&[1, 2, 3, 4]);
+///
+/// assert_eq!("4\n├── 3\n│   └── 1\n└── 2\n", format!("{}", heap));
+/// ```
+impl<T: core::fmt::Display, C> core::fmt::Display for BinaryHeap<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.data.first() {
+            None => writeln!(f, "(empty heap)"),
+            Some(root) => {
+                writeln!(f, "{}", root)?;
+                self.fmt_subtree(f, 0, "")
+            }
+        }
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> Iterator for BinaryHeap<T, C, D> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         self.pop()
     }
+
+    // the number of remaining elements is exactly the size of the heap
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.size();
+        (size, Some(size))
+    }
 }
 
+impl<T, C: Compare<T>, const D: usize> ExactSizeIterator for BinaryHeap<T, C, D> {}
+
+// once the heap is empty, `pop` keeps returning `None`
+impl<T, C: Compare<T>, const D: usize> core::iter::FusedIterator for BinaryHeap<T, C, D> {}
+
+
+#[cfg(test)]
+mod testing;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::lcg;
 
     #[test]
     fn several_inserts_1() {
@@ -468,4 +1406,276 @@ mod tests {
         let values_sorted = BinaryHeap::sort(&values);
         assert_eq!(vec![8, 7, 6, 5, 4, 3, 2, 1, 0, -1, -2], values_sorted);
     }
+
+    #[test]
+    fn eq_1() {
+        let heap_1 = BinaryHeap::from_vec(&[0, 1, 2, 3, 1]);
+        let heap_2 = BinaryHeap::from_vec(&[1, 1, 0, 2, 3]);
+        let heap_3 = BinaryHeap::from_vec(&[3, 1, 2, 0, 2]);
+        assert_ne!(heap_1.data, heap_2.data);
+        assert_eq!(heap_1, heap_2);
+        assert_ne!(heap_1, heap_3);
+        assert_ne!(heap_1, BinaryHeap::from_vec(&[0, 1, 2, 3]));
+        assert_eq!(heap_1, heap_1.clone());
+    }
+
+    #[test]
+    fn hash_1() {
+        use std::hash::{Hash, Hasher};
+        let hash = |heap: &BinaryHeap<isize>| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            heap.hash(&mut hasher);
+            hasher.finish()
+        };
+        let heap_1 = BinaryHeap::from_vec(&[0, 1, 2, 3, 1]);
+        let heap_2 = BinaryHeap::from_vec(&[1, 1, 0, 2, 3]);
+        assert_eq!(hash(&heap_1), hash(&heap_2));
+    }
+
+    #[test]
+    fn display_1() {
+        let mut heap = BinaryHeap::<isize>::new();
+        assert_eq!("(empty heap)\n", heap.to_string());
+        for x in [0, 1, 2, -2, -1, 2, 0] {
+            heap.insert(x);
+        }
+        let expected = "2\n\
+                        ├── 0\n\
+                        │   ├── -2\n\
+                        │   └── -1\n\
+                        └── 2\n    \
+                            ├── 1\n    \
+                            └── 0\n";
+        assert_eq!(expected, heap.to_string());
+    }
+
+    #[test]
+    fn heap_macro() {
+        let heap: BinaryHeap<isize> = heap![];
+        assert_eq!(0, heap.size());
+        let heap = heap![0, 2, -2, 4, 6, 8, -1, 1, 3, 5, 7,];
+        assert_eq!(vec![8, 7, 6, 5, 4, 3, 2, 1, 0, -1, -2], heap.to_vec());
+        let heap = heap![String::from("a"); 2];
+        assert_eq!(vec!["a", "a"], heap.to_vec());
+    }
+
+    #[test]
+    fn size_hint_1() {
+        let mut heap = BinaryHeap::from_vec(&[3, 1, 2]);
+        assert_eq!((3, Some(3)), heap.size_hint());
+        assert_eq!(3, heap.len());
+        heap.next();
+        assert_eq!((2, Some(2)), heap.size_hint());
+        heap.next();
+        heap.next();
+        assert_eq!(0, heap.len());
+        assert_eq!(None, heap.next());
+        assert_eq!(None, heap.next());
+    }
+
+    #[test]
+    fn min_heap_1() {
+        let mut heap = BinaryHeap::new_min();
+        for x in [3, -1, 4, 1, -5, 9, 2, 6] {
+            heap.insert(x);
+        }
+        assert_eq!(Some(-5), heap.get_max());
+        assert!(heap.search(&9));
+        assert!(!heap.search(&0));
+        assert_eq!(vec![-5, -1, 1, 2, 3, 4, 6, 9], heap.to_vec());
+    }
+
+    #[test]
+    fn reverse_1() {
+        let mut heap = BinaryHeap::new();
+        heap.insert(Reverse(2));
+        heap.insert(Reverse(1));
+        assert_eq!(Some(Reverse(1)), heap.pop());
+    }
+
+    #[test]
+    fn new_by_1() {
+        // order by the second component only
+        let mut heap = BinaryHeap::new_by(|a: &(char, i32), b: &(char, i32)| a.1.cmp(&b.1));
+        for x in [('a', 3), ('b', -1), ('c', 7), ('d', 0)] {
+            heap.insert(x);
+        }
+        assert_eq!(Some(('c', 7)), heap.pop());
+        assert_eq!(Some(('a', 3)), heap.pop());
+        assert_eq!(2, heap.size());
+    }
+
+    #[test]
+    fn from_vec_with_comparator_1() {
+        let heap = BinaryHeap::from_vec_with_comparator(vec![4, 8, 1, 9, 3, 3], MinComparator);
+        assert_eq!(vec![1, 3, 3, 4, 8, 9], heap.to_vec());
+    }
+
+    #[test]
+    fn new_by_key_1() {
+        let mut heap = BinaryHeap::new_by_key(|x: &(u8, &str)| x.0);
+        heap.insert((2, "b"));
+        heap.insert((5, "e"));
+        heap.insert((1, "a"));
+        assert_eq!(vec![(5, "e"), (2, "b"), (1, "a")], heap.to_vec());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_heap_ord_1() {
+        #[derive(Debug, HeapOrd)]
+        struct Job {
+            name: &'static str,
+            #[heap_key]
+            priority: u32,
+        }
+
+        #[derive(Debug, HeapOrd)]
+        struct Timer(&'static str, #[heap_key(reverse)] f64);
+
+        let mut jobs = BinaryHeap::new();
+        jobs.insert(Job { name: "build", priority: 2 });
+        jobs.insert(Job { name: "deploy", priority: 5 });
+        jobs.insert(Job { name: "test", priority: 3 });
+        assert_eq!("deploy", jobs.pop().unwrap().name);
+        assert_eq!(Job { name: "other", priority: 3 }, jobs.pop().unwrap());
+
+        let mut timers = BinaryHeap::new();
+        timers.insert(Timer("late", 2.5));
+        timers.insert(Timer("early", 0.5));
+        assert_eq!("early", timers.pop().unwrap().0);
+    }
+
+    #[test]
+    fn ord_heap_1() {
+        let mut heap = BinaryHeap::new_ord();
+        for x in [5, 1, 8, 1, 9, 0, 4] {
+            heap.insert(x);
+        }
+        assert_eq!(Some(9), heap.get_max());
+        assert!(heap.search(&0));
+        assert_eq!(vec![9, 8, 5, 4, 1, 1, 0], heap.to_vec());
+    }
+
+    #[test]
+    fn total_order_1() {
+        let mut heap = BinaryHeap::<f32>::new_total_order();
+        for x in [1.5, -f32::NAN, f32::INFINITY, -2.0, f32::NAN, 0.0] {
+            heap.insert(x);
+        }
+        let sorted = heap.to_vec();
+        assert!(sorted[0].is_nan() && sorted[0].is_sign_positive());
+        assert_eq!(vec![f32::INFINITY, 1.5, 0.0, -2.0], sorted[1..5]);
+        assert!(sorted[5].is_nan() && sorted[5].is_sign_negative());
+    }
+
+    #[test]
+    fn dyn_heap_1() {
+        let comparators: Vec<alloc::boxed::Box<dyn Compare<i32>>> = vec![
+            alloc::boxed::Box::new(MaxComparator),
+            alloc::boxed::Box::new(MinComparator),
+            alloc::boxed::Box::new(FnComparator(|a: &i32, b: &i32| a.abs().cmp(&b.abs()))),
+        ];
+        let expected = [vec![3, 1, -2, -4], vec![-4, -2, 1, 3], vec![-4, 3, -2, 1]];
+        for (comparator, expected) in comparators.into_iter().zip(expected) {
+            let mut heap: DynHeap<i32> = BinaryHeap::with_comparator(comparator);
+            for x in [1, -4, 3, -2] {
+                heap.insert(x);
+            }
+            assert_eq!(expected, heap.to_vec());
+        }
+    }
+
+    #[test]
+    fn insert_unique_1() {
+        let mut heap = BinaryHeap::new();
+        let inserted: Vec<bool> = [5, 3, 5, 8, 3, 1, 8].into_iter().map(|x| heap.insert_unique(x)).collect();
+        assert_eq!(vec![true, true, false, true, false, true, false], inserted);
+        assert_eq!(vec![8, 5, 3, 1], heap.to_vec());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_1() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        // subscriber recording the names of the spans and the levels of the events
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+            fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+                self.0.lock().unwrap().push(attributes.metadata().name().to_string());
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event<'_>) {
+                self.0.lock().unwrap().push(event.metadata().level().to_string());
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Recorder(records.clone()), || {
+            let mut heap = BinaryHeap::from_vec_with_comparator(vec![1, 3, 2], MaxComparator);
+            heap.insert(4);
+            heap.pop();
+        });
+        assert_eq!(vec!["rebuild", "TRACE", "TRACE"], *records.lock().unwrap());
+    }
+
+    #[test]
+    fn is_valid_heap_1() {
+        for size in 0..40 {
+            let mut heap = BinaryHeap::new();
+            for i in 0..size {
+                heap.insert((i * 17) % 23);
+                assert!(heap.is_valid_heap());
+            }
+            while heap.pop().is_some() {
+                assert!(heap.is_valid_heap());
+            }
+        }
+
+        // a child larger than its parent
+        let mut heap = BinaryHeap::from(vec![3, 2, 1]);
+        heap.data.swap(0, 2);
+        assert!(!heap.is_valid_heap());
+    }
+
+    #[cfg(feature = "debug-validate")]
+    #[test]
+    #[should_panic(expected = "heap property")]
+    fn debug_validate_1() {
+        let heap = BinaryHeap::from(vec![3, 2, 1]);
+        heap.transform_monotone(|x| -x);
+    }
+
+    #[test]
+    fn dary_1() {
+        fn check<const D: usize>() {
+            let mut next = lcg(12345);
+            let mut heap = DaryHeap::<u64, D>::new_dary();
+            let mut expected = Vec::new();
+            for _ in 0..1000 {
+                let x = next() % 500;
+                heap.insert(x);
+                expected.push(x);
+            }
+            assert!(heap.is_valid_heap());
+            assert!(heap.search(&expected[123]));
+            let mut heap_2 = heap.clone();
+            heap_2.adjust_all(|x| *x = 500 - *x);
+            assert!(heap_2.is_valid_heap());
+            expected.sort_unstable_by(|a, b| b.cmp(a));
+            assert_eq!(expected, heap.to_vec());
+        }
+        check::<2>();
+        check::<3>();
+        check::<4>();
+        check::<8>();
+    }
 }
@@ -0,0 +1,155 @@
+//! K-way merge of sorted line-based inputs
+//!
+//! The inputs are read in parallel, keeping only the next line of each of them in memory: a heap
+//! holds one cursor per input, and the cursor with the smallest line is popped, its line written
+//! out, and pushed back with the next line of the same input.
+
+use crate::BinaryHeap;
+use std::cmp::{Ordering, Reverse};
+use std::io::{BufRead, Result, Write};
+
+// next line of an input, with its key and the index of the input
+struct Cursor<K> {
+    key: K,
+    line: String,
+    source: usize,
+}
+
+// cursors are ordered by key, then by input index, so that equal lines are written in the order
+// of the inputs
+impl<K: PartialOrd> PartialEq for Cursor<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<K: PartialOrd> PartialOrd for Cursor<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.key.partial_cmp(&other.key) {
+            Some(Ordering::Equal) => Some(self.source.cmp(&other.source)),
+            ordering => ordering,
+        }
+    }
+}
+
+// read the next line of `input`, without its line terminator
+fn read_line<R: BufRead>(input: &mut R) -> Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(Some(line))
+}
+
+/// Merge inputs whose lines are sorted in increasing order of `key(line)`, writing all their
+/// lines to `output` in increasing order of `key(line)`
+///
+/// Lines with equal keys are written in the order of the inputs. Only one line per input is kept
+/// in memory. An error returned by `key` stops the merge and is returned.
+///
+/// Worst-case complexity: $\Theta(n \log k)$, where $n$ is the total number of lines and $k$ is
+/// the number of inputs.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::merge::kmerge_lines_by_key;
+///
+/// let inputs = vec!["1\n10\n".as_bytes(), "2\n3\n".as_bytes()];
+/// let mut output = Vec::<u8>::new();
+/// kmerge_lines_by_key(inputs, &mut output, |line| {
+///     line.parse::<u32>().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+/// }).unwrap();
+///
+/// assert_eq!("1\n2\n3\n10\n", String::from_utf8(output).unwrap());
+/// ```
+pub fn kmerge_lines_by_key<R, W, K, F>(mut inputs: Vec<R>, mut output: W, mut key: F)
+    -> Result<()>
+    where R: BufRead,
+          W: Write,
+          K: PartialOrd,
+          F: FnMut(&str) -> Result<K>
+{
+    // the root of the heap is the cursor with the smallest line
+    let mut cursors = BinaryHeap::<Reverse<Cursor<K>>>::new();
+    for (source, input) in inputs.iter_mut().enumerate() {
+        if let Some(line) = read_line(input)? {
+            cursors.insert(Reverse(Cursor { key: key(&line)?, line, source }));
+        }
+    }
+
+    while let Some(Reverse(cursor)) = cursors.pop() {
+        writeln!(output, "{}", cursor.line)?;
+        if let Some(line) = read_line(&mut inputs[cursor.source])? {
+            cursors.insert(Reverse(Cursor { key: key(&line)?, line, source: cursor.source }));
+        }
+    }
+    output.flush()
+}
+
+/// Merge inputs whose lines are sorted in increasing order, writing all their lines to `output`
+/// in increasing order
+///
+/// Only one line per input is kept in memory.
+///
+/// Worst-case complexity: $\Theta(n \log k)$, where $n$ is the total number of lines and $k$ is
+/// the number of inputs.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::merge::kmerge_lines;
+///
+/// let inputs = vec!["apple\nfig\n".as_bytes(), "banana\ncherry\n".as_bytes()];
+/// let mut output = Vec::<u8>::new();
+/// kmerge_lines(inputs, &mut output).unwrap();
+///
+/// assert_eq!("apple\nbanana\ncherry\nfig\n", String::from_utf8(output).unwrap());
+/// ```
+pub fn kmerge_lines<R: BufRead, W: Write>(inputs: Vec<R>, output: W) -> Result<()> {
+    kmerge_lines_by_key(inputs, output, |line| Ok(String::from(line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(inputs: &[&str]) -> String {
+        let mut output = Vec::<u8>::new();
+        kmerge_lines(inputs.iter().map(|input| input.as_bytes()).collect(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn several_inputs() {
+        assert_eq!("a\nb\nc\nd\ne\nf\ng\n", merged(&["a\nd\ng\n", "b\ne", "", "c\nf\n"]));
+        assert_eq!("", merged(&[]));
+        assert_eq!("x\ny\n", merged(&["x\r\ny\r\n"]));
+    }
+
+    #[test]
+    fn ties_in_input_order() {
+        let inputs = vec!["1\tfirst\n".as_bytes(), "1\tsecond\n".as_bytes(), "0\tthird\n".as_bytes()];
+        let mut output = Vec::<u8>::new();
+        kmerge_lines_by_key(inputs, &mut output, |line| {
+            Ok(String::from(line.split('\t').next().unwrap()))
+        }).unwrap();
+        assert_eq!("0\tthird\n1\tfirst\n1\tsecond\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn key_error() {
+        let inputs = vec!["1\n".as_bytes(), "x\n".as_bytes()];
+        let mut output = Vec::<u8>::new();
+        let result = kmerge_lines_by_key(inputs, &mut output, |line| {
+            line.parse::<u32>().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,341 @@
+//! Min-max heap (double-ended priority queue)
+//!
+//! The heap is stored as an array, like a binary heap, but the levels of the tree alternate
+//! between min levels (starting with the root) and max levels: each element on a min level is
+//! not larger than its descendants, and each element on a max level is not smaller than its
+//! descendants. The minimum is then the root, and the maximum one of its children.
+//! (Atkinson, Sack, Santoro, and Strothotte, *Min-max heaps and generalized priority queues*,
+//! 1986.)
+
+use crate::{Compare, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A double-ended priority queue, giving access to both its minimum and its maximum
+///
+/// `pop_min` and `pop_max` take $\Theta(\log n)$ time, and `peek_min` and `peek_max`
+/// $\Theta(1)$, which makes it possible, for instance, to keep a bounded buffer of the largest
+/// elements seen by evicting the smallest one. The order is given by the comparator `C`, the
+/// natural order of `T` by default.
+///
+/// `MinMaxHeap` implements `Iterator` (popping the maximum) and `DoubleEndedIterator` (popping
+/// the minimum from the back).
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::MinMaxHeap;
+///
+/// let mut heap = MinMaxHeap::from(vec![4, 8, 1, 6]);
+/// heap.insert(3);
+///
+/// assert_eq!(Some(&1), heap.peek_min());
+/// assert_eq!(Some(8), heap.pop_max());
+/// assert_eq!(Some(1), heap.pop_min());
+/// assert_eq!(vec![6, 4, 3], heap.to_vec());
+/// ```
+#[derive(Clone, Debug)]
+pub struct MinMaxHeap<T, C = MaxComparator> {
+    data: Vec<T>,
+    comparator: C,
+}
+
+impl<T: core::cmp::PartialOrd> MinMaxHeap<T> {
+
+    /// Create a new empty `MinMaxHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        MinMaxHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> MinMaxHeap<T, C> {
+
+    /// Create a new empty `MinMaxHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        MinMaxHeap { data: Vec::new(), comparator }
+    }
+
+    /// Build a `MinMaxHeap` ordered by `comparator` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = MinMaxHeap { data: values, comparator };
+        for pos in (0..(heap.size() >> 1)).rev() {
+            heap.push_down(pos);
+        }
+        heap
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        self.data.push(x);
+        self.push_up(self.size() - 1);
+    }
+
+    /// Return a reference to the minimum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Return a reference to the maximum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_pos().map(|pos| &self.data[pos])
+    }
+
+    /// Remove and return the minimum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(self.remove_at(0))
+    }
+
+    /// Remove and return the maximum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop_max(&mut self) -> Option<T> {
+        let pos = self.max_pos()?;
+        Some(self.remove_at(pos))
+    }
+
+    /// Consume the heap and return a vector of all its elements, from the maximum to the minimum
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the
+    /// heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // position of the maximum element: the root if it has no children, or its largest child
+    fn max_pos(&self) -> Option<usize> {
+        match self.size() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.less(1, 2) { 2 } else { 1 }),
+        }
+    }
+
+    // remove the element at position `pos`, replacing it with the last one
+    fn remove_at(&mut self, pos: usize) -> T {
+        let x = self.data.swap_remove(pos);
+        if pos < self.size() {
+            self.push_down(pos);
+        }
+        x
+    }
+
+    // whether the element at position `i` is smaller than the one at position `j`
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        self.comparator.compare(&self.data[i], &self.data[j]) == Some(Ordering::Less)
+    }
+
+    // whether the element at position `i` should be closer to the root than the one at position
+    // `j`, on a min level (`min` is `true`) or a max level (`min` is `false`)
+    #[inline]
+    fn before(&self, i: usize, j: usize, min: bool) -> bool {
+        if min { self.less(i, j) } else { self.less(j, i) }
+    }
+
+    // whether position `pos` is on a min level (the levels have 1, 2, 4, ... elements)
+    #[inline]
+    fn is_min_level(pos: usize) -> bool {
+        (usize::BITS - (pos + 1).leading_zeros()) % 2 == 1
+    }
+
+    // move the element at position `pos` up to its correct position
+    fn push_up(&mut self, pos: usize) {
+        if pos == 0 {
+            return;
+        }
+        let parent = (pos - 1) >> 1;
+        let min = Self::is_min_level(pos);
+
+        // if the element belongs to the levels of the other kind, swap it with its parent
+        if self.before(parent, pos, min) {
+            self.data.swap(parent, pos);
+            self.push_up_levels(parent, !min);
+        } else {
+            self.push_up_levels(pos, min);
+        }
+    }
+
+    // move the element at position `pos` up through the levels of the same kind
+    fn push_up_levels(&mut self, mut pos: usize, min: bool) {
+        while pos > 2 {
+            let grandparent = (pos - 3) >> 2;
+            if self.before(pos, grandparent, min) {
+                self.data.swap(pos, grandparent);
+                pos = grandparent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    // move the element at position `pos` down to its correct position
+    fn push_down(&mut self, mut pos: usize) {
+        let min = Self::is_min_level(pos);
+        let size = self.size();
+        loop {
+            let first_child = (pos << 1) + 1;
+            if first_child >= size {
+                break;
+            }
+
+            // find the first (smallest on a min level, largest on a max level) of the children
+            // and grandchildren
+            let first_grandchild = (pos << 2) + 3;
+            let mut best = first_child;
+            for descendant in ((first_child + 1)..(first_child + 2))
+                .chain(first_grandchild..(first_grandchild + 4))
+                .take_while(|&descendant| descendant < size)
+            {
+                if self.before(descendant, best, min) {
+                    best = descendant;
+                }
+            }
+
+            if !self.before(best, pos, min) {
+                break;
+            }
+            self.data.swap(best, pos);
+            if best < first_grandchild {
+                break;
+            }
+
+            // the element moved down two levels, and may belong below its new parent
+            let parent = (best - 1) >> 1;
+            if self.before(parent, best, min) {
+                self.data.swap(parent, best);
+            }
+            pos = best;
+        }
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for MinMaxHeap<T> {
+
+    /// Build a `MinMaxHeap` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        MinMaxHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T: core::cmp::PartialOrd, const N: usize> From<[T; N]> for MinMaxHeap<T> {
+
+    /// Build a `MinMaxHeap` from an array
+    ///
+    /// Worst-case complexity: $\Theta(N)$.
+    fn from(values: [T; N]) -> Self {
+        MinMaxHeap::from(Vec::from(values))
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for MinMaxHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for MinMaxHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop_max()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size(), Some(self.size()))
+    }
+}
+
+impl<T, C: Compare<T>> DoubleEndedIterator for MinMaxHeap<T, C> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pop_min()
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for MinMaxHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for MinMaxHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use alloc::collections::VecDeque;
+
+    // check that each element is ordered with its descendants according to its level
+    fn check<T, C: Compare<T>>(heap: &MinMaxHeap<T, C>) {
+        for pos in 1..heap.size() {
+            let mut ancestor = (pos - 1) >> 1;
+            loop {
+                let min = MinMaxHeap::<T, C>::is_min_level(ancestor);
+                assert!(!heap.before(pos, ancestor, min));
+                if ancestor == 0 {
+                    break;
+                }
+                ancestor = (ancestor - 1) >> 1;
+            }
+        }
+    }
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(12345);
+        let mut heap = MinMaxHeap::new();
+        let mut sorted = VecDeque::new();
+        for _ in 0..5000 {
+            match next() % 4 {
+                0 => assert_eq!(sorted.pop_front(), heap.pop_min()),
+                1 => assert_eq!(sorted.pop_back(), heap.pop_max()),
+                _ => {
+                    let x = next() % 1000;
+                    heap.insert(x);
+                    let pos = sorted.partition_point(|&y| y < x);
+                    sorted.insert(pos, x);
+                },
+            }
+            check(&heap);
+            assert_eq!(sorted.front(), heap.peek_min());
+            assert_eq!(sorted.back(), heap.peek_max());
+        }
+    }
+
+    #[test]
+    fn from_vec_1() {
+        for size in 0..100u32 {
+            let values: Vec<u32> = (0..size).map(|i| (i * 37) % 101).collect();
+            let heap = MinMaxHeap::from(values.clone());
+            check(&heap);
+            let mut expected = values;
+            expected.sort_unstable();
+            assert_eq!(expected, heap.rev().collect::<Vec<u32>>());
+        }
+    }
+}
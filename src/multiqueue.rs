@@ -0,0 +1,244 @@
+//! Relaxed concurrent priority queue made of several locked heaps
+//!
+//! Each element is inserted in a random heap, and each pop compares the roots of two random heaps
+//! and pops the larger one. The popped elements are not exactly the largest ones, but their rank
+//! among the elements of the queue stays small in expectation, while the threads rarely contend
+//! for the same lock. (Rihani, Sanders, and Dementiev, *MultiQueues: Simple relaxed concurrent
+//! priority queues*, 2015; the rank bounds are those of Alistarh, Kopinsky, Li, and Nadiradze,
+//! *The power of choice in priority scheduling*, 2017.)
+
+use crate::sharded::lock;
+use crate::{BinaryHeap, Compare, MaxComparator};
+use core::cell::Cell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, TryLockError};
+use std::vec::Vec;
+
+std::thread_local! {
+    // state of the random generator of the thread, seeded from its identifier
+    static RANDOM_STATE: Cell<u64> = Cell::new({
+        use std::hash::BuildHasher;
+        std::collections::hash_map::RandomState::new().hash_one(std::thread::current().id()) | 1
+    });
+}
+
+// random number below `n` (at most $2^{32}$), from the generator of the thread (xorshift),
+// scaled in 64-bit arithmetic on all targets
+#[inline]
+fn random_below(n: usize) -> usize {
+    RANDOM_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (((x >> 32) * n as u64) >> 32) as usize
+    })
+}
+
+/// A relaxed concurrent priority queue, trading exact ordering for scalability
+///
+/// The queue is made of several `BinaryHeap`s, each behind its own lock; the recommended number
+/// of heaps is a small multiple $c \cdot p$ of the number $p$ of threads, for instance $c = 2$.
+/// `insert` adds the element to a random heap which is not locked, and `pop` picks two random
+/// heaps and pops the larger of their roots. With $m$ heaps, the rank of a popped element (the
+/// number of larger elements left in the queue) is $O(m)$ in expectation and $O(m \log m)$ with
+/// high probability, independently of the number of elements, which suits applications
+/// tolerating some disorder, such as parallel shortest paths or task scheduling.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{MinComparator, MultiQueue};
+///
+/// // parallel shortest paths, closest vertices first
+/// let frontier = MultiQueue::with_comparator(8, MinComparator);
+/// std::thread::scope(|scope| {
+///     for worker in 0..4 {
+///         let frontier = &frontier;
+///         scope.spawn(move || {
+///             for distance in 0..100 {
+///                 frontier.insert((distance, worker));
+///             }
+///         });
+///     }
+/// });
+///
+/// // the first pop is among the closest vertices, but not necessarily the closest one
+/// let (distance, _) = frontier.pop().unwrap();
+/// assert!(distance < 100);
+/// assert_eq!(399, frontier.size());
+/// ```
+#[derive(Debug)]
+pub struct MultiQueue<T, C = MaxComparator> {
+    queues: Vec<Mutex<BinaryHeap<T, C>>>,   // heaps, each with its own lock
+    size: AtomicUsize,                      // number of elements
+}
+
+impl<T: core::cmp::PartialOrd> MultiQueue<T> {
+
+    /// Create a new empty `MultiQueue` made of `queues` heaps, ordered by the natural order of `T`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `queues` is zero.
+    #[inline]
+    pub fn new(queues: usize) -> Self {
+        MultiQueue::with_comparator(queues, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> MultiQueue<T, C> {
+
+    /// Create a new empty `MultiQueue` made of `queues` heaps, ordered by `comparator`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `queues` is zero.
+    pub fn with_comparator(queues: usize, comparator: C) -> Self
+        where C: Clone
+    {
+        assert!(queues > 0, "a multiqueue needs at least one heap");
+        MultiQueue {
+            queues: (0..queues).map(|_| Mutex::new(BinaryHeap::with_comparator(comparator.clone()))).collect(),
+            size: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the number of heaps
+    #[inline]
+    pub fn queues(&self) -> usize {
+        self.queues.len()
+    }
+
+    /// Get the size of the queue (number of elements)
+    ///
+    /// With concurrent operations, the result may be out of date as soon as it is returned.
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Insert an element in a random heap which is not locked
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap,
+    /// without contention.
+    pub fn insert(&self, x: T) {
+        let mut heap = loop {
+            match self.queues[random_below(self.queues.len())].try_lock() {
+                Ok(heap) => break heap,
+                Err(TryLockError::Poisoned(error)) => break error.into_inner(),
+                Err(TryLockError::WouldBlock) => {},
+            }
+        };
+        heap.insert(x);
+        self.size.fetch_add(1, Ordering::Release);
+    }
+
+    /// Remove and return the larger root of two random heaps (or `None` if the queue is empty)
+    ///
+    /// If both heaps are empty, the other ones are scanned for an element.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap
+    /// popped from, without contention and if one of the two heaps is not empty.
+    pub fn pop(&self) -> Option<T> {
+        let n = self.queues.len();
+        let i = random_below(n);
+        let j = if n > 1 { (i + 1 + random_below(n - 1)) % n } else { i };
+
+        // lock the two heaps in the order of their indices, so that pops can not deadlock
+        let root = if i == j {
+            lock(&self.queues[i]).pop()
+        } else {
+            let (first, second) = (i.min(j), i.max(j));
+            let mut a = lock(&self.queues[first]);
+            let mut b = lock(&self.queues[second]);
+            let a_smaller = match (a.data.first(), b.data.first()) {
+                (Some(x), Some(y)) => a.compare_values(x, y) == Some(core::cmp::Ordering::Less),
+                (None, _) => true,
+                (Some(_), None) => false,
+            };
+            if a_smaller { b.pop() } else { a.pop() }
+        };
+
+        let root = root.or_else(|| self.queues.iter().find_map(|heap| lock(heap).pop()))?;
+        self.size.fetch_sub(1, Ordering::Release);
+        Some(root)
+    }
+}
+
+impl<T, C: Compare<T> + Clone + Default> core::default::Default for MultiQueue<T, C> {
+
+    /// Create a new empty `MultiQueue` with two heaps per available thread
+    fn default() -> Self {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        Self::with_comparator(2 * threads, C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+
+    #[test]
+    fn concurrent_inserts_and_pops() {
+        let queue = MultiQueue::new(16);
+        let popped = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for worker in 0..8u64 {
+                let queue = &queue;
+                let popped = &popped;
+                scope.spawn(move || {
+                    for i in 0..1000 {
+                        queue.insert(1000 * worker + i);
+                        if i % 3 == 0 {
+                            popped.lock().unwrap().push(queue.pop().unwrap());
+                        }
+                    }
+                });
+            }
+        });
+        let mut popped = popped.into_inner().unwrap();
+        assert_eq!(8000, popped.len() + queue.size());
+        popped.extend(core::iter::from_fn(|| queue.pop()));
+        assert_eq!(0, queue.size());
+        popped.sort_unstable();
+        assert_eq!((0..8000).collect::<Vec<u64>>(), popped);
+    }
+
+    #[test]
+    fn rank_error() {
+        let mut next = lcg(12345);
+        let queues = 8;
+        let queue = MultiQueue::new(queues);
+        let mut reference = BinaryHeap::new();
+        for _ in 0..10_000 {
+            let x = next();
+            queue.insert(x);
+            reference.insert(x);
+        }
+
+        // mean rank of the popped elements among those left in the queue
+        let mut total_rank = 0;
+        for _ in 0..1000 {
+            let x = queue.pop().unwrap();
+            let mut larger = Vec::new();
+            while let Some(y) = reference.pop() {
+                if y == x {
+                    break;
+                }
+                larger.push(y);
+            }
+            total_rank += larger.len();
+            for y in larger {
+                reference.insert(y);
+            }
+        }
+        assert!(total_rank < 1000 * 2 * queues);
+    }
+}
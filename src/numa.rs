@@ -0,0 +1,255 @@
+//! Sharded concurrent heap with a group of shards per NUMA node
+//!
+//! Each node has its own `ShardedHeap`, aligned on its own cache lines. Threads insert into and
+//! pop from the shards of their node, so that the cache lines of a shard stay on one node; a pop
+//! only steals from the other nodes when the shards of its node are empty.
+//!
+//! Nodes are only indices: the heap does not detect the topology of the machine, pin threads, or
+//! allocate memory on a given node, which is left to the caller.
+
+use crate::{Compare, MaxComparator, ShardedHeap};
+use std::vec::Vec;
+
+// value aligned on a pair of cache lines, so that values of different nodes never share one
+// (adjacent cache lines are prefetched together on some processors)
+#[derive(Debug)]
+#[repr(align(128))]
+pub(crate) struct CachePadded<T>(pub(crate) T);
+
+/// A concurrent heap made of one `ShardedHeap` per NUMA node, preferring the shards of the
+/// calling thread's node
+///
+/// The node of a thread is given by the caller, usually from the affinity the thread was pinned
+/// with (the heap itself neither pins threads nor queries the topology); `handle` returns a
+/// `NumaHandle` bound to a node. The groups of shards are allocated by the usual allocator where
+/// `new` is called, unless they are built by `from_nodes`, which makes it possible to allocate
+/// each of them from a thread running on its node (memory is usually placed on the node of the
+/// thread which first writes it).
+///
+/// Pops return the largest root of the local shards, and only look at the other nodes when the
+/// local shards are empty: ordering is strict within a node, but not across nodes.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::NumaShardedHeap;
+///
+/// // two nodes with four shards each
+/// let heap = NumaShardedHeap::new(2, 4);
+/// std::thread::scope(|scope| {
+///     for node in 0..2 {
+///         let local = heap.handle(node);
+///         scope.spawn(move || {
+///             for job in 0..100 {
+///                 local.insert(100 * node + job);
+///             }
+///         });
+///     }
+/// });
+///
+/// // node 0 pops its own jobs first, then steals those of node 1
+/// let local = heap.handle(0);
+/// assert_eq!(Some(99), local.pop());
+/// for _ in 0..99 {
+///     local.pop();
+/// }
+/// assert_eq!(Some(199), local.pop());
+/// ```
+#[derive(Debug)]
+pub struct NumaShardedHeap<T, C = MaxComparator> {
+    nodes: Vec<CachePadded<ShardedHeap<T, C>>>,
+}
+
+impl<T: core::cmp::PartialOrd> NumaShardedHeap<T> {
+
+    /// Create a new empty `NumaShardedHeap` with `shards` shards on each of `nodes` nodes,
+    /// ordered by the natural order of `T`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` or `shards` is zero.
+    #[inline]
+    pub fn new(nodes: usize, shards: usize) -> Self {
+        NumaShardedHeap::with_comparator(nodes, shards, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> NumaShardedHeap<T, C> {
+
+    /// Create a new empty `NumaShardedHeap` with `shards` shards on each of `nodes` nodes,
+    /// ordered by `comparator`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` or `shards` is zero.
+    pub fn with_comparator(nodes: usize, shards: usize, comparator: C) -> Self
+        where C: Clone
+    {
+        Self::from_nodes((0..nodes).map(|_| ShardedHeap::with_comparator(shards, comparator.clone())).collect())
+    }
+
+    /// Create a `NumaShardedHeap` from the sharded heaps of each node
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` is empty.
+    pub fn from_nodes(nodes: Vec<ShardedHeap<T, C>>) -> Self {
+        assert!(!nodes.is_empty(), "a NUMA-aware heap needs at least one node");
+        NumaShardedHeap { nodes: nodes.into_iter().map(CachePadded).collect() }
+    }
+
+    /// Get the number of nodes
+    #[inline]
+    pub fn nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// With concurrent operations, the result may be out of date as soon as it is returned.
+    ///
+    /// Worst-case complexity: $\Theta(m)$, where $m$ is the number of nodes.
+    pub fn size(&self) -> usize {
+        self.nodes.iter().map(|node| node.0.size()).sum()
+    }
+
+    /// Return a handle inserting into and popping from the shards of `node` first
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not smaller than the number of nodes.
+    #[inline]
+    pub fn handle(&self, node: usize) -> NumaHandle<'_, T, C> {
+        assert!(node < self.nodes.len(), "node out of bounds");
+        NumaHandle { heap: self, node }
+    }
+
+    /// Insert an element in a shard of `node`
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the shard,
+    /// without contention.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not smaller than the number of nodes.
+    #[inline]
+    pub fn insert(&self, node: usize, x: T) {
+        assert!(node < self.nodes.len(), "node out of bounds");
+        self.nodes[node].0.insert(x);
+    }
+
+    /// Remove and return the largest root of the shards of `node`, or, if they are empty, of the
+    /// first other node whose shards are not (or `None` if the heap is empty)
+    ///
+    /// The other nodes are scanned in order, starting from the one after `node`.
+    ///
+    /// Worst-case complexity: $\Theta(s + \log n)$, where $s$ is the number of shards of a node
+    /// and $n$ the number of elements in the shard of the root, without contention and if the
+    /// shards of `node` are not empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not smaller than the number of nodes.
+    pub fn pop(&self, node: usize) -> Option<T> {
+        assert!(node < self.nodes.len(), "node out of bounds");
+        let count = self.nodes.len();
+        (0..count).find_map(|i| self.nodes[(node + i) % count].0.pop())
+    }
+}
+
+/// A handle to a `NumaShardedHeap`, bound to a node
+#[derive(Debug)]
+pub struct NumaHandle<'a, T, C = MaxComparator> {
+    heap: &'a NumaShardedHeap<T, C>,
+    node: usize,
+}
+
+impl<T, C> Clone for NumaHandle<'_, T, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, C> Copy for NumaHandle<'_, T, C> {}
+
+impl<T, C: Compare<T>> NumaHandle<'_, T, C> {
+
+    /// Get the node of the handle
+    #[inline]
+    pub fn node(&self) -> usize {
+        self.node
+    }
+
+    /// Insert an element in a shard of the node
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the shard,
+    /// without contention.
+    #[inline]
+    pub fn insert(&self, x: T) {
+        self.heap.insert(self.node, x);
+    }
+
+    /// Remove and return the largest root of the shards of the node, stealing from the other
+    /// nodes if they are empty (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(s + \log n)$, where $s$ is the number of shards of a node
+    /// and $n$ the number of elements in the shard of the root, without contention and if the
+    /// shards of the node are not empty.
+    #[inline]
+    pub fn pop(&self) -> Option<T> {
+        self.heap.pop(self.node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn local_first_then_steal() {
+        let heap = NumaShardedHeap::new(4, 2);
+        let popped = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for node in 0..4u64 {
+                let local = heap.handle(node as usize);
+                let popped = &popped;
+                scope.spawn(move || {
+                    for i in 0..1000 {
+                        local.insert(1000 * node + i);
+                    }
+
+                    // the shards of the node are not empty, so the pops stay local
+                    for _ in 0..500 {
+                        let x = local.pop().unwrap();
+                        assert_eq!(node, x / 1000);
+                        popped.lock().unwrap().push(x);
+                    }
+                });
+            }
+        });
+        assert_eq!(2000, heap.size());
+
+        // node 2 pops its own elements, then steals those of node 3, node 0, and node 1
+        let local = heap.handle(2);
+        let mut rest = Vec::new();
+        while let Some(x) = local.pop() {
+            rest.push(x);
+        }
+        let nodes: Vec<u64> = rest.iter().map(|x| x / 1000).collect();
+        assert!(nodes.windows(2).all(|pair| pair[0] == pair[1] || (pair[0] + 1) % 4 == pair[1]));
+        let mut popped = popped.into_inner().unwrap();
+        popped.extend(rest);
+        popped.sort_unstable();
+        assert_eq!((0..4000).collect::<Vec<u64>>(), popped);
+    }
+
+    #[test]
+    #[should_panic(expected = "node out of bounds")]
+    fn pop_out_of_bounds() {
+        let heap = NumaShardedHeap::<u32>::new(2, 2);
+        heap.pop(2);
+    }
+}
@@ -0,0 +1,183 @@
+//! Observer hooks on the structural operations of a heap
+
+use crate::BinaryHeap;
+use alloc::sync::Arc;
+
+/// Functions called by a `BinaryHeap` on its structural operations
+///
+/// All functions do nothing by default, so that an implementation only needs to define those it
+/// is interested in. They take `&self`, since an observer is shared between a heap and its
+/// clones: counters should use interior mutability (for instance atomics).
+///
+/// Only available with the `observer` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, HeapObserver};
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// // largest size reached by the heap
+/// #[derive(Default)]
+/// struct MaxDepth(AtomicUsize);
+///
+/// impl<T> HeapObserver<T> for MaxDepth {
+///     fn on_insert(&self, _element: &T, size: usize) {
+///         self.0.fetch_max(size, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let depth = Arc::new(MaxDepth::default());
+/// let mut heap = BinaryHeap::<u32>::new();
+/// heap.set_observer(depth.clone());
+/// heap.insert(1);
+/// heap.insert(2);
+/// heap.pop();
+/// heap.insert(3);
+///
+/// assert_eq!(2, depth.0.load(Ordering::Relaxed));
+/// ```
+pub trait HeapObserver<T> {
+
+    /// Called after `element` is added to the heap, before it is moved to its position; `size`
+    /// is the new number of elements
+    fn on_insert(&self, _element: &T, _size: usize) {}
+
+    /// Called after the root `element` is removed from the heap and the heap is repaired; `size`
+    /// is the new number of elements
+    fn on_pop(&self, _element: &T, _size: usize) {}
+
+    /// Called after the elements at positions `i` and `j` of the heap array are swapped
+    fn on_swap(&self, _i: usize, _j: usize) {}
+}
+
+// optional observer of a heap
+pub(crate) struct ObserverSlot<T>(Option<Arc<dyn HeapObserver<T> + Send + Sync>>);
+
+impl<T> Clone for ObserverSlot<T> {
+    fn clone(&self) -> Self {
+        ObserverSlot(self.0.clone())
+    }
+}
+
+impl<T> Default for ObserverSlot<T> {
+    fn default() -> Self {
+        ObserverSlot(None)
+    }
+}
+
+impl<T> core::fmt::Debug for ObserverSlot<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Some(..)"),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+impl<T> ObserverSlot<T> {
+
+    #[inline]
+    pub(crate) fn on_insert(&self, element: &T, size: usize) {
+        if let Some(observer) = &self.0 {
+            observer.on_insert(element, size);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn on_pop(&self, element: &T, size: usize) {
+        if let Some(observer) = &self.0 {
+            observer.on_pop(element, size);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn on_swap(&self, i: usize, j: usize) {
+        if let Some(observer) = &self.0 {
+            observer.on_swap(i, j);
+        }
+    }
+}
+
+impl<T, C, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Set the observer of the heap, replacing the previous one (if any)
+    ///
+    /// Clones of the heap share its observer.
+    #[inline]
+    pub fn set_observer(&mut self, observer: Arc<dyn HeapObserver<T> + Send + Sync>) {
+        self.observer = ObserverSlot(Some(observer));
+    }
+
+    /// Remove the observer of the heap and return it (or `None` if the heap had no observer)
+    #[inline]
+    pub fn take_observer(&mut self) -> Option<Arc<dyn HeapObserver<T> + Send + Sync>> {
+        self.observer.0.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counter {
+        inserts: AtomicUsize,
+        pops: AtomicUsize,
+        swaps: AtomicUsize,
+    }
+
+    impl<T> HeapObserver<T> for Counter {
+        fn on_insert(&self, _element: &T, _size: usize) {
+            self.inserts.fetch_add(1, Ordering::Relaxed);
+        }
+        fn on_pop(&self, _element: &T, _size: usize) {
+            self.pops.fetch_add(1, Ordering::Relaxed);
+        }
+        fn on_swap(&self, _i: usize, _j: usize) {
+            self.swaps.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn counts_1() {
+        let counter = Arc::new(Counter::default());
+        let mut heap = BinaryHeap::new();
+        heap.set_observer(counter.clone());
+
+        // each element is moved up to the root
+        for i in 1..=7 {
+            heap.insert(i);
+        }
+        assert_eq!(7, counter.inserts.load(Ordering::Relaxed));
+        assert_eq!(10, counter.swaps.load(Ordering::Relaxed));
+
+        assert_eq!(vec![7, 6, 5, 4, 3, 2, 1], heap.clone().to_vec());
+        assert_eq!(7, counter.pops.load(Ordering::Relaxed));
+
+        assert!(heap.take_observer().is_some());
+        heap.pop();
+        assert_eq!(7, counter.pops.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn transaction_rollback() {
+        let counter = Arc::new(Counter::default());
+        let mut heap = BinaryHeap::from(vec![3]);
+        heap.set_observer(counter.clone());
+
+        // the swap and the insertions are undone in reverse order
+        let result = heap.transaction(|txn| {
+            txn.insert(1);
+            txn.insert(5);
+            Err::<(), ()>(())
+        });
+        assert!(result.is_err());
+        assert_eq!(2, counter.inserts.load(Ordering::Relaxed));
+        assert_eq!(2, counter.swaps.load(Ordering::Relaxed));
+        assert_eq!(2, counter.pops.load(Ordering::Relaxed));
+        assert_eq!(vec![3], heap.to_vec());
+    }
+}
@@ -0,0 +1,185 @@
+//! Recording of the operations on a heap, and deterministic replay
+
+use crate::{BinaryHeap, Compare};
+use alloc::vec::Vec;
+
+/// An operation on a heap, as recorded by `RecordingHeap`
+///
+/// Only available with the `oplog` feature.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Operation<T> {
+    /// Insertion of an element
+    Insert(T),
+    /// Removal of the root, with the element returned (`None` if the heap was empty)
+    Pop(Option<T>),
+}
+
+/// Error returned by `replay` when a pop does not return the recorded element
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplayError<T> {
+    /// Index of the operation in the log
+    pub index: usize,
+    /// Element returned when the log was recorded
+    pub expected: Option<T>,
+    /// Element returned by the replay
+    pub found: Option<T>,
+}
+
+impl<T> core::fmt::Display for ReplayError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "operation {} of the log returned a different element", self.index)
+    }
+}
+
+impl<T: core::fmt::Debug> core::error::Error for ReplayError<T> {}
+
+/// A `BinaryHeap` recording each operation in a log
+///
+/// Every insertion is recorded with (a clone of) the inserted element, and every pop with the
+/// element it returned. The log can be replayed against another heap with `replay`, which finds
+/// the first operation whose result differs; replaying prefixes of the log makes it possible to
+/// bisect a divergence between two versions of the code or two comparators.
+///
+/// Only available with the `oplog` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{replay, BinaryHeap, Operation, RecordingHeap};
+///
+/// let mut heap = RecordingHeap::new(BinaryHeap::new());
+/// heap.insert(2);
+/// heap.insert(5);
+/// heap.pop();
+///
+/// assert_eq!(&[Operation::Insert(2), Operation::Insert(5), Operation::Pop(Some(5))], heap.log());
+///
+/// // a min-heap diverges at the pop
+/// let error = replay(heap.log(), &mut BinaryHeap::new_min()).unwrap_err();
+/// assert_eq!(2, error.index);
+/// assert_eq!(Some(2), error.found);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecordingHeap<T, C> {
+    heap: BinaryHeap<T, C>,
+    log: Vec<Operation<T>>,
+}
+
+impl<T: Clone, C: Compare<T>> RecordingHeap<T, C> {
+
+    /// Record the operations on `heap`, starting with an empty log
+    #[inline]
+    pub fn new(heap: BinaryHeap<T, C>) -> Self {
+        RecordingHeap { heap, log: Vec::new() }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert an element in the heap, recording a clone of it
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$ (amortized for the log), where $n$ is the number of
+    /// elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        self.log.push(Operation::Insert(x.clone()));
+        self.heap.insert(x);
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty), recording a clone of
+    /// it
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$ (amortized for the log), where $n$ is the number of
+    /// elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.heap.pop();
+        self.log.push(Operation::Pop(root.clone()));
+        root
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first()
+    }
+
+    /// Get the operations recorded so far
+    #[inline]
+    pub fn log(&self) -> &[Operation<T>] {
+        &self.log
+    }
+
+    /// Return the heap and the log
+    #[inline]
+    pub fn into_parts(self) -> (BinaryHeap<T, C>, Vec<Operation<T>>) {
+        (self.heap, self.log)
+    }
+}
+
+/// Apply the operations of `log` to `heap`, checking that each pop returns the recorded element
+///
+/// Stops at the first pop returning a different element, and returns its index in the log with
+/// both elements. Elements are compared with `PartialEq`, so a pop returning another element
+/// equal to the recorded one is not a divergence.
+///
+/// Only available with the `oplog` feature.
+///
+/// Worst-case complexity: $\Theta(m \log (n + m))$, where $m$ is the length of the log and $n$
+/// the initial number of elements in the heap.
+pub fn replay<T: Clone + PartialEq, C: Compare<T>>(log: &[Operation<T>], heap: &mut BinaryHeap<T, C>)
+    -> Result<(), ReplayError<T>>
+{
+    for (index, operation) in log.iter().enumerate() {
+        match operation {
+            Operation::Insert(x) => heap.insert(x.clone()),
+            Operation::Pop(expected) => {
+                let found = heap.pop();
+                if found != *expected {
+                    return Err(ReplayError { index, expected: expected.clone(), found });
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::FnComparator;
+
+    #[test]
+    fn replay_1() {
+        let mut next = lcg(12345);
+        let mut heap = RecordingHeap::new(BinaryHeap::new());
+        for _ in 0..1000 {
+            if next().is_multiple_of(3) {
+                heap.pop();
+            } else {
+                heap.insert(next() % 100);
+            }
+        }
+        let (heap, log) = heap.into_parts();
+        assert_eq!(Ok(()), replay(&log, &mut BinaryHeap::new()));
+
+        // the final contents are the same
+        let mut replayed = BinaryHeap::new();
+        replay(&log, &mut replayed).unwrap();
+        assert_eq!(heap.to_vec(), replayed.to_vec());
+
+        // a comparator ignoring the last digit diverges at the first pop where it matters
+        let mut coarse = BinaryHeap::with_comparator(FnComparator(|a: &u64, b: &u64| (a / 10).cmp(&(b / 10))));
+        let error = replay(&log, &mut coarse).unwrap_err();
+        assert!(matches!(log[error.index], Operation::Pop(_)));
+        assert_eq!(Ok(()), replay(&log[..error.index], &mut BinaryHeap::with_comparator(
+            FnComparator(|a: &u64, b: &u64| (a / 10).cmp(&(b / 10)))
+        )));
+    }
+}
@@ -0,0 +1,397 @@
+//! Pairing heap
+//!
+//! A pairing heap is a tree whose root is the largest element, with no constraint on its shape.
+//! Two trees are linked in constant time by making the root with the smaller element the first
+//! child of the other. To pop the root, its children are linked in pairs from left to right, then
+//! the resulting trees are linked from right to left. (Fredman, Sedgewick, Sleator, and Tarjan,
+//! *The pairing heap: A new form of self-adjusting heap*, 1986.)
+//!
+//! The nodes are stored in a slab, like those of `AddressableHeap`; each node records its first
+//! child, its next sibling, and its previous sibling (or its parent, for a first child).
+
+use crate::{Compare, Handle, HandleMap, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+// node of the tree
+#[derive(Clone, Debug)]
+struct Node<T> {
+    value: T,
+    child: Option<usize>,   // first child
+    next: Option<usize>,    // next sibling
+    prev: Option<usize>,    // previous sibling, or parent for a first child
+}
+
+// slot of the slab; the generation is incremented each time the slot is freed
+#[derive(Clone, Debug)]
+struct Slot<T> {
+    generation: u32,
+    node: Option<Node<T>>,
+}
+
+/// A mergeable heap with cheap changes of priority
+///
+/// `insert` and `meld` link the roots of two trees in $\Theta(1)$ time, and moving an element
+/// towards the root with `increase_key` (or `decrease_key` with `MinComparator`, as in Dijkstra's
+/// algorithm or A*) cuts its subtree and links it with the root in $\Theta(1)$ time. `pop`
+/// restructures the tree, in $O(\log n)$ amortized time. Elements are accessed through the
+/// handles returned by `insert`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{MinComparator, PairingHeap};
+///
+/// // tentative distances of a shortest-path search
+/// let mut frontier = PairingHeap::with_comparator(MinComparator);
+/// let a = frontier.insert((7, 'a'));
+/// frontier.insert((4, 'b'));
+///
+/// // a shorter path to `a` was found
+/// frontier.decrease_key(a, (2, 'a'));
+///
+/// let mut other = PairingHeap::with_comparator(MinComparator);
+/// let c = other.insert((3, 'c'));
+/// let handles = frontier.meld(other);
+///
+/// assert_eq!(Some(&(3, 'c')), frontier.get(handles.map(c)));
+/// assert_eq!(Some((2, 'a')), frontier.pop());
+/// assert_eq!(Some((3, 'c')), frontier.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PairingHeap<T, C = MaxComparator> {
+    slots: Vec<Slot<T>>,    // slab of nodes
+    free: Vec<usize>,       // indices of the free slots
+    root: Option<usize>,    // slot of the root
+    size: usize,            // number of elements
+    comparator: C,          // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> PairingHeap<T> {
+
+    /// Create a new empty `PairingHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        PairingHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> PairingHeap<T, C> {
+
+    /// Create a new empty `PairingHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        PairingHeap { slots: Vec::new(), free: Vec::new(), root: None, size: 0, comparator }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap, returning a handle to it
+    ///
+    /// Worst-case complexity: $\Theta(1)$ (amortized, as the slab may grow).
+    pub fn insert(&mut self, x: T) -> Handle {
+        let node = Some(Node { value: x, child: None, next: None, prev: None });
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].node = node;
+                index
+            },
+            None => {
+                self.slots.push(Slot { generation: 0, node });
+                self.slots.len() - 1
+            },
+        };
+        self.root = self.link(self.root, Some(index));
+        self.size += 1;
+        Handle { index, generation: self.slots[index].generation }
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root?;
+        let children = self.node_mut(root).child.take();
+        self.root = self.merge_pairs(children);
+        Some(self.free_slot(root))
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.root.map(|root| &self.node(root).value)
+    }
+
+    /// Return a reference to the element of `handle` (or `None` if it is no longer in the heap)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.index(handle).map(|index| &self.node(index).value)
+    }
+
+    /// Whether the element of `handle` is still in the heap
+    #[inline]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.index(handle).is_some()
+    }
+
+    /// Replace the element of `handle` by a smaller one `x`, returning the old element (or
+    /// `None`, leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact larger, the heap is repaired as well.
+    ///
+    /// Complexity: $\Theta(1)$ if `x` is not smaller than the old element (in particular with
+    /// `MinComparator`, if it is not larger for the natural order), and $O(\log n)$ amortized
+    /// otherwise, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn decrease_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Replace the element of `handle` by a larger one `x`, returning the old element (or `None`,
+    /// leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact smaller, the heap is repaired as well.
+    ///
+    /// Complexity: $\Theta(1)$ if `x` is not smaller than the old element, and $O(\log n)$
+    /// amortized otherwise, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn increase_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Remove the element of `handle` and return it (or `None` if it is no longer in the heap)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let index = self.index(handle)?;
+        if self.root == Some(index) {
+            return self.pop();
+        }
+        self.cut(index);
+        let children = self.node_mut(index).child.take();
+        let subtree = self.merge_pairs(children);
+        self.root = self.link(self.root, subtree);
+        Some(self.free_slot(index))
+    }
+
+    /// Move all the elements of `other` into this heap, returning the translation of the handles
+    /// of `other`
+    ///
+    /// The roots are linked in $\Theta(1)$ time; the nodes of `other` are moved to the slab of
+    /// this heap without any comparison.
+    ///
+    /// Worst-case complexity: $\Theta(m)$ (amortized, as the slab may grow), where $m$ is the
+    /// number of slots of `other`.
+    pub fn meld(&mut self, other: PairingHeap<T, C>) -> HandleMap {
+        let offset = self.slots.len();
+        let shift = |index: Option<usize>| index.map(|index| index + offset);
+        self.slots.extend(other.slots.into_iter().map(|mut slot| {
+            if let Some(node) = slot.node.as_mut() {
+                node.child = shift(node.child);
+                node.next = shift(node.next);
+                node.prev = shift(node.prev);
+            }
+            slot
+        }));
+        self.free.extend(other.free.iter().map(|index| index + offset));
+        self.root = self.link(self.root, shift(other.root));
+        self.size += other.size;
+        HandleMap { offset }
+    }
+
+    // slot index of `handle`, if its element is still in the heap
+    fn index(&self, handle: Handle) -> Option<usize> {
+        self.slots.get(handle.index)
+            .filter(|slot| slot.generation == handle.generation && slot.node.is_some())
+            .map(|_| handle.index)
+    }
+
+    // node in the slot `index`, which must be occupied
+    #[inline]
+    fn node(&self, index: usize) -> &Node<T> {
+        self.slots[index].node.as_ref().unwrap()
+    }
+
+    #[inline]
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        self.slots[index].node.as_mut().unwrap()
+    }
+
+    // free the slot `index` and return its element
+    fn free_slot(&mut self, index: usize) -> T {
+        let slot = &mut self.slots[index];
+        let node = slot.node.take().unwrap();
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        self.size -= 1;
+        node.value
+    }
+
+    // link two trees (without siblings), returning the root of the result
+    fn link(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        let (a, b) = match (a, b) {
+            (Some(a), Some(b)) => (a, b),
+            (a, None) => return a,
+            (None, b) => return b,
+        };
+
+        // the smaller root becomes the first child of the other one
+        let (parent, child) = if self.comparator.compare(&self.node(a).value, &self.node(b).value)
+            == Some(Ordering::Less) { (b, a) } else { (a, b) };
+        let first_child = self.node(parent).child;
+        if let Some(first_child) = first_child {
+            self.node_mut(first_child).prev = Some(child);
+        }
+        let node = self.node_mut(child);
+        node.next = first_child;
+        node.prev = Some(parent);
+        self.node_mut(parent).child = Some(child);
+        Some(parent)
+    }
+
+    // link a list of siblings into a single tree: in pairs from left to right, then from right
+    // to left
+    fn merge_pairs(&mut self, first: Option<usize>) -> Option<usize> {
+        let mut trees = Vec::new();
+        let mut current = first;
+        while let Some(a) = current {
+            let b = self.detach(a);
+            current = match b {
+                Some(b) => {
+                    let next = self.detach(b);
+                    trees.push(self.link(Some(a), Some(b)));
+                    next
+                },
+                None => {
+                    trees.push(Some(a));
+                    None
+                },
+            };
+        }
+        let mut result = None;
+        while let Some(tree) = trees.pop() {
+            result = self.link(tree, result);
+        }
+        result
+    }
+
+    // clear the sibling links of the node `index`, returning its next sibling
+    fn detach(&mut self, index: usize) -> Option<usize> {
+        let node = self.node_mut(index);
+        node.prev = None;
+        node.next.take()
+    }
+
+    // remove the subtree of the node `index` (which is not the root) from its parent
+    fn cut(&mut self, index: usize) {
+        let prev = self.node(index).prev.unwrap();
+        let next = self.detach(index);
+        if self.node(prev).child == Some(index) {
+            self.node_mut(prev).child = next;
+        } else {
+            self.node_mut(prev).next = next;
+        }
+        if let Some(next) = next {
+            self.node_mut(next).prev = Some(prev);
+        }
+    }
+
+    // replace the element of `handle` and repair the heap
+    fn replace(&mut self, handle: Handle, x: T) -> Option<T> {
+        let index = self.index(handle)?;
+        let smaller = self.comparator.compare(&x, &self.node(index).value) == Some(Ordering::Less);
+        let old = core::mem::replace(&mut self.node_mut(index).value, x);
+        if smaller {
+            // the children may now be larger: link them together, then with the rest of the heap
+            let children = self.node_mut(index).child.take();
+            let subtree = self.merge_pairs(children);
+            if self.root == Some(index) {
+                self.root = None;
+            } else {
+                self.cut(index);
+            }
+            let rest = self.link(self.root, subtree);
+            self.root = self.link(rest, Some(index));
+        } else if self.root != Some(index) {
+            // the subtree is still ordered: link it with the root
+            self.cut(index);
+            self.root = self.link(self.root, Some(index));
+        }
+        Some(old)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for PairingHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::MinComparator;
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(7);
+        // distinct elements, so that the popped handle is known
+        let mut counter = 0;
+        let mut distinct = |x: u64| { counter += 1; x * 10_000 + counter };
+        let mut heap = PairingHeap::with_comparator(MinComparator);
+        let mut live: Vec<(Handle, u64)> = Vec::new();
+        for _ in 0..5_000 {
+            match next() % 6 {
+                0 | 1 => {
+                    let x = distinct(next() % 1000);
+                    live.push((heap.insert(x), x));
+                },
+                2 if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let x = distinct(next() % 1000);
+                    assert_eq!(Some(live[i].1), heap.decrease_key(live[i].0, x));
+                    live[i].1 = x;
+                },
+                3 if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let (handle, x) = live.swap_remove(i);
+                    assert_eq!(Some(x), heap.remove(handle));
+                    assert!(!heap.contains(handle));
+                },
+                4 => {
+                    let min = live.iter().enumerate().min_by_key(|&(_, &(_, x))| x).map(|(i, _)| i);
+                    let popped = heap.pop();
+                    assert_eq!(min.map(|i| live.swap_remove(i).1), popped);
+                },
+                _ => {
+                    let mut other = PairingHeap::with_comparator(MinComparator);
+                    let added: Vec<(Handle, u64)> = (0..(next() % 5))
+                        .map(|_| { let x = distinct(next() % 1000); (other.insert(x), x) })
+                        .collect();
+                    let map = heap.meld(other);
+                    live.extend(added.into_iter().map(|(handle, x)| (map.map(handle), x)));
+                },
+            }
+            assert_eq!(live.len(), heap.size());
+            assert_eq!(live.iter().map(|&(_, x)| x).min().as_ref(), heap.peek());
+        }
+        for &(handle, x) in live.iter() {
+            assert_eq!(Some(&x), heap.get(handle));
+        }
+        let mut expected: Vec<u64> = live.iter().map(|&(_, x)| x).collect();
+        expected.sort_unstable();
+        let popped: Vec<u64> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(expected, popped);
+    }
+}
@@ -0,0 +1,488 @@
+//! Parallel algorithms with `rayon`
+//!
+//! Heap construction heapifies the two subtrees of each node in parallel, then sifts the node
+//! down, as in Floyd's algorithm; subtrees below a few thousand elements are heapified
+//! sequentially. The elements of a heap can also be fed to parallel iterators, in the order of
+//! the heap array.
+//!
+//! The parallel k-way merge splits the output at keys sampled from the runs, so that each thread
+//! merges the parts of the runs between two consecutive keys with its own heap.
+//!
+//! The parallel partial sort keeps the indices of the largest elements of each chunk of the slice
+//! in a bounded heap, selects the largest elements among the candidates of all the chunks, then
+//! moves them to the start of the slice and sorts them.
+//!
+//! Only available with the `rayon` feature.
+
+use crate::{BinaryHeap, Compare, FnComparator, MaxComparator, MinComparator};
+use core::cmp::Ordering;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelDrainRange, ParallelIterator};
+use rayon::slice::{ParallelSlice, ParallelSliceMut};
+use std::vec::Vec;
+
+// subtrees with at least this number of levels are heapified in parallel (2^13 elements or more)
+const PARALLEL_LEVELS: u32 = 13;
+
+// pointer to the array of a heap, shared by the threads heapifying disjoint subtrees
+struct SharedArray<T> {
+    ptr: *mut T,
+    len: usize,
+}
+
+// SAFETY: each thread only accesses the elements of its own subtree
+unsafe impl<T: Send> Send for SharedArray<T> {}
+unsafe impl<T: Send> Sync for SharedArray<T> {}
+
+impl<T> SharedArray<T> {
+
+    // whether the element at position `i` is smaller than the one at position `j`
+    //
+    // SAFETY: `i` and `j` must be in bounds, and in the subtree of the calling thread
+    #[inline]
+    unsafe fn less<C: Compare<T>>(&self, comparator: &C, i: usize, j: usize) -> bool {
+        comparator.compare(&*self.ptr.add(i), &*self.ptr.add(j)) == Some(Ordering::Less)
+    }
+
+    // ‘bubble down’ the element at position `pos`
+    //
+    // SAFETY: the subtree of `pos` must only be accessed by the calling thread
+    unsafe fn sift_down<C: Compare<T>>(&self, comparator: &C, pos: usize) {
+        let mut current_pos = pos;
+        loop {
+            let first_child = 2 * current_pos + 1;
+            if first_child >= self.len {
+                break;
+            }
+            let mut largest_child = first_child;
+            if first_child + 1 < self.len && self.less(comparator, first_child, first_child + 1) {
+                largest_child = first_child + 1;
+            }
+            if self.less(comparator, current_pos, largest_child) {
+                core::ptr::swap(self.ptr.add(current_pos), self.ptr.add(largest_child));
+                current_pos = largest_child;
+            } else {
+                break;
+            }
+        }
+    }
+
+    // restore the heap property in the subtree of `pos`, assuming it is in bounds
+    //
+    // SAFETY: the subtree of `pos` must only be accessed by the calling thread
+    unsafe fn heapify<C: Compare<T> + Sync>(&self, comparator: &C, pos: usize)
+        where T: Send
+    {
+        let (left, right) = (2 * pos + 1, 2 * pos + 2);
+        if left >= self.len {
+            return;
+        }
+
+        // number of levels below `pos`
+        let levels = (self.len + 1).ilog2() - (pos + 1).ilog2();
+        if levels >= PARALLEL_LEVELS {
+            rayon::join(
+                || self.heapify(comparator, left),
+                || if right < self.len { self.heapify(comparator, right) },
+            );
+        } else {
+            self.heapify(comparator, left);
+            if right < self.len {
+                self.heapify(comparator, right);
+            }
+        }
+        self.sift_down(comparator, pos);
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Send> BinaryHeap<T> {
+
+    /// Build a `BinaryHeap` from a vector in parallel, reusing its memory
+    ///
+    /// Only available with the `rayon` feature.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ work and $O(\log^2 n)$ span, where $n$ is the number of
+    /// elements.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::par_from_vec((0..100_000).collect());
+    /// assert_eq!(Some(99_999), heap.get_max());
+    /// ```
+    #[inline]
+    pub fn par_from_vec(values: Vec<T>) -> Self {
+        BinaryHeap::par_from_vec_with_comparator(values, MaxComparator)
+    }
+
+    /// Build a `BinaryHeap` from a parallel iterator
+    ///
+    /// Only available with the `rayon` feature.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ work and $O(\log^2 n)$ span to build the heap, where $n$
+    /// is the number of elements, once they are collected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    /// use rayon::prelude::*;
+    ///
+    /// let heap = BinaryHeap::par_from_iter((0..100_000u64).into_par_iter().map(|x| x * x));
+    /// assert_eq!(100_000, heap.size());
+    /// ```
+    #[inline]
+    pub fn par_from_iter<I: IntoParallelIterator<Item = T>>(values: I) -> Self {
+        BinaryHeap::par_from_vec(values.into_par_iter().collect())
+    }
+}
+
+impl<T: Send, C: Compare<T> + Sync> BinaryHeap<T, C> {
+
+    /// Build a `BinaryHeap` ordered by `comparator` from a vector in parallel, reusing its memory
+    ///
+    /// Only available with the `rayon` feature.
+    ///
+    /// Worst-case complexity: $\Theta(n)$ work and $O(\log^2 n)$ span, where $n$ is the number of
+    /// elements.
+    pub fn par_from_vec_with_comparator(mut values: Vec<T>, comparator: C) -> Self {
+        if !values.is_empty() {
+            let array = SharedArray { ptr: values.as_mut_ptr(), len: values.len() };
+            // SAFETY: the vector is borrowed mutably until the end of the block, and the subtree
+            // of the root is the whole array
+            unsafe { array.heapify(&comparator, 0) };
+        }
+        let mut heap = BinaryHeap::with_comparator(comparator);
+        heap.data = values;
+        heap.debug_validate();
+        heap
+    }
+}
+
+impl<T: Send, C, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Remove all the elements of the heap, and return them as a parallel iterator, in the order
+    /// of the heap array (not in sorted order)
+    ///
+    /// The heap is empty afterwards, even if the iterator is not fully consumed.
+    ///
+    /// Only available with the `rayon` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut heap = BinaryHeap::from(vec![3, 1, 4, 1, 5]);
+    /// let total: i32 = heap.par_drain().map(|x| x * x).sum();
+    /// assert_eq!(52, total);
+    /// assert_eq!(0, heap.size());
+    /// ```
+    pub fn par_drain(&mut self) -> rayon::vec::Drain<'_, T> {
+        self.data.par_drain(..)
+    }
+}
+
+/// Consume the heap into a parallel iterator over its elements, in the order of the heap array
+/// (not in sorted order)
+///
+/// Only available with the `rayon` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+/// use rayon::prelude::*;
+///
+/// let heap = BinaryHeap::from(vec![3, 1, 4, 1, 5]);
+/// let mut squares: Vec<i32> = heap.into_par_iter().map(|x| x * x).collect();
+/// squares.sort_unstable();
+/// assert_eq!(vec![1, 1, 9, 16, 25], squares);
+/// ```
+impl<T: Send, C, const D: usize> IntoParallelIterator for BinaryHeap<T, C, D> {
+    type Item = T;
+    type Iter = rayon::vec::IntoIter<T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.data.into_par_iter()
+    }
+}
+
+/// Parallel iterator over references to the elements of the heap, in the order of the heap array
+/// (not in sorted order), also available as `par_iter`
+///
+/// Only available with the `rayon` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::BinaryHeap;
+/// use rayon::prelude::*;
+///
+/// let heap = BinaryHeap::from(vec![3, 1, 4, 1, 5]);
+/// assert_eq!(2, heap.par_iter().filter(|&&x| x == 1).count());
+/// ```
+impl<'a, T: Sync, C, const D: usize> IntoParallelIterator for &'a BinaryHeap<T, C, D> {
+    type Item = &'a T;
+    type Iter = rayon::slice::Iter<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.data.as_slice().into_par_iter()
+    }
+}
+
+// number of partitions per thread of the parallel merge, and of samples per partition and run
+const PARTITIONS_PER_THREAD: usize = 4;
+const SAMPLES_PER_PARTITION: usize = 4;
+
+/// Merge runs sorted in non-decreasing order into a vector sorted in non-decreasing order, in
+/// parallel
+///
+/// The output is split into partitions at keys sampled from the runs, a few partitions per
+/// thread, and each partition is merged by a heap holding the next element of each run. Equal
+/// elements keep the order of the runs. Partitions may be unbalanced when a key repeats many
+/// times, since all the elements equal to a splitting key go to the same partition.
+///
+/// Only available with the `rayon` feature.
+///
+/// Worst-case complexity: $\Theta(n \log k)$ work, where $n$ is the total number of elements
+/// and $k$ the number of runs, and $O((n / p + k) \log k)$ span with $p$ threads when the
+/// partitions are balanced.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::par_kmerge;
+///
+/// let runs: Vec<Vec<u32>> = (0..100).map(|i| (0..1000).map(|j| 100 * j + i).collect()).collect();
+/// let runs: Vec<&[u32]> = runs.iter().map(Vec::as_slice).collect();
+/// assert_eq!((0..100_000).collect::<Vec<u32>>(), par_kmerge(&runs));
+/// ```
+pub fn par_kmerge<T: Ord + Clone + Send + Sync>(runs: &[&[T]]) -> Vec<T> {
+    let total: usize = runs.iter().map(|run| run.len()).sum();
+    let partitions = (rayon::current_num_threads() * PARTITIONS_PER_THREAD).min(total.div_ceil(1024)).max(1);
+
+    // splitting keys: quantiles of evenly spaced samples of the runs
+    let mut samples: Vec<&T> = runs.iter().flat_map(|run| {
+        let count = (partitions * SAMPLES_PER_PARTITION).min(run.len());
+        (0..count).map(move |i| &run[i * run.len() / count])
+    }).collect();
+    samples.sort_unstable();
+    let splitters: Vec<&T> = (1..partitions).map(|i| samples[i * samples.len() / partitions]).collect();
+
+    // bounds of each partition in each run: the elements from the previous key (included) to
+    // the next one (excluded)
+    let bounds: Vec<Vec<usize>> = runs.iter().map(|run| {
+        core::iter::once(0)
+            .chain(splitters.iter().map(|key| run.partition_point(|x| x < key)))
+            .chain(core::iter::once(run.len()))
+            .collect()
+    }).collect();
+
+    let parts: Vec<Vec<T>> = (0..partitions).into_par_iter().map(|part| {
+        let slices: Vec<&[T]> = runs.iter().zip(bounds.iter())
+            .map(|(run, bounds)| &run[bounds[part]..bounds[part + 1]])
+            .collect();
+        merge_slices(&slices)
+    }).collect();
+    parts.concat()
+}
+
+// merge sorted slices with a heap of their next elements, keyed by element then slice index
+fn merge_slices<T: Ord + Clone>(slices: &[&[T]]) -> Vec<T> {
+    let mut merged = Vec::with_capacity(slices.iter().map(|slice| slice.len()).sum());
+    let mut positions = vec![0; slices.len()];
+    let mut heap = BinaryHeap::with_comparator(MinComparator);
+    for (i, slice) in slices.iter().enumerate() {
+        if let Some(x) = slice.first() {
+            heap.insert((x, i));
+        }
+    }
+    while let Some((x, i)) = heap.pop() {
+        merged.push(x.clone());
+        positions[i] += 1;
+        if let Some(next) = slices[i].get(positions[i]) {
+            heap.insert((next, i));
+        }
+    }
+    merged
+}
+
+/// Move the `k` largest elements of a slice to its start, in non-increasing order, in parallel
+///
+/// The order of the other elements is unspecified. Incomparable elements are treated as equal.
+/// If `k` is at least the length of the slice, the whole slice is sorted.
+///
+/// Only available with the `rayon` feature.
+///
+/// Worst-case complexity: $O(n \log k + p k \log(p k))$ comparisons, where $n$ is the length of
+/// the slice and $p$ the number of threads, and $O((n / p) \log k + p k \log(p k))$ span.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::par_partial_sort;
+///
+/// // a permutation of the integers below one million
+/// let mut scores: Vec<u64> = (0..1_000_000).map(|i| i * 7919 % 1_000_000).collect();
+/// par_partial_sort(&mut scores, 3);
+/// assert_eq!([999_999, 999_998, 999_997], scores[..3]);
+/// ```
+#[inline]
+pub fn par_partial_sort<T: PartialOrd + Send + Sync>(values: &mut [T], k: usize) {
+    par_partial_sort_by(values, k, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+}
+
+/// Move the `k` largest elements of a slice for the order defined by `compare` to its start, in
+/// non-increasing order, in parallel
+///
+/// The order of the other elements is unspecified. If `k` is at least the length of the slice,
+/// the whole slice is sorted.
+///
+/// Only available with the `rayon` feature.
+///
+/// Worst-case complexity: $O(n \log k + p k \log(p k))$ calls to `compare`, where $n$ is the
+/// length of the slice and $p$ the number of threads, and $O((n / p) \log k + p k \log(p k))$
+/// span.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::par_partial_sort_by;
+///
+/// // the two shortest words
+/// let mut words = vec!["heap", "a", "leonardo", "of", "tree"];
+/// par_partial_sort_by(&mut words, 2, |a, b| b.len().cmp(&a.len()));
+/// assert_eq!(["a", "of"], words[..2]);
+/// ```
+pub fn par_partial_sort_by<T, F>(values: &mut [T], k: usize, compare: F)
+    where T: Send + Sync,
+          F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if k >= values.len() {
+        values.par_sort_unstable_by(|a, b| compare(b, a));
+        return;
+    }
+    if k == 0 {
+        return;
+    }
+
+    // indices of the `k` largest elements of each chunk, each chunk keeping them in a heap whose
+    // root is the smallest of them
+    let chunk = values.len().div_ceil(rayon::current_num_threads()).max(k);
+    let mut candidates: Vec<usize> = values.par_chunks(chunk).enumerate().flat_map_iter(|(c, elements)| {
+        let offset = c * chunk;
+        let mut heap = BinaryHeap::with_comparator(FnComparator(|&i: &usize, &j: &usize| compare(&values[j], &values[i])));
+        for i in offset..offset + elements.len() {
+            if heap.size() < k {
+                heap.insert(i);
+            } else if compare(&values[i], &values[heap.data[0]]) == Ordering::Greater {
+                heap.data[0] = i;
+                heap.sift_down(0, k);
+            }
+        }
+        heap.data
+    }).collect();
+
+    // the `k` largest candidates, in increasing order of their indices
+    candidates.par_sort_unstable_by(|&i, &j| compare(&values[j], &values[i]));
+    candidates.truncate(k);
+    candidates.sort_unstable();
+
+    // swap the selected elements past the first `k` positions with unselected ones among them
+    let mut selected = candidates.iter().copied().peekable();
+    let outside = candidates.iter().copied().filter(|&i| i >= k);
+    let holes = (0..k).filter(|&i| {
+        let is_selected = selected.peek() == Some(&i);
+        if is_selected {
+            selected.next();
+        }
+        !is_selected
+    });
+    for (hole, i) in holes.zip(outside) {
+        values.swap(hole, i);
+    }
+
+    values[..k].par_sort_unstable_by(|a, b| compare(b, a));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use rayon::iter::IntoParallelRefIterator;
+
+    // size of the largest inputs, smaller when every operation checks the whole heap
+    #[cfg(not(feature = "debug-validate"))]
+    const LARGE: usize = 100_000;
+    #[cfg(feature = "debug-validate")]
+    const LARGE: usize = 10_000;
+
+    #[test]
+    fn par_from_vec_1() {
+        let mut next = lcg(12345);
+        for size in [0, 1, 2, 3, 100, 8191, 8192, LARGE] {
+            let values: Vec<u64> = (0..size).map(|_| next()).collect();
+            let heap = BinaryHeap::par_from_vec(values.clone());
+            assert!(heap.is_valid_heap());
+            let mut expected = values.clone();
+            expected.sort_unstable_by(|a, b| b.cmp(a));
+            assert_eq!(expected, heap.to_vec());
+
+            let heap = BinaryHeap::par_from_vec_with_comparator(values, MinComparator);
+            assert!(heap.is_valid_heap());
+            expected.reverse();
+            assert_eq!(expected, heap.to_vec());
+        }
+    }
+
+    #[test]
+    fn parallel_iterators() {
+        let mut heap = BinaryHeap::par_from_iter(0..10_000u64);
+        assert_eq!(10_000 * 9_999 / 2, heap.par_iter().sum::<u64>());
+        let mut drained: Vec<u64> = heap.par_drain().filter(|x| x % 2 == 0).collect();
+        assert_eq!(0, heap.size());
+        drained.sort_unstable();
+        assert_eq!((0..10_000).step_by(2).collect::<Vec<_>>(), drained);
+
+        heap.insert(7);
+        assert_eq!(vec![7], heap.into_par_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn par_kmerge_1() {
+        let mut next = lcg(7);
+
+        // runs of various lengths, with many duplicates, and a few empty runs
+        let runs: Vec<Vec<(u64, usize)>> = (0..300).map(|i| {
+            let mut run: Vec<u64> = (0..next() % 2000).map(|_| next() % 500).collect();
+            run.sort_unstable();
+            run.into_iter().map(|x| (x, i)).collect()
+        }).collect();
+        let slices: Vec<&[(u64, usize)]> = runs.iter().map(Vec::as_slice).collect();
+        let mut expected = runs.concat();
+        expected.sort();
+        assert_eq!(expected, par_kmerge(&slices));
+        assert_eq!(Vec::<u8>::new(), par_kmerge::<u8>(&[]));
+    }
+
+    #[test]
+    fn par_partial_sort_1() {
+        let mut next = lcg(12345);
+        let values: Vec<u64> = (0..2 * LARGE).map(|_| next() % 50_000).collect();
+        let mut sorted = values.clone();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        for k in [0, 1, 10, 1000, 2 * LARGE - 1, 2 * LARGE, 3 * LARGE] {
+            let mut partial = values.clone();
+            par_partial_sort(&mut partial, k);
+            let k = k.min(values.len());
+            assert_eq!(sorted[..k], partial[..k]);
+            partial.sort_unstable();
+            let mut all = values.clone();
+            all.sort_unstable();
+            assert_eq!(all, partial);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Persistent binary heap
+//!
+//! The heap is a leftist tree (see `LeftistHeap`) whose nodes are immutable and shared through
+//! `Arc`s. Melding two trees copies the nodes on their rightmost paths, which have logarithmic
+//! lengths, and shares all the other ones, so that each operation builds a new version of the
+//! heap in $O(\log n)$ time and space, leaving the old version unchanged.
+
+use crate::{Compare, MaxComparator};
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use core::cmp::Ordering;
+
+// node of the tree
+struct Node<T> {
+    value: T,
+    rank: usize,    // number of nodes on the rightmost path from this node
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+type Tree<T> = Option<Arc<Node<T>>>;
+
+// rank of a tree (zero if it is empty)
+#[inline]
+fn rank<T>(tree: &Tree<T>) -> usize {
+    tree.as_ref().map_or(0, |node| node.rank)
+}
+
+/// An immutable heap, whose operations return new versions sharing structure with the old ones
+///
+/// `insert`, `pop`, and `meld` take $O(\log n)$ time and build $O(\log n)$ new nodes, sharing
+/// all the other ones with the original heap, which remains valid and unchanged; cloning a heap
+/// takes $\Theta(1)$ time. This makes it cheap to keep snapshots of a priority queue, for
+/// instance across transaction boundaries. As the nodes on the modified paths are copied, `T`
+/// must implement `Clone`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::PersistentHeap;
+///
+/// let empty = PersistentHeap::new();
+/// let v1 = empty.insert(3).insert(8);
+/// let v2 = v1.insert(5);
+/// let (max, v3) = v2.pop().unwrap();
+///
+/// assert_eq!(8, max);
+/// assert_eq!(vec![5, 3], v3.to_vec());
+///
+/// // the older versions are unchanged
+/// assert_eq!(vec![8, 3], v1.to_vec());
+/// assert_eq!(vec![8, 5, 3], v2.to_vec());
+/// assert_eq!(0, empty.size());
+/// ```
+pub struct PersistentHeap<T, C = MaxComparator> {
+    root: Tree<T>,
+    size: usize,    // number of elements
+    comparator: C,  // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd + Clone> PersistentHeap<T> {
+
+    /// Create a new empty `PersistentHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        PersistentHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone> PersistentHeap<T, C> {
+
+    /// Create a new empty `PersistentHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        PersistentHeap { root: None, size: 0, comparator }
+    }
+
+    /// Build a `PersistentHeap` ordered by `comparator` from a vector
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let size = values.len();
+        let mut heap = PersistentHeap::with_comparator(comparator);
+        let mut trees: VecDeque<Tree<T>> = values.into_iter()
+            .map(|value| Some(Arc::new(Node { value, rank: 1, left: None, right: None })))
+            .collect();
+        while trees.len() > 1 {
+            let a = trees.pop_front().unwrap();
+            let b = trees.pop_front().unwrap();
+            trees.push_back(heap.meld_trees(a.as_ref(), b.as_ref()));
+        }
+        heap.root = trees.pop_front().flatten();
+        heap.size = size;
+        heap
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
+    /// Return a new version of the heap with `x` inserted
+    ///
+    /// Worst-case complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&self, x: T) -> Self {
+        let node = Arc::new(Node { value: x, rank: 1, left: None, right: None });
+        self.version(self.meld_trees(self.root.as_ref(), Some(&node)), self.size + 1)
+    }
+
+    /// Return the root element and a new version of the heap without it (or `None` if the heap
+    /// is empty)
+    ///
+    /// Worst-case complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&self) -> Option<(T, Self)> {
+        let root = self.root.as_ref()?;
+        let rest = self.meld_trees(root.left.as_ref(), root.right.as_ref());
+        Some((root.value.clone(), self.version(rest, self.size - 1)))
+    }
+
+    /// Return a new version of the heap with all the elements of `other` added
+    ///
+    /// Worst-case complexity: $O(\log n + \log m)$, where $n$ and $m$ are the numbers of elements
+    /// in the two heaps.
+    pub fn meld(&self, other: &PersistentHeap<T, C>) -> Self {
+        let root = self.meld_trees(self.root.as_ref(), other.root.as_ref());
+        self.version(root, self.size + other.size)
+    }
+
+    /// Return a vector of all the elements of the heap in non-increasing order
+    ///
+    /// Worst-case complexity: $O(n \log n)$, where $n$ is the number of elements in the heap.
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut values = Vec::with_capacity(self.size);
+        let mut heap = self.clone();
+        while let Some((x, rest)) = heap.pop() {
+            values.push(x);
+            heap = rest;
+        }
+        values
+    }
+
+    // heap with the same comparator and the tree `root`
+    #[inline]
+    fn version(&self, root: Tree<T>, size: usize) -> Self {
+        PersistentHeap { root, size, comparator: self.comparator.clone() }
+    }
+
+    // meld two trees along their rightmost paths, copying the nodes on these paths
+    fn meld_trees(&self, a: Option<&Arc<Node<T>>>, b: Option<&Arc<Node<T>>>) -> Tree<T> {
+        let (a, b) = match (a, b) {
+            (Some(a), Some(b)) => (a, b),
+            (a, None) => return a.cloned(),
+            (None, b) => return b.cloned(),
+        };
+        let (a, b) = if self.comparator.compare(&a.value, &b.value) == Some(Ordering::Less) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let mut left = a.left.clone();
+        let mut right = self.meld_trees(a.right.as_ref(), Some(b));
+        if rank(&left) < rank(&right) {
+            core::mem::swap(&mut left, &mut right);
+        }
+        Some(Arc::new(Node { value: a.value.clone(), rank: rank(&right) + 1, left, right }))
+    }
+}
+
+impl<T, C> PersistentHeap<T, C> {
+
+    // references to all the elements, in no particular order
+    fn values(&self) -> Vec<&T> {
+        let mut values = Vec::with_capacity(self.size);
+        let mut stack: Vec<&Node<T>> = self.root.iter().map(|node| &**node).collect();
+        while let Some(node) = stack.pop() {
+            values.push(&node.value);
+            stack.extend(node.left.iter().chain(node.right.iter()).map(|node| &**node));
+        }
+        values
+    }
+}
+
+impl<T, C> Drop for PersistentHeap<T, C> {
+
+    // drop the nodes owned by this version only, one by one, as the recursive drop of a long
+    // leftmost path could overflow the stack
+    fn drop(&mut self) {
+        let mut stack: Vec<Arc<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if let Ok(mut node) = Arc::try_unwrap(node) {
+                stack.extend(node.left.take());
+                stack.extend(node.right.take());
+            }
+        }
+    }
+}
+
+impl<T, C: Clone> Clone for PersistentHeap<T, C> {
+
+    /// Clone the heap, sharing all its nodes
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    fn clone(&self) -> Self {
+        PersistentHeap { root: self.root.clone(), size: self.size, comparator: self.comparator.clone() }
+    }
+}
+
+impl<T: core::fmt::Debug, C> core::fmt::Debug for PersistentHeap<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.values()).finish()
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Clone> From<Vec<T>> for PersistentHeap<T> {
+
+    /// Build a `PersistentHeap` from a vector
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        PersistentHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone + Default> core::default::Default for PersistentHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+
+    #[test]
+    fn versions() {
+        let mut next = lcg(12345);
+
+        // each operation is applied to a random earlier version
+        let mut versions = vec![(PersistentHeap::new(), BinaryHeap::new())];
+        for _ in 0..2000 {
+            let (heap, reference) = &versions[(next() as usize) % versions.len()];
+            let mut reference = reference.clone();
+            let heap = match next() % 4 {
+                0 => match heap.pop() {
+                    Some((x, rest)) => {
+                        assert_eq!(reference.pop(), Some(x));
+                        rest
+                    },
+                    None => heap.clone(),
+                },
+                1 => {
+                    let values: Vec<u64> = (0..(next() % 10)).map(|_| next() % 1000).collect();
+                    for &x in values.iter() {
+                        reference.insert(x);
+                    }
+                    heap.meld(&PersistentHeap::from(values))
+                },
+                _ => {
+                    let x = next() % 1000;
+                    reference.insert(x);
+                    heap.insert(x)
+                },
+            };
+            assert_eq!(reference.size(), heap.size());
+            assert_eq!(reference.get_max().as_ref(), heap.peek());
+            versions.push((heap, reference));
+        }
+        for (heap, reference) in versions {
+            assert_eq!(reference.to_vec(), heap.to_vec());
+        }
+    }
+
+    #[test]
+    fn long_left_path() {
+        // each new element becomes the root, the old tree being its left child
+        let mut heap = PersistentHeap::new();
+        let mut snapshots = Vec::new();
+        for i in 0..1_000_000 {
+            heap = heap.insert(i);
+            if i % 100_000 == 0 {
+                snapshots.push(heap.clone());
+            }
+        }
+        drop(heap);
+        assert_eq!(Some(&900_000), snapshots.last().unwrap().peek());
+    }
+}
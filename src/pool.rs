@@ -0,0 +1,215 @@
+//! Thread pool running its tasks by priority
+//!
+//! The pending tasks are kept in a `ShardedHeap`, from which the worker threads pop with
+//! `pop_wait`, sleeping while there is no task. Shutting down inserts one stop marker per worker,
+//! ordered below all the tasks, so that the workers run the pending tasks before stopping.
+//!
+//! Only available with the `std` feature.
+
+use crate::ShardedHeap;
+use core::cmp::Ordering;
+use core::sync::atomic::{self, AtomicU64};
+use std::boxed::Box;
+use std::sync::{mpsc, Arc};
+use std::vec::Vec;
+
+// a task, or the marker stopping a worker
+enum Task<P> {
+    Run { priority: P, seq: u64, run: Box<dyn FnOnce() + Send> },
+    Stop,
+}
+
+// tasks come out by priority, then in the order they were spawned, and the stop markers last
+impl<P: PartialOrd> PartialEq for Task<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<P: PartialOrd> PartialOrd for Task<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Task::Run { priority: p, seq: s, .. }, Task::Run { priority: q, seq: t, .. }) => {
+                match p.partial_cmp(q)? {
+                    Ordering::Equal => Some(t.cmp(s)),
+                    ordering => Some(ordering),
+                }
+            },
+            (Task::Run { .. }, Task::Stop) => Some(Ordering::Greater),
+            (Task::Stop, Task::Run { .. }) => Some(Ordering::Less),
+            (Task::Stop, Task::Stop) => Some(Ordering::Equal),
+        }
+    }
+}
+
+/// A pool of threads running the tasks of highest priority first
+///
+/// Tasks with equal priorities run in the order they were spawned. A task which panics does not
+/// stop its worker: the panic is returned by `JoinHandle::join`. Dropping the pool, or calling
+/// `shutdown`, runs the pending tasks, then waits for the worker threads to stop.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::pool::PriorityThreadPool;
+///
+/// let pool = PriorityThreadPool::new(4);
+/// let urgent = pool.spawn_with_priority(10, || 6 * 7);
+/// let background = pool.spawn_with_priority(0, || "done");
+/// assert_eq!(42, urgent.join().unwrap());
+/// assert_eq!("done", background.join().unwrap());
+/// pool.shutdown();
+/// ```
+pub struct PriorityThreadPool<P: PartialOrd = i64> {
+    tasks: Arc<ShardedHeap<Task<P>>>,       // pending tasks
+    workers: Vec<std::thread::JoinHandle<()>>,
+    next: AtomicU64,                        // sequence number of the next task
+}
+
+impl<P: PartialOrd + Send + 'static> PriorityThreadPool<P> {
+
+    /// Create a pool of `threads` worker threads
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero, or if a thread can not be spawned.
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "a thread pool needs at least one thread");
+        let tasks = Arc::new(ShardedHeap::new(threads));
+        let workers = (0..threads).map(|i| {
+            let tasks = tasks.clone();
+            std::thread::Builder::new()
+                .name(std::format!("priority-pool-{i}"))
+                .spawn(move || {
+                    while let Task::Run { run, .. } = tasks.pop_wait() {
+                        run();
+                    }
+                })
+                .expect("failed to spawn a worker thread")
+        }).collect();
+        PriorityThreadPool { tasks, workers, next: AtomicU64::new(0) }
+    }
+
+    /// Get the number of worker threads
+    #[inline]
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Get the number of tasks waiting for a worker
+    ///
+    /// The result may be out of date as soon as it is returned.
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.tasks.size()
+    }
+
+    /// Spawn a task with priority `priority`, and return a handle to its result
+    ///
+    /// Worst-case complexity: $\Theta(s + \log n)$, where $s$ is the number of threads and $n$
+    /// the number of pending tasks, without contention.
+    pub fn spawn_with_priority<F, R>(&self, priority: P, f: F) -> JoinHandle<R>
+        where F: FnOnce() -> R + Send + 'static,
+              R: Send + 'static,
+    {
+        let (sender, result) = mpsc::sync_channel(1);
+        let run = Box::new(move || {
+            // the handle may have been dropped, in which case the result is discarded
+            let _ = sender.send(std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)));
+        });
+        let seq = self.next.fetch_add(1, atomic::Ordering::Relaxed);
+        self.tasks.insert(Task::Run { priority, seq, run });
+        JoinHandle { result }
+    }
+
+    /// Run the pending tasks, then stop the worker threads and wait for them
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+}
+
+impl<P: PartialOrd> PriorityThreadPool<P> {
+
+    // stop the workers once the pending tasks have run, and join them
+    fn stop(&mut self) {
+        for _ in 0..self.workers.len() {
+            self.tasks.insert(Task::Stop);
+        }
+        for worker in self.workers.drain(..) {
+            // the tasks catch their panics, so a worker can not panic
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<P: PartialOrd> Drop for PriorityThreadPool<P> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl<P: PartialOrd> core::fmt::Debug for PriorityThreadPool<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PriorityThreadPool")
+            .field("threads", &self.workers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Handle to the result of a task spawned on a `PriorityThreadPool`
+#[derive(Debug)]
+pub struct JoinHandle<R> {
+    result: mpsc::Receiver<std::thread::Result<R>>,
+}
+
+impl<R> JoinHandle<R> {
+
+    /// Wait for the task to run, and return its result, or the payload of its panic
+    pub fn join(self) -> std::thread::Result<R> {
+        // the pool runs all its tasks before its workers stop
+        self.result.recv().expect("the task was dropped without running")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn priority_order() {
+        // a single worker, blocked until all the tasks are spawned
+        let pool = PriorityThreadPool::new(1);
+        let (start, blocked) = mpsc::channel::<()>();
+        let gate = pool.spawn_with_priority(100, move || blocked.recv().unwrap());
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let handles: Vec<_> = [(1, 'a'), (5, 'b'), (3, 'c'), (5, 'd')].into_iter().map(|(priority, name)| {
+            let order = order.clone();
+            pool.spawn_with_priority(priority, move || order.lock().unwrap().push(name))
+        }).collect();
+        start.send(()).unwrap();
+        gate.join().unwrap();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(vec!['b', 'd', 'c', 'a'], *order.lock().unwrap());
+    }
+
+    #[test]
+    fn panics_and_shutdown() {
+        let pool = PriorityThreadPool::new(2);
+        let failed = pool.spawn_with_priority(0, || panic!("task failed"));
+        assert!(failed.join().is_err());
+
+        // the pending tasks run before the workers stop
+        let count = Arc::new(AtomicU64::new(0));
+        for priority in 0..100 {
+            let count = count.clone();
+            pool.spawn_with_priority(priority, move || count.fetch_add(1, atomic::Ordering::Relaxed));
+        }
+        pool.shutdown();
+        assert_eq!(100, count.load(atomic::Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,271 @@
+//! Post-order heap
+//!
+//! A post-order heap is a list of perfect binary heaps, stored one after the other in a single
+//! array, each in post-order (the children before their parent, so that the root of each tree
+//! is its last element). The sizes of the trees follow the skew binary representation of the
+//! number of elements: they do not increase from left to right, and only the last two trees may
+//! have the same height. Inserting an element either appends a tree of height 0 or, if the last
+//! two trees have the same height, makes it the root of these two trees and sifts it down; the
+//! total work of $n$ insertions is $O(n)$, as for Floyd's construction. (Harvey and Zatloukal,
+//! *The post-order heap*, 2004.)
+
+use crate::{Compare, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A heap with constant amortized insertions, stored in a single array
+///
+/// `PostOrderHeap` provides the same core functions as `BinaryHeap` (`insert`, `pop`,
+/// `get_max`, `size`, `to_vec`, `with_comparator`, `from_vec_with_comparator`, `From<Vec<T>>`,
+/// and `Iterator`), so it can replace it in insert-heavy workloads: `insert` takes $\Theta(1)$
+/// amortized time, and `peek` and `pop` $O(\log n)$ time.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::PostOrderHeap;
+///
+/// let mut heap = PostOrderHeap::new();
+/// for x in [4, 9, 2, 7, 5] {
+///     heap.insert(x);
+/// }
+///
+/// assert_eq!(Some(&9), heap.peek());
+/// assert_eq!(Some(9), heap.pop());
+/// assert_eq!(vec![7, 5, 4, 2], heap.to_vec());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PostOrderHeap<T, C = MaxComparator> {
+    data: Vec<T>,           // trees in post-order, one after the other
+    heights: Vec<u32>,      // heights of the trees, from left to right
+    comparator: C,          // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> PostOrderHeap<T> {
+
+    /// Create a new empty `PostOrderHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        PostOrderHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> PostOrderHeap<T, C> {
+
+    /// Create a new empty `PostOrderHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        PostOrderHeap { data: Vec::new(), heights: Vec::new(), comparator }
+    }
+
+    /// Build a `PostOrderHeap` ordered by `comparator` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = PostOrderHeap::with_comparator(comparator);
+        heap.data = values;
+        for pos in 0..heap.data.len() {
+            heap.attach(pos);
+        }
+        heap
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Amortized complexity: $\Theta(1)$.
+    ///
+    /// Worst-case complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        self.data.push(x);
+        self.attach(self.data.len() - 1);
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.max_tree().map(|(pos, _)| &self.data[pos])
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let (pos, height) = self.max_tree()?;
+
+        // replace the largest root by the root of the last tree, whose subtrees become trees
+        let last = self.data.len() - 1;
+        self.data.swap(pos, last);
+        let root = self.data.pop();
+        let last_height = self.heights.pop().unwrap();
+        if last_height > 0 {
+            self.heights.push(last_height - 1);
+            self.heights.push(last_height - 1);
+        }
+        if pos < last {
+            self.sift_down(pos, height);
+        }
+        root
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Worst-case complexity: $O(n \log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // add the element at position `pos`, following all the trees, to the heap
+    fn attach(&mut self, pos: usize) {
+        match self.heights[..] {
+            [.., a, b] if a == b => {
+                self.heights.truncate(self.heights.len() - 2);
+                self.heights.push(a + 1);
+                self.sift_down(pos, a + 1);
+            },
+            _ => self.heights.push(0),
+        }
+    }
+
+    // position of the root and height of the tree with the largest root
+    fn max_tree(&self) -> Option<(usize, u32)> {
+        let mut max: Option<(usize, u32)> = None;
+        let mut end = 0;
+        for &height in self.heights.iter() {
+            end += (1 << (height + 1)) - 1;
+            let pos = end - 1;
+            if max.is_none_or(|(max, _)| self.less(max, pos)) {
+                max = Some((pos, height));
+            }
+        }
+        max
+    }
+
+    // whether the element at position `i` is smaller than the one at position `j`
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        self.comparator.compare(&self.data[i], &self.data[j]) == Some(Ordering::Less)
+    }
+
+    // ‘bubble down’ the element at position `pos`, the root of a subtree of height `height`
+    fn sift_down(&mut self, mut pos: usize, mut height: u32) {
+        while height > 0 {
+            // the right subtree ends just before its parent, the left one just before it
+            let right = pos - 1;
+            let left = pos - (1 << height);
+            let largest = if self.less(left, right) { right } else { left };
+            if !self.less(pos, largest) {
+                break;
+            }
+            self.data.swap(pos, largest);
+            pos = largest;
+            height -= 1;
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T>> PostOrderHeap<T, C> {
+
+    /// Return a copy of the maximum element if the heap is not empty
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn get_max(&self) -> Option<T> {
+        self.peek().cloned()
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for PostOrderHeap<T> {
+
+    /// Build a `PostOrderHeap` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        PostOrderHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for PostOrderHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for PostOrderHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size(), Some(self.size()))
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for PostOrderHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for PostOrderHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // natural order, counting the comparisons
+    #[derive(Clone, Default)]
+    struct CountingComparator(Rc<Cell<usize>>);
+
+    impl Compare<u64> for CountingComparator {
+        fn compare(&self, a: &u64, b: &u64) -> Option<Ordering> {
+            self.0.set(self.0.get() + 1);
+            a.partial_cmp(b)
+        }
+    }
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let values: Vec<u64> = (0..500).map(|_| next() % 1000).collect();
+        let mut heap = PostOrderHeap::from(values.clone());
+        let mut reference = BinaryHeap::from(values);
+        for _ in 0..5000 {
+            if next().is_multiple_of(2) {
+                assert_eq!(reference.pop(), heap.pop());
+            } else {
+                let x = next() % 1000;
+                heap.insert(x);
+                reference.insert(x);
+            }
+            assert_eq!(reference.size(), heap.size());
+            assert_eq!(reference.get_max(), heap.get_max());
+        }
+        assert_eq!(reference.to_vec(), heap.to_vec());
+    }
+
+    #[test]
+    fn linear_insertions() {
+        // increasing elements, each new root being sifted down to the bottom of its tree
+        let n = 100_000;
+        let count = Rc::new(Cell::new(0));
+        let mut heap = PostOrderHeap::with_comparator(CountingComparator(count.clone()));
+        for x in 0..n {
+            heap.insert(x);
+        }
+        assert!(count.get() <= 2 * n as usize);
+        assert_eq!(Some(n - 1), heap.pop());
+    }
+}
@@ -0,0 +1,143 @@
+//! Priority queue of (priority, value) pairs
+
+use crate::{BinaryHeap, Compare};
+
+// compare (priority, value) pairs by their priorities only
+#[derive(Clone, Copy, Debug, Default)]
+struct PriorityComparator;
+
+impl<P: core::cmp::PartialOrd, V> Compare<(P, V)> for PriorityComparator {
+    #[inline]
+    fn compare(&self, a: &(P, V), b: &(P, V)) -> Option<core::cmp::Ordering> {
+        a.0.partial_cmp(&b.0)
+    }
+}
+
+/// A priority queue of values of type `V` with priorities of type `P`
+///
+/// Only the priorities are compared (so `V` does not need to implement any comparison trait);
+/// `pop` returns a pair with the highest priority. Pairs with equal priorities are returned in an
+/// unspecified order.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::PriorityQueue;
+///
+/// let mut queue = PriorityQueue::new();
+/// queue.insert(2, "Read ‘The Lord of the Rings’");
+/// queue.insert(8, "Proofread the new draft for the paper");
+/// queue.insert(4, "Clean the car");
+///
+/// assert_eq!(Some(&8), queue.peek_priority());
+/// assert_eq!(Some((8, "Proofread the new draft for the paper")), queue.pop());
+/// assert_eq!(2, queue.size());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PriorityQueue<P, V> {
+    heap: BinaryHeap<(P, V), PriorityComparator>,
+}
+
+impl<P: core::cmp::PartialOrd, V> PriorityQueue<P, V> {
+
+    /// Create a new empty `PriorityQueue`
+    #[inline]
+    pub fn new() -> Self {
+        PriorityQueue { heap: BinaryHeap::with_comparator(PriorityComparator) }
+    }
+
+    /// Get the number of (priority, value) pairs in the queue
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert `value` with priority `priority`
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pairs in the queue.
+    #[inline]
+    pub fn insert(&mut self, priority: P, value: V) {
+        self.heap.insert((priority, value));
+    }
+
+    /// Remove and return a pair with the highest priority (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of pairs in the queue.
+    #[inline]
+    pub fn pop(&mut self) -> Option<(P, V)> {
+        self.heap.pop()
+    }
+
+    /// Return a reference to the highest priority (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_priority(&self) -> Option<&P> {
+        self.heap.data.first().map(|(priority, _)| priority)
+    }
+
+    /// Return references to a pair with the highest priority (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<(&P, &V)> {
+        self.heap.data.first().map(|(priority, value)| (priority, value))
+    }
+}
+
+impl<P: core::cmp::PartialOrd, V> core::default::Default for PriorityQueue<P, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pairs are returned from the highest to the lowest priority
+impl<P: core::cmp::PartialOrd, V> Iterator for PriorityQueue<P, V> {
+    type Item = (P, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heap.size_hint()
+    }
+}
+
+impl<P: core::cmp::PartialOrd, V> ExactSizeIterator for PriorityQueue<P, V> {}
+
+impl<P: core::cmp::PartialOrd, V> core::iter::FusedIterator for PriorityQueue<P, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn pop_1() {
+        let mut queue = PriorityQueue::new();
+        assert_eq!(None, queue.pop());
+        assert_eq!(None, queue.peek_priority());
+        for (priority, value) in [(4, 'c'), (2, 'r'), (8, 'p'), (6, 'u'), (7, 'a')] {
+            queue.insert(priority, value);
+        }
+        assert_eq!(Some((&8, &'p')), queue.peek());
+        let values: Vec<char> = queue.map(|(_, value)| value).collect();
+        assert_eq!(vec!['p', 'a', 'u', 'c', 'r'], values);
+    }
+
+    #[test]
+    fn values_not_compared() {
+        // `V` does not implement `PartialOrd`
+        struct Payload(u8);
+        let mut queue = PriorityQueue::new();
+        queue.insert(1.5, Payload(1));
+        queue.insert(-0.5, Payload(2));
+        queue.insert(3.0, Payload(3));
+        assert_eq!(3, queue.pop().unwrap().1 .0);
+        assert_eq!(1, queue.pop().unwrap().1 .0);
+        assert_eq!(1, queue.size());
+    }
+}
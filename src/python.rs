@@ -0,0 +1,150 @@
+//! Python bindings
+//!
+//! The Python class `BinaryHeap` wraps a [`crate::BinaryHeap`] of Python objects. Items are
+//! ordered by their keys, computed when they are inserted by an optional key function (as for
+//! `sorted` or `max`); without key function, items are their own keys. Keys are compared with
+//! the Python comparison operators; if two keys can not be compared (the comparison raises an
+//! exception), they are treated as incomparable.
+//!
+//! To build the Python module, compile the crate as a dynamic library with the `python`
+//! feature, for instance with `cargo rustc --release --features python --crate-type cdylib`,
+//! and rename the library to `binary_heap.so` (`binary_heap.pyd` on Windows):
+//!
+//! ```python
+//! from binary_heap import BinaryHeap
+//!
+//! heap = BinaryHeap(["apple", "fig", "banana"], key=len)
+//! heap.insert("kiwi")
+//! assert heap.pop() == "banana"
+//! assert heap.peek() == "apple"
+//! assert len(heap) == 3
+//! ```
+
+use crate::BinaryHeap;
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+use std::cmp::Ordering;
+
+// item stored in the heap, with its precomputed key
+struct Entry {
+    key: Py<PyAny>,
+    item: Py<PyAny>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Python::attach(|py| self.key.bind(py).compare(other.key.bind(py)).ok())
+    }
+}
+
+/// Max binary heap of Python objects
+#[pyclass(name = "BinaryHeap", module = "binary_heap")]
+pub struct PyBinaryHeap {
+    heap: BinaryHeap<Entry>,
+    key: Option<Py<PyAny>>,
+}
+
+impl PyBinaryHeap {
+
+    // wrap an item with its key
+    fn entry(&self, item: Bound<'_, PyAny>) -> PyResult<Entry> {
+        let key = match &self.key {
+            Some(key) => key.bind(item.py()).call1((&item,))?.unbind(),
+            None => item.clone().unbind(),
+        };
+        Ok(Entry { key, item: item.unbind() })
+    }
+}
+
+#[pymethods]
+impl PyBinaryHeap {
+
+    /// Create a heap containing the elements of `items` (built in linear time), ordered by
+    /// `key(item)` if a key function is given
+    #[new]
+    #[pyo3(signature = (items=None, key=None))]
+    fn new(items: Option<Bound<'_, PyAny>>, key: Option<Py<PyAny>>) -> PyResult<Self> {
+        let mut heap = PyBinaryHeap { heap: BinaryHeap::new(), key };
+        if let Some(items) = items {
+            let entries = items.try_iter()?
+                .map(|item| heap.entry(item?))
+                .collect::<PyResult<Vec<Entry>>>()?;
+            heap.heap = BinaryHeap::from(entries);
+        }
+        Ok(heap)
+    }
+
+    /// Insert an item
+    fn insert(&mut self, item: Bound<'_, PyAny>) -> PyResult<()> {
+        let entry = self.entry(item)?;
+        self.heap.insert(entry);
+        Ok(())
+    }
+
+    /// Remove and return the largest item; raise `IndexError` if the heap is empty
+    fn pop(&mut self) -> PyResult<Py<PyAny>> {
+        self.heap.pop()
+            .map(|entry| entry.item)
+            .ok_or_else(|| PyIndexError::new_err("pop from an empty heap"))
+    }
+
+    /// Return the largest item without removing it; raise `IndexError` if the heap is empty
+    fn peek(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.heap.data.first()
+            .map(|entry| entry.item.clone_ref(py))
+            .ok_or_else(|| PyIndexError::new_err("peek from an empty heap"))
+    }
+
+    fn __len__(&self) -> usize {
+        self.heap.size()
+    }
+}
+
+/// Python module `binary_heap`
+#[pymodule]
+fn binary_heap(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBinaryHeap>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyList;
+
+    #[test]
+    fn key_function() {
+        Python::initialize();
+        Python::attach(|py| {
+            let items = PyList::new(py, ["apple", "fig", "banana"]).unwrap();
+            let len = py.eval(c"len", None, None).unwrap().unbind();
+            let mut heap = PyBinaryHeap::new(Some(items.into_any()), Some(len)).unwrap();
+            heap.insert(pyo3::types::PyString::new(py, "kiwi").into_any()).unwrap();
+            assert_eq!(4, heap.__len__());
+            assert_eq!("banana", heap.peek(py).unwrap().extract::<String>(py).unwrap());
+            assert_eq!("banana", heap.pop().unwrap().extract::<String>(py).unwrap());
+            assert_eq!("apple", heap.pop().unwrap().extract::<String>(py).unwrap());
+            assert_eq!("kiwi", heap.pop().unwrap().extract::<String>(py).unwrap());
+            assert_eq!("fig", heap.pop().unwrap().extract::<String>(py).unwrap());
+            assert!(heap.pop().unwrap_err().is_instance_of::<PyIndexError>(py));
+        });
+    }
+
+    #[test]
+    fn natural_order() {
+        Python::initialize();
+        Python::attach(|py| {
+            let items = PyList::new(py, [3, 1, 4, 1, 5]).unwrap();
+            let mut heap = PyBinaryHeap::new(Some(items.into_any()), None).unwrap();
+            let popped: Vec<i64> = (0..5)
+                .map(|_| heap.pop().unwrap().extract::<i64>(py).unwrap())
+                .collect();
+            assert_eq!(vec![5, 4, 3, 1, 1], popped);
+        });
+    }
+}
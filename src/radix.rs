@@ -0,0 +1,207 @@
+//! Radix heap for monotone unsigned integer keys
+//!
+//! A radix heap is a monotone priority queue: the keys of the inserted elements are never smaller
+//! than the last popped one, as in Dijkstra's algorithm with non-negative edge weights. The
+//! elements are stored in buckets indexed by the position of the highest bit in which their key
+//! differs from the last popped key. When the bucket of the last popped key is empty, the first
+//! non-empty bucket is emptied into lower ones, relative to its smallest key; as an element only
+//! moves to lower buckets, it moves at most once per bit of the keys. (Ahuja, Mehlhorn, Orlin,
+//! and Tarjan, *Faster algorithms for the shortest path problem*, 1990.)
+
+use alloc::vec::Vec;
+
+/// Unsigned integer types usable as keys of a `RadixHeap`
+pub trait RadixKey: Copy + Ord + Default {
+
+    /// Number of bits of the type
+    const BITS: u32;
+
+    /// Index of the bucket of `self` relative to `last`: 0 if they are equal, and otherwise one
+    /// plus the position of the highest bit in which they differ
+    fn bucket(self, last: Self) -> usize;
+}
+
+// `RadixKey` for each unsigned integer type
+macro_rules! impl_radix_key {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BITS: u32 = <$t>::BITS;
+
+                #[inline]
+                fn bucket(self, last: Self) -> usize {
+                    (<$t>::BITS - (self ^ last).leading_zeros()) as usize
+                }
+            }
+        )*
+    }
+}
+
+impl_radix_key!(u8, u16, u32, u64, u128, usize);
+
+/// A monotone min-priority queue of values of type `V` with unsigned integer keys of type `K`
+///
+/// `pop` returns a pair with the smallest key, and the key of each inserted pair must not be
+/// smaller than the last popped one. `insert` takes $\Theta(1)$ time, and `pop` $O(b)$ amortized
+/// time, where $b$ is the number of bits of `K`; no element is compared with another one except
+/// when a bucket is emptied.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::RadixHeap;
+///
+/// // tentative distances of a shortest-path search
+/// let mut frontier = RadixHeap::new();
+/// frontier.insert(0u32, 'a');
+/// let (distance, _) = frontier.pop().unwrap();
+///
+/// // the new distances are not smaller than the last popped one
+/// frontier.insert(distance + 7, 'b');
+/// frontier.insert(distance + 3, 'c');
+///
+/// assert_eq!(Some((3, 'c')), frontier.pop());
+/// assert_eq!(Some((7, 'b')), frontier.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct RadixHeap<K, V> {
+    buckets: Vec<Vec<(K, V)>>,  // pairs by bucket index relative to `last`
+    last: K,                    // last popped key
+    size: usize,                // number of pairs
+}
+
+impl<K: RadixKey, V> RadixHeap<K, V> {
+
+    /// Create a new empty `RadixHeap`
+    #[inline]
+    pub fn new() -> Self {
+        let buckets = (0..=K::BITS).map(|_| Vec::new()).collect();
+        RadixHeap { buckets, last: K::default(), size: 0 }
+    }
+
+    /// Get the number of pairs in the heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Return the last popped key (or the smallest value of `K` if no pair has been popped)
+    ///
+    /// Inserted keys must not be smaller.
+    #[inline]
+    pub fn last_key(&self) -> K {
+        self.last
+    }
+
+    /// Insert `value` with key `key`
+    ///
+    /// Worst-case complexity: $\Theta(1)$ (amortized, as the buckets may grow).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is smaller than the last popped key.
+    pub fn insert(&mut self, key: K, value: V) {
+        assert!(key >= self.last, "key smaller than the last popped key");
+        self.buckets[key.bucket(self.last)].push((key, value));
+        self.size += 1;
+    }
+
+    /// Return the smallest key (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $O(n)$, where $n$ is the number of pairs in the heap; $\Theta(1)$ if
+    /// a pair has the last popped key.
+    pub fn peek_key(&self) -> Option<K> {
+        let bucket = self.buckets.iter().find(|bucket| !bucket.is_empty())?;
+        bucket.iter().map(|&(key, _)| key).min()
+    }
+
+    /// Remove and return a pair with the smallest key (or `None` if the heap is empty)
+    ///
+    /// Pairs with equal keys are returned in an unspecified order.
+    ///
+    /// Amortized complexity: $O(b)$, where $b$ is the number of bits of `K`.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        if self.buckets[0].is_empty() {
+            // empty the first non-empty bucket into lower buckets, relative to its smallest key
+            let index = self.buckets.iter().position(|bucket| !bucket.is_empty())?;
+            let bucket = core::mem::take(&mut self.buckets[index]);
+            self.last = bucket.iter().map(|&(key, _)| key).min().unwrap();
+            for (key, value) in bucket {
+                self.buckets[key.bucket(self.last)].push((key, value));
+            }
+        }
+        self.size -= 1;
+        self.buckets[0].pop()
+    }
+}
+
+impl<K: RadixKey, V> core::default::Default for RadixHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: RadixKey, V> Iterator for RadixHeap<K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<K: RadixKey, V> ExactSizeIterator for RadixHeap<K, V> {}
+
+impl<K: RadixKey, V> core::iter::FusedIterator for RadixHeap<K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::{BinaryHeap, MinComparator};
+
+    #[test]
+    fn monotone_operations() {
+        let mut next = lcg(12345);
+        let mut heap = RadixHeap::new();
+        let mut reference = BinaryHeap::with_comparator(MinComparator);
+        for i in 0..20_000u32 {
+            if next().is_multiple_of(3) {
+                let popped = heap.pop();
+                assert_eq!(reference.pop(), popped.map(|(key, _)| key));
+            } else {
+                let key = heap.last_key() + (next() % 1000) as u32;
+                heap.insert(key, i);
+                reference.insert(key);
+            }
+            assert_eq!(reference.size(), heap.size());
+            assert_eq!(reference.get_max(), heap.peek_key());
+        }
+        let keys: Vec<u32> = heap.map(|(key, _)| key).collect();
+        assert_eq!(reference.to_vec(), keys);
+    }
+
+    #[test]
+    fn extreme_keys() {
+        let mut heap = RadixHeap::new();
+        for key in [u64::MAX, 0, 1 << 63, u64::MAX, 1] {
+            heap.insert(key, ());
+        }
+        let keys: Vec<u64> = heap.map(|(key, _)| key).collect();
+        assert_eq!(vec![0, 1, 1 << 63, u64::MAX, u64::MAX], keys);
+    }
+
+    #[test]
+    #[should_panic]
+    fn non_monotone_insert() {
+        let mut heap = RadixHeap::new();
+        heap.insert(5u8, ());
+        heap.pop();
+        heap.insert(4, ());
+    }
+}
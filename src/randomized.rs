@@ -0,0 +1,326 @@
+//! Randomized meldable heap
+//!
+//! A randomized meldable heap is a heap-ordered binary tree with no constraint on its shape. Two
+//! heaps are melded by going down a random path of the tree with the larger root, chosen by coin
+//! flips, and melding the other tree at its end; the expected length of such a path is at most
+//! $\log_2(n + 1)$, whatever the shape of the tree. (Gambin and Malinowski, *Randomized meldable
+//! priority queues*, 1998.)
+
+use crate::{Compare, MaxComparator};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::cmp::Ordering;
+
+// seed of the random number generator of new heaps
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+// node of the tree
+struct Node<T> {
+    value: T,
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+type Tree<T> = Option<Box<Node<T>>>;
+
+/// A mergeable heap based on random melds
+///
+/// `meld`, `insert`, and `pop` take $O(\log n)$ expected time, where $n$ is the number of
+/// elements, with no balance information stored in the nodes. The coin flips come from a
+/// pseudo-random number generator with a fixed default seed, which can be changed with
+/// `set_seed`: the shape of the tree, and thus the order in which equal elements are popped, is
+/// reproducible.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::RandomizedHeap;
+///
+/// let mut heap = RandomizedHeap::from(vec![4, 8, 1]);
+/// heap.set_seed(42);
+/// heap.meld(RandomizedHeap::from(vec![7, 2]));
+/// heap.insert(5);
+///
+/// assert_eq!(vec![8, 7, 5, 4, 2, 1], heap.to_vec());
+/// ```
+pub struct RandomizedHeap<T, C = MaxComparator> {
+    root: Tree<T>,
+    size: usize,    // number of elements
+    state: u64,     // state of the random number generator
+    comparator: C,  // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> RandomizedHeap<T> {
+
+    /// Create a new empty `RandomizedHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        RandomizedHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> RandomizedHeap<T, C> {
+
+    /// Create a new empty `RandomizedHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        RandomizedHeap { root: None, size: 0, state: DEFAULT_SEED, comparator }
+    }
+
+    /// Build a `RandomizedHeap` ordered by `comparator` from a vector
+    ///
+    /// The elements are melded in pairs, then the resulting heaps in pairs, and so on.
+    ///
+    /// Expected complexity: $\Theta(n)$, where $n$ is the number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = RandomizedHeap::with_comparator(comparator);
+        heap.size = values.len();
+        let mut trees: VecDeque<Tree<T>> = values.into_iter()
+            .map(|value| Some(Box::new(Node { value, left: None, right: None })))
+            .collect();
+        while trees.len() > 1 {
+            let a = trees.pop_front().unwrap();
+            let b = trees.pop_front().unwrap();
+            trees.push_back(heap.meld_trees(a, b));
+        }
+        heap.root = trees.pop_front().flatten();
+        heap
+    }
+
+    /// Reseed the random number generator used by the melds
+    #[inline]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Expected complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        let node = Some(Box::new(Node { value: x, left: None, right: None }));
+        let root = self.root.take();
+        self.root = self.meld_trees(root, node);
+        self.size += 1;
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Expected complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let node = *self.root.take()?;
+        self.root = self.meld_trees(node.left, node.right);
+        self.size -= 1;
+        Some(node.value)
+    }
+
+    /// Move all the elements of `other` into this heap (the two heaps are merged)
+    ///
+    /// Expected complexity: $O(\log n + \log m)$, where $n$ and $m$ are the numbers of elements
+    /// in the two heaps.
+    pub fn meld(&mut self, mut other: RandomizedHeap<T, C>) {
+        let root = self.root.take();
+        self.root = self.meld_trees(root, other.root.take());
+        self.size += other.size;
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Expected complexity: $O(n \log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // flip a coin
+    #[inline]
+    fn coin(&mut self) -> bool {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.state >> 63 == 1
+    }
+
+    // meld two trees, going down a random path of the one with the larger root
+    //
+    // The path may be as long as the number of elements, so it is built iteratively.
+    fn meld_trees(&mut self, mut a: Tree<T>, mut b: Tree<T>) -> Tree<T> {
+        let mut root = None;
+        let mut slot = &mut root;
+        loop {
+            let (mut top, other) = match (a, b) {
+                (Some(x), Some(y)) => {
+                    if self.comparator.compare(&x.value, &y.value) == Some(Ordering::Less) {
+                        (y, x)
+                    } else {
+                        (x, y)
+                    }
+                },
+                (tree, None) | (None, tree) => {
+                    *slot = tree;
+                    return root;
+                },
+            };
+            b = Some(other);
+            if self.coin() {
+                a = top.left.take();
+                slot = &mut slot.insert(top).left;
+            } else {
+                a = top.right.take();
+                slot = &mut slot.insert(top).right;
+            }
+        }
+    }
+}
+
+impl<T, C> RandomizedHeap<T, C> {
+
+    // references to all the elements, in no particular order
+    fn values(&self) -> Vec<&T> {
+        let mut values = Vec::with_capacity(self.size);
+        let mut stack: Vec<&Node<T>> = self.root.iter().map(|node| &**node).collect();
+        while let Some(node) = stack.pop() {
+            values.push(&node.value);
+            stack.extend(node.left.iter().chain(node.right.iter()).map(|node| &**node));
+        }
+        values
+    }
+}
+
+impl<T, C> Drop for RandomizedHeap<T, C> {
+
+    // drop the nodes one by one, as the recursive drop of a long path could overflow the stack
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone> Clone for RandomizedHeap<T, C> {
+
+    /// Clone the heap, building a new tree from its elements
+    ///
+    /// Expected complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    fn clone(&self) -> Self {
+        let values = self.values().into_iter().cloned().collect();
+        let mut heap = RandomizedHeap::from_vec_with_comparator(values, self.comparator.clone());
+        heap.state = self.state;
+        heap
+    }
+}
+
+impl<T: core::fmt::Debug, C> core::fmt::Debug for RandomizedHeap<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.values()).finish()
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for RandomizedHeap<T> {
+
+    /// Build a `RandomizedHeap` from a vector
+    ///
+    /// Expected complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        RandomizedHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for RandomizedHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for RandomizedHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for RandomizedHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for RandomizedHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::{BinaryHeap, FnComparator};
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let mut heap = RandomizedHeap::new();
+        let mut reference = BinaryHeap::new();
+        for _ in 0..2000 {
+            match next() % 5 {
+                0 | 1 => assert_eq!(reference.pop(), heap.pop()),
+                2 => {
+                    let values: Vec<u64> = (0..(next() % 10)).map(|_| next() % 1000).collect();
+                    for &x in values.iter() {
+                        reference.insert(x);
+                    }
+                    heap.meld(RandomizedHeap::from(values));
+                },
+                _ => {
+                    let x = next() % 1000;
+                    heap.insert(x);
+                    reference.insert(x);
+                },
+            }
+            assert_eq!(heap.size(), heap.values().len());
+            assert_eq!(reference.get_max().as_ref(), heap.peek());
+        }
+        assert_eq!(reference.clone().to_vec(), heap.clone().to_vec());
+        assert_eq!(reference.to_vec(), heap.to_vec());
+    }
+
+    #[test]
+    fn reproducible() {
+        // (priority, insertion index) pairs, ordered by priority only
+        let pops = |seed| {
+            let compare = FnComparator(|a: &(u8, usize), b: &(u8, usize)| a.0.cmp(&b.0));
+            let mut heap = RandomizedHeap::with_comparator(compare);
+            heap.set_seed(seed);
+            for i in 0..100 {
+                heap.insert(((i % 4) as u8, i));
+            }
+            heap.map(|(_, i)| i).collect::<Vec<usize>>()
+        };
+        assert_eq!(pops(1), pops(1));
+        assert_ne!(pops(1), pops(2));
+    }
+
+    #[test]
+    fn long_path() {
+        // each new element becomes the root, the old tree being one of its children
+        let mut heap = RandomizedHeap::new();
+        for i in 0..1_000_000 {
+            heap.insert(i);
+        }
+        let copy = heap.clone();
+        assert_eq!(1_000_000, copy.size());
+        assert_eq!(Some(999_999), heap.pop());
+    }
+}
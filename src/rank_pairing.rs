@@ -0,0 +1,483 @@
+//! Rank-pairing heap
+//!
+//! A rank-pairing heap is a list of *half trees*: binary trees in which each element is not
+//! smaller than those of its left subtree, and whose root has no right child. Each node has a
+//! rank, and two half trees are linked only if their roots have the same rank, as in a binomial
+//! heap. Moving an element towards the root cuts its node with its left subtree, which becomes a
+//! new half tree, and the ranks of its former ancestors are recomputed; unlike in a Fibonacci
+//! heap, this never cascades into other cuts. (Haeupler, Sen, and Tarjan, *Rank-pairing heaps*,
+//! 2011; this is the type-1 variant, in which the children of each node have equal ranks, one
+//! less than that of the node, or one of them has the rank of the node.)
+//!
+//! The nodes are stored in a slab, like those of `PairingHeap`.
+
+use crate::{Compare, Handle, HandleMap, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+// link of a node to the rest of the heap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Parent {
+    Root(usize),    // position in the list of roots
+    Node(usize),    // slot of the parent
+}
+
+// node of a half tree
+#[derive(Clone, Debug)]
+struct Node<T> {
+    value: T,
+    left: Option<usize>,
+    right: Option<usize>,
+    parent: Parent,
+    rank: isize,            // the rank of a missing node is -1
+}
+
+// slot of the slab; the generation is incremented each time the slot is freed
+#[derive(Clone, Debug)]
+struct Slot<T> {
+    generation: u32,
+    node: Option<Node<T>>,
+}
+
+/// A mergeable heap with cheap changes of priority and no cascading cuts
+///
+/// `insert` takes $\Theta(1)$ time and `pop` $O(\log n)$ amortized time. Moving an element
+/// towards the root with `increase_key` (or `decrease_key` with `MinComparator`, as in
+/// Dijkstra's and Prim's algorithms) takes $\Theta(1)$ amortized time, with a single cut: there
+/// are no cascades of cuts as in a `FibonacciHeap`, and the structure is simpler. Elements are
+/// accessed through the handles returned by `insert`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{MinComparator, RankPairingHeap};
+///
+/// // tentative distances of a shortest-path search
+/// let mut frontier = RankPairingHeap::with_comparator(MinComparator);
+/// let a = frontier.insert((7, 'a'));
+/// frontier.insert((4, 'b'));
+/// frontier.insert((5, 'c'));
+///
+/// // a shorter path to `a` was found
+/// frontier.decrease_key(a, (2, 'a'));
+///
+/// assert_eq!(Some((2, 'a')), frontier.pop());
+/// assert_eq!(Some((4, 'b')), frontier.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct RankPairingHeap<T, C = MaxComparator> {
+    slots: Vec<Slot<T>>,    // slab of nodes
+    free: Vec<usize>,       // indices of the free slots
+    roots: Vec<usize>,      // slots of the roots of the half trees
+    max: Option<usize>,     // slot of the root with the largest element
+    size: usize,            // number of elements
+    comparator: C,          // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> RankPairingHeap<T> {
+
+    /// Create a new empty `RankPairingHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        RankPairingHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> RankPairingHeap<T, C> {
+
+    /// Create a new empty `RankPairingHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        RankPairingHeap {
+            slots: Vec::new(),
+            free: Vec::new(),
+            roots: Vec::new(),
+            max: None,
+            size: 0,
+            comparator,
+        }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap, returning a handle to it
+    ///
+    /// Worst-case complexity: $\Theta(1)$ (amortized, as the slab may grow).
+    pub fn insert(&mut self, x: T) -> Handle {
+        let node = Some(Node { value: x, left: None, right: None, parent: Parent::Root(0), rank: 0 });
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].node = node;
+                index
+            },
+            None => {
+                self.slots.push(Slot { generation: 0, node });
+                self.slots.len() - 1
+            },
+        };
+        self.add_root(index);
+        self.size += 1;
+        Handle { index, generation: self.slots[index].generation }
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let max = self.max?;
+        Some(self.remove_root(max))
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.max.map(|max| &self.node(max).value)
+    }
+
+    /// Return a reference to the element of `handle` (or `None` if it is no longer in the heap)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.index(handle).map(|index| &self.node(index).value)
+    }
+
+    /// Whether the element of `handle` is still in the heap
+    #[inline]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.index(handle).is_some()
+    }
+
+    /// Replace the element of `handle` by a smaller one `x`, returning the old element (or
+    /// `None`, leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact larger, the heap is repaired as well.
+    ///
+    /// Complexity: $\Theta(1)$ amortized if `x` is not smaller than the old element (in
+    /// particular with `MinComparator`, if it is not larger for the natural order), and
+    /// $O(\log n)$ amortized otherwise, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn decrease_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Replace the element of `handle` by a larger one `x`, returning the old element (or `None`,
+    /// leaving the heap unchanged, if the element is no longer in the heap)
+    ///
+    /// If `x` is in fact smaller, the heap is repaired as well.
+    ///
+    /// Complexity: $\Theta(1)$ amortized if `x` is not smaller than the old element, and
+    /// $O(\log n)$ amortized otherwise, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn increase_key(&mut self, handle: Handle, x: T) -> Option<T> {
+        self.replace(handle, x)
+    }
+
+    /// Remove the element of `handle` and return it (or `None` if it is no longer in the heap)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let index = self.index(handle)?;
+        if let Parent::Node(_) = self.node(index).parent {
+            self.cut(index);
+        }
+        Some(self.remove_root(index))
+    }
+
+    /// Move all the elements of `other` into this heap, returning the translation of the handles
+    /// of `other`
+    ///
+    /// The lists of roots are concatenated; the nodes of `other` are moved to the slab of this
+    /// heap with a single comparison.
+    ///
+    /// Worst-case complexity: $\Theta(m)$ (amortized, as the slab may grow), where $m$ is the
+    /// number of slots of `other`.
+    pub fn meld(&mut self, other: RankPairingHeap<T, C>) -> HandleMap {
+        let offset = self.slots.len();
+        let roots = self.roots.len();
+        let shift = |index: Option<usize>| index.map(|index| index + offset);
+        self.slots.extend(other.slots.into_iter().map(|mut slot| {
+            if let Some(node) = slot.node.as_mut() {
+                node.left = shift(node.left);
+                node.right = shift(node.right);
+                node.parent = match node.parent {
+                    Parent::Root(position) => Parent::Root(position + roots),
+                    Parent::Node(parent) => Parent::Node(parent + offset),
+                };
+            }
+            slot
+        }));
+        self.free.extend(other.free.iter().map(|index| index + offset));
+        self.roots.extend(other.roots.iter().map(|index| index + offset));
+        if let Some(max) = shift(other.max) {
+            self.update_max(max);
+        }
+        self.size += other.size;
+        HandleMap { offset }
+    }
+
+    // slot index of `handle`, if its element is still in the heap
+    fn index(&self, handle: Handle) -> Option<usize> {
+        self.slots.get(handle.index)
+            .filter(|slot| slot.generation == handle.generation && slot.node.is_some())
+            .map(|_| handle.index)
+    }
+
+    // node in the slot `index`, which must be occupied
+    #[inline]
+    fn node(&self, index: usize) -> &Node<T> {
+        self.slots[index].node.as_ref().unwrap()
+    }
+
+    #[inline]
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        self.slots[index].node.as_mut().unwrap()
+    }
+
+    // rank of a node, or -1 if it is missing
+    #[inline]
+    fn rank(&self, index: Option<usize>) -> isize {
+        index.map_or(-1, |index| self.node(index).rank)
+    }
+
+    // whether the element in the slot `a` is smaller than the one in the slot `b`
+    #[inline]
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.comparator.compare(&self.node(a).value, &self.node(b).value) == Some(Ordering::Less)
+    }
+
+    // make the root `index` the largest one if its element is larger
+    fn update_max(&mut self, index: usize) {
+        match self.max {
+            Some(max) if !self.less(max, index) => {},
+            _ => self.max = Some(index),
+        }
+    }
+
+    // add the node `index`, which has no right child, to the list of roots
+    fn add_root(&mut self, index: usize) {
+        let position = self.roots.len();
+        self.roots.push(index);
+        self.node_mut(index).parent = Parent::Root(position);
+        self.update_max(index);
+    }
+
+    // make the right spine of the left child of the root `index` a list of new roots, then
+    // link the roots and remove `index`, returning its element
+    fn remove_root(&mut self, index: usize) -> T {
+        let Parent::Root(position) = self.node(index).parent else { unreachable!() };
+        self.roots.swap_remove(position);
+        if let Some(&moved) = self.roots.get(position) {
+            self.node_mut(moved).parent = Parent::Root(position);
+        }
+        self.split(index);
+        self.consolidate();
+        let slot = &mut self.slots[index];
+        let node = slot.node.take().unwrap();
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        self.size -= 1;
+        node.value
+    }
+
+    // make the right spine of the left child of the root `index` a list of new roots
+    fn split(&mut self, index: usize) {
+        let mut current = self.node_mut(index).left.take();
+        self.node_mut(index).rank = 0;
+        while let Some(child) = current {
+            current = self.node_mut(child).right.take();
+            let left = self.node(child).left;
+            self.node_mut(child).rank = self.rank(left) + 1;
+            self.roots.push(child);
+        }
+    }
+
+    // link the roots of equal ranks in a single pass, and find the largest one
+    fn consolidate(&mut self) {
+        let roots = core::mem::take(&mut self.roots);
+        let mut by_rank: Vec<Option<usize>> = Vec::new();
+        let mut linked = Vec::new();
+        for root in roots {
+            let rank = self.node(root).rank as usize;
+            if rank >= by_rank.len() {
+                by_rank.resize(rank + 1, None);
+            }
+            match by_rank[rank].take() {
+                Some(other) => linked.push(self.link(other, root)),
+                None => by_rank[rank] = Some(root),
+            }
+        }
+        self.max = None;
+        for root in linked.into_iter().chain(by_rank.into_iter().flatten()) {
+            self.add_root(root);
+        }
+    }
+
+    // link two half trees whose roots have the same rank, returning the root of the result
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        let (winner, loser) = if self.less(a, b) { (b, a) } else { (a, b) };
+        let left = self.node(winner).left;
+        if let Some(left) = left {
+            self.node_mut(left).parent = Parent::Node(loser);
+        }
+        let node = self.node_mut(loser);
+        node.right = left;
+        node.parent = Parent::Node(winner);
+        let node = self.node_mut(winner);
+        node.left = Some(loser);
+        node.rank += 1;
+        winner
+    }
+
+    // make the node `index` (which is not a root) and its left subtree a new half tree, then
+    // recompute the ranks of its former ancestors
+    fn cut(&mut self, index: usize) {
+        let Parent::Node(parent) = self.node(index).parent else { unreachable!() };
+        let right = self.node_mut(index).right.take();
+        if let Some(right) = right {
+            self.node_mut(right).parent = Parent::Node(parent);
+        }
+        let node = self.node_mut(parent);
+        if node.left == Some(index) {
+            node.left = right;
+        } else {
+            node.right = right;
+        }
+        let left = self.node(index).left;
+        self.node_mut(index).rank = self.rank(left) + 1;
+        self.add_root(index);
+
+        let mut current = parent;
+        loop {
+            let node = self.node(current);
+            let (left, right) = (self.rank(node.left), self.rank(node.right));
+            let rank = match node.parent {
+                Parent::Root(_) => left + 1,
+                Parent::Node(_) if left == right => left + 1,
+                Parent::Node(_) => left.max(right),
+            };
+            if rank >= node.rank {
+                break;
+            }
+            let parent = node.parent;
+            self.node_mut(current).rank = rank;
+            match parent {
+                Parent::Root(_) => break,
+                Parent::Node(parent) => current = parent,
+            }
+        }
+    }
+
+    // replace the element of `handle` and repair the heap
+    fn replace(&mut self, handle: Handle, x: T) -> Option<T> {
+        let index = self.index(handle)?;
+        let smaller = self.comparator.compare(&x, &self.node(index).value) == Some(Ordering::Less);
+        let old = core::mem::replace(&mut self.node_mut(index).value, x);
+        if let Parent::Node(_) = self.node(index).parent {
+            self.cut(index);
+        }
+        if smaller {
+            // the left subtree may now hold larger elements: split it into new roots
+            self.split(index);
+            self.consolidate();
+        } else {
+            self.update_max(index);
+        }
+        Some(old)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for RankPairingHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::MinComparator;
+
+    // check the order and the ranks of the half tree of the root `index`, returning its size
+    fn check_half_tree<T, C: Compare<T>>(heap: &RankPairingHeap<T, C>, index: usize) -> usize {
+        let root = heap.node(index);
+        assert!(root.right.is_none());
+        assert_eq!(heap.rank(root.left) + 1, root.rank);
+        let mut size = 1;
+        let mut stack: Vec<(usize, usize)> = root.left.map(|left| (left, index)).into_iter().collect();
+        while let Some((current, ancestor)) = stack.pop() {
+            let node = heap.node(current);
+            assert!(!heap.less(ancestor, current));
+            let (left, right) = (heap.rank(node.left), heap.rank(node.right));
+            assert!((left == right && node.rank == left + 1)
+                || (left != right && node.rank == left.max(right)));
+            size += 1;
+            stack.extend(node.left.map(|left| (left, current)));
+            stack.extend(node.right.map(|right| (right, ancestor)));
+        }
+        size
+    }
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(7);
+        // distinct elements, so that the popped handle is known
+        let mut counter = 0;
+        let mut distinct = |x: u64| { counter += 1; x * 10_000 + counter };
+        let mut heap = RankPairingHeap::with_comparator(MinComparator);
+        let mut live: Vec<(Handle, u64)> = Vec::new();
+        for _ in 0..5_000 {
+            match next() % 6 {
+                0 | 1 => {
+                    let x = distinct(next() % 1000);
+                    live.push((heap.insert(x), x));
+                },
+                2 if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let x = distinct(next() % 1000);
+                    assert_eq!(Some(live[i].1), heap.decrease_key(live[i].0, x));
+                    live[i].1 = x;
+                },
+                3 if !live.is_empty() => {
+                    let i = (next() as usize) % live.len();
+                    let (handle, x) = live.swap_remove(i);
+                    assert_eq!(Some(x), heap.remove(handle));
+                    assert!(!heap.contains(handle));
+                },
+                4 => {
+                    let min = live.iter().enumerate().min_by_key(|&(_, &(_, x))| x).map(|(i, _)| i);
+                    let popped = heap.pop();
+                    assert_eq!(min.map(|i| live.swap_remove(i).1), popped);
+                },
+                _ => {
+                    let mut other = RankPairingHeap::with_comparator(MinComparator);
+                    let added: Vec<(Handle, u64)> = (0..(next() % 5))
+                        .map(|_| { let x = distinct(next() % 1000); (other.insert(x), x) })
+                        .collect();
+                    let map = heap.meld(other);
+                    live.extend(added.into_iter().map(|(handle, x)| (map.map(handle), x)));
+                },
+            }
+            let size: usize = heap.roots.iter().map(|&root| check_half_tree(&heap, root)).sum();
+            assert_eq!(live.len(), size);
+            assert_eq!(live.len(), heap.size());
+            assert_eq!(live.iter().map(|&(_, x)| x).min().as_ref(), heap.peek());
+        }
+        for &(handle, x) in live.iter() {
+            assert_eq!(Some(&x), heap.get(handle));
+        }
+        let mut expected: Vec<u64> = live.iter().map(|&(_, x)| x).collect();
+        expected.sort_unstable();
+        let popped: Vec<u64> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(expected, popped);
+    }
+}
@@ -0,0 +1,169 @@
+//! `serde` support
+//!
+//! A heap is serialized as the sequence of its elements, in the order of its array
+//! representation. Since deserialized data may come from an untrusted source, the sequence read
+//! back is not assumed to satisfy the heap property: depending on the chosen
+//! [`DeserializePolicy`], it is either checked (and rejected if invalid) or rearranged into a
+//! valid heap.
+
+use crate::BinaryHeap;
+use alloc::vec::Vec;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// What to do with a deserialized sequence which may not satisfy the heap property
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DeserializePolicy {
+    /// Return an error if some element is larger than its parent
+    Verify,
+    /// Rearrange the elements into a valid heap (worst-case complexity $\Theta(n)$)
+    #[default]
+    Repair,
+}
+
+impl<'de, T: core::cmp::PartialOrd + Deserialize<'de>> BinaryHeap<T> {
+
+    /// Deserialize a heap, handling input which does not satisfy the heap property according to
+    /// `policy`
+    ///
+    /// Only available with the `serde` feature. The `Deserialize` implementation of `BinaryHeap`
+    /// uses [`DeserializePolicy::Repair`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, DeserializePolicy};
+    ///
+    /// let mut deserializer = serde_json::Deserializer::from_str("[1, 2, 3]");
+    /// let heap = BinaryHeap::<isize>::deserialize_with_policy(
+    ///     &mut deserializer, DeserializePolicy::Verify
+    /// );
+    ///
+    /// assert!(heap.is_err());
+    /// ```
+    pub fn deserialize_with_policy<D: Deserializer<'de>>(deserializer: D,
+                                                         policy: DeserializePolicy)
+        -> Result<Self, D::Error>
+    {
+        let mut heap = BinaryHeap::new();
+        heap.data = Vec::<T>::deserialize(deserializer)?;
+        match policy {
+            DeserializePolicy::Verify => {
+                if !heap.is_valid_heap() {
+                    return Err(serde::de::Error::custom(
+                        "the sequence does not satisfy the heap property"
+                    ));
+                }
+            },
+            DeserializePolicy::Repair => heap.rebuild(),
+        }
+        Ok(heap)
+    }
+}
+
+#[cfg(feature = "postcard")]
+impl<T: core::cmp::PartialOrd + Serialize> BinaryHeap<T> {
+
+    /// Encode the heap with `postcard`
+    ///
+    /// Only available with the `postcard` feature, which does not require `std`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from_vec(&[1u8, 2, 3]);
+    ///
+    /// // length, then one byte per element
+    /// assert_eq!(vec![3, 3, 1, 2], heap.to_postcard().unwrap());
+    /// ```
+    pub fn to_postcard(&self) -> Result<Vec<u8>, postcard::Error> {
+        postcard::to_allocvec(self)
+    }
+}
+
+#[cfg(feature = "postcard")]
+impl<'de, T: core::cmp::PartialOrd + Deserialize<'de>> BinaryHeap<T> {
+
+    /// Decode a heap encoded with `postcard`, handling input which does not satisfy the heap
+    /// property according to `policy`
+    ///
+    /// Only available with the `postcard` feature, which does not require `std`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, DeserializePolicy};
+    ///
+    /// let heap = BinaryHeap::from_vec(&[1u8, 2, 3]);
+    /// let bytes = heap.to_postcard().unwrap();
+    /// let heap_2 = BinaryHeap::<u8>::from_postcard(&bytes, DeserializePolicy::Verify).unwrap();
+    ///
+    /// assert_eq!(heap, heap_2);
+    /// ```
+    pub fn from_postcard(bytes: &'de [u8], policy: DeserializePolicy)
+        -> Result<Self, postcard::Error>
+    {
+        let mut deserializer = postcard::Deserializer::from_bytes(bytes);
+        BinaryHeap::deserialize_with_policy(&mut deserializer, policy)
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Serialize> Serialize for BinaryHeap<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.data)
+    }
+}
+
+impl<'de, T: core::cmp::PartialOrd + Deserialize<'de>> Deserialize<'de> for BinaryHeap<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BinaryHeap::deserialize_with_policy(deserializer, DeserializePolicy::Repair)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_json(json: &str, policy: DeserializePolicy) -> Result<BinaryHeap<isize>, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        BinaryHeap::deserialize_with_policy(&mut deserializer, policy).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn round_trip() {
+        let heap = BinaryHeap::from_vec(&[0, 2, -2, 4, 6, 8, -1, 1]);
+        let json = serde_json::to_string(&heap).unwrap();
+        let heap_2: BinaryHeap<isize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(heap.data, heap_2.data);
+    }
+
+    #[test]
+    fn repair() {
+        let heap: BinaryHeap<isize> = serde_json::from_str("[1, 2, 3, 4, 5, 6]").unwrap();
+        assert!(heap.is_valid_heap());
+        assert_eq!(vec![6, 5, 4, 3, 2, 1], heap.to_vec());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_round_trip() {
+        let heap = BinaryHeap::from_vec(&[0i32, 2, -2, 4, 6, 8, -1, 1]);
+        let bytes = heap.to_postcard().unwrap();
+        let heap_2 = BinaryHeap::<i32>::from_postcard(&bytes, DeserializePolicy::Verify).unwrap();
+        assert_eq!(heap.data, heap_2.data);
+
+        // the bytes for `[1, 2]` do not satisfy the heap property
+        assert!(BinaryHeap::<u8>::from_postcard(&[2, 1, 2], DeserializePolicy::Verify).is_err());
+        let heap_3 = BinaryHeap::<u8>::from_postcard(&[2, 1, 2], DeserializePolicy::Repair);
+        assert_eq!(vec![2, 1], heap_3.unwrap().to_vec());
+    }
+
+    #[test]
+    fn verify() {
+        let heap = from_json("[6, 4, 5, 1, 3, 2]", DeserializePolicy::Verify).unwrap();
+        assert_eq!(vec![6, 4, 5, 1, 3, 2], heap.data);
+        assert!(from_json("[6, 4, 5, 1, 3, 7]", DeserializePolicy::Verify).is_err());
+        assert!(from_json("[]", DeserializePolicy::Verify).unwrap().data.is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Differential testing against `std::collections::BinaryHeap`
+
+use crate::BinaryHeap;
+
+/// A `BinaryHeap` mirrored by a `std::collections::BinaryHeap`
+///
+/// Every operation is applied to both heaps, and the results are compared: any difference (in
+/// the popped element, the maximum, or the size) makes the operation panic. This is meant for
+/// tests, to check the implementation of `BinaryHeap` on the sequences of operations of an
+/// application.
+///
+/// Only available with the `shadow` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::ShadowHeap;
+///
+/// let mut heap = ShadowHeap::<isize>::new();
+/// heap.insert(1);
+/// heap.insert(3);
+///
+/// assert_eq!(Some(3), heap.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShadowHeap<T: core::cmp::Ord + Clone> {
+    heap: BinaryHeap<T>,
+    shadow: alloc::collections::BinaryHeap<T>,
+}
+
+impl<T: core::cmp::Ord + Clone + core::fmt::Debug> ShadowHeap<T> {
+
+    /// Create a new empty `ShadowHeap`
+    pub fn new() -> Self {
+        ShadowHeap { heap: BinaryHeap::new(), shadow: alloc::collections::BinaryHeap::new() }
+    }
+
+    /// Get the number of elements, checking that both heaps agree
+    pub fn size(&self) -> usize {
+        assert_eq!(self.shadow.len(), self.heap.size(), "size differs from the shadow heap");
+        self.heap.size()
+    }
+
+    /// Insert an element in both heaps
+    pub fn insert(&mut self, x: T) {
+        self.shadow.push(x.clone());
+        self.heap.insert(x);
+        self.size();
+    }
+
+    /// Remove and return the root element of both heaps, checking that they are equal
+    pub fn pop(&mut self) -> Option<T> {
+        let expected = self.shadow.pop();
+        let popped = self.heap.pop();
+        assert_eq!(expected, popped, "popped element differs from the shadow heap");
+        self.size();
+        popped
+    }
+
+    /// Return a copy of the maximum element, checking that both heaps agree
+    pub fn get_max(&self) -> Option<T> {
+        let max = self.heap.get_max();
+        assert_eq!(self.shadow.peek(), max.as_ref(), "maximum differs from the shadow heap");
+        max
+    }
+
+    /// Consume the wrapper, checking that both heaps pop the same sequence of elements, and
+    /// return the `BinaryHeap`
+    pub fn into_inner(self) -> BinaryHeap<T> {
+        let heap = self.heap.clone();
+        let popped = self.heap.to_vec();
+        let expected: alloc::vec::Vec<T> = self.shadow.into_sorted_vec().into_iter().rev().collect();
+        assert_eq!(expected, popped, "pop sequence differs from the shadow heap");
+        heap
+    }
+}
+
+impl<T: core::cmp::Ord + Clone + core::fmt::Debug> Default for ShadowHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(12345);
+        let mut heap = ShadowHeap::<u64>::new();
+        for _ in 0..10_000 {
+            if next().is_multiple_of(3) {
+                heap.pop();
+            } else {
+                heap.insert(next() % 100);
+            }
+            heap.get_max();
+        }
+        heap.into_inner();
+    }
+
+    #[test]
+    #[should_panic(expected = "popped element differs")]
+    fn detects_difference() {
+        let mut heap = ShadowHeap::<u8>::new();
+        heap.insert(1);
+        heap.heap.data[0] = 2;
+        heap.pop();
+    }
+}
@@ -0,0 +1,313 @@
+//! Concurrent heap split into shards with their own locks
+//!
+//! Each shard is a `BinaryHeap` behind a mutex. Insertions go to the shards in turn, skipping
+//! those which are locked, so that threads inserting at the same time rarely wait for each
+//! other; pops scan the roots of the shards and pop the largest one. Consumers waiting for an
+//! element sleep on a condition variable, which insertions only notify when a consumer waits.
+
+use crate::{BinaryHeap, Compare, MaxComparator};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+/// A `Send + Sync` heap made of several `BinaryHeap` shards, each with its own lock
+///
+/// `insert` locks a single shard, and `pop` locks the shards one at a time (holding at most two
+/// locks, taken in the order of the shards, so that it can not deadlock) to find the largest
+/// root. Without concurrent operations, `pop` thus returns the largest element; with concurrent
+/// insertions, it may miss an element inserted in a shard it has already scanned. This trades
+/// strict ordering for throughput; the number of shards is typically a small multiple of the
+/// number of threads.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::ShardedHeap;
+///
+/// let heap = ShardedHeap::new(4);
+/// std::thread::scope(|scope| {
+///     for worker in 0..4 {
+///         let heap = &heap;
+///         scope.spawn(move || {
+///             for job in 0..100 {
+///                 heap.insert(100 * worker + job);
+///             }
+///         });
+///     }
+/// });
+///
+/// assert_eq!(400, heap.size());
+/// assert_eq!(Some(399), heap.pop());
+/// assert_eq!(Some(398), heap.pop());
+/// ```
+#[derive(Debug)]
+pub struct ShardedHeap<T, C = MaxComparator> {
+    shards: Vec<Mutex<BinaryHeap<T, C>>>,   // heaps, each with its own lock
+    next: AtomicUsize,                      // counter choosing the shard of the next insertion
+    size: AtomicUsize,                      // number of elements
+    waiting: AtomicUsize,                   // number of consumers waiting for an element
+    sleep: Mutex<()>,                       // lock of the waiting consumers
+    available: Condvar,                     // notified on insertion when a consumer waits
+}
+
+impl<T: core::cmp::PartialOrd> ShardedHeap<T> {
+
+    /// Create a new empty `ShardedHeap` with `shards` shards, ordered by the natural order of `T`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    #[inline]
+    pub fn new(shards: usize) -> Self {
+        ShardedHeap::with_comparator(shards, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> ShardedHeap<T, C> {
+
+    /// Create a new empty `ShardedHeap` with `shards` shards, ordered by `comparator`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    pub fn with_comparator(shards: usize, comparator: C) -> Self
+        where C: Clone
+    {
+        assert!(shards > 0, "a sharded heap needs at least one shard");
+        ShardedHeap {
+            shards: (0..shards).map(|_| Mutex::new(BinaryHeap::with_comparator(comparator.clone()))).collect(),
+            next: AtomicUsize::new(0),
+            size: AtomicUsize::new(0),
+            waiting: AtomicUsize::new(0),
+            sleep: Mutex::new(()),
+            available: Condvar::new(),
+        }
+    }
+
+    /// Get the number of shards
+    #[inline]
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// With concurrent operations, the result may be out of date as soon as it is returned.
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// The element goes to the next shard in turn which is not locked, or, if all of them are,
+    /// to the next shard in turn once it is unlocked.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the shard,
+    /// without contention.
+    pub fn insert(&self, x: T) {
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.shards.len();
+        let mut shard = (0..self.shards.len())
+            .find_map(|i| self.shards[(start + i) % self.shards.len()].try_lock().ok())
+            .unwrap_or_else(|| lock(&self.shards[start]));
+        shard.insert(x);
+        // the size is incremented before the element can be popped, so that it never wraps
+        self.size.fetch_add(1, Ordering::SeqCst);
+        drop(shard);
+
+        // a consumer about to sleep either sees the new size or is woken up
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            drop(lock(&self.sleep));
+            self.available.notify_one();
+        }
+    }
+
+    /// Remove and return the largest root of the shards (or `None` if they are all empty)
+    ///
+    /// Worst-case complexity: $\Theta(s + \log n)$, where $s$ is the number of shards and $n$ the
+    /// number of elements in the shard of the root, without contention.
+    pub fn pop(&self) -> Option<T> {
+        let mut best: Option<MutexGuard<'_, BinaryHeap<T, C>>> = None;
+        for shard in self.shards.iter() {
+            let shard = lock(shard);
+            let Some(root) = shard.data.first() else { continue };
+            let larger = match &best {
+                None => true,
+                Some(best) => best.compare_values(&best.data[0], root) == Some(core::cmp::Ordering::Less),
+            };
+            if larger {
+                best = Some(shard);
+            }
+        }
+        let root = best?.pop();
+        self.size.fetch_sub(1, Ordering::Release);
+        root
+    }
+
+    /// Remove and return the largest root of the shards, waiting until an element is inserted if
+    /// they are all empty
+    ///
+    /// Worst-case complexity: $\Theta(s + \log n)$, where $s$ is the number of shards and $n$ the
+    /// number of elements in the shard of the root, without contention and once an element is
+    /// available.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::ShardedHeap;
+    ///
+    /// let heap = ShardedHeap::new(4);
+    /// std::thread::scope(|scope| {
+    ///     let consumer = scope.spawn(|| heap.pop_wait());
+    ///     heap.insert(7);
+    ///     assert_eq!(7, consumer.join().unwrap());
+    /// });
+    /// ```
+    pub fn pop_wait(&self) -> T {
+        loop {
+            if let Some(root) = self.wait_until(None) {
+                return root;
+            }
+        }
+    }
+
+    /// Remove and return the largest root of the shards, waiting at most `timeout` for an element
+    /// to be inserted if they are all empty (or `None` on timeout)
+    ///
+    /// Worst-case complexity: $\Theta(s + \log n)$, where $s$ is the number of shards and $n$ the
+    /// number of elements in the shard of the root, without contention and once an element is
+    /// available.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::ShardedHeap;
+    /// use std::time::Duration;
+    ///
+    /// let heap: ShardedHeap<u32> = ShardedHeap::new(4);
+    /// assert_eq!(None, heap.pop_wait_timeout(Duration::from_millis(1)));
+    /// ```
+    pub fn pop_wait_timeout(&self, timeout: Duration) -> Option<T> {
+        self.wait_until(Instant::now().checked_add(timeout))
+    }
+
+    // pop an element, waiting until `deadline` (or indefinitely if `None`) if the heap is empty
+    fn wait_until(&self, deadline: Option<Instant>) -> Option<T> {
+        if let Some(root) = self.pop() {
+            return Some(root);
+        }
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        let root = loop {
+            let mut guard = lock(&self.sleep);
+            while self.size.load(Ordering::SeqCst) == 0 {
+                match deadline {
+                    None => guard = self.available.wait(guard).unwrap_or_else(PoisonError::into_inner),
+                    Some(deadline) => {
+                        let Some(left) = deadline.checked_duration_since(Instant::now()) else { break };
+                        guard = self.available.wait_timeout(guard, left).unwrap_or_else(PoisonError::into_inner).0;
+                    },
+                }
+            }
+            drop(guard);
+
+            // another consumer may have taken the element
+            let root = self.pop();
+            if root.is_some() || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break root;
+            }
+        };
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+        root
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the heap.
+    pub fn to_vec(self) -> Vec<T> {
+        core::iter::from_fn(|| self.pop()).collect()
+    }
+}
+
+impl<T, C: Compare<T> + Clone + Default> core::default::Default for ShardedHeap<T, C> {
+
+    /// Create a new empty `ShardedHeap` with one shard per available thread
+    fn default() -> Self {
+        let shards = std::thread::available_parallelism().map_or(1, usize::from);
+        Self::with_comparator(shards, C::default())
+    }
+}
+
+// lock a shard (or a queue of a `MultiQueue`), ignoring poisoning: a panic in a comparison leaves
+// the heap valid, even if an element may be lost
+#[inline]
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_inserts_and_pops() {
+        let heap = ShardedHeap::new(8);
+        let popped = std::sync::Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for worker in 0..8u64 {
+                let heap = &heap;
+                let popped = &popped;
+                scope.spawn(move || {
+                    for i in 0..1000 {
+                        heap.insert(1000 * worker + i);
+                        if i % 3 == 0 {
+                            if let Some(x) = heap.pop() {
+                                popped.lock().unwrap().push(x);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        let mut popped = popped.into_inner().unwrap();
+        assert_eq!(8000, popped.len() + heap.size());
+
+        // without concurrent operations, the elements come out in order
+        let rest = heap.to_vec();
+        assert!(rest.windows(2).all(|pair| pair[0] >= pair[1]));
+        popped.extend(rest);
+        popped.sort_unstable();
+        assert_eq!((0..8000).collect::<Vec<u64>>(), popped);
+    }
+
+    #[test]
+    fn waiting_consumers() {
+        let heap = ShardedHeap::new(2);
+        let popped = std::sync::Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let heap = &heap;
+                let popped = &popped;
+                scope.spawn(move || {
+                    for _ in 0..250 {
+                        popped.lock().unwrap().push(heap.pop_wait());
+                    }
+                });
+            }
+            for i in 0..1000u64 {
+                heap.insert(i);
+            }
+        });
+        let mut popped = popped.into_inner().unwrap();
+        popped.sort_unstable();
+        assert_eq!((0..1000).collect::<Vec<u64>>(), popped);
+
+        let start = Instant::now();
+        assert_eq!(None, heap.pop_wait_timeout(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
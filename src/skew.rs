@@ -0,0 +1,286 @@
+//! Skew heap
+//!
+//! A skew heap is a heap-ordered binary tree with no constraint on its shape. Two heaps are
+//! melded by merging their rightmost paths, as for a leftist heap, but the children of every
+//! node on the merged path are swapped unconditionally, so that no rank needs to be stored; the
+//! operations take $O(\log n)$ amortized time. (Sleator and Tarjan, *Self-adjusting heaps*,
+//! 1986.)
+
+use crate::{Compare, MaxComparator};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::cmp::Ordering;
+
+// node of the tree
+struct Node<T> {
+    value: T,
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+type Tree<T> = Option<Box<Node<T>>>;
+
+/// A mergeable heap based on a self-adjusting binary tree
+///
+/// Each node only holds an element and its two children. `meld`, `insert`, and `pop` take
+/// $O(\log n)$ amortized time, where $n$ is the number of elements.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::SkewHeap;
+///
+/// let mut heap = SkewHeap::from(vec![4, 8, 1]);
+/// heap.meld(SkewHeap::from(vec![7, 2]));
+/// heap.insert(5);
+///
+/// assert_eq!(vec![8, 7, 5, 4, 2, 1], heap.to_vec());
+/// ```
+pub struct SkewHeap<T, C = MaxComparator> {
+    root: Tree<T>,
+    size: usize,    // number of elements
+    comparator: C,  // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> SkewHeap<T> {
+
+    /// Create a new empty `SkewHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        SkewHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> SkewHeap<T, C> {
+
+    /// Create a new empty `SkewHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        SkewHeap { root: None, size: 0, comparator }
+    }
+
+    /// Build a `SkewHeap` ordered by `comparator` from a vector
+    ///
+    /// The elements are melded in pairs, then the resulting heaps in pairs, and so on.
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = SkewHeap::with_comparator(comparator);
+        heap.size = values.len();
+        let mut trees: VecDeque<Tree<T>> = values.into_iter()
+            .map(|value| Some(Box::new(Node { value, left: None, right: None })))
+            .collect();
+        while trees.len() > 1 {
+            let a = trees.pop_front().unwrap();
+            let b = trees.pop_front().unwrap();
+            trees.push_back(heap.meld_trees(a, b));
+        }
+        heap.root = trees.pop_front().flatten();
+        heap
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        let node = Some(Box::new(Node { value: x, left: None, right: None }));
+        let root = self.root.take();
+        self.root = self.meld_trees(root, node);
+        self.size += 1;
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.value)
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Amortized complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let node = *self.root.take()?;
+        self.root = self.meld_trees(node.left, node.right);
+        self.size -= 1;
+        Some(node.value)
+    }
+
+    /// Move all the elements of `other` into this heap (the two heaps are merged)
+    ///
+    /// Amortized complexity: $O(\log n + \log m)$, where $n$ and $m$ are the numbers of elements
+    /// in the two heaps.
+    pub fn meld(&mut self, mut other: SkewHeap<T, C>) {
+        let root = self.root.take();
+        self.root = self.meld_trees(root, other.root.take());
+        self.size += other.size;
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Amortized complexity: $O(n \log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // meld two trees along their rightmost paths, swapping the children of each node on the
+    // merged path
+    //
+    // The merged path may be as long as the number of elements, so it is built iteratively.
+    fn meld_trees(&self, mut a: Tree<T>, mut b: Tree<T>) -> Tree<T> {
+        let mut root = None;
+        let mut slot = &mut root;
+        loop {
+            let (mut top, other) = match (a, b) {
+                (Some(x), Some(y)) => {
+                    if self.comparator.compare(&x.value, &y.value) == Some(Ordering::Less) {
+                        (y, x)
+                    } else {
+                        (x, y)
+                    }
+                },
+                (tree, None) | (None, tree) => {
+                    *slot = tree;
+                    return root;
+                },
+            };
+            a = top.right.take();
+            b = Some(other);
+            top.right = top.left.take();
+            slot = &mut slot.insert(top).left;
+        }
+    }
+}
+
+impl<T, C> SkewHeap<T, C> {
+
+    // references to all the elements, in no particular order
+    fn values(&self) -> Vec<&T> {
+        let mut values = Vec::with_capacity(self.size);
+        let mut stack: Vec<&Node<T>> = self.root.iter().map(|node| &**node).collect();
+        while let Some(node) = stack.pop() {
+            values.push(&node.value);
+            stack.extend(node.left.iter().chain(node.right.iter()).map(|node| &**node));
+        }
+        values
+    }
+}
+
+impl<T, C> Drop for SkewHeap<T, C> {
+
+    // drop the nodes one by one, as the recursive drop of a long path could overflow the stack
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone> Clone for SkewHeap<T, C> {
+
+    /// Clone the heap, building a new tree from its elements
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    fn clone(&self) -> Self {
+        let values = self.values().into_iter().cloned().collect();
+        SkewHeap::from_vec_with_comparator(values, self.comparator.clone())
+    }
+}
+
+impl<T: core::fmt::Debug, C> core::fmt::Debug for SkewHeap<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.values()).finish()
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for SkewHeap<T> {
+
+    /// Build a `SkewHeap` from a vector
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        SkewHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for SkewHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for SkewHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for SkewHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for SkewHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::{BinaryHeap, MinComparator};
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let mut heap = SkewHeap::new();
+        let mut reference = BinaryHeap::new();
+        for _ in 0..2000 {
+            match next() % 5 {
+                0 | 1 => assert_eq!(reference.pop(), heap.pop()),
+                2 => {
+                    let values: Vec<u64> = (0..(next() % 10)).map(|_| next() % 1000).collect();
+                    for &x in values.iter() {
+                        reference.insert(x);
+                    }
+                    heap.meld(SkewHeap::from(values));
+                },
+                _ => {
+                    let x = next() % 1000;
+                    heap.insert(x);
+                    reference.insert(x);
+                },
+            }
+            assert_eq!(heap.size(), heap.values().len());
+            assert_eq!(reference.get_max().as_ref(), heap.peek());
+        }
+        assert_eq!(reference.clone().to_vec(), heap.clone().to_vec());
+        assert_eq!(reference.to_vec(), heap.to_vec());
+    }
+
+    #[test]
+    fn long_left_path() {
+        // each new element becomes the root, the old tree being its left child
+        let mut heap = SkewHeap::with_comparator(MinComparator);
+        for i in (0..1_000_000).rev() {
+            heap.insert(i);
+        }
+        let copy = heap.clone();
+        assert_eq!(1_000_000, copy.size());
+        assert_eq!(Some(0), heap.pop());
+        assert_eq!(Some(1), heap.pop());
+        assert_eq!(999_998, heap.size());
+    }
+}
@@ -0,0 +1,359 @@
+//! Persistent skew binomial heap
+//!
+//! A skew binomial heap is a list of heap-ordered trees, in increasing order of ranks, whose
+//! first two trees only may have the same rank. Inserting an element either adds a tree of rank 0
+//! or, if the first two trees have the same rank, links them under the new element (a *skew
+//! link*), so that no carry propagates and `insert` takes $\Theta(1)$ time in the worst case.
+//! Each node also holds a list of up to `rank` extra elements, left behind by skew links.
+//! (Brodal and Okasaki, *Optimal purely functional priority queues*, 1996; Okasaki, *Purely
+//! Functional Data Structures*, 1998.)
+//!
+//! The trees and lists are immutable and shared through `Arc`s: every operation builds the new
+//! nodes it needs and keeps pointers to the unchanged ones, so that cloning a heap takes
+//! $\Theta(1)$ time and a clone is not affected by later operations on the original.
+
+use crate::{Compare, MaxComparator};
+use alloc::{sync::Arc, vec::Vec};
+use core::cmp::Ordering;
+
+// persistent linked list
+struct Cons<A> {
+    head: A,
+    tail: List<A>,
+}
+
+type List<A> = Option<Arc<Cons<A>>>;
+
+#[inline]
+fn cons<A>(head: A, tail: List<A>) -> List<A> {
+    Some(Arc::new(Cons { head, tail }))
+}
+
+// iterator over the elements of a list
+fn iter<A>(list: &List<A>) -> impl Iterator<Item = &A> {
+    core::iter::successors(list.as_deref(), |cell| cell.tail.as_deref()).map(|cell| &cell.head)
+}
+
+// heap-ordered tree, whose children are in decreasing order of ranks
+struct Node<T> {
+    rank: usize,
+    value: T,
+    extra: List<T>,             // extra elements, not larger than `value`
+    children: List<Tree<T>>,
+}
+
+type Tree<T> = Arc<Node<T>>;
+
+/// A persistent mergeable heap with structural sharing
+///
+/// `insert` takes $\Theta(1)$ time in the worst case, and `peek`, `pop`, and `meld` $O(\log n)$.
+/// Cloning a heap takes $\Theta(1)$ time and shares all its nodes: the old versions of a heap
+/// remain valid and unchanged after operations on the new ones, which makes it suited to undo
+/// stacks or speculative searches. The elements are cloned when nodes are rebuilt, so `T` must
+/// implement `Clone`.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::SkewBinomialHeap;
+///
+/// let mut heap = SkewBinomialHeap::new();
+/// heap.insert(3);
+/// heap.insert(8);
+///
+/// // save a version, then keep working on the heap
+/// let saved = heap.clone();
+/// heap.insert(5);
+/// assert_eq!(Some(8), heap.pop());
+/// assert_eq!(Some(5), heap.pop());
+///
+/// // the saved version is unchanged
+/// assert_eq!(2, saved.size());
+/// assert_eq!(vec![8, 3], saved.to_vec());
+/// ```
+pub struct SkewBinomialHeap<T, C = MaxComparator> {
+    trees: List<Tree<T>>,   // trees in increasing order of ranks
+    size: usize,            // number of elements
+    comparator: C,          // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd + Clone> SkewBinomialHeap<T> {
+
+    /// Create a new empty `SkewBinomialHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        SkewBinomialHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T: Clone, C: Compare<T>> SkewBinomialHeap<T, C> {
+
+    /// Create a new empty `SkewBinomialHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        SkewBinomialHeap { trees: None, size: 0, comparator }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    pub fn insert(&mut self, x: T) {
+        let trees = self.trees.take();
+        self.trees = self.insert_into(x, trees);
+        self.size += 1;
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.max_tree().map(|(_, tree)| &tree.value)
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $O(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let (position, max) = self.max_tree()?;
+        let max = max.clone();
+
+        // meld the children of the largest root with the other trees, then insert its extra
+        // elements
+        let others = iter(&self.trees).enumerate()
+            .filter(|&(i, _)| i != position)
+            .map(|(_, tree)| tree.clone());
+        let mut trees = self.meld_trees(others.chain(iter(&max.children).cloned()));
+        for x in iter(&max.extra) {
+            trees = self.insert_into(x.clone(), trees);
+        }
+        self.trees = trees;
+        self.size -= 1;
+        Some(max.value.clone())
+    }
+
+    /// Add all the elements of `other` to this heap (the two heaps are merged)
+    ///
+    /// The nodes of `other` are shared with the new heap.
+    ///
+    /// Worst-case complexity: $O(\log n + \log m)$, where $n$ and $m$ are the numbers of elements
+    /// in the two heaps.
+    pub fn meld(&mut self, other: &SkewBinomialHeap<T, C>) {
+        let trees = iter(&self.trees).chain(iter(&other.trees)).cloned().collect::<Vec<_>>();
+        self.trees = self.meld_trees(trees);
+        self.size += other.size;
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Worst-case complexity: $O(n \log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // whether `a` is smaller than `b`
+    #[inline]
+    fn less(&self, a: &T, b: &T) -> bool {
+        self.comparator.compare(a, b) == Some(Ordering::Less)
+    }
+
+    // position in the list and tree with the largest root
+    fn max_tree(&self) -> Option<(usize, &Tree<T>)> {
+        iter(&self.trees).enumerate()
+            .reduce(|max, tree| if self.less(&max.1.value, &tree.1.value) { tree } else { max })
+    }
+
+    // insert `x` in a list of trees, with a skew link if the first two trees have the same rank
+    fn insert_into(&self, x: T, trees: List<Tree<T>>) -> List<Tree<T>> {
+        if let Some(first) = &trees {
+            if let Some(second) = &first.tail {
+                if first.head.rank == second.head.rank {
+                    let mut node = self.link(&first.head, &second.head);
+                    if self.less(&x, &node.value) {
+                        node.extra = cons(x, node.extra);
+                    } else {
+                        let value = core::mem::replace(&mut node.value, x);
+                        node.extra = cons(value, node.extra);
+                    }
+                    return cons(Arc::new(node), second.tail.clone());
+                }
+            }
+        }
+        cons(Arc::new(Node { rank: 0, value: x, extra: None, children: None }), trees)
+    }
+
+    // link two trees of the same rank, the one with the smaller root becoming the first child of
+    // the other
+    fn link(&self, a: &Tree<T>, b: &Tree<T>) -> Node<T> {
+        let (parent, child) = if self.less(&a.value, &b.value) { (b, a) } else { (a, b) };
+        Node {
+            rank: parent.rank + 1,
+            value: parent.value.clone(),
+            extra: parent.extra.clone(),
+            children: cons(child.clone(), parent.children.clone()),
+        }
+    }
+
+    // meld trees into a list with distinct ranks, linking trees of the same rank as in a binary
+    // addition
+    fn meld_trees<I: IntoIterator<Item = Tree<T>>>(&self, trees: I) -> List<Tree<T>> {
+        let mut by_rank: Vec<Option<Tree<T>>> = Vec::new();
+        for mut tree in trees {
+            let mut rank = tree.rank;
+            loop {
+                if rank >= by_rank.len() {
+                    by_rank.resize(rank + 1, None);
+                }
+                match by_rank[rank].take() {
+                    Some(other) => {
+                        tree = Arc::new(self.link(&other, &tree));
+                        rank += 1;
+                    },
+                    None => break,
+                }
+            }
+            by_rank[rank] = Some(tree);
+        }
+        by_rank.into_iter().rev().flatten().fold(None, |list, tree| cons(tree, list))
+    }
+}
+
+impl<T, C> SkewBinomialHeap<T, C> {
+
+    // references to all the elements, in no particular order
+    fn values(&self) -> Vec<&T> {
+        let mut values = Vec::with_capacity(self.size);
+        let mut stack: Vec<&Tree<T>> = iter(&self.trees).collect();
+        while let Some(tree) = stack.pop() {
+            values.push(&tree.value);
+            values.extend(iter(&tree.extra));
+            stack.extend(iter(&tree.children));
+        }
+        values
+    }
+}
+
+impl<T, C: Clone> Clone for SkewBinomialHeap<T, C> {
+
+    /// Clone the heap, sharing all its nodes
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    fn clone(&self) -> Self {
+        SkewBinomialHeap { trees: self.trees.clone(), size: self.size, comparator: self.comparator.clone() }
+    }
+}
+
+impl<T: core::fmt::Debug, C> core::fmt::Debug for SkewBinomialHeap<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.values()).finish()
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Clone> From<Vec<T>> for SkewBinomialHeap<T> {
+
+    /// Build a `SkewBinomialHeap` from a vector
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        let mut heap = SkewBinomialHeap::new();
+        for x in values {
+            heap.insert(x);
+        }
+        heap
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Default> core::default::Default for SkewBinomialHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T: Clone, C: Compare<T>> Iterator for SkewBinomialHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<T: Clone, C: Compare<T>> ExactSizeIterator for SkewBinomialHeap<T, C> {}
+
+impl<T: Clone, C: Compare<T>> core::iter::FusedIterator for SkewBinomialHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+
+    #[test]
+    fn versions() {
+        let mut next = lcg(12345);
+
+        // every 100 operations, save the current version with a reference copy
+        let mut heap = SkewBinomialHeap::new();
+        let mut reference = BinaryHeap::new();
+        let mut versions = Vec::new();
+        for i in 0..3000 {
+            match next() % 5 {
+                0 | 1 => assert_eq!(reference.pop(), heap.pop()),
+                2 => {
+                    let values: Vec<u64> = (0..(next() % 10)).map(|_| next() % 1000).collect();
+                    for &x in values.iter() {
+                        reference.insert(x);
+                    }
+                    heap.meld(&SkewBinomialHeap::from(values));
+                },
+                _ => {
+                    let x = next() % 1000;
+                    heap.insert(x);
+                    reference.insert(x);
+                },
+            }
+            assert_eq!(reference.size(), heap.size());
+            assert_eq!(heap.size(), heap.values().len());
+            assert_eq!(reference.get_max().as_ref(), heap.peek());
+            if i % 100 == 0 {
+                versions.push((heap.clone(), reference.clone()));
+            }
+        }
+        for (heap, reference) in versions {
+            assert_eq!(reference.to_vec(), heap.to_vec());
+        }
+    }
+
+    #[test]
+    fn meld_with_itself() {
+        let mut heap = SkewBinomialHeap::from((0..100).collect::<Vec<u32>>());
+        heap.meld(&heap.clone());
+        let mut expected: Vec<u32> = (0..100).flat_map(|x| [x, x]).collect();
+        expected.reverse();
+        assert_eq!(expected, heap.to_vec());
+    }
+
+    #[test]
+    fn ranks() {
+        // only the first two trees may have the same rank
+        let mut heap = SkewBinomialHeap::new();
+        for i in 0..1000 {
+            heap.insert(i);
+            let ranks: Vec<usize> = iter(&heap.trees).map(|tree| tree.rank).collect();
+            assert!(ranks.windows(2).skip(1).all(|pair| pair[0] < pair[1]));
+            assert!(ranks.len() < 2 || ranks[0] <= ranks[1]);
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Lock-free priority queue based on a skiplist
+//!
+//! The elements are kept in a skiplist sorted from the largest to the smallest one (elements
+//! comparing equal are sorted by insertion order), whose links are updated with compare-and-swap
+//! operations. A node is popped by marking the link to it at the bottom level, with a single
+//! fetch-and-or on the link of its predecessor: the popped nodes thus always form a prefix of the
+//! list, and a pop claims the first node after it, which is the largest element at that instant.
+//! Insertions never link a node inside the prefix, as they only succeed on unmarked links. Once
+//! the prefix is long enough, it is unlinked from the head in one step, and its nodes are freed
+//! with epoch-based reclamation once no thread can still hold a reference to them. (Lindén and
+//! Jonsson, *A skiplist-based concurrent priority queue with minimal memory contention*, 2013,
+//! after Lotan and Shavit, *Skiplist-based concurrent priority queues*, 2000.)
+
+use crate::{Compare, MaxComparator};
+use core::cmp::Ordering as CmpOrdering;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+use std::boxed::Box;
+
+// maximum number of levels of the skiplist
+const MAX_HEIGHT: usize = 32;
+
+// length of the prefix of popped nodes from which a pop tries to unlink it
+const MAX_PREFIX: usize = 32;
+
+// node of the skiplist, with its links at each level; a link at the bottom level is marked
+// (tag 1) when the node it points to is popped
+struct Node<T> {
+    value: T,
+    seq: u64,                       // insertion number, ordering elements comparing equal
+    inserting: AtomicBool,          // whether the node is still being linked at the upper levels
+    next: Box<[Atomic<Node<T>>]>,   // links at each level, from the bottom one
+}
+
+impl<T> Node<T> {
+    // whether the next node at the bottom level is popped, which implies that this one is too
+    // (the popped nodes forming a prefix of the list)
+    #[inline]
+    fn is_popped_before(&self, guard: &Guard) -> bool {
+        self.next[0].load(Ordering::Acquire, guard).tag() == 1
+    }
+}
+
+// links from a node, or from the head of the skiplist
+type Tower<T> = [Atomic<Node<T>>];
+
+// predecessors and successors of a position at each level, with the last popped node before it
+type Search<'g, T> = ([&'g Tower<T>; MAX_HEIGHT], [Shared<'g, Node<T>>; MAX_HEIGHT], Shared<'g, Node<T>>);
+
+/// A lock-free concurrent priority queue
+///
+/// `insert` takes $O(\log n)$ expected time without contention and `pop` $O(1)$ amortized time,
+/// and threads never wait for each other. Both operations are linearizable: `pop` returns the
+/// largest element in the queue at the instant it claims it, which is a single atomic operation.
+/// Elements comparing equal are popped in insertion order.
+///
+/// Popped elements are cloned out of their nodes, which other threads may still be comparing
+/// against; the nodes are freed later, with epoch-based reclamation. As they may then be dropped on
+/// any thread, even after the queue, the elements must be `Send + 'static`:
+///
+/// ```compile_fail
+/// use binary_heap::SkipListQueue;
+/// use std::rc::Rc;
+///
+/// let queue = SkipListQueue::new();
+/// queue.insert(Rc::new(1));
+/// ```
+///
+/// Only available with the `concurrent` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{MinComparator, SkipListQueue};
+///
+/// // best asks first
+/// let asks = SkipListQueue::with_comparator(MinComparator);
+/// std::thread::scope(|scope| {
+///     for trader in 0..4 {
+///         let asks = &asks;
+///         scope.spawn(move || {
+///             for price in 0..50 {
+///                 asks.insert(1000 + 4 * price + trader);
+///             }
+///         });
+///     }
+/// });
+///
+/// assert_eq!(200, asks.size());
+/// assert_eq!(Some(1000), asks.pop());
+/// assert_eq!(Some(1001), asks.pop());
+/// ```
+pub struct SkipListQueue<T: Send + 'static, C = MaxComparator> {
+    head: [Atomic<Node<T>>; MAX_HEIGHT],    // links from the head, at each level
+    seq: AtomicU64,                         // number of insertions
+    size: AtomicUsize,                      // number of elements
+    comparator: C,                          // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd + Clone + Send + 'static> SkipListQueue<T> {
+
+    /// Create a new empty `SkipListQueue`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        SkipListQueue::with_comparator(MaxComparator)
+    }
+}
+
+impl<T: Clone + Send + 'static, C: Compare<T>> SkipListQueue<T, C> {
+
+    /// Create a new empty `SkipListQueue` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        SkipListQueue {
+            head: core::array::from_fn(|_| Atomic::null()),
+            seq: AtomicU64::new(0),
+            size: AtomicUsize::new(0),
+            comparator,
+        }
+    }
+
+    /// Get the size of the queue (number of elements)
+    ///
+    /// With concurrent operations, the result may be out of date as soon as it is returned.
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Insert an element in the queue
+    ///
+    /// The element is inserted when it is linked at the bottom level of the skiplist.
+    ///
+    /// Expected complexity: $O(\log n)$, where $n$ is the number of elements in the queue, without
+    /// contention.
+    pub fn insert(&self, x: T) {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let height = height(seq);
+        let guard = &epoch::pin();
+        let node = Owned::new(Node {
+            value: x,
+            seq,
+            inserting: AtomicBool::new(true),
+            next: (0..height).map(|_| Atomic::null()).collect(),
+        }).into_shared(guard);
+        // SAFETY: the node is not freed while it is being inserted
+        let n = unsafe { node.deref() };
+        self.size.fetch_add(1, Ordering::AcqRel);
+
+        // link the node at the bottom level, which inserts the element
+        let (mut preds, mut succs, mut popped) = loop {
+            let (preds, succs, popped) = self.find(&n.value, seq, guard);
+            n.next[0].store(succs[0], Ordering::Relaxed);
+            if preds[0][0].compare_exchange(succs[0], node, Ordering::AcqRel, Ordering::Acquire, guard).is_ok() {
+                break (preds, succs, popped);
+            }
+        };
+
+        // link it at the upper levels, unless it or its successor is popped
+        let mut level = 1;
+        while level < height {
+            n.next[level].store(succs[level], Ordering::Release);
+            // SAFETY: nodes reachable while the guard is held are not freed before it is dropped
+            let succ_popped = unsafe { succs[level].as_ref() }.is_some_and(|s| s.is_popped_before(guard));
+            if n.is_popped_before(guard) || succ_popped || (!popped.is_null() && popped == succs[level]) {
+                break;
+            }
+            if preds[level][level]
+                .compare_exchange(succs[level], node, Ordering::AcqRel, Ordering::Acquire, guard)
+                .is_ok()
+            {
+                level += 1;
+            } else {
+                (preds, succs, popped) = self.find(&n.value, seq, guard);
+                if succs[0] != node {
+                    break;
+                }
+            }
+        }
+        n.inserting.store(false, Ordering::Release);
+    }
+
+    /// Remove and return (a clone of) the largest element (or `None` if the queue is empty)
+    ///
+    /// Amortized complexity: $O(1)$ without contention.
+    pub fn pop(&self) -> Option<T> {
+        let guard = &epoch::pin();
+        let observed_head = self.head[0].load(Ordering::Acquire, guard);
+
+        // walk through the popped prefix, marking the link after it to claim the next node
+        let mut link = &self.head[0];
+        let mut prefix = 0;
+        let mut new_head = Shared::null();
+        let claimed = loop {
+            if link.load(Ordering::Acquire, guard).is_null() {
+                return None;
+            }
+            // a link which is not null never becomes null
+            let next = link.fetch_or(1, Ordering::AcqRel, guard);
+            prefix += 1;
+            if next.tag() == 0 {
+                break next;
+            }
+            // SAFETY: nodes reachable while the guard is held are not freed before it is dropped
+            let n = unsafe { next.with_tag(0).deref() };
+            if new_head.is_null() && n.inserting.load(Ordering::Acquire) {
+                // the nodes still being inserted are kept, as well as those after them
+                new_head = next.with_tag(0);
+            }
+            link = &n.next[0];
+        };
+        // SAFETY: the claimed node is kept at least until the next nodes are popped
+        let value = unsafe { claimed.deref() }.value.clone();
+        self.size.fetch_sub(1, Ordering::AcqRel);
+        if new_head.is_null() {
+            new_head = claimed;
+        }
+
+        // unlink the prefix up to the new head, if it is long enough and no other thread did
+        if prefix > MAX_PREFIX
+            && self.head[0].load(Ordering::Acquire, guard) == observed_head
+            && self.head[0]
+                .compare_exchange(observed_head, new_head.with_tag(1), Ordering::AcqRel, Ordering::Acquire, guard)
+                .is_ok()
+        {
+            self.restructure(guard);
+            let mut curr = observed_head.with_tag(0);
+            while curr != new_head {
+                // SAFETY: the node is only freed below, by the thread which unlinked it
+                let next = unsafe { curr.deref() }.next[0].load(Ordering::Acquire, guard);
+                // SAFETY: the node can no longer be reached from the head
+                unsafe { guard.defer_destroy(curr) };
+                curr = next.with_tag(0);
+            }
+        }
+        Some(value)
+    }
+
+    // whether the element of `node` comes before `value`, inserted with the number `seq`
+    #[inline]
+    fn precedes(&self, node: &Node<T>, value: &T, seq: u64) -> bool {
+        match self.comparator.compare(&node.value, value) {
+            Some(CmpOrdering::Greater) => true,
+            Some(CmpOrdering::Less) => false,
+            _ => node.seq < seq,
+        }
+    }
+
+    // find, at each level, the last node before `value`, inserted with the number `seq`, and the
+    // next one, skipping the popped nodes; also return the last popped node met at the bottom
+    // level (or a null pointer)
+    fn find<'g>(&'g self, value: &T, seq: u64, guard: &'g Guard) -> Search<'g, T> {
+        let mut preds: [&Tower<T>; MAX_HEIGHT] = [&self.head; MAX_HEIGHT];
+        let mut succs = [Shared::null(); MAX_HEIGHT];
+        let mut popped = Shared::null();
+        let mut pred: &Tower<T> = &self.head;
+        for level in (0..MAX_HEIGHT).rev() {
+            let mut next = pred[level].load(Ordering::Acquire, guard);
+            // SAFETY: nodes reachable while the guard is held are not freed before it is dropped
+            while let Some(c) = unsafe { next.with_tag(0).as_ref() } {
+                let is_popped = level == 0 && next.tag() == 1;
+                if !is_popped && !c.is_popped_before(guard) && !self.precedes(c, value, seq) {
+                    break;
+                }
+                if is_popped {
+                    popped = next.with_tag(0);
+                }
+                pred = &c.next;
+                next = c.next[level].load(Ordering::Acquire, guard);
+            }
+            preds[level] = pred;
+            succs[level] = next.with_tag(0);
+        }
+        (preds, succs, popped)
+    }
+
+    // move the upper links of the head past the popped nodes
+    fn restructure(&self, guard: &Guard) {
+        let mut pred: &Tower<T> = &self.head;
+        let mut level = MAX_HEIGHT - 1;
+        while level > 0 {
+            let first = self.head[level].load(Ordering::Acquire, guard);
+            // SAFETY: nodes reachable while the guard is held are not freed before it is dropped
+            if !unsafe { first.as_ref() }.is_some_and(|f| f.is_popped_before(guard)) {
+                level -= 1;
+                continue;
+            }
+            let mut curr = pred[level].load(Ordering::Acquire, guard);
+            // SAFETY: as above
+            while let Some(c) = unsafe { curr.as_ref() } {
+                if !c.is_popped_before(guard) {
+                    break;
+                }
+                pred = &c.next;
+                curr = c.next[level].load(Ordering::Acquire, guard);
+            }
+            if self.head[level].compare_exchange(first, curr, Ordering::AcqRel, Ordering::Acquire, guard).is_ok() {
+                level -= 1;
+            }
+        }
+    }
+}
+
+// number of levels of the node inserted with the number `seq`, the probability of at least
+// `k + 1` levels being `2^-k`
+#[inline]
+fn height(seq: u64) -> usize {
+    // SplitMix64 finalizer, spreading the bits of `seq`
+    let mut z = seq.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+    1 + (z.trailing_ones() as usize).min(MAX_HEIGHT - 1)
+}
+
+impl<T: Clone + Send + 'static, C: Compare<T> + Default> core::default::Default for SkipListQueue<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T: Send + 'static, C> core::fmt::Debug for SkipListQueue<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SkipListQueue").field("size", &self.size.load(Ordering::Acquire)).finish()
+    }
+}
+
+// with no other thread using the queue, the nodes left are exactly those linked at the bottom
+// level from the head, the others having been unlinked with the prefixes of popped nodes
+impl<T: Send + 'static, C> Drop for SkipListQueue<T, C> {
+    fn drop(&mut self) {
+        // SAFETY: the queue is borrowed mutably, so no other thread can access it
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut curr = self.head[0].load(Ordering::Relaxed, guard).with_tag(0);
+            while !curr.is_null() {
+                let next = curr.deref().next[0].load(Ordering::Relaxed, guard);
+                drop(curr.into_owned());
+                curr = next.with_tag(0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    #[test]
+    fn concurrent_inserts_and_pops() {
+        let queue = SkipListQueue::new();
+        let popped = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for worker in 0..8u64 {
+                let queue = &queue;
+                let popped = &popped;
+                scope.spawn(move || {
+                    for i in 0..2000 {
+                        queue.insert((i * 7919 + worker) % 1000);
+                        if i % 2 == 0 {
+                            popped.lock().unwrap().push(queue.pop().unwrap());
+                        }
+                    }
+                });
+            }
+        });
+        let mut popped = popped.into_inner().unwrap();
+        assert_eq!(16000, popped.len() + queue.size());
+
+        // without concurrent operations, the elements come out in order
+        let rest: Vec<u64> = core::iter::from_fn(|| queue.pop()).collect();
+        assert!(rest.windows(2).all(|pair| pair[0] >= pair[1]));
+        popped.extend(rest);
+        popped.sort_unstable();
+        let mut expected: Vec<u64> = (0..8u64)
+            .flat_map(|worker| (0..2000).map(move |i| (i * 7919 + worker) % 1000))
+            .collect();
+        expected.sort_unstable();
+        assert_eq!(expected, popped);
+    }
+
+    #[test]
+    fn against_binary_heap() {
+        // long runs of pops, so that the prefixes of popped nodes are unlinked
+        let mut next = crate::testing::lcg(12345);
+        let queue = SkipListQueue::new();
+        let mut reference = crate::BinaryHeap::new();
+        for _ in 0..20_000 {
+            if next() % 5 < 2 {
+                assert_eq!(reference.pop(), queue.pop());
+            } else {
+                let x = next() % 1000;
+                queue.insert(x);
+                reference.insert(x);
+            }
+            assert_eq!(reference.size(), queue.size());
+        }
+        assert_eq!(reference.to_vec(), core::iter::from_fn(|| queue.pop()).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn equal_elements_in_insertion_order() {
+        let queue = SkipListQueue::with_comparator(crate::KeyComparator(|x: &(u8, u32)| x.0));
+        for i in 0..100 {
+            queue.insert((i as u8 % 3, i));
+        }
+        let popped: Vec<(u8, u32)> = core::iter::from_fn(|| queue.pop()).collect();
+        for key in 0..3 {
+            let order: Vec<u32> = popped.iter().filter(|x| x.0 == key).map(|x| x.1).collect();
+            assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn no_leak() {
+        let counter = Arc::new(());
+        {
+            let queue = SkipListQueue::with_comparator(crate::KeyComparator(|x: &(u32, Arc<()>)| x.0));
+            std::thread::scope(|scope| {
+                for worker in 0..4 {
+                    let queue = &queue;
+                    let counter = &counter;
+                    scope.spawn(move || {
+                        for i in 0..500 {
+                            queue.insert((4 * i + worker, counter.clone()));
+                            if i % 3 == 0 {
+                                queue.pop();
+                            }
+                        }
+
+                        // move the garbage of the thread to the global queue before it exits
+                        epoch::pin().flush();
+                    });
+                }
+            });
+        }
+
+        // the popped nodes are freed once the epoch advances
+        for _ in 0..1000 {
+            epoch::pin().flush();
+            if Arc::strong_count(&counter) == 1 {
+                break;
+            }
+        }
+        assert_eq!(1, Arc::strong_count(&counter));
+    }
+
+    // element counting its drops
+    #[derive(Clone)]
+    struct Counted(u32, Arc<AtomicUsize>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.1.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn drops() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        {
+            let queue = SkipListQueue::with_comparator(crate::KeyComparator(|x: &Counted| x.0));
+            for i in 0..100 {
+                queue.insert(Counted(i, drops.clone()));
+            }
+
+            // each popped clone is dropped once here, and its node once it is freed
+            for i in (50..100).rev() {
+                assert_eq!(i, queue.pop().unwrap().0);
+            }
+        }
+
+        // the 50 elements left are dropped with the queue
+        for _ in 0..1000 {
+            epoch::pin().flush();
+            if drops.load(Ordering::Relaxed) == 150 {
+                break;
+            }
+        }
+        assert_eq!(150, drops.load(Ordering::Relaxed));
+        assert_eq!(1, Arc::strong_count(&drops));
+    }
+}
@@ -0,0 +1,186 @@
+//! Smoothsort
+//!
+//! Smoothsort is a heapsort on a list of *Leonardo heaps*: heap-ordered trees whose sizes are
+//! Leonardo numbers ($L_0 = L_1 = 1$ and $L_{k+2} = L_{k+1} + L_k + 1$), stored one after the
+//! other in the slice, each in post-order, with roots in non-decreasing order from left to
+//! right. The slice is turned into such a list from left to right, then the largest root, always
+//! the last element, is left in place while the list shrinks from right to left. On sorted or
+//! nearly sorted inputs, few elements move and the sort takes close to linear time, against
+//! $O(n \log n)$ in the worst case. (Dijkstra, *Smoothsort, an alternative for sorting in situ*,
+//! 1981.)
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+// Leonardo numbers, saturating at `usize::MAX`
+const LEONARDO: [usize; 92] = {
+    let mut numbers: [usize; 92] = [1; 92];
+    let mut k = 2;
+    while k < numbers.len() {
+        numbers[k] = numbers[k - 1].saturating_add(numbers[k - 2]).saturating_add(1);
+        k += 1;
+    }
+    numbers
+};
+
+/// Sort a slice in increasing order with smoothsort
+///
+/// The sort is in place and not stable. Incomparable elements are treated as equal.
+///
+/// Worst-case complexity: $O(n \log n)$ comparisons, where $n$ is the length of the slice, and
+/// $O(n)$ for a sorted slice.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::smoothsort;
+///
+/// // nearly sorted timestamps
+/// let mut timestamps = vec![1, 2, 4, 3, 5, 6, 8, 7, 9];
+/// smoothsort(&mut timestamps);
+///
+/// assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], timestamps);
+/// ```
+pub fn smoothsort<T: PartialOrd>(values: &mut [T]) {
+    sort_by_less(values, |a, b| a < b);
+}
+
+/// Sort a slice in increasing order for the order defined by `compare`, with smoothsort
+///
+/// The sort is in place and not stable.
+///
+/// Worst-case complexity: $O(n \log n)$ calls to `compare`, where $n$ is the length of the
+/// slice, and $O(n)$ for a sorted slice.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::smoothsort_by;
+///
+/// let mut words = vec!["heap", "a", "leonardo", "tree"];
+/// smoothsort_by(&mut words, |a, b| a.len().cmp(&b.len()));
+///
+/// assert_eq!("a", words[0]);
+/// assert_eq!("leonardo", words[3]);
+/// ```
+pub fn smoothsort_by<T, F: FnMut(&T, &T) -> Ordering>(values: &mut [T], mut compare: F) {
+    sort_by_less(values, |a, b| compare(a, b) == Ordering::Less);
+}
+
+fn sort_by_less<T, F: FnMut(&T, &T) -> bool>(values: &mut [T], mut less: F) {
+    // orders of the Leonardo heaps, from left to right
+    let mut orders: Vec<usize> = Vec::new();
+
+    // build the list of heaps, adding one element at a time
+    for root in 0..values.len() {
+        match orders[..] {
+            [.., a, b] if a == b + 1 => {
+                orders.truncate(orders.len() - 2);
+                orders.push(a + 1);
+            },
+            [.., 1] => orders.push(0),
+            _ => orders.push(1),
+        }
+        rectify(values, &orders, orders.len() - 1, root, &mut less);
+    }
+
+    // remove the last root, the largest element, at each step
+    for root in (0..values.len()).rev() {
+        let order = orders.pop().unwrap();
+        if order >= 2 {
+            // the children become heaps of orders `order - 1` and `order - 2`
+            let left = root - 1 - LEONARDO[order - 2];
+            orders.push(order - 1);
+            rectify(values, &orders, orders.len() - 1, left, &mut less);
+            orders.push(order - 2);
+            rectify(values, &orders, orders.len() - 1, root - 1, &mut less);
+        }
+    }
+}
+
+// move the root at position `root` of the heap of index `index` to the left among the roots,
+// until they are in non-decreasing order, then sift it down in its heap
+fn rectify<T, F: FnMut(&T, &T) -> bool>(
+    values: &mut [T], orders: &[usize], mut index: usize, mut root: usize, less: &mut F)
+{
+    while index > 0 {
+        let previous = root - LEONARDO[orders[index]];
+        if !less(&values[root], &values[previous]) {
+            break;
+        }
+
+        // stop if a child is not smaller than the previous root: sifting it up is enough
+        let order = orders[index];
+        if order >= 2 {
+            let right = root - 1;
+            let left = right - LEONARDO[order - 2];
+            if !less(&values[left], &values[previous]) || !less(&values[right], &values[previous]) {
+                break;
+            }
+        }
+        values.swap(root, previous);
+        root = previous;
+        index -= 1;
+    }
+    sift_down(values, root, orders[index], less);
+}
+
+// ‘bubble down’ the root at position `root` of a heap of order `order`
+fn sift_down<T, F: FnMut(&T, &T) -> bool>(
+    values: &mut [T], mut root: usize, mut order: usize, less: &mut F)
+{
+    while order >= 2 {
+        let right = root - 1;
+        let left = right - LEONARDO[order - 2];
+        let (child, child_order) = if less(&values[left], &values[right]) {
+            (right, order - 2)
+        } else {
+            (left, order - 1)
+        };
+        if !less(&values[root], &values[child]) {
+            break;
+        }
+        values.swap(root, child);
+        root = child;
+        order = child_order;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+
+    #[test]
+    fn random_slices() {
+        let mut next = lcg(12345);
+        for len in (0..200).chain([1000, 4321]) {
+            let mut values: Vec<u64> = (0..len).map(|_| next() % 100).collect();
+            let mut expected = values.clone();
+            expected.sort_unstable();
+            smoothsort(&mut values);
+            assert_eq!(expected, values);
+        }
+    }
+
+    #[test]
+    fn adaptive() {
+        let n = 100_000;
+        for values in [(0..n).collect::<Vec<u32>>(), (0..n).map(|x| x ^ 1).collect()] {
+            let mut count = 0;
+            let mut sorted = values.clone();
+            smoothsort_by(&mut sorted, |a, b| { count += 1; a.cmp(b) });
+            assert!(sorted.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(count <= 6 * n as usize);
+        }
+    }
+
+    #[test]
+    fn reversed() {
+        let mut values: Vec<i32> = (0..10_000).rev().collect();
+        smoothsort_by(&mut values, |a, b| b.cmp(a));
+        assert_eq!((0..10_000).rev().collect::<Vec<i32>>(), values);
+        smoothsort(&mut values);
+        assert_eq!((0..10_000).collect::<Vec<i32>>(), values);
+    }
+}
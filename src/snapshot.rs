@@ -0,0 +1,381 @@
+//! Binary snapshots of a heap
+//!
+//! A snapshot is made of (all integers being encoded in little-endian order):
+//!
+//! * the magic number [`MAGIC`] (4 bytes),
+//! * the format version, as a `u16` (currently [`FORMAT_VERSION`]),
+//! * the number of elements, as a `u64`,
+//! * the elements, in the order of the array representation of the heap,
+//! * the CRC-32 (IEEE) checksum of all the previous bytes, as a `u32`.
+//!
+//! Each element is encoded by its implementation of [`SnapshotElement`], which is provided for
+//! primitive numeric types, `bool`, `char`, `String`, and pairs; other types can implement it to
+//! plug in their own codec.
+//!
+//! Reading a snapshot fails with an error of kind `InvalidData` if the magic number is wrong, if
+//! the version is not supported, or if the checksum does not match, and with an error of kind
+//! `UnexpectedEof` if the snapshot is truncated. Later versions of the crate will keep reading
+//! snapshots written with older format versions.
+
+use crate::BinaryHeap;
+use std::io::{Error, ErrorKind, Read, Result, Write};
+
+/// Magic number at the start of every snapshot
+pub const MAGIC: [u8; 4] = *b"BHSN";
+
+/// Version of the snapshot format written by this version of the crate
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Maximum number of elements for which memory is reserved before they are actually read, so
+/// that a corrupted length does not trigger a huge allocation
+const MAX_PREALLOCATED: usize = 1 << 16;
+
+/// A type whose values can be written to and read back from a snapshot
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::snapshot::SnapshotElement;
+/// use std::io::{Read, Result, Write};
+///
+/// // a task identified by its priority
+/// #[derive(PartialEq, PartialOrd)]
+/// struct Task {
+///     priority: u32,
+/// }
+///
+/// impl SnapshotElement for Task {
+///     fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+///         self.priority.write_to(writer)
+///     }
+///
+///     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+///         Ok(Task { priority: u32::read_from(reader)? })
+///     }
+/// }
+/// ```
+pub trait SnapshotElement: Sized {
+
+    /// Write the encoding of `self` to `writer`
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<()>;
+
+    /// Read a value from `reader`
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self>;
+}
+
+// fixed-size little-endian encoding of numeric types
+macro_rules! impl_snapshot_element_numeric {
+    ($($t:ty),*) => {
+        $(
+            impl SnapshotElement for $t {
+                fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+
+                fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                    let mut bytes = [0u8; std::mem::size_of::<$t>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$t>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_snapshot_element_numeric!(u8, u16, u32, u64, u128, usize,
+                               i8, i16, i32, i64, i128, isize,
+                               f32, f64);
+
+impl SnapshotElement for bool {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        (*self as u8).write_to(writer)
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match u8::read_from(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::new(ErrorKind::InvalidData, "invalid boolean")),
+        }
+    }
+}
+
+impl SnapshotElement for char {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        (*self as u32).write_to(writer)
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        char::from_u32(u32::read_from(reader)?)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid character"))
+    }
+}
+
+// length-prefixed UTF-8 bytes
+impl SnapshotElement for String {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        (self.len() as u64).write_to(writer)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let len = read_len(reader)?;
+        let mut bytes = Vec::<u8>::new();
+        reader.take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "truncated string"));
+        }
+        String::from_utf8(bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+impl<A: SnapshotElement, B: SnapshotElement> SnapshotElement for (A, B) {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.write_to(writer)?;
+        self.1.write_to(writer)
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok((A::read_from(reader)?, B::read_from(reader)?))
+    }
+}
+
+// lookup table for the CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320)
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+// running CRC-32 checksum
+struct Crc32(u32);
+
+impl Crc32 {
+    fn new() -> Self {
+        Crc32(0xFFFF_FFFF)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = CRC_TABLE[((self.0 ^ byte as u32) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+// writer computing the checksum of the bytes written through it
+struct ChecksumWriter<W: Write> {
+    inner: W,
+    crc: Crc32,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.crc.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+// reader computing the checksum of the bytes read through it
+struct ChecksumReader<R: Read> {
+    inner: R,
+    crc: Crc32,
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.crc.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+// read a length encoded as a `u64`, checking that it fits in a `usize`
+fn read_len<R: Read>(reader: &mut R) -> Result<usize> {
+    usize::try_from(u64::read_from(reader)?)
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "length too large"))
+}
+
+impl<T: std::cmp::PartialOrd + SnapshotElement> BinaryHeap<T> {
+
+    /// Write a snapshot of the heap to `writer`
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from_vec(&[1u32, 2, 3]);
+    /// let mut bytes = Vec::<u8>::new();
+    /// heap.write_snapshot(&mut bytes).unwrap();
+    ///
+    /// // header, elements, and checksum
+    /// assert_eq!(14 + 3 * 4 + 4, bytes.len());
+    /// ```
+    pub fn write_snapshot<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = ChecksumWriter { inner: writer, crc: Crc32::new() };
+        writer.write_all(&MAGIC)?;
+        FORMAT_VERSION.write_to(&mut writer)?;
+        (self.size() as u64).write_to(&mut writer)?;
+        for x in self.data.iter() {
+            x.write_to(&mut writer)?;
+        }
+        let checksum = writer.crc.finish();
+        checksum.write_to(&mut writer.inner)
+    }
+
+    /// Read a heap from a snapshot
+    ///
+    /// The elements are rearranged if needed so that the result satisfies the heap property,
+    /// even if the snapshot was modified; a snapshot written by `write_snapshot` is read back with
+    /// the same array representation.
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the snapshot.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from_vec(&[1u32, 2, 3]);
+    /// let mut bytes = Vec::<u8>::new();
+    /// heap.write_snapshot(&mut bytes).unwrap();
+    /// let heap_2 = BinaryHeap::<u32>::read_snapshot(bytes.as_slice()).unwrap();
+    ///
+    /// assert_eq!(heap, heap_2);
+    /// ```
+    pub fn read_snapshot<R: Read>(reader: R) -> Result<Self> {
+        let mut reader = ChecksumReader { inner: reader, crc: Crc32::new() };
+
+        // header
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(Error::new(ErrorKind::InvalidData, "not a heap snapshot"));
+        }
+        let version = u16::read_from(&mut reader)?;
+        if version != FORMAT_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported snapshot format version: {}", version)
+            ));
+        }
+
+        // elements
+        let size = read_len(&mut reader)?;
+        let mut heap = BinaryHeap::new();
+        heap.data.reserve(size.min(MAX_PREALLOCATED));
+        for _ in 0..size {
+            heap.data.push(T::read_from(&mut reader)?);
+        }
+
+        // checksum
+        let checksum = reader.crc.finish();
+        if u32::read_from(&mut reader.inner)? != checksum {
+            return Err(Error::new(ErrorKind::InvalidData, "snapshot checksum mismatch"));
+        }
+
+        heap.rebuild();
+        Ok(heap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_layout() {
+        let heap = BinaryHeap::from_vec(&[0i64, 2, -2, 4, 6, 8, -1, 1]);
+        let mut bytes = Vec::<u8>::new();
+        heap.write_snapshot(&mut bytes).unwrap();
+        let heap_2 = BinaryHeap::<i64>::read_snapshot(bytes.as_slice()).unwrap();
+        assert_eq!(heap.data, heap_2.data);
+    }
+
+    #[test]
+    fn round_trip_pairs() {
+        let heap = BinaryHeap::from_vec(&[(2u8, String::from("b")), (3, String::from("ç"))]);
+        let mut bytes = Vec::<u8>::new();
+        heap.write_snapshot(&mut bytes).unwrap();
+        let heap_2 = BinaryHeap::<(u8, String)>::read_snapshot(bytes.as_slice()).unwrap();
+        assert_eq!(heap.data, heap_2.data);
+    }
+
+    #[test]
+    fn truncated() {
+        let heap = BinaryHeap::from_vec(&[1u32, 2, 3]);
+        let mut bytes = Vec::<u8>::new();
+        heap.write_snapshot(&mut bytes).unwrap();
+        bytes.pop();
+        let err = BinaryHeap::<u32>::read_snapshot(bytes.as_slice()).unwrap_err();
+        assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(0xCBF4_3926, crc.finish());
+    }
+
+    #[test]
+    fn repaired() {
+        let mut writer = ChecksumWriter { inner: Vec::<u8>::new(), crc: Crc32::new() };
+        writer.write_all(&MAGIC).unwrap();
+        FORMAT_VERSION.write_to(&mut writer).unwrap();
+        3u64.write_to(&mut writer).unwrap();
+        for x in [1u16, 2, 3] {
+            x.write_to(&mut writer).unwrap();
+        }
+        let checksum = writer.crc.finish();
+        checksum.write_to(&mut writer.inner).unwrap();
+        let heap = BinaryHeap::<u16>::read_snapshot(writer.inner.as_slice()).unwrap();
+        assert_eq!(vec![3, 2, 1], heap.to_vec());
+    }
+
+    #[test]
+    fn corrupted() {
+        let heap = BinaryHeap::from_vec(&[1u32, 2, 3]);
+        let mut bytes = Vec::<u8>::new();
+        heap.write_snapshot(&mut bytes).unwrap();
+
+        // flipped bit in an element
+        let mut corrupted = bytes.clone();
+        corrupted[15] ^= 0x10;
+        let err = BinaryHeap::<u32>::read_snapshot(corrupted.as_slice()).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+
+        // wrong magic number
+        let mut corrupted = bytes.clone();
+        corrupted[0] = b'X';
+        let err = BinaryHeap::<u32>::read_snapshot(corrupted.as_slice()).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+
+        // unknown version
+        let mut corrupted = bytes.clone();
+        corrupted[4] = 2;
+        let err = BinaryHeap::<u32>::read_snapshot(corrupted.as_slice()).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+    }
+}
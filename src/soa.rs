@@ -0,0 +1,251 @@
+//! Key-value heap with a structure-of-arrays layout
+//!
+//! The heap array is split into an array of keys and an array of payload indices, moved together
+//! during sifts, while the payloads stay in an arena (a vector of slots, with a list of free
+//! slots) until their entries are popped.
+
+use crate::{Compare, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A heap of (key, payload) pairs ordered by key, whose payloads are never moved
+///
+/// Sifts only move the keys and 32-bit payload indices, stored in two separate arrays, which
+/// keeps them cheap and cache-friendly when the payloads are large. The payloads live in an
+/// arena, whose slots are reused after pops.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::SoaHeap;
+///
+/// let mut heap = SoaHeap::new();
+/// heap.insert(2, [0u8; 4096]);
+/// heap.insert(7, [7u8; 4096]);
+/// heap.insert(5, [5u8; 4096]);
+///
+/// assert_eq!(Some((&7, &[7u8; 4096])), heap.peek());
+/// let (key, payload) = heap.pop().unwrap();
+/// assert_eq!((7, 7), (key, payload[0]));
+/// assert_eq!(vec![5, 2], heap.to_vec().into_iter().map(|(key, _)| key).collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SoaHeap<K, V, C = MaxComparator> {
+    keys: Vec<K>,               // heap array of keys
+    indices: Vec<u32>,          // heap array of payload indices, parallel to `keys`
+    payloads: Vec<Option<V>>,   // arena of payloads
+    free: Vec<u32>,             // indices of the free slots of the arena
+    comparator: C,              // order of the keys
+}
+
+impl<K: core::cmp::PartialOrd, V> SoaHeap<K, V> {
+
+    /// Create a new empty `SoaHeap`, ordered by the natural order of `K`
+    #[inline]
+    pub fn new() -> Self {
+        SoaHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<K, V, C: Compare<K>> SoaHeap<K, V, C> {
+
+    /// Create a new empty `SoaHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        SoaHeap {
+            keys: Vec::new(),
+            indices: Vec::new(),
+            payloads: Vec::new(),
+            free: Vec::new(),
+            comparator,
+        }
+    }
+
+    /// Get the size of the heap (number of entries)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return an iterator over the keys, in the order of the heap array
+    #[inline]
+    pub fn keys(&self) -> core::slice::Iter<'_, K> {
+        self.keys.iter()
+    }
+
+    /// Insert an entry in the heap
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of entries in the heap
+    /// (amortized, as the arrays may grow).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the heap already holds $2^{32}$ entries.
+    pub fn insert(&mut self, key: K, payload: V) {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.payloads[index as usize] = Some(payload);
+                index
+            },
+            None => {
+                let index = u32::try_from(self.payloads.len()).expect("too many entries");
+                self.payloads.push(Some(payload));
+                index
+            },
+        };
+        self.keys.push(key);
+        self.indices.push(index);
+        self.sift_up(self.size() - 1);
+    }
+
+    /// Return references to the key and payload of the root entry (or `None` if the heap is
+    /// empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<(&K, &V)> {
+        let key = self.keys.first()?;
+        Some((key, self.payloads[self.indices[0] as usize].as_ref().unwrap()))
+    }
+
+    /// Remove and return the root entry (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of entries in the heap.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let last = self.size().checked_sub(1)?;
+        self.swap(0, last);
+        let key = self.keys.pop().unwrap();
+        let index = self.indices.pop().unwrap();
+
+        // free the slot before sifting, so that it is not lost if the comparator panics
+        let payload = self.payloads[index as usize].take().unwrap();
+        self.free.push(index);
+        self.sift_down(0);
+        Some((key, payload))
+    }
+
+    /// Consume the heap and return a vector of all its entries in non-increasing order of keys
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of entries in the heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<(K, V)> {
+        self.collect()
+    }
+
+    // whether the key at position `i` is smaller than the one at position `j`
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        self.comparator.compare(&self.keys[i], &self.keys[j]) == Some(Ordering::Less)
+    }
+
+    // swap the entries at positions `i` and `j`
+    #[inline]
+    fn swap(&mut self, i: usize, j: usize) {
+        self.keys.swap(i, j);
+        self.indices.swap(i, j);
+    }
+
+    // ‘bubble up’ the entry at position `pos`
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) >> 1;
+            if !self.less(parent, pos) {
+                break;
+            }
+            self.swap(parent, pos);
+            pos = parent;
+        }
+    }
+
+    // ‘bubble down’ the entry at position `pos`
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let mut largest = pos;
+            for child in [(pos << 1) + 1, (pos << 1) + 2] {
+                if child < self.size() && self.less(largest, child) {
+                    largest = child;
+                }
+            }
+            if largest == pos {
+                break;
+            }
+            self.swap(pos, largest);
+            pos = largest;
+        }
+    }
+}
+
+impl<K, V, C: Compare<K> + Default> core::default::Default for SoaHeap<K, V, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<K, V, C: Compare<K>> Iterator for SoaHeap<K, V, C> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size(), Some(self.size()))
+    }
+}
+
+impl<K, V, C: Compare<K>> ExactSizeIterator for SoaHeap<K, V, C> {}
+
+impl<K, V, C: Compare<K>> core::iter::FusedIterator for SoaHeap<K, V, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let mut heap = SoaHeap::new();
+        let mut reference = BinaryHeap::new();
+        for _ in 0..5000 {
+            if next().is_multiple_of(3) {
+                let popped = heap.pop();
+                assert_eq!(reference.pop(), popped.as_ref().map(|&(key, _)| key));
+                if let Some((key, payload)) = popped {
+                    assert_eq!(alloc::format!("payload {}", key), payload);
+                }
+            } else {
+                let key = next() % 1000;
+                heap.insert(key, alloc::format!("payload {}", key));
+                reference.insert(key);
+            }
+            assert_eq!(reference.size(), heap.size());
+            assert_eq!(reference.get_max().as_ref(), heap.peek().map(|(key, _)| key));
+            assert_eq!(heap.payloads.len(), heap.size() + heap.free.len());
+        }
+        let keys: Vec<u64> = heap.to_vec().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(reference.to_vec(), keys);
+    }
+
+    #[test]
+    fn panicking_comparator() {
+        // the comparator panics when comparing 0
+        let mut heap = SoaHeap::with_comparator(crate::FnComparator(|a: &u32, b: &u32| {
+            assert!(*a != 0 && *b != 0, "comparator failed");
+            a.cmp(b)
+        }));
+        heap.insert(2, 'a');
+        heap.insert(1, 'b');
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| heap.insert(0, 'c')));
+        assert!(result.is_err());
+
+        // the slot of the popped payload is freed even though sifting the last entry down panics
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| heap.pop()));
+        assert!(result.is_err());
+        assert_eq!(2, heap.size());
+        assert_eq!(heap.payloads.len(), heap.size() + heap.free.len());
+    }
+}
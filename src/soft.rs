@@ -0,0 +1,354 @@
+//! Soft heap, an approximate priority queue
+//!
+//! A soft heap trades exactness for speed: some elements may be *corrupted*, that is, given a key
+//! smaller than themselves (in the order of the heap), under which they are stored and popped, so
+//! that they may be popped after smaller elements. With a corruption parameter
+//! $\varepsilon \in (0, 1]$, the guarantee is the following: at any time, at most $\varepsilon n$
+//! of the elements in the heap are corrupted, where $n$ is the total number of insertions so far.
+//! In exchange, `insert` takes $O(1)$ amortized time and `pop` $O(\log(1 / \varepsilon))$, which
+//! beats the lower bound for exact comparison-based heaps; this makes soft heaps useful for
+//! linear-time selection and approximate sorting.
+//!
+//! Each node of a soft heap holds a list of elements sharing a common key, not larger than any of
+//! them, and taken from the list of a child when the node is refilled. Nodes of rank at most
+//! $r = \lceil \log_2(1 / \varepsilon) \rceil + 5$ hold a single element, and those of higher
+//! ranks are refilled with the lists of their children until they reach a target size, growing by
+//! a factor $3/2$ per rank; merging lists is what corrupts elements. (Chazelle, *The soft heap:
+//! an approximate priority queue with optimal error rate*, 2000; the implementation follows Kaplan
+//! and Zwick, *A simpler implementation and analysis of Chazelle's soft heaps*, 2009.)
+//!
+//! # Example
+//!
+//! ```
+//! use binary_heap::soft::SoftHeap;
+//!
+//! let mut heap = SoftHeap::new(0.25);
+//! for x in [5, 1, 9, 3, 7, 2, 8] {
+//!     heap.insert(x);
+//! }
+//!
+//! // the popped element is never smaller than the key it was stored under
+//! let key = *heap.peek_key().unwrap();
+//! let x = heap.pop().unwrap();
+//! assert!(x >= key);
+//! assert_eq!(6, heap.size());
+//! ```
+
+use crate::{Compare, MaxComparator};
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::cmp::Ordering;
+
+// node of a tree
+#[derive(Clone, Debug)]
+struct Node<T> {
+    list: Vec<T>,       // elements stored at this node
+    key: Option<T>,     // common key of the elements of the list, not larger than any of them
+    rank: usize,
+    target: usize,      // target size of the list
+    left: Option<Box<Node<T>>>,
+    right: Option<Box<Node<T>>>,
+}
+
+impl<T> Node<T> {
+
+    #[inline]
+    fn is_leaf(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
+
+    // common key of the elements of the list, which must not be empty
+    #[inline]
+    fn key(&self) -> &T {
+        self.key.as_ref().unwrap()
+    }
+}
+
+// refill the list of `node` with those of its descendants, until it reaches its target size or
+// the node becomes a leaf
+fn sift<T, C: Compare<T>>(comparator: &C, node: &mut Node<T>) {
+    while node.list.len() < node.target && !node.is_leaf() {
+
+        // take the list of the child with the largest key
+        let swap = match (&node.left, &node.right) {
+            (Some(left), Some(right)) => {
+                comparator.compare(left.key(), right.key()) == Some(Ordering::Less)
+            },
+            (left, _) => left.is_none(),
+        };
+        if swap {
+            core::mem::swap(&mut node.left, &mut node.right);
+        }
+        let child = node.left.as_mut().unwrap();
+        let mut list = core::mem::take(&mut child.list);
+        if list.len() < node.list.len() {
+            node.list.append(&mut list);
+        } else {
+            list.append(&mut node.list);
+            node.list = list;
+        }
+        node.key = child.key.take();
+
+        // remove the child if it has no descendants to refill its list with
+        if child.is_leaf() {
+            node.left = None;
+        } else {
+            sift(comparator, child);
+        }
+    }
+}
+
+/// An approximate priority queue, in which a fraction of the elements may be corrupted
+///
+/// See the [module documentation](self) for the corruption guarantee.
+#[derive(Clone, Debug)]
+pub struct SoftHeap<T, C = MaxComparator> {
+    trees: Vec<Option<Box<Node<T>>>>,   // trees indexed by the rank of their root
+    best: Vec<Option<usize>>,           // rank of the root with the largest key among the ranks
+                                        // not smaller than each index
+    size: usize,                        // number of elements
+    max_exact_rank: usize,              // largest rank of the nodes holding a single element
+    comparator: C,                      // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd + Clone> SoftHeap<T> {
+
+    /// Create a new empty `SoftHeap` with corruption parameter `epsilon`, ordered by the natural
+    /// order of `T`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` is not in $(0, 1]$.
+    #[inline]
+    pub fn new(epsilon: f64) -> Self {
+        SoftHeap::with_comparator(epsilon, MaxComparator)
+    }
+}
+
+impl<T: Clone, C: Compare<T>> SoftHeap<T, C> {
+
+    /// Create a new empty `SoftHeap` with corruption parameter `epsilon`, ordered by `comparator`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` is not in $(0, 1]$.
+    pub fn with_comparator(epsilon: f64, comparator: C) -> Self {
+        assert!(epsilon > 0.0 && epsilon <= 1.0, "the corruption parameter must be in (0, 1]");
+        let mut max_exact_rank = 5;
+        let mut power = 1.0;
+        while power * epsilon < 1.0 {
+            power *= 2.0;
+            max_exact_rank += 1;
+        }
+        SoftHeap { trees: Vec::new(), best: Vec::new(), size: 0, max_exact_rank, comparator }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Amortized complexity: $O(1)$.
+    pub fn insert(&mut self, x: T) {
+        let key = Some(x.clone());
+        let node = Node { list: vec![x], key, rank: 0, target: 1, left: None, right: None };
+        self.add_tree(Box::new(node));
+        self.size += 1;
+    }
+
+    /// Return a reference to the largest key (or `None` if the heap is empty)
+    ///
+    /// This is the key of the element `pop` would return, which is not larger than it, and not
+    /// smaller than the key of any element of the heap.
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek_key(&self) -> Option<&T> {
+        let rank = self.best.first().copied().flatten()?;
+        self.trees[rank].as_ref().map(|root| root.key())
+    }
+
+    /// Remove and return an element with the largest key (or `None` if the heap is empty)
+    ///
+    /// If some elements are corrupted, the returned element may be smaller than one of them.
+    ///
+    /// Amortized complexity: $O(\log(1 / \varepsilon))$.
+    pub fn pop(&mut self) -> Option<T> {
+        let rank = self.best.first().copied().flatten()?;
+        let root = self.trees[rank].as_mut().unwrap();
+        let x = root.list.pop().unwrap();
+        self.size -= 1;
+
+        // refill the list when it is down to half its target size
+        if 2 * root.list.len() <= root.target {
+            if !root.is_leaf() {
+                sift(&self.comparator, root);
+                self.update_best(rank);
+            } else if root.list.is_empty() {
+                self.trees[rank] = None;
+                self.update_best(rank);
+            }
+        }
+        Some(x)
+    }
+
+    /// Move all the elements of `other` into this heap (the two heaps are merged)
+    ///
+    /// The corruption parameter of this heap is kept; `other` should have been created with the
+    /// same one.
+    ///
+    /// Worst-case complexity: $O(\log n + \log m)$, where $n$ and $m$ are the numbers of elements
+    /// in the two heaps.
+    pub fn meld(&mut self, other: SoftHeap<T, C>) {
+        self.size += other.size;
+        for tree in other.trees.into_iter().flatten() {
+            self.add_tree(tree);
+        }
+    }
+
+    // add a tree to the heap, combining trees of equal ranks as in a binary addition
+    fn add_tree(&mut self, mut tree: Box<Node<T>>) {
+        let mut rank = tree.rank;
+        loop {
+            if rank >= self.trees.len() {
+                self.trees.resize_with(rank + 1, || None);
+                self.best.resize(rank + 1, None);
+            }
+            match self.trees[rank].take() {
+                Some(other) => {
+                    tree = self.combine(other, tree);
+                    rank += 1;
+                },
+                None => break,
+            }
+        }
+        self.trees[rank] = Some(tree);
+        self.update_best(rank);
+    }
+
+    // combine two trees of the same rank under a new root
+    fn combine(&self, x: Box<Node<T>>, y: Box<Node<T>>) -> Box<Node<T>> {
+        let rank = x.rank + 1;
+        let target = if rank <= self.max_exact_rank { 1 } else { (3 * x.target).div_ceil(2) };
+        let mut node = Node { list: Vec::new(), key: None, rank, target, left: Some(x), right: Some(y) };
+        sift(&self.comparator, &mut node);
+        Box::new(node)
+    }
+
+    // recompute the ranks of the best roots for the ranks up to `rank`
+    fn update_best(&mut self, rank: usize) {
+        for i in (0..=rank).rev() {
+            let next = self.best.get(i + 1).copied().flatten();
+            self.best[i] = match (&self.trees[i], next) {
+                (Some(root), Some(j)) => {
+                    let best = self.trees[j].as_ref().unwrap();
+                    if self.comparator.compare(root.key(), best.key()) == Some(Ordering::Less) {
+                        Some(j)
+                    } else {
+                        Some(i)
+                    }
+                },
+                (Some(_), None) => Some(i),
+                (None, next) => next,
+            };
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T>> Iterator for SoftHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
+
+impl<T: Clone, C: Compare<T>> ExactSizeIterator for SoftHeap<T, C> {}
+
+impl<T: Clone, C: Compare<T>> core::iter::FusedIterator for SoftHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+
+    // check the order of the keys in the subtree of `node`, returning the number of elements and
+    // the number of corrupted ones
+    fn check_tree(node: &Node<u64>) -> (usize, usize) {
+        let key = *node.key();
+        assert!(node.list.iter().all(|&x| x >= key));
+        let mut counts = (node.list.len(), node.list.iter().filter(|&&x| x > key).count());
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            assert!(*child.key() <= key);
+            let (size, corrupted) = check_tree(child);
+            counts.0 += size;
+            counts.1 += corrupted;
+        }
+        counts
+    }
+
+    // number of corrupted elements in the heap, after checking its structure
+    fn corrupted(heap: &SoftHeap<u64>) -> usize {
+        let mut counts = (0, 0);
+        for root in heap.trees.iter().flatten() {
+            let (size, corrupted) = check_tree(root);
+            counts.0 += size;
+            counts.1 += corrupted;
+            assert!(root.key() <= heap.peek_key().unwrap());
+        }
+        assert_eq!(heap.size(), counts.0);
+        counts.1
+    }
+
+    #[test]
+    fn corruption_bound() {
+        let mut next = lcg(12345);
+        let epsilon = 0.125;
+        let mut heap = SoftHeap::new(epsilon);
+        let mut insertions = 0;
+        for _ in 0..20_000 {
+            if next().is_multiple_of(3) {
+                if let Some(key) = heap.peek_key().copied() {
+                    assert!(heap.pop().unwrap() >= key);
+                }
+            } else {
+                heap.insert(next() % 100_000);
+                insertions += 1;
+            }
+            if insertions % 1000 == 0 {
+                assert!(corrupted(&heap) as f64 <= epsilon * insertions as f64);
+            }
+        }
+        assert!(corrupted(&heap) > 0);
+        assert_eq!(heap.size(), heap.count());
+    }
+
+    #[test]
+    fn exact_for_small_heaps() {
+        // with fewer than 2^6 elements, all nodes hold a single element
+        let values: Vec<u64> = (0..60).map(|x| (x * 37) % 61).collect();
+        let mut heap = SoftHeap::new(1.0);
+        let mut reference = BinaryHeap::new();
+        for (i, &x) in values.iter().enumerate() {
+            heap.insert(x);
+            reference.insert(x);
+            if i % 3 == 2 {
+                assert_eq!(reference.pop(), heap.pop());
+            }
+        }
+        let mut other = SoftHeap::new(1.0);
+        other.insert(100);
+        heap.meld(other);
+        reference.insert(100);
+        assert_eq!(reference.to_vec(), heap.collect::<Vec<u64>>());
+    }
+}
@@ -0,0 +1,271 @@
+//! Wait-free single-producer, single-consumer priority queue with a fixed capacity
+//!
+//! The producer writes its elements to a ring buffer, and the consumer moves them to its own
+//! `BinaryHeap` before each operation. Both sides only use loads, stores, and fetch-and-add on
+//! atomics, with loops bounded by the capacity: no operation waits for the other side, takes a
+//! lock, or allocates (the ring buffer and the heap are allocated when the queue is created).
+//! This makes the queue usable from a real-time thread, such as an audio callback. (The `viz`
+//! and `observer` features record or report the operations of the heap, which may allocate.)
+//!
+//! Memory is only freed when both sides have been dropped.
+
+use crate::{BinaryHeap, Compare, MaxComparator};
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+// state shared by the producer and the consumer
+struct Shared<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,   // ring buffer
+    head: AtomicUsize,                          // number of elements read by the consumer
+    tail: AtomicUsize,                          // number of elements written by the producer
+    len: AtomicUsize,                           // number of elements in the ring or the heap
+}
+
+// SAFETY: each slot is accessed by one side at a time, as arbitrated by `head` and `tail`
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let (head, tail) = (*self.head.get_mut(), *self.tail.get_mut());
+        for i in head..tail {
+            // SAFETY: the slots between `head` and `tail` hold elements which were not read
+            unsafe { self.slots[i % self.slots.len()].get_mut().assume_init_drop() };
+        }
+    }
+}
+
+/// Producing half of a single-producer, single-consumer priority queue
+pub struct Producer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Consuming half of a single-producer, single-consumer priority queue
+pub struct Consumer<T, C = MaxComparator> {
+    shared: Arc<Shared<T>>,
+    heap: BinaryHeap<T, C>,     // elements moved out of the ring buffer
+}
+
+/// Create a queue holding at most `capacity` elements, popping the largest one first by the
+/// natural order of `T`
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::spsc::priority_queue;
+///
+/// // events sent to an audio callback, most urgent first
+/// let (mut producer, mut consumer) = priority_queue(2);
+/// assert_eq!(Ok(()), producer.push(3));
+/// assert_eq!(Ok(()), producer.push(7));
+/// assert_eq!(Err(5), producer.push(5));
+///
+/// std::thread::spawn(move || {
+///     assert_eq!(Some(7), consumer.pop());
+///     assert_eq!(Some(3), consumer.pop());
+///     assert_eq!(None, consumer.pop());
+/// }).join().unwrap();
+/// ```
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+#[inline]
+pub fn priority_queue<T: core::cmp::PartialOrd>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    priority_queue_with_comparator(capacity, MaxComparator)
+}
+
+/// Create a queue holding at most `capacity` elements, popping the largest one first according
+/// to `comparator`
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn priority_queue_with_comparator<T, C: Compare<T>>(capacity: usize, comparator: C) -> (Producer<T>, Consumer<T, C>) {
+    assert!(capacity > 0, "a bounded queue needs a positive capacity");
+    let shared = Arc::new(Shared {
+        slots: (0..capacity).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        len: AtomicUsize::new(0),
+    });
+    let mut heap = BinaryHeap::with_comparator(comparator);
+    heap.data.reserve_exact(capacity);
+    (Producer { shared: shared.clone() }, Consumer { shared, heap })
+}
+
+impl<T> Producer<T> {
+
+    /// Get the capacity of the queue
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.shared.slots.len()
+    }
+
+    /// Push an element, or return it in an error if the queue is full
+    ///
+    /// Worst-case complexity: $\Theta(1)$, with two atomic loads, one atomic store, and one
+    /// atomic addition.
+    pub fn push(&mut self, x: T) -> Result<(), T> {
+        let shared = &*self.shared;
+        if shared.len.load(Ordering::Acquire) == shared.slots.len() {
+            return Err(x);
+        }
+
+        // the ring holds at most `len` elements, so the slot at `tail` is free
+        let tail = shared.tail.load(Ordering::Relaxed);
+        // SAFETY: the consumer does not access the slots between `tail` and `head + capacity`
+        unsafe { (*shared.slots[tail % shared.slots.len()].get()).write(x) };
+        shared.tail.store(tail + 1, Ordering::Release);
+        shared.len.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<T, C: Compare<T>> Consumer<T, C> {
+
+    /// Get the capacity of the queue
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.shared.slots.len()
+    }
+
+    /// Get the number of elements in the queue
+    ///
+    /// The result may be out of date as soon as it is returned, if the producer pushes elements.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.shared.len.load(Ordering::Acquire)
+    }
+
+    /// Get a reference to the largest element pushed so far (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta(k \log n)$, where $k$ is the number of elements pushed since
+    /// the last operation of the consumer and $n \le$ `capacity` the number of elements in the
+    /// queue, thus $O(c \log c)$ for a capacity $c$.
+    pub fn peek(&mut self) -> Option<&T> {
+        self.receive();
+        self.heap.data.first()
+    }
+
+    /// Remove and return the largest element pushed so far (or `None` if the queue is empty)
+    ///
+    /// Worst-case complexity: $\Theta((k + 1) \log n)$, where $k$ is the number of elements pushed
+    /// since the last operation of the consumer and $n \le$ `capacity` the number of elements in
+    /// the queue, thus $O(c \log c)$ for a capacity $c$.
+    pub fn pop(&mut self) -> Option<T> {
+        self.receive();
+        let root = self.heap.pop()?;
+        self.shared.len.fetch_sub(1, Ordering::Release);
+        Some(root)
+    }
+
+    // move the elements of the ring buffer to the heap, which has enough capacity for all of them
+    fn receive(&mut self) {
+        let shared = &*self.shared;
+        let head = shared.head.load(Ordering::Relaxed);
+        let tail = shared.tail.load(Ordering::Acquire);
+        for i in head..tail {
+            // SAFETY: the producer has written the slots between `head` and `tail`, and does not
+            // access them before `head` is moved past them
+            let x = unsafe { (*shared.slots[i % shared.slots.len()].get()).assume_init_read() };
+            // move `head` past the slot before the comparator runs, so that the element is not
+            // dropped again with the ring buffer if it panics
+            shared.head.store(i + 1, Ordering::Release);
+            self.heap.insert(x);
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Producer").field("capacity", &self.capacity()).finish_non_exhaustive()
+    }
+}
+
+impl<T: core::fmt::Debug, C: core::fmt::Debug> core::fmt::Debug for Consumer<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Consumer").field("heap", &self.heap).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn producer_and_consumer_threads() {
+        let (mut producer, mut consumer) = priority_queue(16);
+        let n = 10_000u64;
+        let popped = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for i in 0..n {
+                    let mut x = i;
+                    while let Err(back) = producer.push(x) {
+                        x = back;
+                        std::hint::spin_loop();
+                    }
+                }
+            });
+            let mut popped = Vec::new();
+            while (popped.len() as u64) < n {
+                if let Some(x) = consumer.pop() {
+                    assert!(consumer.size() <= consumer.capacity());
+                    popped.push(x);
+                }
+            }
+            popped
+        });
+        let mut popped = popped;
+        popped.sort_unstable();
+        assert_eq!((0..n).collect::<Vec<_>>(), popped);
+    }
+
+    #[test]
+    fn capacity_and_drop() {
+        let (mut producer, mut consumer) = priority_queue(3);
+        let counter = Arc::new(());
+        for _ in 0..3 {
+            producer.push(counter.clone()).unwrap();
+        }
+        assert!(producer.push(counter.clone()).is_err());
+        assert_eq!(3, consumer.size());
+
+        // the elements moved to the heap still count, and those left in the ring buffer are dropped
+        assert!(consumer.peek().is_some());
+        producer.push(counter.clone()).unwrap_err();
+        consumer.pop().unwrap();
+        producer.push(counter.clone()).unwrap();
+        drop(producer);
+        drop(consumer);
+        assert_eq!(1, Arc::strong_count(&counter));
+    }
+
+    #[test]
+    fn panicking_comparator() {
+        // element counting its drops
+        struct Counted(u32, Arc<AtomicUsize>);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.1.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let (mut producer, mut consumer) = priority_queue_with_comparator(
+            4,
+            crate::FnComparator(|_: &Counted, _: &Counted| -> core::cmp::Ordering { panic!("comparator failed") }),
+        );
+        producer.push(Counted(1, drops.clone())).ok().unwrap();
+        producer.push(Counted(2, drops.clone())).ok().unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| consumer.pop().map(|x| x.0)));
+        assert!(result.is_err());
+        drop(producer);
+        drop(consumer);
+        assert_eq!(2, drops.load(Ordering::Relaxed));
+    }
+}
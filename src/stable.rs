@@ -0,0 +1,154 @@
+//! Heap with first-in first-out order between equal elements
+
+use crate::{BinaryHeap, Compare, MaxComparator};
+use core::cmp::Ordering;
+
+// order of the (element, sequence number) pairs: elements are compared with `C`, and equal
+// elements by sequence number, the earliest being the largest
+#[derive(Clone, Copy, Debug, Default)]
+struct StableComparator<C>(C);
+
+impl<T, C: Compare<T>> Compare<(T, u64)> for StableComparator<C> {
+    #[inline]
+    fn compare(&self, a: &(T, u64), b: &(T, u64)) -> Option<Ordering> {
+        match self.0.compare(&a.0, &b.0) {
+            Some(Ordering::Equal) => Some(b.1.cmp(&a.1)),
+            ordering => ordering,
+        }
+    }
+}
+
+/// A heap returning equal elements in insertion order
+///
+/// Each element is stored with a sequence number, incremented at each insertion, which breaks
+/// ties between equal elements: among elements with the same priority, the first inserted is the
+/// first popped. This is useful, for instance, for fair job scheduling.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{KeyComparator, StableBinaryHeap};
+///
+/// let mut jobs = StableBinaryHeap::with_comparator(KeyComparator(|job: &(u8, &str)| job.0));
+/// jobs.insert((1, "first"));
+/// jobs.insert((2, "urgent"));
+/// jobs.insert((1, "second"));
+/// jobs.insert((1, "third"));
+///
+/// assert_eq!(Some((2, "urgent")), jobs.pop());
+/// assert_eq!(Some((1, "first")), jobs.pop());
+/// assert_eq!(Some((1, "second")), jobs.pop());
+/// assert_eq!(Some((1, "third")), jobs.pop());
+/// ```
+#[derive(Clone, Debug)]
+pub struct StableBinaryHeap<T, C = MaxComparator> {
+    heap: BinaryHeap<(T, u64), StableComparator<C>>,
+    next_sequence: u64, // sequence number of the next inserted element
+}
+
+impl<T: core::cmp::PartialOrd> StableBinaryHeap<T> {
+
+    /// Create a new empty `StableBinaryHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        StableBinaryHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> StableBinaryHeap<T, C> {
+
+    /// Create a new empty `StableBinaryHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        StableBinaryHeap {
+            heap: BinaryHeap::with_comparator(StableComparator(comparator)),
+            next_sequence: 0,
+        }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert an element in the heap, after all the equal elements already in it
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        self.heap.insert((x, self.next_sequence));
+        self.next_sequence += 1;
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty); among equal elements,
+    /// the first inserted is returned
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|(x, _)| x)
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first().map(|(x, _)| x)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for StableBinaryHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for StableBinaryHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heap.size_hint()
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for StableBinaryHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for StableBinaryHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyComparator;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn fifo_ties_1() {
+        let mut heap = StableBinaryHeap::with_comparator(KeyComparator(|x: &(u8, u32)| x.0));
+        for i in 0..100 {
+            heap.insert(((i % 3) as u8, i));
+        }
+        assert_eq!(Some(&(2, 2)), heap.peek());
+        let order: Vec<(u8, u32)> = heap.collect();
+        for window in order.windows(2) {
+            let (first, second) = (window[0], window[1]);
+            assert!(first.0 > second.0 || (first.0 == second.0 && first.1 < second.1));
+        }
+    }
+
+    #[test]
+    fn natural_order_1() {
+        let mut heap = StableBinaryHeap::new();
+        for x in [3, 1, 4, 1, 5] {
+            heap.insert(x);
+        }
+        assert_eq!(5, heap.size());
+        assert_eq!(vec![5, 4, 3, 1, 1], heap.collect::<Vec<_>>());
+    }
+}
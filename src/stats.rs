@@ -0,0 +1,125 @@
+//! Operation counters
+
+use crate::BinaryHeap;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Numbers of operations performed by a heap since its creation or the last call to
+/// `reset_stats`
+///
+/// Only available with the `stats` feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HeapStats {
+    /// Number of comparisons between elements of the heap
+    pub comparisons: usize,
+    /// Number of swaps of two elements of the heap array
+    pub swaps: usize,
+    /// Largest number of levels by which an element was moved up or down in a single sift
+    pub max_sift_depth: usize,
+}
+
+// counters updated by the heap operations
+//
+// Comparisons are made through shared references, hence the atomic counter (which, unlike a
+// `Cell`, keeps the heap `Sync`).
+#[derive(Debug, Default)]
+pub(crate) struct StatCounters {
+    comparisons: AtomicUsize,
+    swaps: usize,
+    max_sift_depth: usize,
+    sift_depth: usize,  // depth of the current sift
+}
+
+impl Clone for StatCounters {
+    fn clone(&self) -> Self {
+        StatCounters {
+            comparisons: AtomicUsize::new(self.comparisons.load(Ordering::Relaxed)),
+            swaps: self.swaps,
+            max_sift_depth: self.max_sift_depth,
+            sift_depth: self.sift_depth,
+        }
+    }
+}
+
+impl StatCounters {
+
+    #[inline]
+    pub(crate) fn comparison(&self) {
+        self.comparisons.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn swap(&mut self) {
+        self.swaps += 1;
+    }
+
+    #[inline]
+    pub(crate) fn start_sift(&mut self) {
+        self.sift_depth = 0;
+    }
+
+    #[inline]
+    pub(crate) fn sift_step(&mut self) {
+        self.sift_depth += 1;
+        self.max_sift_depth = self.max_sift_depth.max(self.sift_depth);
+    }
+}
+
+impl<T, C, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Get the numbers of operations performed since the creation of the heap or the last call to
+    /// `reset_stats`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::<u32>::new();
+    /// heap.insert(1);
+    /// heap.insert(2);
+    ///
+    /// let stats = heap.stats();
+    /// assert_eq!(1, stats.comparisons);
+    /// assert_eq!(1, stats.swaps);
+    /// assert_eq!(1, stats.max_sift_depth);
+    /// ```
+    pub fn stats(&self) -> HeapStats {
+        HeapStats {
+            comparisons: self.stats.comparisons.load(Ordering::Relaxed),
+            swaps: self.stats.swaps,
+            max_sift_depth: self.stats.max_sift_depth,
+        }
+    }
+
+    /// Set all the operation counters to zero
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.stats = StatCounters::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_1() {
+        let mut heap = BinaryHeap::new();
+
+        // each element is moved up to the root, with one comparison per level
+        for i in 1..=7 {
+            heap.insert(i);
+        }
+        assert_eq!(HeapStats { comparisons: 10, swaps: 10, max_sift_depth: 2 }, heap.stats());
+
+        heap.reset_stats();
+        assert_eq!(HeapStats::default(), heap.stats());
+
+        // the array is [7, 4, 6, 1, 3, 2, 5]: the root is swapped with the last element (5),
+        // which is then moved down by one level
+        assert_eq!(Some(7), heap.pop());
+        let stats = heap.stats();
+        assert_eq!(1, stats.max_sift_depth);
+        assert_eq!(2, stats.swaps);
+    }
+}
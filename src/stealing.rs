@@ -0,0 +1,260 @@
+//! Per-worker heaps with work stealing
+//!
+//! Each worker has its own `BinaryHeap`, on its own cache lines, which it inserts into and pops
+//! from; its lock is thus almost never contended. A worker whose heap is empty steals the largest
+//! half of the elements of another worker's heap, so that elements of high priority spread to idle
+//! workers while most operations stay local. The elements popped by a worker are the largest ones
+//! of its own heap, which only approximates the global order. (The stealing of half the elements
+//! follows Hendler and Shavit, *Non-blocking steal-half work queues*, 2002.)
+
+use crate::numa::CachePadded;
+use crate::sharded::lock;
+use crate::{BinaryHeap, Compare, MaxComparator};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::vec::Vec;
+
+/// A concurrent heap made of one `BinaryHeap` per worker, with work stealing
+///
+/// Each worker accesses the heap through a `StealingWorker` returned by `worker`. Insertions go
+/// to the worker's own heap, and pops take its root; when its heap is empty, a worker steals the
+/// largest half of the elements of the first non-empty heap among the following workers, keeps
+/// them in its own heap, and pops the largest one. A worker never holds two locks at once.
+///
+/// This gives task schedulers an approximate global priority order: each worker runs its own
+/// tasks in order, and idle workers take the most urgent tasks of busy ones.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::WorkStealingHeap;
+///
+/// let heap = WorkStealingHeap::new(2);
+/// let (busy, idle) = (heap.worker(0), heap.worker(1));
+/// for task in 0..10 {
+///     busy.insert(task);
+/// }
+///
+/// // the idle worker steals the five most urgent tasks
+/// assert_eq!(Some(9), idle.pop());
+/// assert_eq!(Some(4), busy.pop());
+/// assert_eq!(Some(8), idle.pop());
+/// ```
+#[derive(Debug)]
+pub struct WorkStealingHeap<T, C = MaxComparator> {
+    heaps: Vec<CachePadded<Mutex<BinaryHeap<T, C>>>>,   // heaps of the workers
+    size: AtomicUsize,                                  // number of elements
+}
+
+impl<T: core::cmp::PartialOrd> WorkStealingHeap<T> {
+
+    /// Create a new empty `WorkStealingHeap` for `workers` workers, ordered by the natural order
+    /// of `T`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `workers` is zero.
+    #[inline]
+    pub fn new(workers: usize) -> Self {
+        WorkStealingHeap::with_comparator(workers, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> WorkStealingHeap<T, C> {
+
+    /// Create a new empty `WorkStealingHeap` for `workers` workers, ordered by `comparator`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `workers` is zero.
+    pub fn with_comparator(workers: usize, comparator: C) -> Self
+        where C: Clone
+    {
+        assert!(workers > 0, "a work-stealing heap needs at least one worker");
+        WorkStealingHeap {
+            heaps: (0..workers).map(|_| CachePadded(Mutex::new(BinaryHeap::with_comparator(comparator.clone())))).collect(),
+            size: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the number of workers
+    #[inline]
+    pub fn workers(&self) -> usize {
+        self.heaps.len()
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// With concurrent operations, the result may be out of date as soon as it is returned.
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Return the handle of worker `worker`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not smaller than the number of workers.
+    #[inline]
+    pub fn worker(&self, worker: usize) -> StealingWorker<'_, T, C> {
+        assert!(worker < self.heaps.len(), "worker out of bounds");
+        StealingWorker { heap: self, worker }
+    }
+
+    /// Insert an element in the heap of `worker`
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap
+    /// of the worker, without contention.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not smaller than the number of workers.
+    pub fn insert(&self, worker: usize, x: T) {
+        let mut heap = lock(&self.heaps[worker].0);
+        heap.insert(x);
+        // the size is incremented before the element can be popped or stolen, so that it never
+        // wraps
+        self.size.fetch_add(1, Ordering::Release);
+        drop(heap);
+    }
+
+    /// Remove and return the root of the heap of `worker`, stealing from another worker if it is
+    /// empty (or `None` if all the heaps are empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap
+    /// of the worker, without contention and if it is not empty; $\Theta(w + k \log k')$ when
+    /// stealing $k$ elements from a heap of $k'$ elements, where $w$ is the number of workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not smaller than the number of workers.
+    pub fn pop(&self, worker: usize) -> Option<T> {
+        // the lock of the worker's heap is released before stealing
+        let local = lock(&self.heaps[worker].0).pop();
+        let root = local.or_else(|| self.steal(worker))?;
+        self.size.fetch_sub(1, Ordering::Release);
+        Some(root)
+    }
+
+    // move the largest half of the elements of the first non-empty heap after the one of `thief`
+    // to the latter, and return the largest of them
+    fn steal(&self, thief: usize) -> Option<T> {
+        let count = self.heaps.len();
+        let stolen: Vec<T> = (1..count).find_map(|i| {
+            let mut victim = lock(&self.heaps[(thief + i) % count].0);
+            let half = victim.size().div_ceil(2);
+            (half > 0).then(|| (0..half).filter_map(|_| victim.pop()).collect())
+        })?;
+
+        // the stolen elements were popped in non-increasing order
+        let mut stolen = stolen.into_iter();
+        let root = stolen.next();
+        let mut heap = lock(&self.heaps[thief].0);
+        for x in stolen {
+            heap.insert(x);
+        }
+        root
+    }
+}
+
+/// A handle to a `WorkStealingHeap`, bound to a worker
+#[derive(Debug)]
+pub struct StealingWorker<'a, T, C = MaxComparator> {
+    heap: &'a WorkStealingHeap<T, C>,
+    worker: usize,
+}
+
+impl<T, C> Clone for StealingWorker<'_, T, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, C> Copy for StealingWorker<'_, T, C> {}
+
+impl<T, C: Compare<T>> StealingWorker<'_, T, C> {
+
+    /// Get the index of the worker
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.worker
+    }
+
+    /// Insert an element in the heap of the worker
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap
+    /// of the worker, without contention.
+    #[inline]
+    pub fn insert(&self, x: T) {
+        self.heap.insert(self.worker, x);
+    }
+
+    /// Remove and return the root of the heap of the worker, stealing from another worker if it
+    /// is empty (or `None` if all the heaps are empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap
+    /// of the worker, without contention and if it is not empty.
+    #[inline]
+    pub fn pop(&self) -> Option<T> {
+        self.heap.pop(self.worker)
+    }
+}
+
+impl<T, C: Compare<T> + Clone + Default> core::default::Default for WorkStealingHeap<T, C> {
+
+    /// Create a new empty `WorkStealingHeap` with one worker per available thread
+    fn default() -> Self {
+        let workers = std::thread::available_parallelism().map_or(1, usize::from);
+        Self::with_comparator(workers, C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steal_half() {
+        let heap = WorkStealingHeap::new(3);
+        for x in 0..9 {
+            heap.insert(2, x);
+        }
+
+        // worker 0 steals from worker 1 first, which is empty, then from worker 2
+        assert_eq!(Some(8), heap.pop(0));
+        assert_eq!(Some(3), heap.pop(2));
+        assert_eq!(vec![7, 6, 5, 4], core::iter::from_fn(|| lock(&heap.heaps[0].0).pop()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn concurrent_workers() {
+        let heap = WorkStealingHeap::new(4);
+        let popped = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for worker in 0..4u64 {
+                let local = heap.worker(worker as usize);
+                let popped = &popped;
+                scope.spawn(move || {
+                    // only worker 0 produces, the others steal
+                    if worker == 0 {
+                        for i in 0..4000 {
+                            local.insert(i);
+                        }
+                    }
+                    while let Some(x) = local.pop() {
+                        popped.lock().unwrap().push(x);
+                    }
+                });
+            }
+        });
+        assert_eq!(0, heap.size());
+        let mut popped = popped.into_inner().unwrap();
+        popped.sort_unstable();
+        assert_eq!((0..4000).collect::<Vec<u64>>(), popped);
+    }
+}
@@ -0,0 +1,95 @@
+//! `Stream` adapter popping the elements of a heap
+//!
+//! Only available with the `futures` feature.
+
+use crate::{BinaryHeap, Compare};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_core::stream::{FusedStream, Stream};
+
+/// A `Stream` yielding the elements of a heap in non-increasing order, returned by
+/// `BinaryHeap::into_stream_sorted`
+///
+/// The stream is always ready: each poll pops an element in $\Theta(\log n)$ time, where $n$ is the
+/// number of elements left.
+///
+/// Only available with the `futures` feature.
+#[derive(Clone, Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct SortedStream<T, C, const D: usize = 2> {
+    heap: BinaryHeap<T, C, D>,
+}
+
+impl<T, C: Compare<T>, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Consume the heap into a `Stream` yielding its elements in non-increasing order
+    ///
+    /// Only available with the `futures` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    /// use futures::{executor::block_on, StreamExt};
+    ///
+    /// let heap = BinaryHeap::from(vec![3, 8, 1, 5]);
+    /// let doubled: Vec<i32> = block_on(heap.into_stream_sorted().map(|x| 2 * x).collect());
+    /// assert_eq!(vec![16, 10, 6, 2], doubled);
+    /// ```
+    #[inline]
+    pub fn into_stream_sorted(self) -> SortedStream<T, C, D> {
+        SortedStream { heap: self }
+    }
+}
+
+impl<T, C, const D: usize> SortedStream<T, C, D> {
+
+    /// Get the heap of the remaining elements back
+    #[inline]
+    pub fn into_inner(self) -> BinaryHeap<T, C, D> {
+        self.heap
+    }
+}
+
+// the heap is never pinned, as popping moves the elements out
+impl<T, C, const D: usize> Unpin for SortedStream<T, C, D> {}
+
+impl<T, C: Compare<T>, const D: usize> Stream for SortedStream<T, C, D> {
+    type Item = T;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<T>> {
+        Poll::Ready(self.get_mut().heap.pop())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.size(), Some(self.heap.size()))
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> FusedStream for SortedStream<T, C, D> {
+    #[inline]
+    fn is_terminated(&self) -> bool {
+        self.heap.size() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use futures::{executor::block_on, StreamExt};
+
+    #[test]
+    fn stream_in_order() {
+        let heap = BinaryHeap::from(vec![4, 9, 2, 7, 7]);
+        let mut stream = heap.into_stream_sorted();
+        assert_eq!((5, Some(5)), stream.size_hint());
+        assert_eq!(Some(9), block_on(stream.next()));
+        let pairs: Vec<(i32, i32)> = block_on(stream.by_ref().zip(futures::stream::iter(0..)).take(3).collect());
+        assert_eq!(vec![(7, 0), (7, 1), (4, 2)], pairs);
+        assert!(!stream.is_terminated());
+        assert_eq!(vec![2], stream.into_inner().to_vec());
+    }
+}
@@ -0,0 +1,12 @@
+//! Helpers shared by the unit tests
+
+/// Linear congruential generator, for reproducible pseudo-random sequences
+///
+/// Each call of the returned closure gives the 31 high bits of the next state.
+pub(crate) fn lcg(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        state >> 33
+    }
+}
@@ -0,0 +1,306 @@
+//! Timer queue with one deadline per key
+//!
+//! The (deadline, key) pairs are kept in an `AddressableHeap` ordered by deadline, the earliest at
+//! the root, and a hash map gives the handle of each key, so that a timer can be rescheduled or
+//! cancelled in $\Theta(\log n)$ time. A recurring timer keeps its place in the heap when it
+//! fires: its deadline is moved to the next occurrence, and the heap repaired.
+
+use crate::{AddressableHeap, Compare, Handle};
+use core::cmp::Ordering;
+use std::boxed::Box;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// order of the timers: the earliest deadline is the largest
+#[derive(Clone, Copy, Debug, Default)]
+struct EarliestFirst;
+
+impl<K, T: PartialOrd> Compare<(T, K)> for EarliestFirst {
+    #[inline]
+    fn compare(&self, a: &(T, K), b: &(T, K)) -> Option<Ordering> {
+        b.0.partial_cmp(&a.0)
+    }
+}
+
+// function computing the next deadline of a recurring timer
+type NextDeadline<T> = Box<dyn FnMut(&T) -> Option<T> + Send>;
+
+/// Rule computing the next deadline of a recurring timer from the one which fired, or `None`
+/// to stop
+pub struct Recurrence<T> {
+    next: NextDeadline<T>,
+}
+
+impl<T> Recurrence<T> {
+
+    /// Recur at a fixed interval after each deadline
+    ///
+    /// The interval should be positive: a timer whose next deadline is not after the current
+    /// time fires again at once.
+    pub fn every<I>(interval: I) -> Self
+        where T: Clone + core::ops::Add<I, Output = T>,
+              I: Clone + Send + 'static,
+    {
+        Recurrence { next: Box::new(move |deadline: &T| Some(deadline.clone() + interval.clone())) }
+    }
+
+    /// Recur at the deadlines computed by `next` from the previous ones, until it returns `None`
+    pub fn with<F: FnMut(&T) -> Option<T> + Send + 'static>(next: F) -> Self {
+        Recurrence { next: Box::new(next) }
+    }
+}
+
+impl<T> core::fmt::Debug for Recurrence<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Recurrence { .. }")
+    }
+}
+
+/// A queue of timers, each identified by a key of type `K` and firing at a deadline of type `T`
+///
+/// Each key has at most one timer: scheduling a key again moves its deadline. `schedule`,
+/// `cancel`, and popping an expired timer take $\Theta(\log n)$ expected time; the deadlines can
+/// be `std::time::Instant`s, ticks, or any other partially ordered type. A timer scheduled with
+/// `schedule_recurring` is rescheduled by its `Recurrence` each time it fires.
+///
+/// Only available with the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::TimerQueue;
+///
+/// // retransmission timers of TCP segments, by sequence number, in milliseconds
+/// let mut timers = TimerQueue::new();
+/// timers.schedule(1000, 200);
+/// timers.schedule(2000, 250);
+/// timers.schedule(3000, 300);
+///
+/// // segment 2000 is acknowledged, segment 1000 is retransmitted
+/// timers.cancel(&2000);
+/// timers.schedule(1000, 600);
+///
+/// assert_eq!(vec![(3000, 300)], timers.pop_expired(400).collect::<Vec<_>>());
+/// assert_eq!(Some(600), timers.next_deadline().copied());
+/// ```
+#[derive(Debug)]
+pub struct TimerQueue<K, T> {
+    timers: AddressableHeap<(T, K), EarliestFirst>,
+    handles: HashMap<K, Handle>,    // handle of the timer of each key
+    rules: HashMap<K, Recurrence<T>>, // recurrence rules of the recurring timers
+}
+
+impl<K: Hash + Eq + Clone, T: PartialOrd> TimerQueue<K, T> {
+
+    /// Create a new empty `TimerQueue`
+    #[inline]
+    pub fn new() -> Self {
+        TimerQueue {
+            timers: AddressableHeap::with_comparator(EarliestFirst),
+            handles: HashMap::new(),
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Get the number of timers
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.timers.size()
+    }
+
+    /// Check whether `key` has a timer
+    ///
+    /// Expected complexity: $\Theta(1)$.
+    #[inline]
+    pub fn contains(&self, key: &K) -> bool {
+        self.handles.contains_key(key)
+    }
+
+    /// Get the deadline of the timer of `key` (or `None` if it has none)
+    ///
+    /// Expected complexity: $\Theta(1)$.
+    pub fn deadline(&self, key: &K) -> Option<&T> {
+        self.handles.get(key).and_then(|&handle| self.timers.get(handle)).map(|(deadline, _)| deadline)
+    }
+
+    /// Get the earliest deadline (or `None` if there is no timer)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn next_deadline(&self) -> Option<&T> {
+        self.timers.peek().map(|(deadline, _)| deadline)
+    }
+
+    /// Schedule the timer of `key` at `deadline`, returning its previous deadline if it had one
+    ///
+    /// The timer fires once, even if it was recurring.
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of timers.
+    pub fn schedule(&mut self, key: K, deadline: T) -> Option<T> {
+        self.rules.remove(&key);
+        self.set(key, deadline)
+    }
+
+    /// Schedule the timer of `key` at `deadline`, then at the deadlines given by `rule` each time
+    /// it fires, returning its previous deadline if it had one
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of timers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::{Recurrence, TimerQueue};
+    ///
+    /// // a heartbeat every 10 ticks from tick 12, and reports at ticks 5, 10, 20, and 40
+    /// let mut timers = TimerQueue::new();
+    /// timers.schedule_recurring("heartbeat", 12, Recurrence::every(10));
+    /// timers.schedule_recurring("report", 5, Recurrence::with(|&t| (t < 40).then(|| 2 * t)));
+    ///
+    /// let fired: Vec<_> = timers.pop_expired(30).collect();
+    /// assert_eq!(vec![("report", 5), ("report", 10), ("heartbeat", 12), ("report", 20), ("heartbeat", 22)], fired);
+    /// assert_eq!(Some(&32), timers.deadline(&"heartbeat"));
+    /// ```
+    pub fn schedule_recurring(&mut self, key: K, deadline: T, rule: Recurrence<T>) -> Option<T> {
+        self.rules.insert(key.clone(), rule);
+        self.set(key, deadline)
+    }
+
+    // set the deadline of the timer of `key`, returning its previous deadline if it had one
+    fn set(&mut self, key: K, deadline: T) -> Option<T> {
+        match self.handles.get(&key) {
+            Some(&handle) => self.timers.update(handle, |timer| core::mem::replace(&mut timer.0, deadline)),
+            None => {
+                let handle = self.timers.insert((deadline, key.clone()));
+                self.handles.insert(key, handle);
+                None
+            },
+        }
+    }
+
+    /// Cancel the timer of `key`, returning its deadline (or `None` if it had no timer)
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of timers.
+    pub fn cancel(&mut self, key: &K) -> Option<T> {
+        self.rules.remove(key);
+        let handle = self.handles.remove(key)?;
+        self.timers.remove(handle).map(|(deadline, _)| deadline)
+    }
+
+    /// Remove and return the timer with the earliest deadline if it is not after `now`
+    ///
+    /// A recurring timer is rescheduled instead of removed, unless its rule returns `None`.
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of timers, plus the cost of
+    /// the recurrence rule.
+    pub fn pop_next_expired(&mut self, now: &T) -> Option<(K, T)> {
+        if self.next_deadline()?.partial_cmp(now)? == Ordering::Greater {
+            return None;
+        }
+
+        // move the deadline of a recurring timer to its next occurrence
+        let handle = self.timers.peek_handle()?;
+        let rule = self.timers.get(handle).and_then(|(_, key)| self.rules.get_mut(key));
+        if let Some(next) = rule.and_then(|rule| (rule.next)(&self.timers.get(handle)?.0)) {
+            return self.timers.update(handle, |timer| (timer.1.clone(), core::mem::replace(&mut timer.0, next)));
+        }
+
+        let (deadline, key) = self.timers.pop()?;
+        self.handles.remove(&key);
+        self.rules.remove(&key);
+        Some((key, deadline))
+    }
+
+    /// Return an iterator removing the timers whose deadlines are not after `now`, in order of
+    /// deadline, with their keys
+    ///
+    /// Timers not consumed by the iterator stay in the queue.
+    ///
+    /// Expected complexity: $\Theta(\log n)$ per timer, where $n$ is the number of timers.
+    #[inline]
+    pub fn pop_expired(&mut self, now: T) -> PopExpired<'_, K, T> {
+        PopExpired { queue: self, now }
+    }
+}
+
+impl<K: Hash + Eq + Clone, T: PartialOrd> core::default::Default for TimerQueue<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator removing the expired timers of a `TimerQueue`, returned by `TimerQueue::pop_expired`
+#[derive(Debug)]
+pub struct PopExpired<'a, K, T> {
+    queue: &'a mut TimerQueue<K, T>,
+    now: T,
+}
+
+impl<K: Hash + Eq + Clone, T: PartialOrd> Iterator for PopExpired<'_, K, T> {
+    type Item = (K, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, T)> {
+        self.queue.pop_next_expired(&self.now)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.queue.size()))
+    }
+}
+
+impl<K: Hash + Eq + Clone, T: PartialOrd> core::iter::FusedIterator for PopExpired<'_, K, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use std::collections::BTreeMap;
+    use std::vec::Vec;
+
+    #[test]
+    fn against_map() {
+        let mut next = lcg(12345);
+        let mut timers = TimerQueue::new();
+        let mut expected: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut now = 0;
+        for _ in 0..10_000 {
+            let key = next() % 100;
+            match next() % 4 {
+                0 => assert_eq!(expected.remove(&key), timers.cancel(&key)),
+                1 if key < 10 => {
+                    // keys below 10 recur every 7 ticks
+                    let deadline = now + next() % 100;
+                    assert_eq!(expected.insert(key, deadline), timers.schedule_recurring(key, deadline, Recurrence::every(7)));
+                },
+                1 => {
+                    now += next() % 50;
+                    let mut fired: Vec<(u64, u64)> = timers.pop_expired(now).collect();
+                    assert!(fired.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+                    fired.sort_unstable();
+                    let mut due = Vec::new();
+                    for (&k, d) in expected.iter_mut() {
+                        while *d <= now {
+                            due.push((k, *d));
+                            if k >= 10 || !timers.rules.contains_key(&k) {
+                                break;
+                            }
+                            *d += 7;
+                        }
+                    }
+                    due.sort_unstable();
+                    expected.retain(|_, d| *d > now);
+                    assert_eq!(due, fired);
+                },
+                _ => {
+                    let deadline = now + next() % 100;
+                    assert_eq!(expected.insert(key, deadline), timers.schedule(key, deadline));
+                },
+            }
+            assert_eq!(expected.len(), timers.size());
+            assert_eq!(expected.values().min(), timers.next_deadline());
+        }
+    }
+}
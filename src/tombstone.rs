@@ -0,0 +1,291 @@
+//! Heap with lazy deletion
+//!
+//! Cancelled elements are not removed from the heap array but marked dead (a ‘tombstone’) in
+//! their slot, and skipped when they reach the root. When the dead elements exceed a given
+//! proportion of the array, they are all removed at once and the heap rebuilt.
+
+use crate::{BinaryHeap, Compare, Handle, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+// state of a slot; the generation is incremented each time the slot is freed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Free,
+    Live,
+    Dead,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Slot {
+    generation: u32,
+    state: State,
+}
+
+// order of the (element, slot index) pairs: only the elements are compared
+#[derive(Clone, Copy, Debug, Default)]
+struct EntryComparator<C>(C);
+
+impl<T, C: Compare<T>> Compare<(T, usize)> for EntryComparator<C> {
+    #[inline]
+    fn compare(&self, a: &(T, usize), b: &(T, usize)) -> Option<Ordering> {
+        self.0.compare(&a.0, &b.0)
+    }
+}
+
+/// A heap whose elements can be cancelled in $\Theta(1)$ time
+///
+/// `insert` returns a `Handle`, which can be passed to `cancel`: the element is then marked dead,
+/// and skipped by `pop` and `peek`. Once the dead elements exceed a proportion of the heap array
+/// (by default one half, see `set_compaction_ratio`), they are all removed and the heap rebuilt
+/// in $\Theta(n)$ time. This is faster than removing elements one by one when cancellations are
+/// frequent, at the cost of memory.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::TombstoneHeap;
+///
+/// let mut timers = TombstoneHeap::new();
+/// let first = timers.insert(30);
+/// let second = timers.insert(20);
+/// timers.insert(10);
+///
+/// assert!(timers.cancel(first));
+/// assert!(!timers.cancel(first));
+///
+/// assert_eq!(2, timers.size());
+/// assert_eq!(Some(20), timers.pop());
+/// assert!(!timers.cancel(second));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TombstoneHeap<T, C = MaxComparator> {
+    heap: BinaryHeap<(T, usize), EntryComparator<C>>,   // elements with their slot indices
+    slots: Vec<Slot>,           // state of each slot
+    free: Vec<usize>,           // indices of the free slots
+    dead: usize,                // number of dead elements in the heap array
+    compaction_ratio: f64,      // maximum proportion of dead elements
+}
+
+impl<T: core::cmp::PartialOrd> TombstoneHeap<T> {
+
+    /// Create a new empty `TombstoneHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        TombstoneHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> TombstoneHeap<T, C> {
+
+    /// Create a new empty `TombstoneHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        TombstoneHeap {
+            heap: BinaryHeap::with_comparator(EntryComparator(comparator)),
+            slots: Vec::new(),
+            free: Vec::new(),
+            dead: 0,
+            compaction_ratio: 0.5,
+        }
+    }
+
+    /// Set the proportion of dead elements in the heap array above which they are removed
+    ///
+    /// With a ratio of `0.0`, dead elements are removed at each cancellation; with a ratio of
+    /// `1.0` or more, they are only removed when they reach the root (or by `compact`).
+    #[inline]
+    pub fn set_compaction_ratio(&mut self, ratio: f64) {
+        self.compaction_ratio = ratio;
+    }
+
+    /// Get the number of live (not cancelled) elements
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size() - self.dead
+    }
+
+    /// Get the number of dead elements still in the heap array
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn dead(&self) -> usize {
+        self.dead
+    }
+
+    /// Insert an element in the heap, returning a handle to it
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the size of the heap array
+    /// (amortized, as the slots may grow).
+    pub fn insert(&mut self, x: T) -> Handle {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot { generation: 0, state: State::Free });
+                self.slots.len() - 1
+            },
+        };
+        self.slots[index].state = State::Live;
+        self.heap.insert((x, index));
+        Handle { index, generation: self.slots[index].generation }
+    }
+
+    /// Cancel the element of `handle`, returning `false` if it is no longer in the heap
+    ///
+    /// The element is dropped when it reaches the root or at the next compaction (which happens
+    /// when the dead elements exceed the compaction ratio after a call to `cancel` or `pop`).
+    ///
+    /// Worst-case complexity: $\Theta(1)$, except when it triggers a compaction ($\Theta(n)$, where
+    /// $n$ is the size of the heap array).
+    pub fn cancel(&mut self, handle: Handle) -> bool {
+        match self.slots.get_mut(handle.index) {
+            Some(slot) if slot.generation == handle.generation && slot.state == State::Live => {
+                slot.state = State::Dead;
+            },
+            _ => return false,
+        }
+        self.dead += 1;
+        self.compact_if_needed();
+        true
+    }
+
+    /// Whether the element of `handle` is still in the heap and not cancelled
+    #[inline]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.slots.get(handle.index)
+            .is_some_and(|slot| slot.generation == handle.generation && slot.state == State::Live)
+    }
+
+    /// Remove and return the root live element (or `None` if there is none), dropping the dead
+    /// elements found at the root
+    ///
+    /// Worst-case complexity: $\Theta((d + 1) \log n)$, where $n$ is the size of the heap array
+    /// and $d$ the number of dead elements dropped.
+    pub fn pop(&mut self) -> Option<T> {
+        self.drop_dead_roots();
+        let (x, index) = self.heap.pop()?;
+        self.free_slot(index);
+        self.compact_if_needed();
+        Some(x)
+    }
+
+    /// Return a reference to the root live element (or `None` if there is none), dropping the
+    /// dead elements found at the root
+    ///
+    /// Worst-case complexity: $\Theta(d \log n)$, where $n$ is the size of the heap array and $d$
+    /// the number of dead elements dropped.
+    pub fn peek(&mut self) -> Option<&T> {
+        self.drop_dead_roots();
+        self.heap.data.first().map(|(x, _)| x)
+    }
+
+    /// Remove all the dead elements and rebuild the heap
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the size of the heap array.
+    pub fn compact(&mut self) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("compact", dead = self.dead, size = self.heap.size()).entered();
+        let slots = &mut self.slots;
+        let free = &mut self.free;
+        self.heap.data.retain(|&(_, index)| {
+            if slots[index].state == State::Dead {
+                slots[index].state = State::Free;
+                slots[index].generation = slots[index].generation.wrapping_add(1);
+                free.push(index);
+                false
+            } else {
+                true
+            }
+        });
+        self.dead = 0;
+        self.heap.rebuild();
+    }
+
+    // remove the dead elements if they exceed the compaction ratio
+    fn compact_if_needed(&mut self) {
+        if (self.dead as f64) > self.compaction_ratio * (self.heap.size() as f64) {
+            self.compact();
+        }
+    }
+
+    // pop the dead elements at the root
+    fn drop_dead_roots(&mut self) {
+        while let Some(&(_, index)) = self.heap.data.first() {
+            if self.slots[index].state != State::Dead {
+                break;
+            }
+            self.heap.pop();
+            self.free_slot(index);
+            self.dead -= 1;
+        }
+    }
+
+    // free the slot `index` after its element left the heap
+    fn free_slot(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        slot.state = State::Free;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for TombstoneHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+
+    #[test]
+    fn random_operations() {
+        let mut next = lcg(99);
+        for ratio in [0.0, 0.25, 0.5, 2.0] {
+            let mut heap = TombstoneHeap::new();
+            heap.set_compaction_ratio(ratio);
+            let mut live: Vec<(Handle, u64)> = Vec::new();
+            for _ in 0..3_000 {
+                match next() % 3 {
+                    0 => {
+                        let x = next() % 1000;
+                        live.push((heap.insert(x), x));
+                    },
+                    1 if !live.is_empty() => {
+                        let (handle, _) = live.swap_remove((next() as usize) % live.len());
+                        assert!(heap.cancel(handle));
+                        assert!(!heap.contains(handle));
+                    },
+                    _ => {
+                        let max = live.iter().map(|&(_, x)| x).max();
+                        assert_eq!(max.as_ref(), heap.peek());
+                        let popped = heap.pop();
+                        assert_eq!(max, popped);
+                        live.retain(|&(handle, _)| heap.contains(handle));
+                    },
+                }
+                assert_eq!(live.len(), heap.size());
+                assert!((heap.dead() as f64) <= ratio * (heap.heap.size() as f64));
+            }
+        }
+    }
+
+    #[test]
+    fn compact_1() {
+        let mut heap = TombstoneHeap::new();
+        heap.set_compaction_ratio(1.0);
+        let handles: Vec<Handle> = (0..10).map(|x| heap.insert(x)).collect();
+        for &handle in handles.iter().step_by(2) {
+            heap.cancel(handle);
+        }
+        assert_eq!(5, heap.dead());
+        heap.compact();
+        assert_eq!(0, heap.dead());
+        assert_eq!(5, heap.size());
+        assert_eq!(Some(9), heap.pop());
+    }
+}
@@ -0,0 +1,229 @@
+//! Batches of operations applied atomically, with an undo log
+
+use crate::{BinaryHeap, Compare, IncomparablePolicy};
+use alloc::vec::Vec;
+
+// change to the heap array, undone in reverse order on rollback
+#[derive(Debug)]
+enum Undo<T> {
+    Swap(usize, usize), // swap of two positions
+    Push,               // element pushed at the end
+    Pop(T),             // element popped from the end
+}
+
+/// Operations of a transaction on a `BinaryHeap`, created by `BinaryHeap::transaction`
+///
+/// Each change to the heap array is recorded in an undo log; if the transaction is not
+/// committed, the changes are undone in reverse order when it is dropped.
+#[derive(Debug)]
+pub struct Transaction<'a, T, C: Compare<T>, const D: usize = 2> {
+    heap: &'a mut BinaryHeap<T, C, D>,
+    log: Vec<Undo<T>>,
+}
+
+impl<T, C: Compare<T>, const D: usize> Transaction<'_, T, C, D> {
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first()
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Incomparable elements are handled according to the `IncomparablePolicy` of the heap, as
+    /// by `BinaryHeap::insert`.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$ (amortized for the log), where $n$ is the number of
+    /// elements in the heap.
+    pub fn insert(&mut self, x: T) {
+        match self.heap.policy {
+            IncomparablePolicy::Ignore | IncomparablePolicy::TreatAsSmallest => {},
+            IncomparablePolicy::Reject => if self.heap.is_incomparable(&x) {
+                return;
+            },
+            IncomparablePolicy::Panic => if self.heap.is_incomparable(&x) {
+                panic!("element incomparable with an element of the heap");
+            },
+        }
+        self.log.push(Undo::Push);
+        let log = &mut self.log;
+        self.heap.push_with(x, |_, i, j| log.push(Undo::Swap(i, j)));
+    }
+
+    /// Commit the changes made so far, so that they are kept even if the rest of the transaction
+    /// is rolled back
+    ///
+    /// Worst-case complexity: $\Theta(m)$, where $m$ is the length of the undo log.
+    #[inline]
+    pub fn commit(&mut self) {
+        self.log.clear();
+    }
+
+    /// Undo the changes made since the start of the transaction (or the last call to `commit`)
+    ///
+    /// Worst-case complexity: $\Theta(m)$, where $m$ is the length of the undo log.
+    pub fn rollback(&mut self) {
+        while let Some(undo) = self.log.pop() {
+            match undo {
+                Undo::Swap(i, j) => self.heap.swap(i, j),
+                Undo::Push => {
+                    let _x = self.heap.data.pop();
+                    #[cfg(feature = "observer")]
+                    if let Some(x) = &_x {
+                        self.heap.observer.on_pop(x, self.heap.size());
+                    }
+                },
+                Undo::Pop(x) => {
+                    self.heap.data.push(x);
+                    #[cfg(feature = "observer")]
+                    {
+                        let size = self.heap.size();
+                        self.heap.observer.on_insert(&self.heap.data[size - 1], size);
+                    }
+                },
+            }
+        }
+        self.heap.debug_validate();
+    }
+}
+
+impl<T: Clone, C: Compare<T>, const D: usize> Transaction<'_, T, C, D> {
+
+    /// Remove the root element and return a clone of it (or `None` if the heap is empty)
+    ///
+    /// The element itself is kept in the undo log until the transaction is committed, so that it
+    /// can be put back.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$ (amortized for the log), where $n$ is the number of
+    /// elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let log = &mut self.log;
+        let root = self.heap.pop_with(|_, i, j| log.push(Undo::Swap(i, j)))?;
+        self.log.push(Undo::Pop(root.clone()));
+        Some(root)
+    }
+}
+
+// changes which have not been committed are undone, including when the transaction panics
+impl<T, C: Compare<T>, const D: usize> Drop for Transaction<'_, T, C, D> {
+    fn drop(&mut self) {
+        self.rollback();
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> BinaryHeap<T, C, D> {
+
+    /// Apply the operations of `f` atomically: they are kept if it returns `Ok`, and undone if it
+    /// returns `Err` or panics
+    ///
+    /// The changes to the heap array are recorded in an undo log, so that a rollback takes time
+    /// proportional to the number of operations (and not to the size of the heap), and a commit
+    /// only drops the log. `Transaction::pop` returns clones of the elements, which requires
+    /// `T: Clone`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut jobs = BinaryHeap::from(vec![3, 8, 5]);
+    ///
+    /// // the worker rejects the batch: the jobs stay in the queue
+    /// let result: Result<(), &str> = jobs.transaction(|txn| {
+    ///     let first = txn.pop().unwrap();
+    ///     let second = txn.pop().unwrap();
+    ///     if first + second > 10 { Err("batch too large") } else { Ok(()) }
+    /// });
+    /// assert_eq!(Err("batch too large"), result);
+    /// assert_eq!(3, jobs.size());
+    ///
+    /// // the worker accepts it: the jobs are handed off
+    /// let batch = jobs.transaction(|txn| Ok::<_, ()>(vec![txn.pop(), txn.pop()]));
+    /// assert_eq!(Ok(vec![Some(8), Some(5)]), batch);
+    /// assert_eq!(vec![3], jobs.to_vec());
+    /// ```
+    pub fn transaction<R, E, F>(&mut self, f: F) -> Result<R, E>
+        where F: FnOnce(&mut Transaction<'_, T, C, D>) -> Result<R, E>
+    {
+        let mut txn = Transaction { heap: self, log: Vec::new() };
+        let result = f(&mut txn);
+        if result.is_ok() {
+            txn.commit();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+
+    #[test]
+    fn rollback_restores_array() {
+        let mut next = lcg(12345);
+        let mut heap = BinaryHeap::from((0..100).map(|_| next() % 1000).collect::<Vec<u64>>());
+        for round in 0..50 {
+            let before = heap.data.clone();
+            let ops: Vec<u64> = (0..(next() % 20)).map(|_| next()).collect();
+            let mut reference = BinaryHeap::from(before.clone());
+            let result = heap.transaction(|txn| {
+                for &op in ops.iter() {
+                    if op % 3 == 0 {
+                        assert_eq!(reference.pop(), txn.pop());
+                    } else {
+                        txn.insert(op % 1000);
+                        reference.insert(op % 1000);
+                    }
+                    assert_eq!(reference.size(), txn.size());
+                }
+                if round % 2 == 0 { Ok(()) } else { Err(()) }
+            });
+            assert!(heap.is_valid_heap());
+            if result.is_err() {
+                assert_eq!(before, heap.data);
+            } else {
+                assert_eq!(reference.to_vec(), heap.clone().to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn partial_commit() {
+        let mut heap = BinaryHeap::from(vec![1, 2, 3]);
+        let result = heap.transaction(|txn| -> Result<(), ()> {
+            txn.pop();
+            txn.commit();
+            txn.insert(10);
+            txn.pop();
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(vec![2, 1], heap.to_vec());
+    }
+
+    #[test]
+    fn rollback_on_panic() {
+        let mut heap = BinaryHeap::from(vec![1, 2, 3]);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            heap.transaction(|txn| -> Result<(), ()> {
+                txn.pop();
+                txn.insert(7);
+                panic!("worker crashed")
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(vec![3, 2, 1], heap.to_vec());
+    }
+}
@@ -0,0 +1,111 @@
+//! Heap without duplicates, with a hash set for membership tests
+
+use crate::BinaryHeap;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A heap holding at most one copy of each element
+///
+/// A hash set of the elements of the heap is kept alongside it, so that `contains` and
+/// `insert` check membership in $\Theta(1)$ expected time (instead of the $\Theta(n)$ worst case
+/// of `BinaryHeap::insert_unique`), at the cost of storing each element twice.
+///
+/// Only available with the `hash-index` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::UniqueBinaryHeap;
+///
+/// let mut heap = UniqueBinaryHeap::new();
+/// assert!(heap.insert("b"));
+/// assert!(heap.insert("a"));
+/// assert!(!heap.insert("b"));
+///
+/// assert!(heap.contains(&"a"));
+/// assert_eq!(Some("b"), heap.pop());
+/// assert!(heap.insert("b"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct UniqueBinaryHeap<T> {
+    heap: BinaryHeap<T>,
+    index: HashSet<T>,   // elements of the heap
+}
+
+impl<T: core::cmp::PartialOrd + Hash + Eq + Clone> UniqueBinaryHeap<T> {
+
+    /// Create a new empty `UniqueBinaryHeap`
+    #[inline]
+    pub fn new() -> Self {
+        UniqueBinaryHeap { heap: BinaryHeap::new(), index: HashSet::new() }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Whether the heap contains `x`
+    ///
+    /// Expected complexity: $\Theta(1)$.
+    #[inline]
+    pub fn contains(&self, x: &T) -> bool {
+        self.index.contains(x)
+    }
+
+    /// Insert `x` if it is not already in the heap, returning `true` if it was inserted and
+    /// `false` otherwise
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) -> bool {
+        if !self.index.insert(x.clone()) {
+            return false;
+        }
+        self.heap.insert(x);
+        true
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Expected complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.heap.pop()?;
+        self.index.remove(&root);
+        Some(root)
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first()
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Hash + Eq + Clone> core::default::Default for UniqueBinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_1() {
+        let mut heap = UniqueBinaryHeap::new();
+        for i in 0..1000u32 {
+            heap.insert(i % 37);
+        }
+        assert_eq!(37, heap.size());
+        assert_eq!(Some(&36), heap.peek());
+        let popped: Vec<u32> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!((0..37).rev().collect::<Vec<u32>>(), popped);
+        assert!(!heap.contains(&0));
+    }
+}
@@ -0,0 +1,82 @@
+//! Proof harnesses for the [Kani](https://model-checking.github.io/kani/) model checker
+//!
+//! Run with `cargo kani` (or `make verify`). For every heap of up to `MAX_SIZE` elements
+//! satisfying the heap property, and every element to insert, the harnesses check that `insert`,
+//! `pop`, and the bulk construction from a vector never panic (in particular, never index out of
+//! bounds) and leave a valid heap with the expected elements. Out-of-bounds accesses and
+//! arithmetic overflows are reported by Kani as failures.
+
+use crate::BinaryHeap;
+use alloc::vec::Vec;
+
+// largest number of elements in the heaps considered
+const MAX_SIZE: usize = 5;
+
+// check that no element is larger than its parent
+fn is_heap(data: &[u8]) -> bool {
+    (1..data.len()).all(|pos| data[(pos - 1) >> 1] >= data[pos])
+}
+
+// number of occurrences of `x` in `data`
+fn count(data: &[u8], x: u8) -> usize {
+    data.iter().filter(|&&y| y == x).count()
+}
+
+// any vector of at most `MAX_SIZE` elements
+fn any_vec() -> Vec<u8> {
+    let size: usize = kani::any();
+    kani::assume(size <= MAX_SIZE);
+    let values: [u8; MAX_SIZE] = kani::any();
+    values[..size].to_vec()
+}
+
+// any heap of at most `MAX_SIZE` elements satisfying the heap property
+fn any_heap() -> BinaryHeap<u8> {
+    let mut heap = BinaryHeap::new();
+    heap.data = any_vec();
+    kani::assume(is_heap(&heap.data));
+    heap
+}
+
+#[kani::proof]
+#[kani::unwind(7)]
+fn insert_preserves_heap_property() {
+    let mut heap = any_heap();
+    let original = heap.data.clone();
+    let x: u8 = kani::any();
+    heap.insert(x);
+
+    assert!(heap.size() == original.len() + 1);
+    assert!(is_heap(&heap.data));
+    assert!(count(&heap.data, x) == count(&original, x) + 1);
+}
+
+#[kani::proof]
+#[kani::unwind(7)]
+fn pop_returns_maximum_and_preserves_heap_property() {
+    let mut heap = any_heap();
+    let original = heap.data.clone();
+    let popped = heap.pop();
+
+    match popped {
+        None => assert!(original.is_empty()),
+        Some(x) => {
+            assert!(original.iter().all(|&y| y <= x));
+            assert!(heap.size() == original.len() - 1);
+            assert!(count(&heap.data, x) + 1 == count(&original, x));
+        },
+    }
+    assert!(is_heap(&heap.data));
+}
+
+#[kani::proof]
+#[kani::unwind(7)]
+fn from_vec_builds_valid_heap() {
+    let values = any_vec();
+    let heap = BinaryHeap::from(values.clone());
+
+    assert!(heap.size() == values.len());
+    assert!(is_heap(&heap.data));
+    let x: u8 = kani::any();
+    assert!(count(&heap.data, x) == count(&values, x));
+}
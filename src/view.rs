@@ -0,0 +1,167 @@
+//! Heap operations over a borrowed slice
+
+use crate::{Compare, MaxComparator};
+use core::cmp::Ordering;
+
+/// A heap built in place over a borrowed slice
+///
+/// The slice is split into an active region, at the front, which is a heap, and a region at the
+/// back holding the popped elements: `pop_to_end` moves the root to the back, just before the
+/// elements popped earlier, and shrinks the active region. Popping $k$ elements thus leaves the
+/// $k$ largest ones, sorted in non-decreasing order, at the end of the slice, and popping all of
+/// them sorts it, without allocating.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::HeapView;
+///
+/// let mut scores = [12, 87, 45, 3, 99, 64];
+///
+/// // partial selection of the top 2 scores
+/// let mut view = HeapView::new(&mut scores);
+/// view.pop_to_end();
+/// view.pop_to_end();
+/// assert_eq!(&[87, 99], view.popped());
+/// assert_eq!(Some(&64), view.peek());
+///
+/// // heapsort of the whole slice
+/// view.sort();
+/// assert_eq!([3, 12, 45, 64, 87, 99], scores);
+/// ```
+#[derive(Debug)]
+pub struct HeapView<'a, T, C = MaxComparator> {
+    data: &'a mut [T],  // active region followed by the popped elements
+    size: usize,        // length of the active region
+    comparator: C,      // order of the elements
+}
+
+impl<'a, T: core::cmp::PartialOrd> HeapView<'a, T> {
+
+    /// Rearrange `data` into a heap, ordered by the natural order of `T`
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the length of `data`.
+    #[inline]
+    pub fn new(data: &'a mut [T]) -> Self {
+        HeapView::with_comparator(data, MaxComparator)
+    }
+}
+
+impl<'a, T, C: Compare<T>> HeapView<'a, T, C> {
+
+    /// Rearrange `data` into a heap ordered by `comparator`
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the length of `data`.
+    pub fn with_comparator(data: &'a mut [T], comparator: C) -> Self {
+        let size = data.len();
+        let mut view = HeapView { data, size, comparator };
+        if size > 1 {
+            for pos in (0..=((size - 2) >> 1)).rev() {
+                view.sift_down(pos);
+            }
+        }
+        view
+    }
+
+    /// Get the size of the heap (number of elements in the active region)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.active().first()
+    }
+
+    /// Return the active region, in the order of the heap array
+    #[inline]
+    pub fn active(&self) -> &[T] {
+        &self.data[..self.size]
+    }
+
+    /// Return the popped elements, in non-decreasing order
+    #[inline]
+    pub fn popped(&self) -> &[T] {
+        &self.data[self.size..]
+    }
+
+    /// Move the root element to the end of the active region, which shrinks by one, and return a
+    /// reference to it (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop_to_end(&mut self) -> Option<&T> {
+        let last = self.size.checked_sub(1)?;
+        self.data.swap(0, last);
+        self.size = last;
+        self.sift_down(0);
+        Some(&self.data[last])
+    }
+
+    /// Pop all the elements of the heap, leaving the slice sorted in non-decreasing order
+    ///
+    /// Worst-case complexity: $\Theta(n \log n)$, where $n$ is the number of elements in the heap.
+    pub fn sort(&mut self) {
+        while self.pop_to_end().is_some() {}
+    }
+
+    // whether the element at position `i` is smaller than the one at position `j`
+    #[inline]
+    fn less(&self, i: usize, j: usize) -> bool {
+        self.comparator.compare(&self.data[i], &self.data[j]) == Some(Ordering::Less)
+    }
+
+    // ‘bubble down’ the element at position `pos` within the active region
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let mut largest = pos;
+            for child in [(pos << 1) + 1, (pos << 1) + 2] {
+                if child < self.size && self.less(largest, child) {
+                    largest = child;
+                }
+            }
+            if largest == pos {
+                break;
+            }
+            self.data.swap(pos, largest);
+            pos = largest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::MinComparator;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn sort_random() {
+        let mut next = lcg(12345);
+        for len in 0..100 {
+            let mut values: Vec<u64> = (0..len).map(|_| next() % 50).collect();
+            let mut expected = values.clone();
+            expected.sort_unstable();
+            HeapView::new(&mut values).sort();
+            assert_eq!(expected, values);
+        }
+    }
+
+    #[test]
+    fn partial_selection() {
+        let mut values: Vec<u32> = (0..100).map(|i| (i * 37) % 100).collect();
+        let mut view = HeapView::with_comparator(&mut values, MinComparator);
+        for i in 0..10 {
+            assert_eq!(Some(&i), view.pop_to_end());
+        }
+        assert_eq!(90, view.size());
+        assert_eq!(&[9, 8, 7, 6, 5, 4, 3, 2, 1, 0], view.popped());
+        assert!(view.active().iter().all(|&x| x >= 10));
+    }
+}
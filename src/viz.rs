@@ -0,0 +1,211 @@
+//! Export of the implicit tree of a heap as a Graphviz DOT or Mermaid graph description
+//!
+//! Node `i` of the graph holds the element at index `i` of the array representation; its label
+//! is obtained from the `Display` implementation of the element type.
+
+use crate::BinaryHeap;
+use alloc::{format, string::{String, ToString}, vec::Vec};
+use core::fmt::Write;
+
+// style of highlighted nodes and edges
+const HIGHLIGHT_COLOR: &str = "#ff9966";
+
+// escape a label so that it can be used between double quotes in DOT
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// escape a label so that it can be used between double quotes in Mermaid
+fn escape_mermaid(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
+
+impl<T, C> BinaryHeap<T, C> {
+
+    /// Return the positions (indices in the array representation) visited by the element moved
+    /// during the last `insert` or `pop`, in the order in which it visited them
+    ///
+    /// Only available with the `viz` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from_vec(&[1, 2, 3]);
+    /// heap.insert(4);
+    ///
+    /// assert_eq!(&[3, 1, 0], heap.last_sift_path());
+    /// ```
+    pub fn last_sift_path(&self) -> &[usize] {
+        &self.last_sift_path
+    }
+}
+
+impl<T: core::fmt::Display, C> BinaryHeap<T, C> {
+
+    /// Return a Graphviz DOT description of the heap tree
+    ///
+    /// Only available with the `viz` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from_vec(&[1, 2]);
+    ///
+    /// assert_eq!(
+    ///     "digraph heap {\n    n0 [label=\"2\"];\n    n1 [label=\"1\"];\n    n0 -> n1;\n}\n",
+    ///     heap.to_dot()
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String {
+        self.to_dot_highlighting(&[])
+    }
+
+    /// Return a Graphviz DOT description of the heap tree, highlighting the nodes at positions
+    /// `path` and the edges from a parent to a child which are both in `path`
+    ///
+    /// Positions outside the heap are ignored. Passing `heap.last_sift_path()` shows the path
+    /// followed by the element moved during the last operation.
+    ///
+    /// Only available with the `viz` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from_vec(&[1, 2, 3]);
+    /// heap.insert(4);
+    /// let dot = heap.to_dot_highlighting(heap.last_sift_path());
+    ///
+    /// assert!(dot.contains("n0 -> n1 [color=\"#ff9966\", penwidth=2];"));
+    /// ```
+    pub fn to_dot_highlighting(&self, path: &[usize]) -> String {
+        let size = self.size();
+        let on_path = |i: usize| path.contains(&i);
+        let mut dot = String::from("digraph heap {\n");
+        for (i, x) in self.data.iter().enumerate() {
+            let label = escape_dot(&x.to_string());
+            if on_path(i) {
+                let _ = writeln!(dot, "    n{} [label=\"{}\", style=filled, fillcolor=\"{}\"];",
+                                 i, label, HIGHLIGHT_COLOR);
+            } else {
+                let _ = writeln!(dot, "    n{} [label=\"{}\"];", i, label);
+            }
+        }
+        for child in 1..size {
+            let parent = (child - 1) >> 1;
+            if on_path(parent) && on_path(child) {
+                let _ = writeln!(dot, "    n{} -> n{} [color=\"{}\", penwidth=2];",
+                                 parent, child, HIGHLIGHT_COLOR);
+            } else {
+                let _ = writeln!(dot, "    n{} -> n{};", parent, child);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Return a Mermaid flowchart description of the heap tree
+    ///
+    /// Only available with the `viz` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from_vec(&[1, 2]);
+    ///
+    /// assert_eq!("graph TD\n    n0[\"2\"]\n    n1[\"1\"]\n    n0 --> n1\n", heap.to_mermaid());
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        self.to_mermaid_highlighting(&[])
+    }
+
+    /// Return a Mermaid flowchart description of the heap tree, highlighting the nodes at
+    /// positions `path` and the edges from a parent to a child which are both in `path`
+    ///
+    /// Positions outside the heap are ignored.
+    ///
+    /// Only available with the `viz` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use binary_heap::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from_vec(&[1, 2, 3]);
+    /// heap.insert(4);
+    /// let mermaid = heap.to_mermaid_highlighting(heap.last_sift_path());
+    ///
+    /// assert!(mermaid.contains("class n0,n1,n3 sift"));
+    /// ```
+    pub fn to_mermaid_highlighting(&self, path: &[usize]) -> String {
+        let size = self.size();
+        let on_path = |i: usize| path.contains(&i);
+        let mut mermaid = String::from("graph TD\n");
+        for (i, x) in self.data.iter().enumerate() {
+            let _ = writeln!(mermaid, "    n{}[\"{}\"]", i, escape_mermaid(&x.to_string()));
+        }
+
+        // edges are numbered in the order in which they are declared, i.e., edge `child - 1`
+        // links `child` to its parent
+        let mut highlighted_edges = Vec::<String>::new();
+        for child in 1..size {
+            let parent = (child - 1) >> 1;
+            let _ = writeln!(mermaid, "    n{} --> n{}", parent, child);
+            if on_path(parent) && on_path(child) {
+                highlighted_edges.push((child - 1).to_string());
+            }
+        }
+
+        let highlighted_nodes: Vec<String> = (0..size)
+            .filter(|&i| on_path(i))
+            .map(|i| format!("n{}", i))
+            .collect();
+        if !highlighted_nodes.is_empty() {
+            let _ = writeln!(mermaid, "    classDef sift fill:{}", HIGHLIGHT_COLOR);
+            let _ = writeln!(mermaid, "    class {} sift", highlighted_nodes.join(","));
+        }
+        if !highlighted_edges.is_empty() {
+            let _ = writeln!(mermaid, "    linkStyle {} stroke:{},stroke-width:2px",
+                             highlighted_edges.join(","), HIGHLIGHT_COLOR);
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_sift_path_pop() {
+        let mut heap = BinaryHeap::<isize>::new();
+        for x in [0, 1, 2, -2, -1, 2, 0] {
+            heap.insert(x);
+        }
+        // [2, 0, 2, -2, -1, 1, 0]: the last element 0 is moved to the root, then sifted down
+        // through the right child
+        heap.pop();
+        assert_eq!(&[0, 2, 5], heap.last_sift_path());
+    }
+
+    #[test]
+    fn escape_labels() {
+        let heap = BinaryHeap::from_vec(&["a\"b"]);
+        assert!(heap.to_dot().contains("n0 [label=\"a\\\"b\"];"));
+        assert!(heap.to_mermaid().contains("n0[\"a#quot;b\"]"));
+    }
+
+    #[test]
+    fn empty_heap() {
+        let heap = BinaryHeap::<isize>::new();
+        assert_eq!("digraph heap {\n}\n", heap.to_dot());
+        assert_eq!("graph TD\n", heap.to_mermaid());
+    }
+}
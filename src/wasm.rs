@@ -0,0 +1,108 @@
+//! JavaScript bindings
+//!
+//! The JavaScript class `BinaryHeap` is a max-heap of numeric keys, each associated with an
+//! opaque payload handle (an unsigned 32-bit integer, for instance an index into a JavaScript
+//! array or a key of a `Map` holding the actual payloads):
+//!
+//! ```js
+//! import { BinaryHeap } from "binary_heap";
+//!
+//! const payloads = ["low", "high"];
+//! const heap = new BinaryHeap();
+//! heap.insert(1.0, 0);
+//! heap.insert(5.0, 1);
+//! console.log(payloads[heap.pop()]); // "high"
+//! console.log(heap.size());          // 1
+//! ```
+
+use crate::BinaryHeap;
+use wasm_bindgen::prelude::*;
+
+// payload handle with its key; only the key is compared
+struct Entry {
+    key: f64,
+    handle: u32,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+/// Max binary heap of payload handles ordered by numeric keys
+#[wasm_bindgen(js_name = BinaryHeap)]
+#[derive(Default)]
+pub struct WasmBinaryHeap {
+    heap: BinaryHeap<Entry>,
+}
+
+#[wasm_bindgen(js_class = BinaryHeap)]
+impl WasmBinaryHeap {
+
+    /// Create a new empty heap
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        WasmBinaryHeap::default()
+    }
+
+    /// Insert the payload handle `handle` with key `key`; return `false` (and do nothing) if
+    /// `key` is NaN
+    pub fn insert(&mut self, key: f64, handle: u32) -> bool {
+        if key.is_nan() {
+            return false;
+        }
+        self.heap.insert(Entry { key, handle });
+        true
+    }
+
+    /// Remove and return the handle with the largest key, or `undefined` if the heap is empty
+    pub fn pop(&mut self) -> Option<u32> {
+        self.heap.pop().map(|entry| entry.handle)
+    }
+
+    /// Return the handle with the largest key without removing it, or `undefined` if the heap
+    /// is empty
+    pub fn peek(&self) -> Option<u32> {
+        self.heap.data.first().map(|entry| entry.handle)
+    }
+
+    /// Return the largest key, or `undefined` if the heap is empty
+    #[wasm_bindgen(js_name = peekKey)]
+    pub fn peek_key(&self) -> Option<f64> {
+        self.heap.data.first().map(|entry| entry.key)
+    }
+
+    /// Return the number of elements in the heap
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_by_key() {
+        let mut heap = WasmBinaryHeap::new();
+        assert!(heap.insert(1.0, 10));
+        assert!(heap.insert(5.0, 11));
+        assert!(heap.insert(-2.5, 12));
+        assert!(!heap.insert(f64::NAN, 13));
+        assert_eq!(3, heap.size());
+        assert_eq!(Some(11), heap.peek());
+        assert_eq!(Some(5.0), heap.peek_key());
+        assert_eq!(Some(11), heap.pop());
+        assert_eq!(Some(10), heap.pop());
+        assert_eq!(Some(12), heap.pop());
+        assert_eq!(None, heap.pop());
+        assert_eq!(None, heap.peek());
+    }
+}
@@ -0,0 +1,125 @@
+//! Heap notifying a callback whenever its root changes
+
+use crate::{BinaryHeap, Compare};
+use core::cmp::Ordering;
+
+/// A `BinaryHeap` calling a function whenever its root element changes
+///
+/// The function is called with the new root (or `None` if the heap becomes empty) after each
+/// insertion of an element which becomes the root, and after each `pop`. Insertions which do not
+/// change the root do not call it, so a consumer only needs to wake up when the next element to
+/// process is a different one. To notify another thread, the function can send the new root on a
+/// channel.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::{BinaryHeap, WatchedHeap};
+/// use std::sync::mpsc;
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let mut heap = WatchedHeap::new(BinaryHeap::<u32>::new(), move |root: Option<&u32>| {
+///     sender.send(root.copied()).unwrap();
+/// });
+/// heap.insert(5);
+/// heap.insert(3);   // does not change the root
+/// heap.insert(8);
+/// heap.pop();
+///
+/// assert_eq!(vec![Some(5), Some(8), Some(5)], receiver.try_iter().collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Debug)]
+pub struct WatchedHeap<T, C, F> {
+    heap: BinaryHeap<T, C>,
+    on_change: F,   // called with the new root when it changes
+}
+
+impl<T, C: Compare<T>, F: FnMut(Option<&T>)> WatchedHeap<T, C, F> {
+
+    /// Wrap `heap`, calling `on_change` whenever its root changes
+    #[inline]
+    pub fn new(heap: BinaryHeap<T, C>, on_change: F) -> Self {
+        WatchedHeap { heap, on_change }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first()
+    }
+
+    /// Insert an element in the heap, calling the function if it becomes the root
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn insert(&mut self, x: T) {
+
+        // the element is sifted up to the root if and only if it is larger than the current root
+        let new_root = match self.heap.data.first() {
+            None => true,
+            Some(root) => self.heap.compare_values(root, &x) == Some(Ordering::Less),
+        };
+        let size = self.heap.size();
+        self.heap.insert(x);
+
+        // the element may have been rejected by the policy for incomparable elements
+        if new_root && self.heap.size() > size {
+            (self.on_change)(self.heap.data.first());
+        }
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty), calling the function
+    /// with the new root if the heap was not empty
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.heap.pop()?;
+        (self.on_change)(self.heap.data.first());
+        Some(root)
+    }
+
+    /// Return the underlying heap
+    #[inline]
+    pub fn into_inner(self) -> BinaryHeap<T, C> {
+        self.heap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+
+    #[test]
+    fn notifications_1() {
+        let mut next = lcg(12345);
+        let mut roots = Vec::new();
+        let mut expected = Vec::new();
+        let mut heap = WatchedHeap::new(BinaryHeap::new(), |root: Option<&u64>| roots.push(root.copied()));
+        for _ in 0..1000 {
+            let before = heap.peek().copied();
+            if next().is_multiple_of(3) {
+                if heap.pop().is_some() {
+                    expected.push(heap.peek().copied());
+                }
+            } else {
+                let x = next() % 100;
+                heap.insert(x);
+                if before.is_none_or(|root| root < x) {
+                    expected.push(Some(x));
+                }
+            }
+        }
+        drop(heap);
+        assert_eq!(expected, roots);
+    }
+}
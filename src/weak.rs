@@ -0,0 +1,270 @@
+//! Weak heap
+//!
+//! A weak heap relaxes the heap property: each element is only required to be not smaller than
+//! the elements of its right subtree, and the root has no left subtree. The children of node $i$
+//! are at positions $2i + r_i$ (left) and $2i + 1 - r_i$ (right) of the array, where $r_i$ is a
+//! bit which can be flipped to swap the two subtrees at no cost. Building the heap takes $n - 1$
+//! comparisons and removing the root at most $\lceil \log_2 n \rceil$. (Dutton, *Weak-heap
+//! sort*, 1993; Edelkamp and Wegener, *On the performance of Weak-Heapsort*, 2000.)
+
+use crate::{Compare, MaxComparator};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A heap minimizing the number of comparisons between elements
+///
+/// `WeakHeap` provides the same core functions as `BinaryHeap` (`insert`, `pop`, `get_max`,
+/// `size`, `to_vec`, `with_comparator`, `from_vec_with_comparator`, `From<Vec<T>>`, and
+/// `Iterator`), so it can replace it where comparisons are expensive: `pop` uses at most
+/// $\lceil \log_2 n \rceil$ comparisons, against up to $2 \log_2 n$ for a binary heap, and building
+/// the heap from $n$ elements exactly $n - 1$.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::WeakHeap;
+///
+/// let mut heap = WeakHeap::from(vec![3, 8, 1, 5]);
+/// heap.insert(6);
+///
+/// assert_eq!(Some(8), heap.pop());
+/// assert_eq!(Some(&6), heap.peek());
+/// assert_eq!(vec![6, 5, 3, 1], heap.to_vec());
+/// ```
+#[derive(Clone, Debug)]
+pub struct WeakHeap<T, C = MaxComparator> {
+    data: Vec<T>,           // elements
+    reverse: Vec<bool>,     // whether the children of each node are swapped
+    comparator: C,          // order of the elements
+}
+
+impl<T: core::cmp::PartialOrd> WeakHeap<T> {
+
+    /// Create a new empty `WeakHeap`, ordered by the natural order of `T`
+    #[inline]
+    pub fn new() -> Self {
+        WeakHeap::with_comparator(MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T>> WeakHeap<T, C> {
+
+    /// Create a new empty `WeakHeap` ordered by `comparator`
+    #[inline]
+    pub fn with_comparator(comparator: C) -> Self {
+        WeakHeap { data: Vec::new(), reverse: Vec::new(), comparator }
+    }
+
+    /// Build a `WeakHeap` ordered by `comparator` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, with exactly $n - 1$ comparisons, where $n$ is the
+    /// number of elements.
+    pub fn from_vec_with_comparator(values: Vec<T>, comparator: C) -> Self {
+        let mut heap = WeakHeap::with_comparator(comparator);
+        heap.reverse = alloc::vec![false; values.len()];
+        heap.data = values;
+        for j in (1..heap.size()).rev() {
+            heap.join(heap.ancestor(j), j);
+        }
+        heap
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// Worst-case complexity: $O(\log n)$ comparisons, where $n$ is the number of elements in the
+    /// heap.
+    pub fn insert(&mut self, x: T) {
+        let mut j = self.size();
+        self.data.push(x);
+        self.reverse.push(false);
+
+        // a new element at an even position is the left child of its parent
+        if j & 1 == 0 {
+            self.reverse[j / 2] = false;
+        }
+
+        while j > 0 {
+            let i = self.ancestor(j);
+            if self.join(i, j) {
+                break;
+            }
+            j = i;
+        }
+    }
+
+    /// Return a reference to the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $O(\log n)$, with at most $\lceil \log_2 n \rceil$ comparisons,
+    /// where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.pop()?;
+        self.reverse.pop();
+        if self.data.is_empty() {
+            return Some(last);
+        }
+        let root = core::mem::replace(&mut self.data[0], last);
+
+        // go down the leftmost path of the right subtree of the root, then join the root with
+        // each node on the way back up
+        let n = self.size();
+        if n > 1 {
+            let mut x = 1;
+            while 2 * x + (self.reverse[x] as usize) < n {
+                x = 2 * x + self.reverse[x] as usize;
+            }
+            while x > 0 {
+                self.join(0, x);
+                x /= 2;
+            }
+        }
+        Some(root)
+    }
+
+    /// Consume the heap and return a vector of all its elements in non-increasing order
+    ///
+    /// Worst-case complexity: $O(n \log n)$, where $n$ is the number of elements in the heap.
+    #[inline]
+    pub fn to_vec(self) -> Vec<T> {
+        self.collect()
+    }
+
+    // distinguished ancestor of the node `j` (the parent of the first node on its path to the
+    // root which is a right child), whose element must not be smaller
+    #[inline]
+    fn ancestor(&self, mut j: usize) -> usize {
+        while (j & 1) == self.reverse[j / 2] as usize {
+            j /= 2;
+        }
+        j / 2
+    }
+
+    // make the element at `i`, an ancestor of `j`, not smaller than that at `j` and the
+    // elements of its right subtree, returning whether this was already the case
+    //
+    // If the element at `j` is larger, the two elements are swapped and so are the subtrees of
+    // `j`.
+    #[inline]
+    fn join(&mut self, i: usize, j: usize) -> bool {
+        if self.comparator.compare(&self.data[i], &self.data[j]) == Some(Ordering::Less) {
+            self.data.swap(i, j);
+            self.reverse[j] = !self.reverse[j];
+            false
+        } else {
+            true
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T>> WeakHeap<T, C> {
+
+    /// Return a copy of the maximum element if the heap is not empty
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn get_max(&self) -> Option<T> {
+        self.data.first().cloned()
+    }
+}
+
+impl<T: core::cmp::PartialOrd> From<Vec<T>> for WeakHeap<T> {
+
+    /// Build a `WeakHeap` from a vector, whose memory is reused
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements.
+    fn from(values: Vec<T>) -> Self {
+        WeakHeap::from_vec_with_comparator(values, MaxComparator)
+    }
+}
+
+impl<T, C: Compare<T> + Default> core::default::Default for WeakHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Iterator for WeakHeap<T, C> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size(), Some(self.size()))
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for WeakHeap<T, C> {}
+
+impl<T, C: Compare<T>> core::iter::FusedIterator for WeakHeap<T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::lcg;
+    use crate::BinaryHeap;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // natural order, counting the comparisons
+    #[derive(Clone, Default)]
+    struct CountingComparator(Rc<Cell<usize>>);
+
+    impl Compare<u64> for CountingComparator {
+        fn compare(&self, a: &u64, b: &u64) -> Option<Ordering> {
+            self.0.set(self.0.get() + 1);
+            a.partial_cmp(b)
+        }
+    }
+
+    #[test]
+    fn against_binary_heap() {
+        let mut next = lcg(12345);
+        let values: Vec<u64> = (0..500).map(|_| next() % 1000).collect();
+        let mut heap = WeakHeap::from(values.clone());
+        let mut reference = BinaryHeap::from(values);
+        for _ in 0..5000 {
+            if next().is_multiple_of(2) {
+                assert_eq!(reference.pop(), heap.pop());
+            } else {
+                let x = next() % 1000;
+                heap.insert(x);
+                reference.insert(x);
+            }
+            assert_eq!(reference.size(), heap.size());
+            assert_eq!(reference.get_max(), heap.get_max());
+        }
+        assert_eq!(reference.to_vec(), heap.to_vec());
+    }
+
+    #[test]
+    fn comparisons() {
+        let mut next = lcg(7);
+        let n = 1000;
+        let count = Rc::new(Cell::new(0));
+        let values: Vec<u64> = (0..n).map(|_| next()).collect();
+        let mut heap = WeakHeap::from_vec_with_comparator(values, CountingComparator(count.clone()));
+        assert_eq!(n - 1, count.get());
+        for size in (1..=n).rev() {
+            count.set(0);
+            heap.pop();
+            assert!(count.get() <= (size as f64).log2().ceil() as usize);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Heap for sensitive payloads, wiping its memory with `zeroize`
+
+use crate::BinaryHeap;
+use alloc::vec::Vec;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Wipe all the elements of the heap and its whole backing buffer, leaving it empty
+impl<T: core::cmp::PartialOrd + Zeroize> Zeroize for BinaryHeap<T> {
+    fn zeroize(&mut self) {
+        self.data.zeroize();
+        #[cfg(feature = "viz")]
+        self.last_sift_path.clear();
+    }
+}
+
+/// A `BinaryHeap` which does not leave copies of its elements in memory
+///
+/// * popped elements are returned wrapped in `Zeroizing`, so they are wiped when dropped;
+/// * the slot freed by each pop is zeroized;
+/// * when the backing buffer grows, the old one is zeroized before being freed;
+/// * `clear` and `Drop` wipe the elements and the whole backing buffer.
+///
+/// Only available with the `zeroize` feature.
+///
+/// # Example
+///
+/// ```
+/// use binary_heap::ZeroizingBinaryHeap;
+///
+/// // session tokens, ordered by expiry
+/// let mut tokens = ZeroizingBinaryHeap::<(u64, [u8; 16])>::new();
+/// tokens.insert((1_700_000_000, [1; 16]));
+/// tokens.insert((1_700_000_600, [2; 16]));
+///
+/// let token = tokens.pop().unwrap();
+/// assert_eq!(1_700_000_600, token.0);
+/// // `token` is wiped when it goes out of scope
+/// ```
+pub struct ZeroizingBinaryHeap<T: core::cmp::PartialOrd + Zeroize> {
+    heap: BinaryHeap<T>,
+}
+
+impl<T: core::cmp::PartialOrd + Zeroize> ZeroizingBinaryHeap<T> {
+
+    /// Create a new empty `ZeroizingBinaryHeap`
+    #[inline]
+    pub fn new() -> Self {
+        ZeroizingBinaryHeap { heap: BinaryHeap::new() }
+    }
+
+    /// Get the size of the heap (number of elements)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.heap.size()
+    }
+
+    /// Insert an element in the heap
+    ///
+    /// If the backing buffer is full, the elements are moved to a buffer twice as large, and the
+    /// old one is zeroized.
+    ///
+    /// Worst-case complexity: $\Theta(n)$, where $n$ is the number of elements in the heap
+    /// (amortized: $\Theta(\log n)$).
+    pub fn insert(&mut self, x: T) {
+        let data = &mut self.heap.data;
+        if data.len() == data.capacity() {
+            let mut grown = Vec::with_capacity(core::cmp::max(4, 2 * data.capacity()));
+            grown.append(data);
+            // `data` is now empty: this wipes the whole old buffer
+            data.zeroize();
+            *data = grown;
+        }
+        self.heap.insert(x);
+    }
+
+    /// Remove and return the root element (or `None` if the heap is empty)
+    ///
+    /// The slot freed in the backing buffer is zeroized.
+    ///
+    /// Worst-case complexity: $\Theta(\log n)$, where $n$ is the number of elements in the heap.
+    pub fn pop(&mut self) -> Option<Zeroizing<T>> {
+        let popped = self.heap.pop()?;
+        self.heap.data.spare_capacity_mut()[..1].zeroize();
+        Some(Zeroizing::new(popped))
+    }
+
+    /// Return a reference to the maximum element (or `None` if the heap is empty)
+    ///
+    /// Worst-case complexity: $\Theta(1)$.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.data.first()
+    }
+
+    /// Remove all the elements, wiping them and the whole backing buffer
+    ///
+    /// Worst-case complexity: $\Theta(c)$, where $c$ is the capacity of the backing buffer.
+    pub fn clear(&mut self) {
+        self.heap.zeroize();
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Zeroize> core::default::Default for ZeroizingBinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Zeroize> Drop for ZeroizingBinaryHeap<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: core::cmp::PartialOrd + Zeroize> ZeroizeOnDrop for ZeroizingBinaryHeap<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // check that the spare capacity of the backing buffer only contains zeros
+    fn spare_is_zeroed(heap: &mut ZeroizingBinaryHeap<u64>) -> bool {
+        heap.heap.data.spare_capacity_mut().iter().all(|slot| unsafe { slot.assume_init() } == 0)
+    }
+
+    #[test]
+    fn pop_1() {
+        let mut heap = ZeroizingBinaryHeap::<u64>::new();
+        for x in [3, 7, 1, 9] {
+            heap.insert(x);
+        }
+        assert_eq!(Some(&9), heap.peek());
+        assert_eq!(9, *heap.pop().unwrap());
+        assert_eq!(7, *heap.pop().unwrap());
+        assert_eq!(2, heap.size());
+        assert!(spare_is_zeroed(&mut heap));
+    }
+
+    #[test]
+    fn insert_1() {
+        let mut heap = ZeroizingBinaryHeap::<u64>::new();
+        for x in 1..=20 {
+            heap.insert(x);
+        }
+        assert_eq!(20, heap.size());
+        assert_eq!(Some(&20), heap.peek());
+    }
+
+    #[test]
+    fn clear_1() {
+        let mut heap = ZeroizingBinaryHeap::<u64>::new();
+        for x in 1..=5 {
+            heap.insert(x);
+        }
+        heap.clear();
+        assert_eq!(0, heap.size());
+        assert_eq!(None, heap.pop());
+        assert!(spare_is_zeroed(&mut heap));
+    }
+}